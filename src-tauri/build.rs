@@ -1,15 +1,28 @@
-use std::env;
-use std::path::Path;
-use std::process::Command;
-
 fn main() {
-    let out_dir = env::var("OUT_DIR").unwrap();
-
     // Only compile on macOS
     #[cfg(target_os = "macos")]
     {
+        use std::env;
+        use std::path::Path;
+        use std::process::Command;
+
+        let out_dir = env::var("OUT_DIR").unwrap();
+
         // Paths to Swift files
-        let swift_files = vec!["./src/swift/contacts.swift", "./src/swift/apps.swift"];
+        let swift_files = vec![
+            "./src/swift/contacts.swift",
+            "./src/swift/apps.swift",
+            "./src/swift/files.swift",
+            "./src/swift/power.swift",
+            "./src/swift/login.swift",
+            "./src/swift/browser.swift",
+            "./src/swift/system.swift",
+            "./src/swift/network.swift",
+            "./src/swift/energy.swift",
+            "./src/swift/thumbnails.swift",
+            "./src/swift/ocr.swift",
+            "./src/swift/keychain.swift",
+        ];
 
         // Check if Swift files exist
         for swift_file in &swift_files {
@@ -35,6 +48,16 @@ fn main() {
                 "AppKit",
                 "-framework",
                 "CoreGraphics",
+                "-framework",
+                "ServiceManagement",
+                "-framework",
+                "QuickLookThumbnailing",
+                "-framework",
+                "Vision",
+                "-framework",
+                "PDFKit",
+                "-framework",
+                "Security",
             ])
             .status()
             .expect("Failed to compile Swift code");
@@ -0,0 +1,372 @@
+/*
+This file contains the local HTTP API. When it's enabled in the settings the app serves /search, /semantic-search, /ask and the MCP routes on 127.0.0.1, so editor plugins, launcher scripts and shell tools can query the running index
+Every request needs the token from the app data directory as a bearer token, other local users and web pages can't read it
+*/
+
+use axum::extract::{Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Listener, Manager};
+use tokio::sync::oneshot;
+use tracing::{error, info};
+
+use crate::file_processor::{
+    get_processor, get_semantic_files_data, search_files, FileProcessorState,
+};
+use crate::messages::{ErrorCode, UserMessage};
+use crate::server::ask_llm;
+use crate::settings::ApiServerSettings;
+
+pub(crate) const API_TOKEN_FILE_NAME: &str = "api_token";
+
+/// Results returned when the request doesn't set a limit
+const DEFAULT_RESULT_LIMIT: usize = 20;
+
+struct RunningApiServer {
+    port: u16,
+    shutdown: oneshot::Sender<()>,
+    // shared with the request handlers so a new token applies without a restart
+    token: Arc<RwLock<String>>,
+}
+
+static API_SERVER: Mutex<Option<RunningApiServer>> = Mutex::new(None);
+
+#[derive(Clone)]
+pub(crate) struct ApiState {
+    pub(crate) app_handle: AppHandle,
+    token: Arc<RwLock<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchParams {
+    q: String,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AskBody {
+    prompt: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ApiError {
+    error: String,
+    /// Set for the errors that have one, like the LLM not running
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<ErrorCode>,
+}
+
+fn error_response(status: StatusCode, error: impl Into<String>) -> Response {
+    (
+        status,
+        Json(ApiError {
+            error: error.into(),
+            code: None,
+        }),
+    )
+        .into_response()
+}
+
+fn message_response(status: StatusCode, message: UserMessage) -> Response {
+    (
+        status,
+        Json(ApiError {
+            code: Some(message.code),
+            error: message.message,
+        }),
+    )
+        .into_response()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiServerStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub port: u16,
+    /// Bearer token the requests need, shown in the settings so it can be copied into scripts
+    pub token: Option<String>,
+}
+
+/// 32 random bytes as hex, read from the OS random source
+/// Falls back to hashing the randomly seeded std hasher keys where /dev/urandom isn't available
+pub(crate) fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    let from_os = fs::File::open("/dev/urandom")
+        .and_then(|mut urandom| urandom.read_exact(&mut bytes))
+        .is_ok();
+
+    if !from_os {
+        let mut hasher = Sha256::new();
+        for _ in 0..4 {
+            hasher.update(RandomState::new().build_hasher().finish().to_le_bytes());
+        }
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos())
+            .unwrap_or_default();
+        hasher.update(nanos.to_le_bytes());
+        bytes.copy_from_slice(&hasher.finalize());
+    }
+
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn token_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(API_TOKEN_FILE_NAME))
+        .map_err(|e| format!("Failed to get app data directory: {}", e))
+}
+
+/// Writes the token so only the current user can read it
+/// The token goes into a new file that is created readable by the owner only and then renamed into place,
+/// so it is never readable by others, not even for a moment
+fn save_token(path: &Path, token: &str) -> Result<(), String> {
+    let temp_path = path.with_extension("tmp");
+    let _ = fs::remove_file(&temp_path);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let result = options
+        .open(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(token.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp_path, path));
+
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(format!("Failed to save the API token: {}", e));
+    }
+
+    Ok(())
+}
+
+/// Returns the saved token, creating one the first time the API is used
+fn load_or_create_token(app_handle: &AppHandle) -> Result<String, String> {
+    let path = token_path(app_handle)?;
+
+    if let Ok(token) = fs::read_to_string(&path) {
+        let token = token.trim().to_string();
+        if !token.is_empty() {
+            return Ok(token);
+        }
+    }
+
+    let token = generate_token();
+    save_token(&path, &token)?;
+    Ok(token)
+}
+
+async fn require_token(State(api): State<ApiState>, request: Request, next: Next) -> Response {
+    let authorized = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| tokens_match(token, &api.token.read().unwrap()));
+
+    if !authorized {
+        return error_response(StatusCode::UNAUTHORIZED, "Missing or invalid API token");
+    }
+
+    next.run(request).await
+}
+
+/// Compares every byte so the time taken doesn't reveal how much of the token matched
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn search(State(api): State<ApiState>, Query(params): Query<SearchParams>) -> Response {
+    let state = api.app_handle.state::<FileProcessorState>();
+    let result = get_processor(&state).and_then(|processor| search_files(&processor, &params.q));
+
+    match result {
+        Ok(mut files) => {
+            files.truncate(params.limit.unwrap_or(DEFAULT_RESULT_LIMIT));
+            Json(files).into_response()
+        }
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+async fn semantic_search(
+    State(api): State<ApiState>,
+    Query(params): Query<SearchParams>,
+) -> Response {
+    let state = api.app_handle.state::<FileProcessorState>();
+
+    match get_semantic_files_data(params.q, state, api.app_handle.clone()).await {
+        Ok(mut files) => {
+            files.truncate(params.limit.unwrap_or(DEFAULT_RESULT_LIMIT));
+            Json(files).into_response()
+        }
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+async fn ask(State(api): State<ApiState>, Json(body): Json<AskBody>) -> Response {
+    if body.prompt.trim().is_empty() {
+        return error_response(StatusCode::BAD_REQUEST, "The prompt can't be empty");
+    }
+
+    match ask_llm(api.app_handle.clone(), body.prompt).await {
+        Ok(response) => Json(response).into_response(),
+        Err(e) => message_response(StatusCode::SERVICE_UNAVAILABLE, e),
+    }
+}
+
+fn router(api: ApiState) -> Router {
+    Router::new()
+        .route("/search", get(search))
+        .route("/semantic-search", get(semantic_search))
+        .route("/ask", post(ask))
+        .merge(crate::mcp::routes())
+        .layer(middleware::from_fn_with_state(api.clone(), require_token))
+        .with_state(api)
+}
+
+fn stop_api_server() {
+    if let Some(running) = API_SERVER.lock().unwrap().take() {
+        let _ = running.shutdown.send(());
+        info!("Stopped the API server on port {}", running.port);
+    }
+}
+
+fn start_api_server(app_handle: &AppHandle, port: u16) -> Result<(), String> {
+    let token = Arc::new(RwLock::new(load_or_create_token(app_handle)?));
+    let api = ApiState {
+        app_handle: app_handle.clone(),
+        token: token.clone(),
+    };
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    *API_SERVER.lock().unwrap() = Some(RunningApiServer {
+        port,
+        shutdown: shutdown_tx,
+        token,
+    });
+
+    tauri::async_runtime::spawn(async move {
+        // only reachable from this machine
+        let listener = match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Failed to start the API server on port {}: {}", port, e);
+                let mut running = API_SERVER.lock().unwrap();
+                if running.as_ref().is_some_and(|running| running.port == port) {
+                    *running = None;
+                }
+                return;
+            }
+        };
+
+        info!("API server listening on 127.0.0.1:{}", port);
+        let shutdown = async {
+            let _ = shutdown_rx.await;
+        };
+        if let Err(e) = axum::serve(listener, router(api))
+            .with_graceful_shutdown(shutdown)
+            .await
+        {
+            error!("API server error: {}", e);
+        }
+    });
+
+    Ok(())
+}
+
+/// Starts, stops or restarts the server so it matches the api_server settings
+fn sync_api_server(app_handle: &AppHandle) {
+    let settings = ApiServerSettings::from_app_handle(app_handle);
+    let running_port = API_SERVER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|running| running.port);
+
+    if !settings.enabled() {
+        stop_api_server();
+        return;
+    }
+
+    if running_port == Some(settings.port()) {
+        return;
+    }
+
+    stop_api_server();
+    if let Err(e) = start_api_server(app_handle, settings.port()) {
+        error!("{}", e);
+    }
+}
+
+/// Starts the API server if it's enabled and follows the settings while the app runs
+pub fn init_api_server(app_handle: AppHandle) {
+    sync_api_server(&app_handle);
+
+    let app_handle_for_changes = app_handle.clone();
+    app_handle.listen("settings-changed", move |_| {
+        sync_api_server(&app_handle_for_changes);
+    });
+}
+
+#[tauri::command]
+pub fn get_api_server_status(app_handle: AppHandle) -> Result<ApiServerStatus, String> {
+    let settings = ApiServerSettings::from_app_handle(&app_handle);
+    let running = API_SERVER.lock().unwrap().is_some();
+
+    // the token is created the first time the API is enabled
+    let token = if settings.enabled() {
+        Some(load_or_create_token(&app_handle)?)
+    } else {
+        fs::read_to_string(token_path(&app_handle)?)
+            .ok()
+            .map(|token| token.trim().to_string())
+    };
+
+    Ok(ApiServerStatus {
+        enabled: settings.enabled(),
+        running,
+        port: settings.port(),
+        token,
+    })
+}
+
+/// Replaces the token, clients using the old one get 401 from then on
+#[tauri::command]
+pub fn regenerate_api_token(app_handle: AppHandle) -> Result<String, String> {
+    let token = generate_token();
+    save_token(&token_path(&app_handle)?, &token)?;
+
+    if let Some(running) = API_SERVER.lock().unwrap().as_ref() {
+        *running.token.write().unwrap() = token.clone();
+    }
+
+    Ok(token)
+}
@@ -1,10 +1,46 @@
+/*
+This file contains the app launcher commands. Listing, launching and quitting apps is different on every OS, so the commands go through the AppPlatform trait and each OS implements it in its own apps_<os> module
+*/
+
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::ffi::{CStr, CString};
-use std::os::raw::c_char;
-use tauri::Emitter;
-
-use crate::resource_monitor::AppResourceUsage;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{error, info};
+
+#[cfg(target_os = "linux")]
+use crate::apps_linux::LinuxApps as PlatformApps;
+#[cfg(target_os = "macos")]
+use crate::apps_macos::MacApps as PlatformApps;
+#[cfg(windows)]
+use crate::apps_windows::WindowsApps as PlatformApps;
+use crate::favorites::ItemKind;
+use crate::file_processor::{get_processor, FileProcessorState};
+use crate::icon_cache::{icon_cache, modified_secs, IconCache};
+use crate::open_history::{load_app_frecency, save_open};
+use crate::resource_monitor::{get_processes_resource_usage, AppResourceUsage};
+
+/// Apps that keep the desktop working, quitting all heavy apps never touches these
+const ESSENTIAL_APPS: [&str; 8] = [
+    "Finder",
+    "Dock",
+    "SystemUIServer",
+    "WindowServer",
+    "loginwindow",
+    "explorer",
+    "gnome-shell",
+    "plasmashell",
+];
+
+/// Number of recent documents listed under each app
+const RECENT_DOCUMENTS_PER_APP: usize = 5;
+
+/// Apps using more memory than this are heavy unless the caller passes a different limit
+const DEFAULT_HEAVY_MEMORY_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Apps using more CPU than this are heavy unless the caller passes a different limit
+const DEFAULT_HEAVY_CPU_USAGE: f64 = 50.0;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppMetadata {
@@ -13,70 +49,94 @@ pub struct AppMetadata {
     pub pid: Option<u32>,
     pub icon: Option<String>,
     pub resource_usage: Option<AppResourceUsage>,
+    /// How often and how recently the user launched the app, higher is used more
+    #[serde(default)]
+    pub frecency: i64,
+    /// Documents the user recently opened in the app, most recent first
+    #[serde(default)]
+    pub recent_documents: Vec<RecentDocument>,
+    #[serde(default)]
+    pub bundle_id: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    /// When the app was last opened according to Spotlight, RFC 3339
+    #[serde(default)]
+    pub last_used: Option<String>,
 }
 
-extern "C" {
-    fn get_combined_apps_swift() -> *mut c_char;
-    fn get_running_apps_swift() -> *mut c_char;
-    fn get_app_icon_swift(path: *const c_char) -> *mut c_char;
-    fn switch_to_app_swift(pid: i32) -> bool;
-    fn force_quit_app_swift(pid: i32) -> bool;
-    fn restart_app_swift(path: *const c_char) -> bool;
-    fn check_process_running_swift(pid: i32) -> bool;
-    fn free_string_swift(pointer: *mut c_char);
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecentDocument {
+    pub name: String,
+    pub path: String,
 }
 
-#[derive(Deserialize)]
-struct AppsResponse {
-    running_apps: Vec<AppMetadata>,
-    installed_apps: Vec<AppMetadata>,
+/// What the launcher needs from the OS
+pub trait AppPlatform {
+    /// Returns the running apps and the installed apps, an app can be in both
+    fn combined_apps() -> Result<(Vec<AppMetadata>, Vec<AppMetadata>), String> {
+        Ok((Self::running_apps()?, Self::installed_apps()?))
+    }
+    fn installed_apps() -> Result<Vec<AppMetadata>, String>;
+    fn running_apps() -> Result<Vec<AppMetadata>, String>;
+    /// Returns the icon as a data URL
+    fn app_icon(path: &str) -> Result<Option<String>, String>;
+    /// Brings the app's windows to the front, returns false if the app couldn't be activated
+    fn switch_to_app(pid: u32) -> bool;
+    fn launch_app(path: &str) -> bool;
+    /// Asks the app to quit, returns false if the request couldn't be sent
+    fn force_quit_app(pid: u32) -> bool;
+    /// Hides all of the app's windows, returns false if the app couldn't be hidden
+    fn hide_app(pid: u32) -> bool;
+    /// Minimizes all of the app's windows, returns false if no window was minimized
+    fn minimize_app(pid: u32) -> bool;
+    fn is_process_running(pid: u32) -> bool;
+    /// Documents recently opened in the app, empty where the OS doesn't track them per app
+    fn recent_documents(_path: &str, _limit: usize) -> Vec<RecentDocument> {
+        Vec::new()
+    }
 }
 
 pub fn get_running_apps() -> Result<Vec<AppMetadata>, String> {
-    let apps_json_ptr = unsafe { get_running_apps_swift() };
-    if apps_json_ptr.is_null() {
-        return Err("Failed to get apps".to_string());
-    }
-    let apps_json = unsafe {
-        let c_str = CStr::from_ptr(apps_json_ptr);
-        let result = c_str
-            .to_str()
-            .map_err(|_| "Invalid UTF-8".to_string())?
-            .to_owned();
-        free_string_swift(apps_json_ptr);
-        result
-    };
-
-    let apps_response: Vec<AppMetadata> =
-        serde_json::from_str(&apps_json).map_err(|e| e.to_string())?;
-
-    Ok(apps_response)
+    PlatformApps::running_apps()
 }
 
 pub fn get_app_icon(app_path: &str) -> Result<Option<String>, String> {
-    let path_cstring =
-        CString::new(app_path).map_err(|_| "Failed to create C string".to_string())?;
-
-    let icon_ptr = unsafe { get_app_icon_swift(path_cstring.as_ptr()) };
-
-    if icon_ptr.is_null() {
-        return Ok(None);
-    }
-
-    let icon = unsafe {
-        let c_str = CStr::from_ptr(icon_ptr);
-        let result = c_str
-            .to_str()
-            .map_err(|_| "Invalid UTF-8".to_string())?
-            .to_owned();
-        free_string_swift(icon_ptr);
-        result
-    };
+    PlatformApps::app_icon(app_path)
+}
 
-    Ok(Some(icon))
+/// Icon with the first letter of the app's name for apps without an icon, the color is derived from the name
+pub fn fallback_icon(path: &str) -> String {
+    let name = std::path::Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let first_letter = name
+        .chars()
+        .next()
+        .map(|c| c.to_uppercase().to_string())
+        .unwrap_or_else(|| "A".to_string());
+    let hue = name.bytes().map(|b| b as u32).sum::<u32>() % 360;
+
+    let svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 64"><rect x="0" y="0" width="64" height="64" rx="12" fill="hsl({}, 70%, 60%)"/><text x="32" y="42" font-family="Arial" font-size="32" font-weight="bold" text-anchor="middle" fill="white">{}</text></svg>"#,
+        hue, first_letter
+    );
+
+    let encoded: String = svg
+        .bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() {
+                (b as char).to_string()
+            } else {
+                format!("%{:02X}", b)
+            }
+        })
+        .collect();
+    format!("data:image/svg+xml,{}", encoded)
 }
 
-fn filter_apps(app: Vec<AppMetadata>) -> Vec<AppMetadata> {
+/// Drops helpers, agents and other background processes that aren't apps the user launches
+pub fn filter_apps(app: Vec<AppMetadata>) -> Vec<AppMetadata> {
     let filtered_apps: Vec<AppMetadata> = app
         .into_iter()
         .filter(|app| {
@@ -106,59 +166,119 @@ fn filter_apps(app: Vec<AppMetadata>) -> Vec<AppMetadata> {
 }
 
 #[tauri::command]
-pub fn get_apps_data() -> Result<Vec<AppMetadata>, String> {
-    let apps_json_ptr = unsafe { get_combined_apps_swift() };
+pub fn get_apps_data(app_handle: AppHandle) -> Result<Vec<AppMetadata>, String> {
+    let (running_apps, installed_apps) = PlatformApps::combined_apps()?;
 
-    if apps_json_ptr.is_null() {
-        return Err("Failed to get apps".to_string());
-    }
-
-    let apps_json = unsafe {
-        let c_str = CStr::from_ptr(apps_json_ptr);
-        let result = c_str
-            .to_str()
-            .map_err(|_| "Invalid UTF-8".to_string())?
-            .to_owned();
-        free_string_swift(apps_json_ptr);
-        result
-    };
+    let mut combined_apps = running_apps;
 
-    let apps_response: AppsResponse =
-        serde_json::from_str(&apps_json).map_err(|e| e.to_string())?;
-
-    let mut combined_apps = apps_response.running_apps;
-
-    let unique_installed_apps: Vec<AppMetadata> = apps_response
-        .installed_apps
+    let unique_installed_apps: Vec<AppMetadata> = installed_apps
         .into_iter()
         .filter(|installed| {
             !combined_apps
                 .iter()
-                .any(|running| running.name == installed.name)
+                .any(|running| running.name == installed.name || running.path == installed.path)
         })
         .collect();
 
     combined_apps.extend(unique_installed_apps);
 
-    combined_apps.par_iter_mut().for_each(|app| {
-        if let Ok(icon) = get_app_icon(&app.path) {
+    let mut apps = filter_apps(combined_apps);
+    let cache = icon_cache(&app_handle);
+
+    apps.par_iter_mut().for_each(|app| {
+        app.recent_documents = PlatformApps::recent_documents(&app.path, RECENT_DOCUMENTS_PER_APP);
+        load_app_icon(cache, app);
+    });
+
+    if let Ok(mut cache) = cache.lock() {
+        let app_paths: HashSet<&str> = apps.iter().map(|app| app.path.as_str()).collect();
+        cache.retain_paths(&app_paths);
+        cache.save();
+    }
+
+    // the sort is stable so apps that were never launched keep the running apps first
+    let frecency = app_frecency(&app_handle);
+    for app in apps.iter_mut() {
+        app.frecency = frecency.get(&app.path).copied().unwrap_or(0);
+    }
+    apps.sort_by(|a, b| b.frecency.cmp(&a.frecency));
+
+    Ok(apps)
+}
+
+/// Sets the app's icon, icons are only extracted for apps that are new or changed since they were cached
+pub fn load_app_icon(cache: &Mutex<IconCache>, app: &mut AppMetadata) {
+    let modified = modified_secs(&app.path);
+
+    if let Some(modified) = modified {
+        let cached = cache
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(&app.path, modified));
+        if let Some(icon) = cached {
             app.icon = icon;
+            return;
         }
-    });
+    }
 
-    Ok(filter_apps(combined_apps))
+    if let Ok(icon) = get_app_icon(&app.path) {
+        if let (Some(modified), Ok(mut cache)) = (modified, cache.lock()) {
+            cache.insert(&app.path, modified, icon.clone());
+        }
+        app.icon = icon;
+    }
+}
+
+/// Frecency of the launched apps by path, empty if the index isn't ready
+fn app_frecency(app_handle: &AppHandle) -> HashMap<String, i64> {
+    let Some(state) = app_handle.try_state::<FileProcessorState>() else {
+        return HashMap::new();
+    };
+
+    let result = get_processor(&state)
+        .and_then(|processor| processor.get_read_connection())
+        .and_then(|conn| load_app_frecency(&conn).map_err(|e| e.to_string()));
+
+    result.unwrap_or_else(|e| {
+        error!("Failed to load app frecency: {}", e);
+        HashMap::new()
+    })
+}
+
+/// Stores the launch in the open history, which the frecency is computed from
+async fn record_launch(app_handle: &AppHandle, app: &AppMetadata, query: Option<String>) {
+    let Some(state) = app_handle.try_state::<FileProcessorState>() else {
+        return;
+    };
+    let Ok(processor) = get_processor(&state) else {
+        return;
+    };
+
+    if let Err(e) = save_open(
+        processor.db_path.clone(),
+        ItemKind::App,
+        app.path.clone(),
+        app.name.clone(),
+        query,
+    )
+    .await
+    {
+        error!("Failed to record the launch of {}: {}", app.name, e);
+    }
 }
 
 #[tauri::command]
 pub async fn launch_or_switch_to_app(
     app: AppMetadata,
+    query: Option<String>,
     app_handle: tauri::AppHandle,
 ) -> Result<(), String> {
     if let Some(pid) = app.pid {
-        let int3pid = pid as i32;
-        let switched = unsafe { switch_to_app_swift(int3pid) };
+        let switched = PlatformApps::switch_to_app(pid);
 
         if switched {
+            record_launch(&app_handle, &app, query).await;
+
             tokio::spawn(async move {
                 // wait for app to be active
                 tokio::time::sleep(std::time::Duration::from_millis(200)).await;
@@ -175,15 +295,12 @@ pub async fn launch_or_switch_to_app(
     }
 
     // If switching fails or no PID, launch the app
-    let path_cstring =
-        CString::new(app.path.clone()).map_err(|_| "Failed to create C string".to_string())?;
-
-    let restarted = unsafe { restart_app_swift(path_cstring.as_ptr()) };
-
-    if !restarted {
+    if !PlatformApps::launch_app(&app.path) {
         return Err(format!("Failed to launch application: {}", app.path));
     }
 
+    record_launch(&app_handle, &app, query).await;
+
     // For newly launched apps, monitor and update resource usage
     let app_path = app.path.clone();
     tokio::spawn(async move {
@@ -209,7 +326,7 @@ pub async fn launch_or_switch_to_app(
 #[tauri::command]
 pub async fn force_quit_application(pid: u32) -> Result<(), String> {
     // Initial attempt to force quit
-    let result = unsafe { force_quit_app_swift(pid as i32) };
+    let result = PlatformApps::force_quit_app(pid);
 
     if !result {
         return Err(format!(
@@ -220,7 +337,7 @@ pub async fn force_quit_application(pid: u32) -> Result<(), String> {
 
     // Now poll to see if the application has actually terminated
     // Define a timeout (5 seconds)
-    // the swift .terminate() method sends a kill signal to the app, but the app might be saving data and take time to close, so we poll it to see if the process is still running, since we can't call async functions from rust to swift
+    // on macOS the swift .terminate() method sends a kill signal to the app, but the app might be saving data and take time to close, so we poll it to see if the process is still running, since we can't call async functions from rust to swift
     let timeout = std::time::Duration::from_secs(5);
     let start_time = std::time::Instant::now();
 
@@ -245,8 +362,153 @@ pub async fn force_quit_application(pid: u32) -> Result<(), String> {
     ))
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuitOutcome {
+    pub pid: u32,
+    pub name: Option<String>,
+    /// Memory the app used before it was quit
+    pub memory_bytes: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BatchQuitReport {
+    pub succeeded: Vec<QuitOutcome>,
+    pub failed: Vec<QuitOutcome>,
+    /// Memory used by the apps that quit
+    pub freed_memory_bytes: u64,
+}
+
+/// Quits the apps concurrently and waits for each one to exit, a failure doesn't stop the others
+async fn quit_apps(apps: Vec<(u32, Option<String>)>) -> Result<BatchQuitReport, String> {
+    let pids: Vec<u32> = apps.iter().map(|(pid, _)| *pid).collect();
+    let usage = tokio::task::spawn_blocking(move || get_processes_resource_usage(&pids))
+        .await
+        .map_err(|e| format!("spawn_blocking error: {e}"))?;
+
+    let results = futures::future::join_all(apps.into_iter().map(|(pid, name)| {
+        let memory_bytes = usage.get(&pid).map(|u| u.memory_bytes).unwrap_or(0);
+        async move {
+            QuitOutcome {
+                pid,
+                name,
+                memory_bytes,
+                error: force_quit_application(pid).await.err(),
+            }
+        }
+    }))
+    .await;
+
+    let (succeeded, failed): (Vec<QuitOutcome>, Vec<QuitOutcome>) = results
+        .into_iter()
+        .partition(|outcome| outcome.error.is_none());
+    let freed_memory_bytes = succeeded.iter().map(|outcome| outcome.memory_bytes).sum();
+
+    info!(
+        "Quit {} apps, {} failed, freed {} bytes",
+        succeeded.len(),
+        failed.len(),
+        freed_memory_bytes
+    );
+
+    Ok(BatchQuitReport {
+        succeeded,
+        failed,
+        freed_memory_bytes,
+    })
+}
+
+/// Force quits all the given apps at the same time and reports which ones quit
+#[tauri::command]
+pub async fn force_quit_applications(pids: Vec<u32>) -> Result<BatchQuitReport, String> {
+    let running_apps = get_running_apps().unwrap_or_default();
+    let current_pid = std::process::id();
+
+    let apps = pids
+        .into_iter()
+        .filter(|pid| *pid != current_pid)
+        .collect::<HashSet<u32>>()
+        .into_iter()
+        .map(|pid| {
+            let name = running_apps
+                .iter()
+                .find(|app| app.pid == Some(pid))
+                .map(|app| app.name.clone());
+            (pid, name)
+        })
+        .collect();
+
+    quit_apps(apps).await
+}
+
+/// Quits every running app above the memory or CPU limit to free up resources under memory pressure
+/// Essential system apps and kita itself are never quit
+#[tauri::command]
+pub async fn quit_heavy_applications(
+    memory_threshold_bytes: Option<u64>,
+    cpu_threshold: Option<f64>,
+) -> Result<BatchQuitReport, String> {
+    let memory_threshold_bytes = memory_threshold_bytes.unwrap_or(DEFAULT_HEAVY_MEMORY_BYTES);
+    let cpu_threshold = cpu_threshold.unwrap_or(DEFAULT_HEAVY_CPU_USAGE);
+    let current_pid = std::process::id();
+
+    let candidates: Vec<(u32, String)> = get_running_apps()?
+        .into_iter()
+        .filter(|app| !ESSENTIAL_APPS.contains(&app.name.as_str()))
+        .filter_map(|app| Some((app.pid?, app.name)))
+        .filter(|(pid, _)| *pid != current_pid)
+        .collect();
+
+    let pids: Vec<u32> = candidates.iter().map(|(pid, _)| *pid).collect();
+    let usage = tokio::task::spawn_blocking(move || get_processes_resource_usage(&pids))
+        .await
+        .map_err(|e| format!("spawn_blocking error: {e}"))?;
+
+    let heavy_apps: Vec<(u32, Option<String>)> = candidates
+        .into_iter()
+        .filter(|(pid, _)| {
+            usage.get(pid).is_some_and(|u| {
+                u.memory_bytes > memory_threshold_bytes || u.cpu_usage > cpu_threshold
+            })
+        })
+        .map(|(pid, name)| (pid, Some(name)))
+        .collect();
+
+    quit_apps(heavy_apps).await
+}
+
+/// Returns the documents recently opened in the app at `app_path`
+#[tauri::command]
+pub async fn get_recent_documents(
+    app_path: String,
+    limit: Option<usize>,
+) -> Result<Vec<RecentDocument>, String> {
+    let limit = limit.unwrap_or(RECENT_DOCUMENTS_PER_APP);
+    tokio::task::spawn_blocking(move || PlatformApps::recent_documents(&app_path, limit))
+        .await
+        .map_err(|e| format!("spawn_blocking error: {e}"))
+}
+
+/// Hides the app, it stays running and comes back when the user switches to it
+#[tauri::command]
+pub async fn hide_application(pid: u32) -> Result<(), String> {
+    if !PlatformApps::hide_app(pid) {
+        return Err(format!("Failed to hide application with PID {}", pid));
+    }
+    Ok(())
+}
+
+/// Minimizes all windows of the app
+#[tauri::command]
+pub async fn minimize_application(pid: u32) -> Result<(), String> {
+    if !PlatformApps::minimize_app(pid) {
+        return Err(format!("Failed to minimize application with PID {}", pid));
+    }
+    Ok(())
+}
+
 fn is_process_running(pid: u32) -> bool {
-    unsafe { check_process_running_swift(pid as i32) }
+    PlatformApps::is_process_running(pid)
 }
 
 #[tauri::command]
@@ -262,14 +524,8 @@ pub async fn restart_application(
         tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
     }
 
-    // Prepare path as C string
-    let path_cstring =
-        CString::new(app.path.clone()).map_err(|_| "Failed to create C string".to_string())?;
-
     // Restart the application
-    let restarted = unsafe { restart_app_swift(path_cstring.as_ptr()) };
-
-    if !restarted {
+    if !PlatformApps::launch_app(&app.path) {
         return Err(format!("Failed to restart application: {}", app.path));
     }
 
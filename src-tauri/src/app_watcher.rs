@@ -0,0 +1,83 @@
+/*
+This file contains the watcher for running apps. It compares the running apps every few seconds and emits app-started and app-terminated, so the frontend doesn't have to poll get_apps_data to notice apps opening and closing
+*/
+
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::task;
+use tracing::error;
+
+use crate::app_handler::{filter_apps, get_running_apps, load_app_icon, AppMetadata};
+use crate::icon_cache::icon_cache;
+
+/// How often the running apps are compared
+const APP_WATCH_INTERVAL: Duration = Duration::from_secs(3);
+
+fn running_apps_by_pid() -> Result<HashMap<u32, AppMetadata>, String> {
+    Ok(filter_apps(get_running_apps()?)
+        .into_iter()
+        .filter_map(|app| Some((app.pid?, app)))
+        .collect())
+}
+
+/// Starts comparing the running apps in the background, the first comparison only records the apps that are already running
+pub fn start_app_watcher(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut previous: Option<HashMap<u32, AppMetadata>> = None;
+
+        loop {
+            let current = match task::spawn_blocking(running_apps_by_pid).await {
+                Ok(Ok(apps)) => apps,
+                Ok(Err(e)) => {
+                    error!("Failed to get running apps: {}", e);
+                    tokio::time::sleep(APP_WATCH_INTERVAL).await;
+                    continue;
+                }
+                Err(e) => {
+                    error!("spawn_blocking error: {}", e);
+                    tokio::time::sleep(APP_WATCH_INTERVAL).await;
+                    continue;
+                }
+            };
+
+            if let Some(previous) = &previous {
+                for (pid, app) in previous {
+                    if !current.contains_key(pid) {
+                        let _ = app_handle.emit("app-terminated", app);
+                    }
+                }
+
+                let started: Vec<AppMetadata> = current
+                    .iter()
+                    .filter(|(pid, _)| !previous.contains_key(pid))
+                    .map(|(_, app)| app.clone())
+                    .collect();
+
+                if !started.is_empty() {
+                    let app_handle_for_icons = app_handle.clone();
+                    let started = task::spawn_blocking(move || {
+                        let cache = icon_cache(&app_handle_for_icons);
+                        let mut started = started;
+                        for app in started.iter_mut() {
+                            load_app_icon(cache, app);
+                        }
+                        if let Ok(mut cache) = cache.lock() {
+                            cache.save();
+                        }
+                        started
+                    })
+                    .await
+                    .unwrap_or_default();
+
+                    for app in started {
+                        let _ = app_handle.emit("app-started", app);
+                    }
+                }
+            }
+
+            previous = Some(current);
+            tokio::time::sleep(APP_WATCH_INTERVAL).await;
+        }
+    });
+}
@@ -0,0 +1,417 @@
+/*
+This file contains the Linux implementation of the app launcher. Installed apps are the .desktop entries in the XDG data directories, running apps are the processes in /proc whose executable matches one of those entries, so both point at the same .desktop file
+*/
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::app_handler::{fallback_icon, AppMetadata, AppPlatform};
+
+const DESKTOP_ENTRY_GROUP: &str = "[Desktop Entry]";
+const DESKTOP_FILE_EXTENSION: &str = "desktop";
+
+/// Executables that start other programs, processes running them can't be matched to an entry
+const GENERIC_LAUNCHERS: [&str; 6] = ["flatpak", "snap", "sh", "bash", "python3", "java"];
+
+/// Icon sizes to look for in the icon theme, the first one that exists is used
+const ICON_SIZES: [&str; 6] = ["64x64", "48x48", "128x128", "256x256", "32x32", "scalable"];
+
+/// The parts of a .desktop file the launcher needs
+#[derive(Debug, Clone)]
+struct DesktopEntry {
+    name: String,
+    exec: String,
+    icon: Option<String>,
+    path: PathBuf,
+}
+
+impl DesktopEntry {
+    /// Parses the [Desktop Entry] group, returns None for entries that shouldn't be shown in a launcher
+    fn parse(path: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(path).ok()?;
+
+        let mut in_entry_group = false;
+        let mut fields: HashMap<&str, &str> = HashMap::new();
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.starts_with('[') {
+                in_entry_group = line == DESKTOP_ENTRY_GROUP;
+                continue;
+            }
+            if !in_entry_group || line.starts_with('#') {
+                continue;
+            }
+            // localized keys like Name[de] are skipped, the plain key is the fallback for every locale
+            if let Some((key, value)) = line.split_once('=') {
+                fields.entry(key.trim()).or_insert(value.trim());
+            }
+        }
+
+        let is_true = |key: &str| {
+            fields
+                .get(key)
+                .map(|value| *value == "true")
+                .unwrap_or(false)
+        };
+        if fields.get("Type") != Some(&"Application") || is_true("NoDisplay") || is_true("Hidden") {
+            return None;
+        }
+
+        Some(Self {
+            name: fields.get("Name")?.to_string(),
+            exec: fields.get("Exec")?.to_string(),
+            icon: fields
+                .get("Icon")
+                .filter(|icon| !icon.is_empty())
+                .map(|icon| icon.to_string()),
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Exec line without the field codes like %f and %U, we never pass files to the app
+    fn command_line(&self) -> String {
+        self.exec
+            .split_whitespace()
+            .filter(|arg| !(arg.len() == 2 && arg.starts_with('%')))
+            .collect::<Vec<_>>()
+            .join(" ")
+            .replace("%%", "%")
+    }
+
+    /// File name of the executable, used to match running processes to the entry
+    fn executable_name(&self) -> Option<String> {
+        let mut args = self.exec.split_whitespace();
+        let mut program = args.next()?;
+        // "env FOO=bar app" is common in .desktop files
+        if program == "env" {
+            program = args.find(|arg| !arg.contains('='))?;
+        }
+
+        Path::new(program.trim_matches('"'))
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    }
+
+    fn to_app_metadata(&self, pid: Option<u32>) -> AppMetadata {
+        AppMetadata {
+            name: self.name.clone(),
+            path: self.path.to_string_lossy().into_owned(),
+            pid,
+            icon: None,
+            resource_usage: None,
+            frecency: 0,
+            recent_documents: Vec::new(),
+            bundle_id: None,
+            version: None,
+            last_used: None,
+        }
+    }
+}
+
+/// Data directories in XDG order, the user's own directory comes first so its entries override the system ones
+fn xdg_data_dirs() -> Vec<PathBuf> {
+    let mut data_dirs: Vec<PathBuf> = Vec::new();
+
+    match std::env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+        Some(data_home) => data_dirs.push(PathBuf::from(data_home)),
+        None => {
+            if let Some(home) = dirs::home_dir() {
+                data_dirs.push(home.join(".local/share"));
+            }
+        }
+    }
+
+    let system_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    data_dirs.extend(system_dirs.split(':').map(PathBuf::from));
+
+    // Flatpak apps export their entries outside of the default directories
+    if let Some(home) = dirs::home_dir() {
+        data_dirs.push(home.join(".local/share/flatpak/exports/share"));
+    }
+    data_dirs.push(PathBuf::from("/var/lib/flatpak/exports/share"));
+
+    data_dirs
+}
+
+fn collect_desktop_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_desktop_files(&path, files);
+        } else if path.extension().and_then(|ext| ext.to_str()) == Some(DESKTOP_FILE_EXTENSION) {
+            files.push(path);
+        }
+    }
+}
+
+/// Reads every visible .desktop entry, an entry in an earlier data directory hides the one with the same id in later ones
+fn desktop_entries() -> Vec<DesktopEntry> {
+    let mut seen_ids: HashSet<String> = HashSet::new();
+    let mut entries: Vec<DesktopEntry> = Vec::new();
+
+    for data_dir in xdg_data_dirs() {
+        let applications_dir = data_dir.join("applications");
+        let mut files: Vec<PathBuf> = Vec::new();
+        collect_desktop_files(&applications_dir, &mut files);
+
+        for file in files {
+            // the desktop file id is the path below the applications directory
+            let id = file
+                .strip_prefix(&applications_dir)
+                .map(|relative| relative.to_string_lossy().replace('/', "-"))
+                .unwrap_or_default();
+            if !seen_ids.insert(id) {
+                continue;
+            }
+
+            if let Some(entry) = DesktopEntry::parse(&file) {
+                entries.push(entry);
+            }
+        }
+    }
+
+    entries
+}
+
+/// Reads the executable name of a process from /proc, falls back to the command line for processes we can't readlink
+fn process_executable_name(pid: u32) -> Option<String> {
+    let proc_dir = PathBuf::from(format!("/proc/{}", pid));
+
+    if let Ok(exe) = fs::read_link(proc_dir.join("exe")) {
+        return exe
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned());
+    }
+
+    let cmdline = fs::read(proc_dir.join("cmdline")).ok()?;
+    let program = cmdline.split(|b| *b == 0).next()?;
+    Path::new(&*String::from_utf8_lossy(program))
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+fn process_ids() -> Vec<u32> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+
+    let mut pids: Vec<u32> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .collect();
+    // the lowest pid is usually the app's main process, the others are its helpers
+    pids.sort_unstable();
+    pids
+}
+
+/// Looks the icon name up in the hicolor theme and pixmaps, absolute paths are used as they are
+fn resolve_icon_path(icon: &str) -> Option<PathBuf> {
+    let icon_path = Path::new(icon);
+    if icon_path.is_absolute() {
+        return icon_path.is_file().then(|| icon_path.to_path_buf());
+    }
+
+    let data_dirs = xdg_data_dirs();
+    for size in ICON_SIZES {
+        for data_dir in &data_dirs {
+            let apps_dir = data_dir.join("icons/hicolor").join(size).join("apps");
+            for extension in ["png", "svg"] {
+                let candidate = apps_dir.join(format!("{}.{}", icon, extension));
+                if candidate.is_file() {
+                    return Some(candidate);
+                }
+            }
+        }
+    }
+
+    ["png", "svg", "xpm"]
+        .iter()
+        .map(|extension| PathBuf::from(format!("/usr/share/pixmaps/{}.{}", icon, extension)))
+        .find(|candidate| candidate.is_file())
+}
+
+fn icon_data_url(path: &Path) -> Option<String> {
+    let mime_type = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("png") => "image/png",
+        Some("svg") => "image/svg+xml",
+        _ => return None,
+    };
+
+    let bytes = fs::read(path).ok()?;
+    Some(format!(
+        "data:{};base64,{}",
+        mime_type,
+        STANDARD.encode(bytes)
+    ))
+}
+
+/// Finds the ids of the windows owned by the process with wmctrl, only works on X11
+fn window_ids_for_pid(pid: u32) -> Vec<String> {
+    let Ok(output) = Command::new("wmctrl").arg("-lp").output() else {
+        return Vec::new();
+    };
+    if !output.status.success() {
+        return Vec::new();
+    }
+
+    // each line is "<window id> <desktop> <pid> <host> <title>"
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let window_id = columns.next()?;
+            let window_pid = columns.nth(1)?.parse::<u32>().ok()?;
+            (window_pid == pid).then(|| window_id.to_string())
+        })
+        .collect()
+}
+
+fn run_wmctrl(args: &[&str]) -> bool {
+    Command::new("wmctrl")
+        .args(args)
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+pub struct LinuxApps;
+
+impl AppPlatform for LinuxApps {
+    fn combined_apps() -> Result<(Vec<AppMetadata>, Vec<AppMetadata>), String> {
+        // both lists come from the same entries, so only read them once
+        let entries = desktop_entries();
+        Ok((running_apps_from(&entries), installed_apps_from(&entries)))
+    }
+
+    fn installed_apps() -> Result<Vec<AppMetadata>, String> {
+        Ok(installed_apps_from(&desktop_entries()))
+    }
+
+    fn running_apps() -> Result<Vec<AppMetadata>, String> {
+        Ok(running_apps_from(&desktop_entries()))
+    }
+
+    fn app_icon(path: &str) -> Result<Option<String>, String> {
+        let icon = DesktopEntry::parse(Path::new(path))
+            .and_then(|entry| entry.icon)
+            .and_then(|icon| resolve_icon_path(&icon))
+            .and_then(|icon_path| icon_data_url(&icon_path));
+
+        Ok(Some(icon.unwrap_or_else(|| fallback_icon(path))))
+    }
+
+    /// Activating another app's window isn't possible on Wayland, the launcher launches the app instead
+    /// which brings the existing window to the front for single instance apps
+    fn switch_to_app(pid: u32) -> bool {
+        let Some(window_id) = window_ids_for_pid(pid).into_iter().next() else {
+            return false;
+        };
+
+        run_wmctrl(&["-i", "-a", &window_id])
+    }
+
+    fn launch_app(path: &str) -> bool {
+        let mut command = if Path::new(path).extension().and_then(|ext| ext.to_str())
+            == Some(DESKTOP_FILE_EXTENSION)
+        {
+            match DesktopEntry::parse(Path::new(path)) {
+                Some(entry) => {
+                    // Exec lines use shell quoting rules
+                    let mut command = Command::new("sh");
+                    command.args(["-c", &entry.command_line()]);
+                    command
+                }
+                None => return false,
+            }
+        } else {
+            let mut command = Command::new("xdg-open");
+            command.arg(path);
+            command
+        };
+
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .is_ok()
+    }
+
+    fn force_quit_app(pid: u32) -> bool {
+        unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) == 0 }
+    }
+
+    /// X11 has no hidden state for apps, hiding minimizes the app like minimize does
+    fn hide_app(pid: u32) -> bool {
+        Self::minimize_app(pid)
+    }
+
+    /// The hidden state is how EWMH window managers mark minimized windows
+    fn minimize_app(pid: u32) -> bool {
+        let mut minimized_any = false;
+        for window_id in window_ids_for_pid(pid) {
+            minimized_any |= run_wmctrl(&["-i", "-r", &window_id, "-b", "add,hidden"]);
+        }
+        minimized_any
+    }
+
+    fn is_process_running(pid: u32) -> bool {
+        // zombies keep their /proc entry until the parent reaps them
+        fs::read_to_string(format!("/proc/{}/stat", pid))
+            .ok()
+            .and_then(|stat| {
+                let state = stat.rsplit_once(')')?.1.split_whitespace().next()?;
+                Some(state != "Z" && state != "X")
+            })
+            .unwrap_or(false)
+    }
+}
+
+fn installed_apps_from(entries: &[DesktopEntry]) -> Vec<AppMetadata> {
+    let mut apps: Vec<AppMetadata> = entries
+        .iter()
+        .map(|entry| entry.to_app_metadata(None))
+        .collect();
+    apps.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    apps
+}
+
+fn running_apps_from(entries: &[DesktopEntry]) -> Vec<AppMetadata> {
+    let entries_by_executable: HashMap<String, &DesktopEntry> = entries
+        .iter()
+        .filter_map(|entry| Some((entry.executable_name()?, entry)))
+        .filter(|(name, _)| !GENERIC_LAUNCHERS.contains(&name.as_str()))
+        .collect();
+
+    let current_pid = std::process::id();
+    let mut matched: HashSet<PathBuf> = HashSet::new();
+    let mut apps: Vec<AppMetadata> = Vec::new();
+
+    for pid in process_ids() {
+        if pid == current_pid {
+            continue;
+        }
+        let Some(entry) =
+            process_executable_name(pid).and_then(|name| entries_by_executable.get(&name).copied())
+        else {
+            continue;
+        };
+
+        if matched.insert(entry.path.clone()) {
+            apps.push(entry.to_app_metadata(Some(pid)));
+        }
+    }
+
+    apps.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    apps
+}
@@ -0,0 +1,122 @@
+/*
+This file contains the macOS implementation of the app launcher. Everything goes through the Swift bridge in swift/apps.swift, which uses NSWorkspace for the running apps and scans the Applications folders for the installed ones
+*/
+
+use serde::Deserialize;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use tracing::error;
+
+use crate::app_handler::{AppMetadata, AppPlatform, RecentDocument};
+
+extern "C" {
+    fn get_combined_apps_swift() -> *mut c_char;
+    fn get_running_apps_swift() -> *mut c_char;
+    fn get_app_icon_swift(path: *const c_char) -> *mut c_char;
+    fn switch_to_app_swift(pid: i32) -> bool;
+    fn force_quit_app_swift(pid: i32) -> bool;
+    fn hide_app_swift(pid: i32) -> bool;
+    fn minimize_app_swift(pid: i32) -> bool;
+    fn restart_app_swift(path: *const c_char) -> bool;
+    fn check_process_running_swift(pid: i32) -> bool;
+    fn get_recent_documents_swift(path: *const c_char, limit: i32) -> *mut c_char;
+    fn free_string_swift(pointer: *mut c_char);
+}
+
+#[derive(Deserialize)]
+struct AppsResponse {
+    running_apps: Vec<AppMetadata>,
+    installed_apps: Vec<AppMetadata>,
+}
+
+/// Copies a string returned by the Swift bridge and frees it
+fn take_swift_string(pointer: *mut c_char) -> Result<Option<String>, String> {
+    if pointer.is_null() {
+        return Ok(None);
+    }
+
+    unsafe {
+        let c_str = CStr::from_ptr(pointer);
+        let result = c_str.to_str().map(str::to_owned);
+        free_string_swift(pointer);
+        result.map(Some).map_err(|_| "Invalid UTF-8".to_string())
+    }
+}
+
+pub struct MacApps;
+
+impl AppPlatform for MacApps {
+    fn combined_apps() -> Result<(Vec<AppMetadata>, Vec<AppMetadata>), String> {
+        let apps_json = take_swift_string(unsafe { get_combined_apps_swift() })?
+            .ok_or_else(|| "Failed to get apps".to_string())?;
+
+        let apps_response: AppsResponse =
+            serde_json::from_str(&apps_json).map_err(|e| e.to_string())?;
+
+        Ok((apps_response.running_apps, apps_response.installed_apps))
+    }
+
+    fn installed_apps() -> Result<Vec<AppMetadata>, String> {
+        Self::combined_apps().map(|(_, installed_apps)| installed_apps)
+    }
+
+    fn running_apps() -> Result<Vec<AppMetadata>, String> {
+        let apps_json = take_swift_string(unsafe { get_running_apps_swift() })?
+            .ok_or_else(|| "Failed to get apps".to_string())?;
+
+        serde_json::from_str(&apps_json).map_err(|e| e.to_string())
+    }
+
+    fn app_icon(path: &str) -> Result<Option<String>, String> {
+        let path_cstring =
+            CString::new(path).map_err(|_| "Failed to create C string".to_string())?;
+
+        take_swift_string(unsafe { get_app_icon_swift(path_cstring.as_ptr()) })
+    }
+
+    fn switch_to_app(pid: u32) -> bool {
+        unsafe { switch_to_app_swift(pid as i32) }
+    }
+
+    fn launch_app(path: &str) -> bool {
+        let Ok(path_cstring) = CString::new(path) else {
+            return false;
+        };
+
+        unsafe { restart_app_swift(path_cstring.as_ptr()) }
+    }
+
+    fn force_quit_app(pid: u32) -> bool {
+        unsafe { force_quit_app_swift(pid as i32) }
+    }
+
+    fn hide_app(pid: u32) -> bool {
+        unsafe { hide_app_swift(pid as i32) }
+    }
+
+    fn minimize_app(pid: u32) -> bool {
+        unsafe { minimize_app_swift(pid as i32) }
+    }
+
+    fn is_process_running(pid: u32) -> bool {
+        unsafe { check_process_running_swift(pid as i32) }
+    }
+
+    fn recent_documents(path: &str, limit: usize) -> Vec<RecentDocument> {
+        let Ok(path_cstring) = CString::new(path) else {
+            return Vec::new();
+        };
+
+        let documents_json = match take_swift_string(unsafe {
+            get_recent_documents_swift(path_cstring.as_ptr(), limit as i32)
+        }) {
+            Ok(Some(json)) => json,
+            _ => return Vec::new(),
+        };
+
+        serde_json::from_str(&documents_json).unwrap_or_else(|e| {
+            error!("Failed to parse recent documents of {}: {}", path, e);
+            Vec::new()
+        })
+    }
+}
@@ -0,0 +1,313 @@
+/*
+This file contains the Windows implementation of the app launcher. Installed apps come from the Start Menu shortcuts and the App Paths registry keys, running apps are the processes that own a visible top-level window
+*/
+
+use std::collections::HashSet;
+use std::fs;
+use std::os::windows::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::error;
+use windows::core::{BOOL, PWSTR};
+use windows::Win32::Foundation::{CloseHandle, HANDLE, HWND, LPARAM};
+use windows::Win32::System::Threading::{
+    GetExitCodeProcess, OpenProcess, QueryFullProcessImageNameW, TerminateProcess,
+    PROCESS_ACCESS_RIGHTS, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+    PROCESS_TERMINATE,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    EnumWindows, GetWindowTextLengthW, GetWindowThreadProcessId, IsIconic, IsWindowVisible,
+    SetForegroundWindow, ShowWindow, SW_MINIMIZE, SW_RESTORE,
+};
+use winreg::enums::{HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE};
+use winreg::RegKey;
+
+use crate::app_handler::{fallback_icon, AppMetadata, AppPlatform};
+
+const START_MENU_PROGRAMS_DIR: &str = r"Microsoft\Windows\Start Menu\Programs";
+const APP_PATHS_KEY: &str = r"SOFTWARE\Microsoft\Windows\CurrentVersion\App Paths";
+
+/// Exit code GetExitCodeProcess returns while the process is still running
+const STILL_ACTIVE: u32 = 259;
+
+/// Keeps the launched app from opening a console window
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// Closes the process handle when dropped
+struct ProcessHandle(HANDLE);
+
+impl ProcessHandle {
+    fn open(pid: u32, access: PROCESS_ACCESS_RIGHTS) -> Option<Self> {
+        unsafe { OpenProcess(access, false, pid) }.ok().map(Self)
+    }
+}
+
+impl Drop for ProcessHandle {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = CloseHandle(self.0);
+        }
+    }
+}
+
+fn process_image_path(pid: u32) -> Option<String> {
+    let process = ProcessHandle::open(pid, PROCESS_QUERY_LIMITED_INFORMATION)?;
+
+    let mut buffer = [0u16; 1024];
+    let mut size = buffer.len() as u32;
+    unsafe {
+        QueryFullProcessImageNameW(
+            process.0,
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut size,
+        )
+    }
+    .ok()?;
+
+    Some(String::from_utf16_lossy(&buffer[..size as usize]))
+}
+
+unsafe extern "system" fn collect_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    let windows = &mut *(lparam.0 as *mut Vec<(HWND, u32)>);
+
+    // windows without a title are tool windows and other helpers, not something the user switches to
+    if IsWindowVisible(hwnd).as_bool() && GetWindowTextLengthW(hwnd) > 0 {
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        if pid != 0 {
+            windows.push((hwnd, pid));
+        }
+    }
+
+    true.into()
+}
+
+/// Returns the visible top-level windows with the id of the process that owns them
+fn visible_windows() -> Vec<(HWND, u32)> {
+    let mut windows: Vec<(HWND, u32)> = Vec::new();
+    let result = unsafe {
+        EnumWindows(
+            Some(collect_window),
+            LPARAM(&mut windows as *mut Vec<(HWND, u32)> as isize),
+        )
+    };
+    if let Err(e) = result {
+        error!("Failed to enumerate windows: {}", e);
+    }
+    windows
+}
+
+fn app_name(path: &Path) -> Option<String> {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+}
+
+/// Collects the .lnk files in the Start Menu folder, uninstallers and links to documentation are skipped
+fn collect_shortcuts(dir: &Path, apps: &mut Vec<AppMetadata>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_shortcuts(&path, apps);
+            continue;
+        }
+
+        let is_shortcut = path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("lnk"))
+            .unwrap_or(false);
+        let Some(name) = app_name(&path) else {
+            continue;
+        };
+        let lowercase_name = name.to_lowercase();
+        if !is_shortcut
+            || lowercase_name.contains("uninstall")
+            || lowercase_name.contains("readme")
+            || lowercase_name.contains("documentation")
+        {
+            continue;
+        }
+
+        apps.push(AppMetadata {
+            name,
+            path: path.to_string_lossy().into_owned(),
+            pid: None,
+            icon: None,
+            resource_usage: None,
+            frecency: 0,
+            recent_documents: Vec::new(),
+            bundle_id: None,
+            version: None,
+            last_used: None,
+        });
+    }
+}
+
+fn start_menu_dirs() -> Vec<PathBuf> {
+    let mut program_dirs: Vec<PathBuf> = Vec::new();
+    if let Some(program_data) = std::env::var_os("ProgramData") {
+        program_dirs.push(PathBuf::from(program_data).join(START_MENU_PROGRAMS_DIR));
+    }
+    // the roaming app data directory holds the user's own Start Menu
+    if let Some(app_data) = dirs::data_dir() {
+        program_dirs.push(app_data.join(START_MENU_PROGRAMS_DIR));
+    }
+    program_dirs
+}
+
+/// Reads the executables registered under App Paths, the key names are the executable names
+fn collect_app_paths(apps: &mut Vec<AppMetadata>) {
+    for hive in [HKEY_LOCAL_MACHINE, HKEY_CURRENT_USER] {
+        let Ok(app_paths) = RegKey::predef(hive).open_subkey(APP_PATHS_KEY) else {
+            continue;
+        };
+
+        for key_name in app_paths.enum_keys().flatten() {
+            let Ok(path) = app_paths
+                .open_subkey(&key_name)
+                .and_then(|key| key.get_value::<String, _>(""))
+            else {
+                continue;
+            };
+
+            let path = path.trim().trim_matches('"').to_string();
+            if !Path::new(&path).is_file() {
+                continue;
+            }
+            let Some(name) = app_name(Path::new(&key_name)) else {
+                continue;
+            };
+
+            apps.push(AppMetadata {
+                name,
+                path,
+                pid: None,
+                icon: None,
+                resource_usage: None,
+                frecency: 0,
+                recent_documents: Vec::new(),
+                bundle_id: None,
+                version: None,
+                last_used: None,
+            });
+        }
+    }
+}
+
+pub struct WindowsApps;
+
+impl AppPlatform for WindowsApps {
+    fn installed_apps() -> Result<Vec<AppMetadata>, String> {
+        let mut apps: Vec<AppMetadata> = Vec::new();
+        for dir in start_menu_dirs() {
+            collect_shortcuts(&dir, &mut apps);
+        }
+        collect_app_paths(&mut apps);
+
+        // the same app is often in both Start Menus and in App Paths, the first one wins
+        let mut seen: HashSet<String> = HashSet::new();
+        apps.retain(|app| seen.insert(app.name.to_lowercase()));
+        apps.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+
+        Ok(apps)
+    }
+
+    fn running_apps() -> Result<Vec<AppMetadata>, String> {
+        let mut seen: HashSet<u32> = HashSet::new();
+        let current_pid = std::process::id();
+
+        let mut apps: Vec<AppMetadata> = visible_windows()
+            .into_iter()
+            .filter(|(_, pid)| *pid != current_pid && seen.insert(*pid))
+            .filter_map(|(_, pid)| {
+                let path = process_image_path(pid)?;
+                Some(AppMetadata {
+                    name: app_name(Path::new(&path))?,
+                    path,
+                    pid: Some(pid),
+                    icon: None,
+                    resource_usage: None,
+                    frecency: 0,
+                    recent_documents: Vec::new(),
+                    bundle_id: None,
+                    version: None,
+                    last_used: None,
+                })
+            })
+            .collect();
+        apps.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+
+        Ok(apps)
+    }
+
+    /// Icons aren't extracted from the executables yet, every app gets the letter icon
+    fn app_icon(path: &str) -> Result<Option<String>, String> {
+        Ok(Some(fallback_icon(path)))
+    }
+
+    fn switch_to_app(pid: u32) -> bool {
+        let Some((hwnd, _)) = visible_windows()
+            .into_iter()
+            .find(|(_, window_pid)| *window_pid == pid)
+        else {
+            return false;
+        };
+
+        unsafe {
+            if IsIconic(hwnd).as_bool() {
+                let _ = ShowWindow(hwnd, SW_RESTORE);
+            }
+            SetForegroundWindow(hwnd).as_bool()
+        }
+    }
+
+    fn launch_app(path: &str) -> bool {
+        // start resolves shortcuts and executables the same way double clicking them does
+        Command::new("cmd")
+            .args(["/C", "start", "", path])
+            .creation_flags(CREATE_NO_WINDOW)
+            .spawn()
+            .is_ok()
+    }
+
+    fn force_quit_app(pid: u32) -> bool {
+        let Some(process) = ProcessHandle::open(pid, PROCESS_TERMINATE) else {
+            return false;
+        };
+
+        unsafe { TerminateProcess(process.0, 1) }.is_ok()
+    }
+
+    /// Windows has no hidden state for apps, hiding minimizes the app like minimize does
+    fn hide_app(pid: u32) -> bool {
+        Self::minimize_app(pid)
+    }
+
+    fn minimize_app(pid: u32) -> bool {
+        let mut minimized_any = false;
+        for (hwnd, _) in visible_windows()
+            .into_iter()
+            .filter(|(_, window_pid)| *window_pid == pid)
+        {
+            unsafe {
+                let _ = ShowWindow(hwnd, SW_MINIMIZE);
+            }
+            minimized_any = true;
+        }
+        minimized_any
+    }
+
+    fn is_process_running(pid: u32) -> bool {
+        let Some(process) = ProcessHandle::open(pid, PROCESS_QUERY_LIMITED_INFORMATION) else {
+            return false;
+        };
+
+        let mut exit_code = 0u32;
+        unsafe { GetExitCodeProcess(process.0, &mut exit_code) }.is_ok()
+            && exit_code == STILL_ACTIVE
+    }
+}
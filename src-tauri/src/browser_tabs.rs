@@ -0,0 +1,180 @@
+/*
+This file contains the browser tab search. The open tabs of Safari and Chrome are read with AppleScript through the Swift bridge, so the launcher can list them and focus one. When the index_browser_tabs setting is on the titles and URLs are also kept in memory and searched by the launcher next to apps and files
+*/
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::settings::SettingsManagerState;
+
+#[cfg(target_os = "macos")]
+use std::ffi::{CStr, CString};
+#[cfg(target_os = "macos")]
+use std::os::raw::c_char;
+
+#[cfg(target_os = "macos")]
+extern "C" {
+    fn get_browser_tabs_swift() -> *mut c_char;
+    fn focus_browser_tab_swift(browser: *const c_char, window_index: i32, tab_index: i32) -> bool;
+    fn free_string_swift(pointer: *mut c_char);
+}
+
+/// Searches reuse the tabs read within this window instead of running the AppleScript on every keystroke
+const TAB_INDEX_MAX_AGE: Duration = Duration::from_secs(10);
+
+/// Number of matching tabs returned when the caller doesn't ask for a specific number
+const DEFAULT_TAB_SEARCH_LIMIT: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowserTab {
+    /// "safari" or "chrome"
+    pub browser: String,
+    /// 1-based like AppleScript, only valid until the windows are reordered
+    pub window_index: i32,
+    pub tab_index: i32,
+    pub title: String,
+    pub url: String,
+}
+
+/// Tabs from the last read and when they were read
+static TAB_INDEX: Mutex<Option<(Instant, Vec<BrowserTab>)>> = Mutex::new(None);
+
+/// AppleScript calls are serialized, the scripting bridge doesn't like concurrent scripts
+#[cfg(target_os = "macos")]
+static SCRIPT_LOCK: Mutex<()> = Mutex::new(());
+
+#[cfg(target_os = "macos")]
+fn read_browser_tabs() -> Result<Vec<BrowserTab>, String> {
+    let _guard = SCRIPT_LOCK.lock().map_err(|e| e.to_string())?;
+
+    let tabs_json_ptr = unsafe { get_browser_tabs_swift() };
+    if tabs_json_ptr.is_null() {
+        return Err("Failed to get browser tabs".to_string());
+    }
+
+    let tabs_json = unsafe {
+        let c_str = CStr::from_ptr(tabs_json_ptr);
+        let result = c_str
+            .to_str()
+            .map_err(|_| "Invalid UTF-8".to_string())?
+            .to_owned();
+        free_string_swift(tabs_json_ptr);
+        result
+    };
+
+    serde_json::from_str(&tabs_json).map_err(|e| e.to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn read_browser_tabs() -> Result<Vec<BrowserTab>, String> {
+    Err("Browser tabs are only supported on macOS".to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn focus_tab(tab: &BrowserTab) -> bool {
+    let _guard = SCRIPT_LOCK.lock();
+
+    let Ok(browser) = CString::new(tab.browser.as_str()) else {
+        return false;
+    };
+    unsafe { focus_browser_tab_swift(browser.as_ptr(), tab.window_index, tab.tab_index) }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn focus_tab(_tab: &BrowserTab) -> bool {
+    false
+}
+
+/// Reads the tabs and stores them in the in-memory index
+fn refresh_tab_index() -> Result<Vec<BrowserTab>, String> {
+    let tabs = read_browser_tabs()?;
+    if let Ok(mut index) = TAB_INDEX.lock() {
+        *index = Some((Instant::now(), tabs.clone()));
+    }
+    Ok(tabs)
+}
+
+fn is_tab_indexing_enabled(app_handle: &AppHandle) -> bool {
+    app_handle
+        .try_state::<SettingsManagerState>()
+        .and_then(|state| state.0.get_settings().ok())
+        .and_then(|settings| settings.index_browser_tabs)
+        .unwrap_or(false)
+}
+
+/// Every word of the query has to be in the title or the URL
+fn tab_matches(tab: &BrowserTab, query_words: &[String]) -> bool {
+    let title = tab.title.to_lowercase();
+    let url = tab.url.to_lowercase();
+    query_words
+        .iter()
+        .all(|word| title.contains(word.as_str()) || url.contains(word.as_str()))
+}
+
+/// Returns the open tabs of all running browsers, browsers that aren't running are never launched
+#[tauri::command]
+pub async fn get_browser_tabs() -> Result<Vec<BrowserTab>, String> {
+    tokio::task::spawn_blocking(refresh_tab_index)
+        .await
+        .map_err(|e| format!("spawn_blocking error: {e}"))?
+}
+
+/// Selects the tab and brings its browser window to the front
+#[tauri::command]
+pub async fn focus_browser_tab(tab: BrowserTab) -> Result<(), String> {
+    let focused = tokio::task::spawn_blocking(move || focus_tab(&tab))
+        .await
+        .map_err(|e| format!("spawn_blocking error: {e}"))?;
+
+    if !focused {
+        return Err("Failed to focus the browser tab".to_string());
+    }
+
+    // focusing moves the window to the front, which changes the window indexes
+    if let Ok(mut index) = TAB_INDEX.lock() {
+        *index = None;
+    }
+    Ok(())
+}
+
+/// Searches the titles and URLs of the open tabs for the launcher, returns nothing when tab indexing is off
+#[tauri::command]
+pub async fn search_browser_tabs(
+    query: String,
+    limit: Option<usize>,
+    app_handle: AppHandle,
+) -> Result<Vec<BrowserTab>, String> {
+    if !is_tab_indexing_enabled(&app_handle) {
+        return Ok(Vec::new());
+    }
+
+    let query_words: Vec<String> = query
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect();
+    if query_words.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let cached = TAB_INDEX
+        .lock()
+        .ok()
+        .and_then(|index| index.clone())
+        .filter(|(read_at, _)| read_at.elapsed() < TAB_INDEX_MAX_AGE)
+        .map(|(_, tabs)| tabs);
+
+    let tabs = match cached {
+        Some(tabs) => tabs,
+        None => tokio::task::spawn_blocking(refresh_tab_index)
+            .await
+            .map_err(|e| format!("spawn_blocking error: {e}"))??,
+    };
+
+    Ok(tabs
+        .into_iter()
+        .filter(|tab| tab_matches(tab, &query_words))
+        .take(limit.unwrap_or(DEFAULT_TAB_SEARCH_LIMIT))
+        .collect())
+}
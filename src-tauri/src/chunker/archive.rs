@@ -0,0 +1,286 @@
+use async_trait::async_trait;
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use tracing::{debug, warn};
+use zip::ZipArchive;
+
+use crate::embedder::Embedder;
+use crate::file_processor::FileMetadata;
+
+use super::common::{Chunk, ChunkerConfig, ChunkerResult};
+use super::{Chunker, ChunkerError, ChunkerOrchestrator};
+
+/// Entries larger than this are skipped so a small archive can't fill the disk when it's extracted
+const MAX_ENTRY_SIZE: u64 = 100 * 1024 * 1024;
+
+/// Most entries chunked from one archive
+const MAX_ENTRIES: usize = 1000;
+
+#[derive(Debug, Clone, Copy)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveKind {
+    fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+/// An entry of the archive extracted to a temporary file so the inner chunkers can read it
+struct ArchiveEntry {
+    name: String,
+    path: PathBuf,
+    size: u64,
+}
+
+/// Parser for zip and tar archives, every entry is chunked by the chunker of its type
+/// The chunks point back into the archive with an `archive!entry` source path
+/// `.tar.gz` is matched on the full name, a plain `.gz` file like `app.log.gz` isn't an archive
+#[derive(Default)]
+pub struct ArchiveChunker {
+    /// Chunkers for the entries, built on first use since this chunker is itself registered in an orchestrator
+    inner: OnceLock<Arc<ChunkerOrchestrator>>,
+}
+
+impl ArchiveChunker {
+    fn inner(&self, config: &ChunkerConfig) -> Arc<ChunkerOrchestrator> {
+        self.inner
+            .get_or_init(|| Arc::new(ChunkerOrchestrator::new(config.clone())))
+            .clone()
+    }
+}
+
+#[async_trait]
+impl Chunker for ArchiveChunker {
+    fn name(&self) -> &str {
+        "archive"
+    }
+
+    fn supported_mime_types(&self) -> Vec<&str> {
+        vec!["application/zip", "application/x-tar"]
+    }
+
+    fn extensions(&self) -> Vec<&str> {
+        vec!["zip", "tar", "tgz"]
+    }
+
+    fn can_chunk_file_type(&self, path: &Path) -> bool {
+        ArchiveKind::from_path(path).is_some()
+    }
+
+    async fn chunk_file(
+        &self,
+        file: &FileMetadata,
+        config: &ChunkerConfig,
+        embedder: Arc<Embedder>,
+    ) -> ChunkerResult<Vec<(Chunk, Vec<f32>)>> {
+        debug!("Creating archive chunks for file {:?}", file.base.path);
+
+        let archive_path = PathBuf::from(&file.base.path);
+        let orchestrator = self.inner(config);
+        let (work_dir, entries) = extract_archive(&archive_path, orchestrator.clone()).await?;
+
+        let mut chunk_embeddings = Vec::new();
+        for entry in entries {
+            let mut entry_file = file.clone();
+            entry_file.base.path = entry.path.to_string_lossy().to_string();
+            entry_file.base.name = entry_file_name(&entry.name);
+            entry_file.extension = entry
+                .path
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_string())
+                .unwrap_or_default();
+            entry_file.size = entry.size as i64;
+
+            match orchestrator.chunk_file(&entry_file, embedder.clone()).await {
+                Ok(entry_chunks) => {
+                    let source_path = virtual_path(&archive_path, &entry.name);
+                    chunk_embeddings.extend(entry_chunks.into_iter().map(
+                        |(mut chunk, embedding)| {
+                            chunk.metadata.source_path = source_path.clone();
+                            (chunk, embedding)
+                        },
+                    ));
+                }
+                Err(e) => warn!("Skipping {} in {:?}: {}", entry.name, archive_path, e),
+            }
+        }
+
+        let _ = tokio::fs::remove_dir_all(&work_dir).await;
+        Ok(chunk_embeddings)
+    }
+
+    async fn extract_text(&self, path: &Path) -> ChunkerResult<String> {
+        let orchestrator = self.inner(&ChunkerConfig::default());
+        let (work_dir, entries) = extract_archive(path, orchestrator.clone()).await?;
+
+        let mut texts = Vec::new();
+        for entry in entries {
+            match orchestrator.extract_text(&entry.path).await {
+                Ok(text) => texts.push(format!("{}\n{}", entry.name, text)),
+                Err(e) => warn!("Skipping {} in {:?}: {}", entry.name, path, e),
+            }
+        }
+
+        let _ = tokio::fs::remove_dir_all(&work_dir).await;
+        Ok(texts.join("\n\n"))
+    }
+}
+
+/// Path of an entry inside the archive, like `/path/to/notes.zip!docs/readme.md`
+fn virtual_path(archive_path: &Path, entry_name: &str) -> PathBuf {
+    PathBuf::from(format!("{}!{}", archive_path.display(), entry_name))
+}
+
+fn entry_file_name(entry_name: &str) -> String {
+    entry_name
+        .rsplit('/')
+        .next()
+        .unwrap_or(entry_name)
+        .to_string()
+}
+
+fn archive_error(e: impl std::fmt::Display) -> ChunkerError {
+    ChunkerError::Other(format!("Failed to read archive: {}", e))
+}
+
+/// Extracts the entries an inner chunker can handle into a new temporary directory
+/// The directory is returned so it can be removed once the entries are chunked
+async fn extract_archive(
+    archive_path: &Path,
+    orchestrator: Arc<ChunkerOrchestrator>,
+) -> ChunkerResult<(PathBuf, Vec<ArchiveEntry>)> {
+    let kind = ArchiveKind::from_path(archive_path).ok_or_else(|| {
+        ChunkerError::UnsupportedType(format!("Not a zip or tar archive: {:?}", archive_path))
+    })?;
+
+    let archive_path = archive_path.to_path_buf();
+    let work_dir = std::env::temp_dir().join(format!(
+        "kita-archive-{}-{}",
+        std::process::id(),
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+
+    let extract_dir = work_dir.clone();
+    let entries = tokio::task::spawn_blocking(move || -> ChunkerResult<Vec<ArchiveEntry>> {
+        std::fs::create_dir_all(&extract_dir)?;
+
+        let file = File::open(&archive_path)?;
+        let mut entries = Vec::new();
+
+        match kind {
+            ArchiveKind::Zip => {
+                let mut zip = ZipArchive::new(file).map_err(archive_error)?;
+                for index in 0..zip.len() {
+                    if entries.len() >= MAX_ENTRIES {
+                        break;
+                    }
+                    let mut entry = zip.by_index(index).map_err(archive_error)?;
+                    if entry.is_dir() {
+                        continue;
+                    }
+                    let name = entry.name().to_string();
+                    let size = entry.size();
+                    entries.extend(extract_entry(
+                        name,
+                        size,
+                        &mut entry,
+                        &extract_dir,
+                        entries.len(),
+                        &orchestrator,
+                    )?);
+                }
+            }
+            ArchiveKind::Tar | ArchiveKind::TarGz => {
+                let reader: Box<dyn Read> = match kind {
+                    ArchiveKind::TarGz => Box::new(GzDecoder::new(file)),
+                    _ => Box::new(file),
+                };
+                let mut tar = tar::Archive::new(reader);
+                for entry in tar.entries()? {
+                    if entries.len() >= MAX_ENTRIES {
+                        break;
+                    }
+                    let mut entry = entry?;
+                    if !entry.header().entry_type().is_file() {
+                        continue;
+                    }
+                    let name = entry.path()?.to_string_lossy().to_string();
+                    let size = entry.size();
+                    entries.extend(extract_entry(
+                        name,
+                        size,
+                        &mut entry,
+                        &extract_dir,
+                        entries.len(),
+                        &orchestrator,
+                    )?);
+                }
+            }
+        }
+
+        Ok(entries)
+    })
+    .await
+    .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?;
+
+    match entries {
+        Ok(entries) => Ok((work_dir, entries)),
+        Err(e) => {
+            let _ = tokio::fs::remove_dir_all(&work_dir).await;
+            Err(e)
+        }
+    }
+}
+
+/// Writes the entry to the work directory when a chunker other than this one can handle it
+/// The temporary file keeps the entry's extension since that's how the chunkers are picked
+fn extract_entry(
+    name: String,
+    size: u64,
+    reader: &mut impl Read,
+    work_dir: &Path,
+    index: usize,
+    orchestrator: &ChunkerOrchestrator,
+) -> ChunkerResult<Option<ArchiveEntry>> {
+    let file_name = entry_file_name(&name);
+    // resource forks macOS adds to zips and hidden files
+    if name.starts_with("__MACOSX/") || file_name.starts_with('.') {
+        return Ok(None);
+    }
+    if size > MAX_ENTRY_SIZE {
+        debug!("Skipping {}, it's larger than the entry limit", name);
+        return Ok(None);
+    }
+
+    // nested archives aren't opened
+    match orchestrator.chunker_name_for_file(Path::new(&file_name)) {
+        Some(chunker) if chunker != "archive" => {}
+        _ => return Ok(None),
+    }
+
+    let path = match Path::new(&file_name).extension() {
+        Some(ext) => work_dir.join(format!("entry-{}.{}", index, ext.to_string_lossy())),
+        None => work_dir.join(format!("entry-{}", index)),
+    };
+
+    let mut output = File::create(&path)?;
+    std::io::copy(&mut reader.take(MAX_ENTRY_SIZE), &mut output)?;
+
+    Ok(Some(ArchiveEntry { name, path, size }))
+}
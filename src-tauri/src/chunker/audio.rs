@@ -0,0 +1,334 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, OnceLock};
+use tauri::Manager;
+use tracing::{debug, warn};
+
+use crate::embedder::Embedder;
+use crate::file_processor::FileMetadata;
+
+use super::common::{Chunk, ChunkMetadata, ChunkerConfig, ChunkerResult};
+use super::Chunker;
+use super::{util, ChunkerError};
+
+/// whisper.cpp command line tool, shipped in the resources next to llama-server
+const WHISPER_BINARY_NAME: &str = "whisper-cli";
+
+/// Multilingual whisper model, looked up in the models folder and then in the resources
+const WHISPER_MODEL_NAME: &str = "ggml-base.bin";
+
+const AUDIO_MIME_TYPES: [&str; 3] = ["audio/mpeg", "audio/x-wav", "audio/m4a"];
+
+/// Resource and models directories of the app, set at startup since the chunkers have no app handle
+static WHISPER_DIRS: OnceLock<Vec<PathBuf>> = OnceLock::new();
+
+#[derive(Deserialize)]
+struct WhisperOutput {
+    transcription: Vec<WhisperSegment>,
+}
+
+#[derive(Deserialize)]
+struct WhisperSegment {
+    offsets: WhisperOffsets,
+    text: String,
+}
+
+/// Start and end of a segment in milliseconds
+#[derive(Deserialize)]
+struct WhisperOffsets {
+    from: u64,
+    to: u64,
+}
+
+/// Parser for voice memos and recordings, transcribed with whisper.cpp into chunks that keep their time range
+#[derive(Default)]
+pub struct AudioChunker;
+
+#[async_trait]
+impl Chunker for AudioChunker {
+    fn name(&self) -> &str {
+        "audio"
+    }
+
+    fn supported_mime_types(&self) -> Vec<&str> {
+        AUDIO_MIME_TYPES.to_vec()
+    }
+
+    fn extensions(&self) -> Vec<&str> {
+        vec!["m4a", "mp3", "wav"]
+    }
+
+    fn can_chunk_file_type(&self, path: &Path) -> bool {
+        match util::detect_mime_type(path) {
+            Ok(mime) => AUDIO_MIME_TYPES.contains(&mime.as_str()),
+            Err(_) => false,
+        }
+    }
+
+    async fn chunk_file(
+        &self,
+        file: &FileMetadata,
+        config: &ChunkerConfig,
+        embedder: Arc<Embedder>,
+    ) -> ChunkerResult<Vec<(Chunk, Vec<f32>)>> {
+        debug!("Creating audio chunks for file {:?}", file.base.path);
+
+        let path = PathBuf::from(&file.base.path);
+        let segments = transcribe(&path).await?;
+        let chunks = segments_to_chunks(&segments, &path, config);
+
+        if chunks.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        tokio::task::spawn_blocking(move || {
+            let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
+
+            match embedder.model.embed(texts, embedder.batch_size()) {
+                Ok(embeddings) => {
+                    let chunk_embeddings: Vec<(Chunk, Vec<f32>)> = chunks
+                        .into_iter()
+                        .zip(embeddings)
+                        .filter(|(_, embedding)| !embedding.is_empty())
+                        .collect();
+
+                    Ok(chunk_embeddings)
+                }
+                Err(_) => Err(ChunkerError::Other(
+                    "Failed to generate embeddings".to_string(),
+                )),
+            }
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?
+    }
+
+    async fn extract_text(&self, path: &Path) -> ChunkerResult<String> {
+        Ok(transcribe(path)
+            .await?
+            .iter()
+            .map(|segment| segment.text.trim())
+            .collect::<Vec<_>>()
+            .join(" "))
+    }
+}
+
+/// Remembers where whisper-cli and its model can be found, called once at startup
+pub fn init_transcription(app: &tauri::App) {
+    let mut dirs = Vec::new();
+    if let Ok(app_data_dir) = app.path().app_data_dir() {
+        dirs.push(app_data_dir.join("models"));
+    }
+    if let Ok(resource_dir) = app.path().resource_dir() {
+        dirs.push(resource_dir);
+    }
+    // src-tauri/resources during development
+    if let Ok(cwd) = std::env::current_dir() {
+        dirs.push(cwd.join("resources"));
+    }
+
+    let _ = WHISPER_DIRS.set(dirs);
+}
+
+fn find_in_app_dirs(name: &str) -> Option<PathBuf> {
+    WHISPER_DIRS
+        .get()?
+        .iter()
+        .map(|dir| dir.join(name))
+        .find(|path| path.exists())
+}
+
+fn whisper_binary() -> PathBuf {
+    match find_in_app_dirs(WHISPER_BINARY_NAME) {
+        Some(path) => {
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                if let Ok(metadata) = std::fs::metadata(&path) {
+                    let mut perms = metadata.permissions();
+                    if perms.mode() & 0o111 == 0 {
+                        perms.set_mode(0o755);
+                        let _ = std::fs::set_permissions(&path, perms);
+                    }
+                }
+            }
+            path
+        }
+        // fall back to a whisper-cli installed on the PATH
+        None => PathBuf::from(WHISPER_BINARY_NAME),
+    }
+}
+
+async fn transcribe(path: &Path) -> ChunkerResult<Vec<WhisperSegment>> {
+    let path = path.to_path_buf();
+
+    tokio::task::spawn_blocking(move || -> ChunkerResult<Vec<WhisperSegment>> {
+        let work_dir = std::env::temp_dir().join(format!(
+            "kita-audio-{}-{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        std::fs::create_dir_all(&work_dir)?;
+
+        let result = transcribe_in(&path, &work_dir);
+        let _ = std::fs::remove_dir_all(&work_dir);
+        result
+    })
+    .await
+    .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?
+}
+
+/// Converts the recording to the 16 kHz mono WAV whisper.cpp expects and transcribes it
+fn transcribe_in(path: &Path, work_dir: &Path) -> ChunkerResult<Vec<WhisperSegment>> {
+    let model = find_in_app_dirs(WHISPER_MODEL_NAME).ok_or_else(|| {
+        ChunkerError::Other(format!(
+            "Whisper model {} not found, add it to the models folder to transcribe audio",
+            WHISPER_MODEL_NAME
+        ))
+    })?;
+
+    let wav_path = work_dir.join("audio.wav");
+    convert_to_wav(path, &wav_path)?;
+
+    let output_base = work_dir.join("transcript");
+    let threads = std::thread::available_parallelism()
+        .map(|threads| threads.get().min(4))
+        .unwrap_or(2);
+
+    run_tool(
+        Command::new(whisper_binary())
+            .arg("-m")
+            .arg(&model)
+            .arg("-f")
+            .arg(&wav_path)
+            .arg("-t")
+            .arg(threads.to_string())
+            .args(["-np", "-oj", "-of"])
+            .arg(&output_base),
+        WHISPER_BINARY_NAME,
+    )?;
+
+    let json = std::fs::read_to_string(output_base.with_extension("json"))?;
+    let output: WhisperOutput = serde_json::from_str(&json)
+        .map_err(|e| ChunkerError::Other(format!("Failed to read the transcript: {}", e)))?;
+
+    Ok(output.transcription)
+}
+
+#[cfg(target_os = "macos")]
+fn convert_to_wav(input: &Path, output: &Path) -> ChunkerResult<()> {
+    run_tool(
+        Command::new("afconvert")
+            .args(["-f", "WAVE", "-d", "LEI16@16000", "-c", "1"])
+            .arg(input)
+            .arg(output),
+        "afconvert",
+    )
+    .map(|_| ())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn convert_to_wav(input: &Path, output: &Path) -> ChunkerResult<()> {
+    run_tool(
+        Command::new("ffmpeg")
+            .args(["-nostdin", "-loglevel", "error", "-y", "-i"])
+            .arg(input)
+            .args(["-ar", "16000", "-ac", "1", "-c:a", "pcm_s16le"])
+            .arg(output),
+        "ffmpeg",
+    )
+    .map(|_| ())
+}
+
+fn run_tool(command: &mut Command, name: &str) -> ChunkerResult<String> {
+    let output = command.output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            ChunkerError::Other(format!("{} is not installed, it's needed for audio", name))
+        } else {
+            ChunkerError::Other(format!("Failed to run {}: {}", name, e))
+        }
+    })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        warn!("{} failed: {}", name, stderr);
+        return Err(ChunkerError::Other(format!("{} failed: {}", name, stderr)));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Packs whole segments into chunks of up to chunk_size words, a chunk spans from the start of its
+/// first segment to the end of its last
+fn segments_to_chunks(
+    segments: &[WhisperSegment],
+    path: &Path,
+    config: &ChunkerConfig,
+) -> Vec<Chunk> {
+    let mime_type = util::detect_mime_type(path).unwrap_or_else(|_| "audio/mpeg".to_string());
+
+    let mut chunks: Vec<Chunk> = Vec::new();
+    let mut content = String::new();
+    let mut words = 0;
+    let mut range: Option<(u64, u64)> = None;
+
+    for segment in segments {
+        let text = segment.text.trim();
+        // whisper marks silence and noise with bracketed tags like [BLANK_AUDIO]
+        if text.is_empty() || (text.starts_with('[') && text.ends_with(']')) {
+            continue;
+        }
+
+        let segment_words = text.split_whitespace().count();
+        if words > 0 && words + segment_words > config.chunk_size {
+            push_chunk(&mut chunks, &mut content, range.take(), path, &mime_type);
+            words = 0;
+        }
+
+        if !content.is_empty() {
+            content.push(' ');
+        }
+        content.push_str(text);
+        words += segment_words;
+        range = Some(match range {
+            Some((start, _)) => (start, segment.offsets.to),
+            None => (segment.offsets.from, segment.offsets.to),
+        });
+    }
+    push_chunk(&mut chunks, &mut content, range, path, &mime_type);
+
+    let total_chunks = chunks.len();
+    for chunk in &mut chunks {
+        chunk.metadata.total_chunks = Some(total_chunks);
+    }
+    chunks
+}
+
+fn push_chunk(
+    chunks: &mut Vec<Chunk>,
+    content: &mut String,
+    time_range: Option<(u64, u64)>,
+    path: &Path,
+    mime_type: &str,
+) {
+    if content.trim().is_empty() {
+        content.clear();
+        return;
+    }
+
+    chunks.push(Chunk {
+        content: std::mem::take(content),
+        metadata: ChunkMetadata {
+            source_path: path.to_path_buf(),
+            chunk_index: chunks.len(),
+            total_chunks: None,
+            page_number: None,
+            section: None,
+            mime_type: mime_type.to_string(),
+            row_range: None,
+            time_range,
+        },
+    });
+}
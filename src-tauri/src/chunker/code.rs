@@ -0,0 +1,338 @@
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{debug, warn};
+use tree_sitter::{Node, Parser};
+
+use crate::embedder::Embedder;
+use crate::file_processor::FileMetadata;
+
+use super::common::{Chunk, ChunkMetadata, ChunkerConfig, ChunkerResult};
+use super::Chunker;
+use super::ChunkerError;
+
+/// Definitions that are split into their members when they don't fit in a chunk
+const CONTAINER_KINDS: [&str; 9] = [
+    "impl_item",
+    "trait_item",
+    "mod_item",
+    "class_definition",
+    "decorated_definition",
+    "class_declaration",
+    "abstract_class_declaration",
+    "interface_declaration",
+    "export_statement",
+];
+
+#[derive(Debug, Clone, Copy)]
+enum Language {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Tsx,
+}
+
+impl Language {
+    fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_string_lossy().to_lowercase();
+        match ext.as_str() {
+            "rs" => Some(Language::Rust),
+            "py" => Some(Language::Python),
+            "js" | "jsx" | "mjs" | "cjs" => Some(Language::JavaScript),
+            "ts" | "mts" | "cts" => Some(Language::TypeScript),
+            "tsx" => Some(Language::Tsx),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Language::Rust => "rust",
+            Language::Python => "python",
+            Language::JavaScript => "javascript",
+            Language::TypeScript | Language::Tsx => "typescript",
+        }
+    }
+
+    fn mime_type(self) -> &'static str {
+        match self {
+            Language::Rust => "text/rust",
+            Language::Python => "text/x-python",
+            Language::JavaScript => "application/javascript",
+            Language::TypeScript | Language::Tsx => "application/typescript",
+        }
+    }
+
+    fn grammar(self) -> tree_sitter::Language {
+        match self {
+            Language::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Language::Python => tree_sitter_python::LANGUAGE.into(),
+            Language::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            Language::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Language::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+        }
+    }
+}
+
+/// Parser for source code, splits Rust, Python, JavaScript and TypeScript files on their top level definitions
+/// The syntax tree comes from tree-sitter, files it can't parse are split on lines instead
+#[derive(Default)]
+pub struct CodeChunker;
+
+#[async_trait]
+impl Chunker for CodeChunker {
+    fn name(&self) -> &str {
+        "code"
+    }
+
+    fn supported_mime_types(&self) -> Vec<&str> {
+        vec![
+            "text/rust",
+            "text/x-python",
+            "application/javascript",
+            "application/typescript",
+        ]
+    }
+
+    fn extensions(&self) -> Vec<&str> {
+        vec!["jsx", "mjs", "cjs", "tsx", "mts", "cts"]
+    }
+
+    fn can_chunk_file_type(&self, path: &Path) -> bool {
+        Language::from_path(path).is_some()
+    }
+
+    async fn chunk_file(
+        &self,
+        file: &FileMetadata,
+        config: &ChunkerConfig,
+        embedder: Arc<Embedder>,
+    ) -> ChunkerResult<Vec<(Chunk, Vec<f32>)>> {
+        debug!("Creating code chunks for file {:?}", file.base.path);
+
+        let path = PathBuf::from(&file.base.path);
+        let language = Language::from_path(&path).ok_or_else(|| {
+            ChunkerError::UnsupportedType(format!("Not a source file: {:?}", path))
+        })?;
+        let source = tokio::fs::read_to_string(&path).await?;
+        let config = config.clone();
+
+        let chunks =
+            tokio::task::spawn_blocking(move || code_to_chunks(&source, language, &path, &config))
+                .await
+                .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?;
+
+        if chunks.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        tokio::task::spawn_blocking(move || {
+            let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
+
+            match embedder.model.embed(texts, embedder.batch_size()) {
+                Ok(embeddings) => {
+                    let chunk_embeddings: Vec<(Chunk, Vec<f32>)> = chunks
+                        .into_iter()
+                        .zip(embeddings)
+                        .filter(|(_, embedding)| !embedding.is_empty())
+                        .collect();
+
+                    Ok(chunk_embeddings)
+                }
+                Err(_) => Err(ChunkerError::Other(
+                    "Failed to generate embeddings".to_string(),
+                )),
+            }
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?
+    }
+
+    async fn extract_text(&self, path: &Path) -> ChunkerResult<String> {
+        Ok(tokio::fs::read_to_string(path).await?)
+    }
+}
+
+fn word_count(lines: &[&str], start: usize, end: usize) -> usize {
+    lines
+        .get(start..=end)
+        .unwrap_or_default()
+        .iter()
+        .map(|line| line.split_whitespace().count())
+        .sum()
+}
+
+/// Comments and attributes belong to the definition that follows them
+fn is_attached(kind: &str) -> bool {
+    kind.contains("comment") || kind == "attribute_item" || kind == "decorator"
+}
+
+/// The node holding the members of a container, looking through decorators and exports
+fn container_body(node: Node) -> Option<Node> {
+    match node
+        .child_by_field_name("definition")
+        .or_else(|| node.child_by_field_name("declaration"))
+    {
+        Some(inner) => container_body(inner),
+        None => node.child_by_field_name("body"),
+    }
+}
+
+/// Collects the line ranges of the definitions under the node, zero based and inclusive
+/// Containers that are larger than max_words are split into their members, the first and
+/// last member take the lines of the container's header and closing brace
+fn collect_units(node: Node, lines: &[&str], max_words: usize, units: &mut Vec<(usize, usize)>) {
+    let mut cursor = node.walk();
+    let mut attached: Option<(usize, usize)> = None;
+
+    for child in node.named_children(&mut cursor) {
+        let start = attached
+            .map(|(start, _)| start)
+            .unwrap_or(child.start_position().row);
+        let end = child.end_position().row.min(lines.len() - 1);
+
+        if is_attached(child.kind()) {
+            attached = Some((start, end));
+            continue;
+        }
+        attached = None;
+
+        if CONTAINER_KINDS.contains(&child.kind()) && word_count(lines, start, end) > max_words {
+            if let Some(body) = container_body(child) {
+                let first = units.len();
+                collect_units(body, lines, max_words, units);
+                if units.len() > first {
+                    units[first].0 = start;
+                    if let Some(last) = units.last_mut() {
+                        last.1 = end;
+                    }
+                    continue;
+                }
+            }
+        }
+
+        units.push((start, end));
+    }
+
+    if let Some(trailing) = attached {
+        units.push(trailing);
+    }
+}
+
+/// Splits the file on its definitions and packs neighbouring small ones together up to chunk_size words
+/// Definitions are never cut unless one alone is larger than a chunk, then it's split on lines
+fn code_to_chunks(
+    source: &str,
+    language: Language,
+    path: &Path,
+    config: &ChunkerConfig,
+) -> Vec<Chunk> {
+    let source = source.trim_start_matches('\u{FEFF}');
+    let lines: Vec<&str> = source.lines().collect();
+    if lines.is_empty() {
+        return Vec::new();
+    }
+
+    let mut parser = Parser::new();
+    let tree = match parser.set_language(&language.grammar()) {
+        Ok(()) => parser.parse(source, None),
+        Err(e) => {
+            warn!("Failed to load the {} grammar: {}", language.name(), e);
+            None
+        }
+    };
+
+    let mut units = Vec::new();
+    match tree {
+        Some(tree) => collect_units(tree.root_node(), &lines, config.chunk_size, &mut units),
+        None => units.push((0, lines.len() - 1)),
+    }
+
+    // a definition can end on the line the next one starts on
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in units {
+        let start = match ranges.last() {
+            Some(&(_, previous_end)) => start.max(previous_end + 1),
+            None => start,
+        };
+        if start <= end {
+            ranges.push((start, end));
+        }
+    }
+
+    let mut pieces: Vec<(usize, usize)> = Vec::new();
+    let mut current: Option<(usize, usize)> = None;
+    let mut current_words = 0;
+
+    for (start, end) in ranges {
+        let words = word_count(&lines, start, end);
+
+        if words > config.chunk_size {
+            pieces.extend(current.take());
+            current_words = 0;
+            pieces.extend(split_lines(&lines, start, end, config.chunk_size));
+            continue;
+        }
+
+        if current.is_some() && current_words + words > config.chunk_size {
+            pieces.extend(current.take());
+            current_words = 0;
+        }
+
+        current = Some(match current {
+            Some((current_start, _)) => (current_start, end),
+            None => (start, end),
+        });
+        current_words += words;
+    }
+    pieces.extend(current);
+
+    let mut chunks: Vec<Chunk> = Vec::new();
+    for (start, end) in pieces {
+        let content = lines[start..=end].join("\n");
+        if content.trim().is_empty() {
+            continue;
+        }
+
+        chunks.push(Chunk {
+            content,
+            metadata: ChunkMetadata {
+                source_path: path.to_path_buf(),
+                chunk_index: chunks.len(),
+                total_chunks: None,
+                page_number: None,
+                section: Some(language.name().to_string()),
+                mime_type: language.mime_type().to_string(),
+                row_range: Some((start + 1, end + 1)),
+                time_range: None,
+            },
+        });
+    }
+
+    let total_chunks = chunks.len();
+    for chunk in &mut chunks {
+        chunk.metadata.total_chunks = Some(total_chunks);
+    }
+    chunks
+}
+
+/// Splits the lines into ranges of up to max_words words, a line is never cut
+fn split_lines(lines: &[&str], start: usize, end: usize, max_words: usize) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut range_start = start;
+    let mut words = 0;
+
+    for (row, line) in lines.iter().enumerate().take(end + 1).skip(start) {
+        let line_words = line.split_whitespace().count();
+        if words > 0 && words + line_words > max_words {
+            ranges.push((range_start, row - 1));
+            range_start = row;
+            words = 0;
+        }
+        words += line_words;
+    }
+    ranges.push((range_start, end));
+
+    ranges
+}
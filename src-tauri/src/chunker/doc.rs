@@ -0,0 +1,143 @@
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use crate::embedder::Embedder;
+use crate::file_processor::FileMetadata;
+
+use super::common::{Chunk, ChunkerConfig, ChunkerResult};
+use super::rtf::{is_rtf, rtf_to_text, text_to_chunks};
+use super::Chunker;
+use super::{util, ChunkerError};
+
+const DOC_MIME_TYPE: &str = "application/msword";
+
+/// Converters for the binary Word format, tried in order, each prints the text of the file to stdout
+#[cfg(target_os = "macos")]
+const CONVERTERS: [(&str, &[&str]); 3] = [
+    ("textutil", &["-convert", "txt", "-stdout"]),
+    ("antiword", &[]),
+    ("catdoc", &[]),
+];
+
+#[cfg(not(target_os = "macos"))]
+const CONVERTERS: [(&str, &[&str]); 2] = [("antiword", &[]), ("catdoc", &[])];
+
+/// Parser for legacy Word documents
+/// There's no Rust reader for the format so the text is extracted with textutil on macOS or antiword elsewhere
+#[derive(Default)]
+pub struct DocChunker;
+
+#[async_trait]
+impl Chunker for DocChunker {
+    fn name(&self) -> &str {
+        "doc"
+    }
+
+    fn supported_mime_types(&self) -> Vec<&str> {
+        vec![DOC_MIME_TYPE]
+    }
+
+    fn extensions(&self) -> Vec<&str> {
+        vec!["doc"]
+    }
+
+    fn can_chunk_file_type(&self, path: &Path) -> bool {
+        match util::detect_mime_type(path) {
+            Ok(mime) => mime == DOC_MIME_TYPE,
+            Err(_) => false,
+        }
+    }
+
+    async fn chunk_file(
+        &self,
+        file: &FileMetadata,
+        config: &ChunkerConfig,
+        embedder: Arc<Embedder>,
+    ) -> ChunkerResult<Vec<(Chunk, Vec<f32>)>> {
+        debug!("Creating DOC chunks for file {:?}", file.base.path);
+
+        let path = PathBuf::from(&file.base.path);
+        let config = config.clone();
+
+        let chunks = tokio::task::spawn_blocking(move || {
+            let text = extract_text_from_doc(&path)?;
+            Ok::<_, ChunkerError>(text_to_chunks(&text, &path, &config, DOC_MIME_TYPE))
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))??;
+
+        if chunks.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        tokio::task::spawn_blocking(move || {
+            let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
+
+            match embedder.model.embed(texts, embedder.batch_size()) {
+                Ok(embeddings) => {
+                    let chunk_embeddings: Vec<(Chunk, Vec<f32>)> = chunks
+                        .into_iter()
+                        .zip(embeddings)
+                        .filter(|(_, embedding)| !embedding.is_empty())
+                        .collect();
+
+                    Ok(chunk_embeddings)
+                }
+                Err(_) => Err(ChunkerError::Other(
+                    "Failed to generate embeddings".to_string(),
+                )),
+            }
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?
+    }
+
+    async fn extract_text(&self, path: &Path) -> ChunkerResult<String> {
+        let path = path.to_path_buf();
+
+        tokio::task::spawn_blocking(move || extract_text_from_doc(&path))
+            .await
+            .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?
+    }
+}
+
+/// Extracts the text with the first converter that's installed and can read the file
+/// Word often saves RTF with a .doc extension, those files are read directly
+fn extract_text_from_doc(path: &Path) -> ChunkerResult<String> {
+    let buffer = std::fs::read(path)?;
+    if is_rtf(&buffer) {
+        return Ok(rtf_to_text(&buffer));
+    }
+
+    let mut errors = Vec::new();
+    for (program, args) in CONVERTERS {
+        match Command::new(program).args(args).arg(path).output() {
+            Ok(output) if output.status.success() => {
+                return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+            }
+            Ok(output) => {
+                let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+                warn!("{} failed to read {:?}: {}", program, path, stderr);
+                errors.push(format!("{}: {}", program, stderr));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                debug!("{} is not installed", program);
+            }
+            Err(e) => errors.push(format!("{}: {}", program, e)),
+        }
+    }
+
+    if errors.is_empty() {
+        Err(ChunkerError::Other(
+            "No converter for .doc files found, install antiword to index them".to_string(),
+        ))
+    } else {
+        Err(ChunkerError::Other(format!(
+            "Failed to read .doc file: {}",
+            errors.join("; ")
+        )))
+    }
+}
@@ -4,6 +4,7 @@ use std::path::Path;
 use std::sync::Arc;
 use tokio::fs::File;
 use tokio::io::AsyncReadExt;
+use tracing::debug;
 
 use crate::embedder::Embedder;
 use crate::file_processor::FileMetadata;
@@ -18,24 +19,24 @@ pub struct DocxChunker;
 
 #[async_trait]
 impl Chunker for DocxChunker {
+    fn name(&self) -> &str {
+        "docx"
+    }
+
     fn supported_mime_types(&self) -> Vec<&str> {
-        vec![
-            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
-            "application/msword",
-        ]
+        vec!["application/vnd.openxmlformats-officedocument.wordprocessingml.document"]
     }
 
     fn can_chunk_file_type(&self, path: &Path) -> bool {
         match util::detect_mime_type(path) {
             Ok(mime) => {
                 mime == "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
-                    || mime == "application/msword"
             }
             Err(_) => {
                 // Fallback to extension check
                 if let Some(ext) = path.extension() {
                     let ext_str = ext.to_string_lossy().to_lowercase();
-                    ext_str == "docx" || ext_str == "doc"
+                    ext_str == "docx"
                 } else {
                     false
                 }
@@ -49,7 +50,7 @@ impl Chunker for DocxChunker {
         config: &ChunkerConfig,
         embedder: Arc<Embedder>,
     ) -> ChunkerResult<Vec<(Chunk, Vec<f32>)>> {
-        println!("Creating DOCX chunks for file {:?}", file.base.path);
+        debug!("Creating DOCX chunks for file {:?}", file.base.path);
 
         let path = Path::new(&file.base.path);
         let path_buf = path.to_path_buf();
@@ -76,11 +77,7 @@ impl Chunker for DocxChunker {
             };
 
             // Use the common text chunking utility
-            let text_chunks = util::chunk_text(
-                &processed_text,
-                config_clone.chunk_size,
-                config_clone.chunk_overlap,
-            );
+            let text_chunks = util::chunk_text(&processed_text, &config_clone);
 
             // Create chunks with metadata
             let total_chunks = text_chunks.len();
@@ -96,6 +93,8 @@ impl Chunker for DocxChunker {
                         page_number: None,
                         section: None,
                         mime_type: "application/vnd.openxmlformats-officedocument.wordprocessingml.document".to_string(),
+                        row_range: None,
+                        time_range: None,
                     },
                 })
                 .collect();
@@ -113,7 +112,7 @@ impl Chunker for DocxChunker {
         tokio::task::spawn_blocking(move || {
             let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
 
-            match embedder.model.embed(texts, None) {
+            match embedder.model.embed(texts, embedder.batch_size()) {
                 Ok(embeddings) => {
                     let chunk_embeddings: Vec<(Chunk, Vec<f32>)> = chunks
                         .into_iter()
@@ -131,6 +130,14 @@ impl Chunker for DocxChunker {
         .await
         .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?
     }
+
+    async fn extract_text(&self, path: &Path) -> ChunkerResult<String> {
+        let buffer = tokio::fs::read(path).await?;
+
+        tokio::task::spawn_blocking(move || extract_text_from_docx(&buffer))
+            .await
+            .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?
+    }
 }
 
 /// Extract all text from a DOCX file using a much simpler approach
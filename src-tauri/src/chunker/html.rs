@@ -0,0 +1,255 @@
+use async_trait::async_trait;
+use scraper::{ElementRef, Html, Node, Selector};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::debug;
+
+use crate::embedder::Embedder;
+use crate::file_processor::FileMetadata;
+
+use super::common::{Chunk, ChunkMetadata, ChunkerConfig, ChunkerResult};
+use super::Chunker;
+use super::{util, ChunkerError};
+
+/// Elements whose contents aren't readable text
+const SKIPPED_ELEMENTS: [&str; 8] = [
+    "script", "style", "noscript", "template", "head", "svg", "iframe", "object",
+];
+
+/// Headings that start a new section, lower levels stay in the section of their parent
+const SECTION_HEADINGS: [&str; 3] = ["h1", "h2", "h3"];
+
+/// Elements that end a line of text when they close
+const BLOCK_ELEMENTS: [&str; 22] = [
+    "p",
+    "div",
+    "br",
+    "li",
+    "ul",
+    "ol",
+    "tr",
+    "table",
+    "section",
+    "article",
+    "header",
+    "footer",
+    "nav",
+    "aside",
+    "blockquote",
+    "pre",
+    "h4",
+    "h5",
+    "h6",
+    "dt",
+    "dd",
+    "figcaption",
+];
+
+/// Readable text under the heading it was found after
+struct HtmlSection {
+    heading: Option<String>,
+    text: String,
+}
+
+/// Parser for HTML files, splits the page on its h1 to h3 headings after dropping scripts and styles
+#[derive(Default)]
+pub struct HtmlChunker;
+
+#[async_trait]
+impl Chunker for HtmlChunker {
+    fn name(&self) -> &str {
+        "html"
+    }
+
+    fn supported_mime_types(&self) -> Vec<&str> {
+        vec!["text/html"]
+    }
+
+    fn extensions(&self) -> Vec<&str> {
+        vec!["xhtml"]
+    }
+
+    fn can_chunk_file_type(&self, path: &Path) -> bool {
+        match util::detect_mime_type(path) {
+            Ok(mime) => mime == "text/html",
+            Err(_) => false,
+        }
+    }
+
+    async fn chunk_file(
+        &self,
+        file: &FileMetadata,
+        config: &ChunkerConfig,
+        embedder: Arc<Embedder>,
+    ) -> ChunkerResult<Vec<(Chunk, Vec<f32>)>> {
+        debug!("Creating HTML chunks for file {:?}", file.base.path);
+
+        let path = PathBuf::from(&file.base.path);
+        let html = read_html(&path).await?;
+        let config = config.clone();
+
+        let chunks = tokio::task::spawn_blocking(move || {
+            let sections = extract_sections(&html);
+            sections_to_chunks(sections, &path, &config)
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?;
+
+        if chunks.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        tokio::task::spawn_blocking(move || {
+            let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
+
+            match embedder.model.embed(texts, embedder.batch_size()) {
+                Ok(embeddings) => {
+                    let chunk_embeddings: Vec<(Chunk, Vec<f32>)> = chunks
+                        .into_iter()
+                        .zip(embeddings)
+                        .filter(|(_, embedding)| !embedding.is_empty())
+                        .collect();
+
+                    Ok(chunk_embeddings)
+                }
+                Err(_) => Err(ChunkerError::Other(
+                    "Failed to generate embeddings".to_string(),
+                )),
+            }
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?
+    }
+
+    async fn extract_text(&self, path: &Path) -> ChunkerResult<String> {
+        let html = read_html(path).await?;
+
+        tokio::task::spawn_blocking(move || {
+            extract_sections(&html)
+                .into_iter()
+                .map(|section| section.text)
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))
+    }
+}
+
+/// Reads the page, pages saved in another encoding than UTF-8 are read lossily instead of failing
+async fn read_html(path: &Path) -> ChunkerResult<String> {
+    let bytes = tokio::fs::read(path).await?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Walks the page in document order and collects its readable text by section
+/// Text before the first heading is put under the page title
+fn extract_sections(html: &str) -> Vec<HtmlSection> {
+    let document = Html::parse_document(html);
+
+    let title = Selector::parse("title").ok().and_then(|selector| {
+        document
+            .select(&selector)
+            .next()
+            .map(|title| collapse_whitespace(&title.text().collect::<String>()))
+            .filter(|title| !title.is_empty())
+    });
+
+    let mut sections = vec![HtmlSection {
+        heading: title,
+        text: String::new(),
+    }];
+    walk(document.root_element(), &mut sections);
+
+    for section in &mut sections {
+        section.text = section
+            .text
+            .lines()
+            .map(collapse_whitespace)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+    sections.retain(|section| !section.text.is_empty());
+    sections
+}
+
+fn walk(element: ElementRef, sections: &mut Vec<HtmlSection>) {
+    for child in element.children() {
+        if let Some(child_element) = ElementRef::wrap(child) {
+            let name = child_element.value().name();
+            if SKIPPED_ELEMENTS.contains(&name) {
+                continue;
+            }
+
+            if SECTION_HEADINGS.contains(&name) {
+                let heading = collapse_whitespace(&child_element.text().collect::<String>());
+                if !heading.is_empty() {
+                    // the heading is part of the text too so the chunks read like the page
+                    sections.push(HtmlSection {
+                        text: format!("{}\n", heading),
+                        heading: Some(heading),
+                    });
+                }
+                continue;
+            }
+
+            walk(child_element, sections);
+
+            if BLOCK_ELEMENTS.contains(&name) {
+                push_text(sections, "\n");
+            }
+        } else if let Node::Text(text) = child.value() {
+            push_text(sections, text);
+        }
+    }
+}
+
+fn push_text(sections: &mut [HtmlSection], text: &str) {
+    if let Some(section) = sections.last_mut() {
+        section.text.push_str(text);
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Chunks every section on its own so a chunk never mixes the text of two headings
+fn sections_to_chunks(
+    sections: Vec<HtmlSection>,
+    path: &Path,
+    config: &ChunkerConfig,
+) -> Vec<Chunk> {
+    let mut chunks: Vec<Chunk> = Vec::new();
+
+    for section in sections {
+        let text = if config.normalize_text {
+            util::normalize_text(&section.text)
+        } else {
+            section.text
+        };
+
+        for content in util::chunk_text(&text, config) {
+            chunks.push(Chunk {
+                content,
+                metadata: ChunkMetadata {
+                    source_path: path.to_path_buf(),
+                    chunk_index: chunks.len(),
+                    total_chunks: None,
+                    page_number: None,
+                    section: section.heading.clone(),
+                    mime_type: "text/html".to_string(),
+                    row_range: None,
+                    time_range: None,
+                },
+            });
+        }
+    }
+
+    let total_chunks = chunks.len();
+    for chunk in &mut chunks {
+        chunk.metadata.total_chunks = Some(total_chunks);
+    }
+    chunks
+}
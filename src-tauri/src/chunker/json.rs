@@ -4,6 +4,7 @@ use std::path::Path;
 use std::sync::Arc;
 use tokio::fs::File;
 use tokio::io::AsyncReadExt;
+use tracing::debug;
 
 use crate::embedder::Embedder;
 use crate::file_processor::FileMetadata;
@@ -18,6 +19,10 @@ pub struct JsonChunker;
 
 #[async_trait]
 impl Chunker for JsonChunker {
+    fn name(&self) -> &str {
+        "json"
+    }
+
     fn supported_mime_types(&self) -> Vec<&str> {
         vec!["application/json"]
     }
@@ -35,7 +40,7 @@ impl Chunker for JsonChunker {
         config: &ChunkerConfig,
         embedder: Arc<Embedder>,
     ) -> ChunkerResult<Vec<(Chunk, Vec<f32>)>> {
-        println!("Creating JSON chunks for file {:?}", file.base.path);
+        debug!("Creating JSON chunks for file {:?}", file.base.path);
 
         let path = Path::new(&file.base.path);
 
@@ -68,7 +73,7 @@ impl Chunker for JsonChunker {
             let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
 
             // Generate embeddings
-            match embedder.model.embed(texts, None) {
+            match embedder.model.embed(texts, embedder.batch_size()) {
                 Ok(embeddings) => {
                     // Pair chunks with their embeddings
                     let chunk_embeddings: Vec<(Chunk, Vec<f32>)> = chunks
@@ -213,6 +218,8 @@ fn create_chunk(
             page_number: None,
             section,
             mime_type: "application/json".to_string(),
+            row_range: None,
+            time_range: None,
         },
     }
 }
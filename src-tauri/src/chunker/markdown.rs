@@ -17,6 +17,10 @@ pub struct MarkdownChunker;
 
 #[async_trait]
 impl Chunker for MarkdownChunker {
+    fn name(&self) -> &str {
+        "markdown"
+    }
+
     fn supported_mime_types(&self) -> Vec<&str> {
         vec!["text/markdown", "text/x-markdown"]
     }
@@ -57,7 +61,7 @@ impl Chunker for MarkdownChunker {
             let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
 
             // Generate embeddings in one batch call
-            match embedder.model.embed(texts, None) {
+            match embedder.model.embed(texts, embedder.batch_size()) {
                 Ok(embeddings) => {
                     // Pair chunks with their embeddings
                     let chunk_embeddings: Vec<(Chunk, Vec<f32>)> = chunks
@@ -116,6 +120,8 @@ async fn get_chunks_from_large_file(
                         page_number: None,
                         section: Some(current_section.clone()),
                         mime_type: "text/markdown".to_string(),
+                        row_range: None,
+                        time_range: None,
                     },
                 });
 
@@ -172,6 +178,8 @@ async fn get_chunks_from_large_file(
                     page_number: None,
                     section: Some(current_section.clone()),
                     mime_type: "text/markdown".to_string(),
+                    row_range: None,
+                    time_range: None,
                 },
             });
 
@@ -219,6 +227,8 @@ async fn get_chunks_from_large_file(
                 page_number: None,
                 section: Some(current_section),
                 mime_type: "text/markdown".to_string(),
+                row_range: None,
+                time_range: None,
             },
         });
     }
@@ -257,8 +267,7 @@ async fn get_chunks_from_small_file(
         };
 
         // Create text chunks for this section
-        let text_chunks =
-            util::chunk_text(&processed_content, config.chunk_size, config.chunk_overlap);
+        let text_chunks = util::chunk_text(&processed_content, config);
 
         for content in text_chunks {
             chunks.push(Chunk {
@@ -270,6 +279,8 @@ async fn get_chunks_from_small_file(
                     page_number: None,
                     section: Some(section_title.clone()),
                     mime_type: "text/markdown".to_string(),
+                    row_range: None,
+                    time_range: None,
                 },
             });
 
@@ -285,8 +296,7 @@ async fn get_chunks_from_small_file(
             content
         };
 
-        let text_chunks =
-            util::chunk_text(&processed_content, config.chunk_size, config.chunk_overlap);
+        let text_chunks = util::chunk_text(&processed_content, config);
 
         chunks = text_chunks
             .into_iter()
@@ -300,6 +310,8 @@ async fn get_chunks_from_small_file(
                     page_number: None,
                     section: None,
                     mime_type: "text/markdown".to_string(),
+                    row_range: None,
+                    time_range: None,
                 },
             })
             .collect();
@@ -6,17 +6,28 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use thiserror::Error;
-use tracing::error;
+use tracing::{debug, error};
 
+pub mod archive;
+pub mod audio;
+pub mod code;
+pub mod doc;
 pub mod docx;
+pub mod html;
 pub mod json;
 pub mod markdown;
+pub mod ocr;
 pub mod pdf;
+pub mod plugin;
+pub mod pptx;
+pub mod rtf;
+pub mod structured;
 pub mod txt;
+pub mod xlsx;
 
 use crate::{embedder::Embedder, file_processor::FileMetadata};
 
-pub use self::common::{Chunk, ChunkerConfig, ChunkerError, ChunkerResult};
+pub use self::common::{Chunk, ChunkerConfig, ChunkerError, ChunkerResult, TextSplitter};
 
 pub mod common {
     use super::*;
@@ -35,16 +46,75 @@ pub mod common {
         pub page_number: Option<usize>,
         pub section: Option<String>,
         pub mime_type: String,
+        /// First and last row of the chunk, counted from 1, for spreadsheets and the lines of source code
+        pub row_range: Option<(usize, usize)>,
+        /// Start and end of the chunk in milliseconds, for audio transcripts
+        pub time_range: Option<(u64, u64)>,
+    }
+
+    /// How util::chunk_text splits a text into chunks
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+    #[serde(rename_all = "lowercase")]
+    pub enum TextSplitter {
+        /// Windows of chunk_size words that overlap by chunk_overlap words
+        #[default]
+        Words,
+        /// Splits on paragraphs, then lines, then sentences, then words, only going finer for pieces that are too large
+        /// so chunks end where the text has a break
+        Recursive,
     }
 
     #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct ChunkerConfig {
+        /// Largest text chunk in words
         pub chunk_size: usize,
+        /// Largest text chunk in tokens of the embedding model, used instead of chunk_size once the embedder is loaded
+        /// Dense text like code or URLs has many tokens per word and would be cut off by the model otherwise
+        pub chunk_size_tokens: Option<usize>,
         pub chunk_overlap: usize,
         pub normalize_text: bool,
         pub extract_metadata: bool,
         pub max_concurrent_files: usize,
         pub use_gpu_acceleration: bool,
+        /// PDFs with fewer non-whitespace characters per page than this are treated as scans and OCRed, 0 turns OCR off
+        pub ocr_min_chars_per_page: usize,
+        /// Text splitter for the chunkers that have none set in chunker_text_splitters
+        pub text_splitter: TextSplitter,
+        /// Text splitter by chunker name, like "markdown"
+        pub chunker_text_splitters: HashMap<String, TextSplitter>,
+    }
+
+    impl ChunkerConfig {
+        /// The config the chunker is called with, its text_splitter is the one set for the chunker
+        pub fn for_chunker(&self, name: &str) -> ChunkerConfig {
+            let mut config = self.clone();
+            if let Some(splitter) = self.chunker_text_splitters.get(name) {
+                config.text_splitter = *splitter;
+            }
+            config
+        }
+    }
+
+    impl Default for ChunkerConfig {
+        fn default() -> Self {
+            Self {
+                chunk_size: 100,
+                // the sequence length all-MiniLM-L6-v2 was trained on
+                chunk_size_tokens: Some(256),
+                chunk_overlap: 2,
+                normalize_text: true,
+                extract_metadata: true,
+                max_concurrent_files: 4,
+                use_gpu_acceleration: true,
+                ocr_min_chars_per_page: 50,
+                text_splitter: TextSplitter::Words,
+                // prose reads better in chunks that end on a paragraph or sentence
+                chunker_text_splitters: ["txt", "markdown", "docx", "doc", "rtf"]
+                    .into_iter()
+                    .map(|name| (name.to_string(), TextSplitter::Recursive))
+                    .collect(),
+            }
+        }
     }
 
     pub type ChunkerResult<T> = Result<T, ChunkerError>;
@@ -74,8 +144,16 @@ pub mod common {
 // chunker trait that each chunker needs to explicitly implement
 #[async_trait]
 pub trait Chunker: Send + Sync {
+    /// Short name of the chunker, used when reporting errors
+    fn name(&self) -> &str;
+
     fn supported_mime_types(&self) -> Vec<&str>;
 
+    /// Extensions handled besides the ones of the supported MIME types, like the ones of a plugin
+    fn extensions(&self) -> Vec<&str> {
+        Vec::new()
+    }
+
     fn can_chunk_file_type(&self, path: &Path) -> bool;
 
     async fn chunk_file(
@@ -84,6 +162,11 @@ pub trait Chunker: Send + Sync {
         config: &ChunkerConfig,
         embedder: Arc<Embedder>,
     ) -> ChunkerResult<Vec<(Chunk, Vec<f32>)>>;
+
+    /// Extracts the plain text of the file without chunking it, used for previews
+    async fn extract_text(&self, path: &Path) -> ChunkerResult<String> {
+        Ok(tokio::fs::read_to_string(path).await?)
+    }
 }
 
 pub struct ChunkerOrchestrator {
@@ -107,6 +190,22 @@ impl ChunkerOrchestrator {
         orchestrator.register_chunker(Box::new(json::JsonChunker::default()));
         orchestrator.register_chunker(Box::new(docx::DocxChunker::default()));
         orchestrator.register_chunker(Box::new(markdown::MarkdownChunker::default()));
+        orchestrator.register_chunker(Box::new(xlsx::XlsxChunker::default()));
+        orchestrator.register_chunker(Box::new(xlsx::CsvChunker::default()));
+        orchestrator.register_chunker(Box::new(html::HtmlChunker::default()));
+        orchestrator.register_chunker(Box::new(pptx::PptxChunker::default()));
+        orchestrator.register_chunker(Box::new(rtf::RtfChunker::default()));
+        orchestrator.register_chunker(Box::new(doc::DocChunker::default()));
+        orchestrator.register_chunker(Box::new(code::CodeChunker::default()));
+        orchestrator.register_chunker(Box::new(ocr::OcrChunker::default()));
+        orchestrator.register_chunker(Box::new(audio::AudioChunker::default()));
+        orchestrator.register_chunker(Box::new(archive::ArchiveChunker::default()));
+        orchestrator.register_chunker(Box::new(structured::StructuredChunker::default()));
+
+        // plugins come last so they can take over an extension from a built in chunker
+        for chunker_plugin in crate::plugins::chunker_plugins() {
+            orchestrator.register_chunker(Box::new(plugin::PluginChunker::new(chunker_plugin)));
+        }
 
         orchestrator
     }
@@ -135,6 +234,9 @@ impl ChunkerOrchestrator {
                 "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => {
                     self.extension_map.insert("xlsx".to_string(), chunker_index);
                 }
+                "application/vnd.openxmlformats-officedocument.presentationml.presentation" => {
+                    self.extension_map.insert("pptx".to_string(), chunker_index);
+                }
                 "text/rust" => {
                     self.extension_map.insert("rs".to_string(), chunker_index);
                 }
@@ -167,6 +269,11 @@ impl ChunkerOrchestrator {
             }
         }
 
+        for extension in chunker.extensions() {
+            self.extension_map
+                .insert(extension.to_lowercase(), chunker_index);
+        }
+
         self.chunkers.push(chunker);
     }
 
@@ -183,7 +290,7 @@ impl ChunkerOrchestrator {
         match util::detect_mime_type(path) {
             Ok(mime) => {
                 if let Some(&chunker_idx) = self.mime_map.get(&mime) {
-                    println!("Found chunker by MIME type for file {:?}", path);
+                    debug!("Found chunker by MIME type for file {:?}", path);
                     return Some(self.chunkers[chunker_idx].as_ref());
                 }
             }
@@ -192,17 +299,23 @@ impl ChunkerOrchestrator {
 
         // Fallback: try each chunker directly (slower but more thorough)
         for (i, chunker) in self.chunkers.iter().enumerate() {
-            println!("Trying chunker {} directly for file {:?}", i, path);
+            debug!("Trying chunker {} directly for file {:?}", i, path);
             if chunker.can_chunk_file_type(path) {
-                println!("Chunker {} accepted file {:?}", i, path);
+                debug!("Chunker {} accepted file {:?}", i, path);
                 return Some(chunker.as_ref());
             }
         }
 
-        println!("No chunker found for file: {:?}", path);
+        debug!("No chunker found for file: {:?}", path);
         None
     }
 
+    /// Returns the name of the chunker that would be used for the file, if any
+    pub fn chunker_name_for_file(&self, path: &Path) -> Option<String> {
+        self.find_chunker_for_file(path)
+            .map(|chunker| chunker.name().to_string())
+    }
+
     /// Find the right chunker for the file and chunk a single file
     pub async fn chunk_file(
         &self,
@@ -213,7 +326,21 @@ impl ChunkerOrchestrator {
             .find_chunker_for_file(Path::new(&file.base.path))
             .ok_or_else(|| ChunkerError::UnsupportedType(file.extension.clone()))?;
 
-        chunker.chunk_file(file, &self.config, embedder).await
+        let config = self.config.for_chunker(chunker.name());
+        chunker.chunk_file(file, &config, embedder).await
+    }
+
+    /// Find the right chunker for the file and extract its text
+    pub async fn extract_text(&self, path: &Path) -> ChunkerResult<String> {
+        let chunker: &dyn Chunker = self.find_chunker_for_file(path).ok_or_else(|| {
+            ChunkerError::UnsupportedType(
+                path.extension()
+                    .map(|ext| ext.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            )
+        })?;
+
+        chunker.extract_text(path).await
     }
 }
 
@@ -271,6 +398,14 @@ pub mod util {
                             .to_string(),
                     )
                 }
+                "doc" => return Ok("application/msword".to_string()),
+                "rtf" => return Ok("application/rtf".to_string()),
+                "pptx" => {
+                    return Ok(
+                        "application/vnd.openxmlformats-officedocument.presentationml.presentation"
+                            .to_string(),
+                    )
+                }
                 "rs" => return Ok("text/rust".to_string()),
                 "js" => return Ok("application/javascript".to_string()),
                 "ts" => return Ok("application/typescript".to_string()),
@@ -306,8 +441,17 @@ pub mod util {
         normalized
     }
 
-    /// Chunks texts based on a configured chunk_size and overlap
-    pub fn chunk_text(text: &str, chunk_size: usize, overlap: usize) -> Vec<String> {
+    /// Chunks texts based on the configured chunk size, overlap and text splitter
+    pub fn chunk_text(text: &str, config: &ChunkerConfig) -> Vec<String> {
+        match config.text_splitter {
+            TextSplitter::Words => chunk_words(text, config),
+            TextSplitter::Recursive => chunk_recursive(text, config),
+        }
+    }
+
+    /// Chunks texts into windows of words
+    /// With chunk_size_tokens the chunks are filled up to that many model tokens, otherwise up to chunk_size words
+    fn chunk_words(text: &str, config: &ChunkerConfig) -> Vec<String> {
         if text.is_empty() {
             return Vec::new();
         }
@@ -318,13 +462,32 @@ pub mod util {
             return vec![text.to_string()];
         }
 
+        // falls back to words until the embedder has loaded its tokenizer
+        let token_limit = config.chunk_size_tokens.and_then(|limit| {
+            let counts = crate::tokenizer::model_token_counts(&words)?;
+            let limit = limit.saturating_sub(crate::tokenizer::model_added_tokens());
+            Some((limit, counts))
+        });
+
         let mut chunks: Vec<String> = Vec::new();
         let mut start: usize = 0;
 
         while start < words.len() {
-            // if the total amount of words is less than the chunk size then just return the entire text
-            // otherwise create a chunk of the chunk size + the start position and put it into the vector
-            let end: usize = std::cmp::min(start + chunk_size, words.len());
+            let end: usize = match &token_limit {
+                // takes words while they fit, a single word longer than the limit still makes its own chunk
+                Some((limit, counts)) => {
+                    let mut end = start;
+                    let mut tokens = 0;
+                    while end < words.len() && (end == start || tokens + counts[end] <= *limit) {
+                        tokens += counts[end];
+                        end += 1;
+                    }
+                    end
+                }
+                // if the total amount of words is less than the chunk size then just return the entire text
+                // otherwise create a chunk of the chunk size + the start position
+                None => std::cmp::min(start + config.chunk_size.max(1), words.len()),
+            };
             let chunk: String = words[start..end].join(" ");
             chunks.push(chunk);
 
@@ -332,10 +495,134 @@ pub mod util {
             if end == words.len() {
                 break; // We've reached the end
             } else {
-                // Move forward by (chunk_size - overlap)
-                start = std::cmp::min(start + chunk_size - overlap, words.len() - 1);
+                // Move back by the overlap, but always forward from the last chunk's start
+                start = std::cmp::max(end.saturating_sub(config.chunk_overlap), start + 1);
             }
         }
         chunks
     }
+
+    /// Separators the recursive splitter tries in order, sentence ends are found by split_sentences
+    const SEPARATORS: [&str; 4] = ["\n\n", "\n", ". ", " "];
+
+    /// Chunks texts like LangChain's recursive character splitter
+    /// Pieces between separators are merged up to the chunk size, a piece that doesn't fit on its own is split
+    /// again on the next separator and a single word longer than the chunk size becomes its own chunk
+    fn chunk_recursive(text: &str, config: &ChunkerConfig) -> Vec<String> {
+        if text.trim().is_empty() {
+            return Vec::new();
+        }
+
+        // measures in model tokens when the embedder has loaded its tokenizer, otherwise in words
+        let token_limit = config
+            .chunk_size_tokens
+            .filter(|_| crate::tokenizer::has_model_tokenizer());
+        let measure = |piece: &str| -> usize {
+            match token_limit {
+                Some(_) => crate::tokenizer::model_token_count(piece).unwrap_or(0),
+                None => piece.split_whitespace().count(),
+            }
+        };
+        let limit = match token_limit {
+            Some(limit) => limit.saturating_sub(crate::tokenizer::model_added_tokens()),
+            None => config.chunk_size,
+        }
+        .max(1);
+
+        let mut chunks = Vec::new();
+        split_recursive(text, 0, limit, config.chunk_overlap, &measure, &mut chunks);
+        chunks
+    }
+
+    fn split_recursive(
+        text: &str,
+        level: usize,
+        limit: usize,
+        overlap: usize,
+        measure: &dyn Fn(&str) -> usize,
+        chunks: &mut Vec<String>,
+    ) {
+        let separator = SEPARATORS[level];
+        let pieces: Vec<&str> = match separator {
+            ". " => split_sentences(text),
+            " " => text.split_whitespace().collect(),
+            _ => text.split(separator).collect(),
+        };
+
+        // pieces of the chunk being built with their size and word count
+        let mut current: Vec<(&str, usize, usize)> = Vec::new();
+        let mut current_size = 0;
+
+        for piece in pieces {
+            let piece = piece.trim();
+            if piece.is_empty() {
+                continue;
+            }
+
+            let size = measure(piece);
+            if size > limit && level + 1 < SEPARATORS.len() {
+                push_pieces(&current, separator, chunks);
+                current.clear();
+                current_size = 0;
+                split_recursive(piece, level + 1, limit, overlap, measure, chunks);
+                continue;
+            }
+
+            if !current.is_empty() && current_size + size > limit {
+                push_pieces(&current, separator, chunks);
+
+                // keeps the last pieces that fit in the overlap so the next chunk starts with them
+                let mut overlap_words: usize = current.iter().map(|(_, _, words)| words).sum();
+                while !current.is_empty()
+                    && (overlap_words > overlap || current_size + size > limit)
+                {
+                    let (_, removed_size, removed_words) = current.remove(0);
+                    current_size -= removed_size;
+                    overlap_words -= removed_words;
+                }
+            }
+
+            current.push((piece, size, piece.split_whitespace().count()));
+            current_size += size;
+        }
+
+        push_pieces(&current, separator, chunks);
+    }
+
+    fn push_pieces(pieces: &[(&str, usize, usize)], separator: &str, chunks: &mut Vec<String>) {
+        if pieces.is_empty() {
+            return;
+        }
+
+        // sentences keep their own punctuation, so they're joined with a space
+        let separator = if separator == ". " { " " } else { separator };
+        let chunk = pieces
+            .iter()
+            .map(|(piece, _, _)| *piece)
+            .collect::<Vec<_>>()
+            .join(separator);
+        chunks.push(chunk);
+    }
+
+    /// Splits after a '.', '!' or '?' that's followed by whitespace, keeping the punctuation with its sentence
+    fn split_sentences(text: &str) -> Vec<&str> {
+        let mut sentences = Vec::new();
+        let mut start = 0;
+        let mut chars = text.char_indices().peekable();
+
+        while let Some((_, c)) = chars.next() {
+            if !matches!(c, '.' | '!' | '?') {
+                continue;
+            }
+            if let Some(&(next_index, next)) = chars.peek() {
+                if next.is_whitespace() {
+                    sentences.push(&text[start..next_index]);
+                    start = next_index;
+                }
+            }
+        }
+
+        sentences.push(&text[start..]);
+        sentences
+    }
 }
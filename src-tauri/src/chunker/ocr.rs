@@ -0,0 +1,260 @@
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::debug;
+
+#[cfg(target_os = "macos")]
+use std::ffi::{CStr, CString};
+#[cfg(target_os = "macos")]
+use std::os::raw::c_char;
+#[cfg(not(target_os = "macos"))]
+use std::process::Command;
+
+#[cfg(target_os = "macos")]
+extern "C" {
+    fn recognize_text_swift(path: *const c_char) -> *mut c_char;
+    fn free_string_swift(pointer: *mut c_char);
+}
+
+use crate::embedder::Embedder;
+use crate::file_processor::FileMetadata;
+
+use super::common::{Chunk, ChunkMetadata, ChunkerConfig, ChunkerResult};
+use super::Chunker;
+use super::{util, ChunkerError};
+
+/// Separates the pages in the output of Vision and tesseract
+const PAGE_SEPARATOR: char = '\u{0C}';
+
+const IMAGE_MIME_TYPES: [&str; 3] = ["image/png", "image/jpeg", "image/tiff"];
+
+/// Parser for scanned images, the text is recognized with Vision on macOS and tesseract elsewhere
+/// Images aren't in the default indexed extensions since most of them are photos without text
+#[derive(Default)]
+pub struct OcrChunker;
+
+#[async_trait]
+impl Chunker for OcrChunker {
+    fn name(&self) -> &str {
+        "ocr"
+    }
+
+    fn supported_mime_types(&self) -> Vec<&str> {
+        IMAGE_MIME_TYPES.to_vec()
+    }
+
+    fn extensions(&self) -> Vec<&str> {
+        vec!["png", "jpg", "jpeg", "tif", "tiff"]
+    }
+
+    fn can_chunk_file_type(&self, path: &Path) -> bool {
+        match util::detect_mime_type(path) {
+            Ok(mime) => IMAGE_MIME_TYPES.contains(&mime.as_str()),
+            Err(_) => false,
+        }
+    }
+
+    async fn chunk_file(
+        &self,
+        file: &FileMetadata,
+        config: &ChunkerConfig,
+        embedder: Arc<Embedder>,
+    ) -> ChunkerResult<Vec<(Chunk, Vec<f32>)>> {
+        debug!("Creating OCR chunks for file {:?}", file.base.path);
+
+        let path = PathBuf::from(&file.base.path);
+        let mime_type = util::detect_mime_type(&path).unwrap_or_else(|_| "image/png".to_string());
+        let pages = recognize_pages(&path).await?;
+        let chunks = pages_to_chunks(&pages, &path, config, &mime_type);
+
+        if chunks.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        tokio::task::spawn_blocking(move || {
+            let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
+
+            match embedder.model.embed(texts, embedder.batch_size()) {
+                Ok(embeddings) => {
+                    let chunk_embeddings: Vec<(Chunk, Vec<f32>)> = chunks
+                        .into_iter()
+                        .zip(embeddings)
+                        .filter(|(_, embedding)| !embedding.is_empty())
+                        .collect();
+
+                    Ok(chunk_embeddings)
+                }
+                Err(_) => Err(ChunkerError::Other(
+                    "Failed to generate embeddings".to_string(),
+                )),
+            }
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?
+    }
+
+    async fn extract_text(&self, path: &Path) -> ChunkerResult<String> {
+        Ok(recognize_pages(path).await?.join("\n\n"))
+    }
+}
+
+/// Recognizes the text of every page of an image or PDF, images have a single page unless they're multi-page TIFFs
+pub(super) async fn recognize_pages(path: &Path) -> ChunkerResult<Vec<String>> {
+    let path = path.to_path_buf();
+
+    let text = tokio::task::spawn_blocking(move || recognize_text(&path))
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))??;
+
+    Ok(text
+        .split(PAGE_SEPARATOR)
+        .map(|page| page.trim().to_string())
+        .collect())
+}
+
+#[cfg(target_os = "macos")]
+fn recognize_text(path: &Path) -> ChunkerResult<String> {
+    let path_cstring = CString::new(path.to_string_lossy().as_bytes())
+        .map_err(|e| ChunkerError::Other(format!("Invalid path: {}", e)))?;
+
+    let text_ptr = unsafe { recognize_text_swift(path_cstring.as_ptr()) };
+    if text_ptr.is_null() {
+        return Err(ChunkerError::Other(format!(
+            "Failed to recognize the text of {:?}",
+            path
+        )));
+    }
+
+    unsafe {
+        let text = CStr::from_ptr(text_ptr).to_string_lossy().into_owned();
+        free_string_swift(text_ptr);
+        Ok(text)
+    }
+}
+
+/// PDFs are rendered to images with pdftoppm first, tesseract only reads images
+#[cfg(not(target_os = "macos"))]
+fn recognize_text(path: &Path) -> ChunkerResult<String> {
+    let is_pdf = path
+        .extension()
+        .is_some_and(|ext| ext.to_string_lossy().eq_ignore_ascii_case("pdf"));
+    if !is_pdf {
+        return tesseract(path);
+    }
+
+    let render_dir = std::env::temp_dir().join(format!(
+        "kita-ocr-{}-{}",
+        std::process::id(),
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+    std::fs::create_dir_all(&render_dir)?;
+
+    let result = (|| -> ChunkerResult<String> {
+        run_tool(
+            Command::new("pdftoppm")
+                .args(["-r", "300", "-png"])
+                .arg(path)
+                .arg(render_dir.join("page")),
+            "pdftoppm",
+        )?;
+
+        // pdftoppm pads the page numbers so the names sort in page order
+        let mut images: Vec<PathBuf> = std::fs::read_dir(&render_dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect();
+        images.sort();
+
+        let pages = images
+            .iter()
+            .map(|image| tesseract(image))
+            .collect::<ChunkerResult<Vec<_>>>()?;
+        Ok(pages.join(&PAGE_SEPARATOR.to_string()))
+    })();
+
+    let _ = std::fs::remove_dir_all(&render_dir);
+    result
+}
+
+#[cfg(not(target_os = "macos"))]
+fn tesseract(image: &Path) -> ChunkerResult<String> {
+    let text = run_tool(
+        Command::new("tesseract").arg(image).arg("stdout"),
+        "tesseract",
+    )?;
+    // tesseract ends every page with a separator, the last one would make an empty page
+    Ok(text.trim_end_matches(['\n', PAGE_SEPARATOR]).to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn run_tool(command: &mut Command, name: &str) -> ChunkerResult<String> {
+    let output = command.output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            ChunkerError::Other(format!("{} is not installed, it's needed for OCR", name))
+        } else {
+            ChunkerError::Other(format!("Failed to run {}: {}", name, e))
+        }
+    })?;
+
+    if !output.status.success() {
+        return Err(ChunkerError::Other(format!(
+            "{} failed: {}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Chunks every page on its own so the chunks keep their page number, counted from 1
+pub(super) fn pages_to_chunks(
+    pages: &[String],
+    path: &Path,
+    config: &ChunkerConfig,
+    mime_type: &str,
+) -> Vec<Chunk> {
+    let mut chunks: Vec<Chunk> = Vec::new();
+
+    for (index, page) in pages.iter().enumerate() {
+        let text = if config.normalize_text {
+            util::normalize_text(page)
+        } else {
+            page.clone()
+        };
+
+        for content in util::chunk_text(&text, config) {
+            if content.trim().is_empty() {
+                continue;
+            }
+            chunks.push(Chunk {
+                content,
+                metadata: ChunkMetadata {
+                    source_path: path.to_path_buf(),
+                    chunk_index: chunks.len(),
+                    total_chunks: None,
+                    page_number: Some(index + 1),
+                    section: None,
+                    mime_type: mime_type.to_string(),
+                    row_range: None,
+                    time_range: None,
+                },
+            });
+        }
+    }
+
+    let total_chunks = chunks.len();
+    for chunk in &mut chunks {
+        chunk.metadata.total_chunks = Some(total_chunks);
+    }
+    chunks
+}
+
+/// True when the text layer of a PDF is too thin to be anything but a scan
+pub(super) fn needs_ocr(text: &str, page_count: usize, min_chars_per_page: usize) -> bool {
+    if min_chars_per_page == 0 {
+        return false;
+    }
+
+    let chars = text.chars().filter(|c| !c.is_whitespace()).count();
+    chars < min_chars_per_page * page_count.max(1)
+}
@@ -1,20 +1,25 @@
 use async_trait::async_trait;
-use pdf_extract::extract_text;
+use pdf_extract::{output_doc, Document, PlainTextOutput};
 use std::path::Path;
 use std::sync::Arc;
+use tracing::{debug, warn};
 
 use crate::embedder::Embedder;
 use crate::file_processor::FileMetadata;
 
 use super::common::{Chunk, ChunkMetadata, ChunkerConfig, ChunkerResult};
 use super::Chunker;
-use super::{util, ChunkerError};
+use super::{ocr, util, ChunkerError};
 
 #[derive(Default)]
 pub struct PdfChunker;
 
 #[async_trait]
 impl Chunker for PdfChunker {
+    fn name(&self) -> &str {
+        "pdf"
+    }
+
     fn supported_mime_types(&self) -> Vec<&str> {
         vec!["application/pdf"]
     }
@@ -34,10 +39,14 @@ impl Chunker for PdfChunker {
     ) -> ChunkerResult<Vec<(Chunk, Vec<f32>)>> {
         let path = Path::new(&file.base.path);
 
-        // Extract text from PDF
-        let pdf_text = extract_pdf_text(path).await?;
+        // Extract text from PDF, scans without a real text layer are OCRed instead
+        let (pdf_text, page_count) = extract_pdf_text(path).await;
 
-        let chunks = chunk_pdf_text(&pdf_text, path, config).await?;
+        let chunks =
+            match ocr_pages(path, &pdf_text, page_count, config.ocr_min_chars_per_page).await {
+                Some(pages) => ocr::pages_to_chunks(&pages, path, config, "application/pdf"),
+                None => chunk_pdf_text(&pdf_text?, path, config).await?,
+            };
 
         if chunks.is_empty() {
             return Ok(Vec::new());
@@ -46,7 +55,7 @@ impl Chunker for PdfChunker {
         tokio::task::spawn_blocking(move || {
             let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
 
-            match embedder.model.embed(texts, None) {
+            match embedder.model.embed(texts, embedder.batch_size()) {
                 Ok(embeddings) => {
                     // Pair chunks with their embeddings
                     let chunk_embeddings: Vec<(Chunk, Vec<f32>)> = chunks
@@ -65,23 +74,86 @@ impl Chunker for PdfChunker {
         .await
         .map_err(|e| ChunkerError::PdFilefError(format!("Thread error: {:?}", e)))?
     }
+
+    async fn extract_text(&self, path: &Path) -> ChunkerResult<String> {
+        let (pdf_text, page_count) = extract_pdf_text(path).await;
+        let min_chars_per_page = ChunkerConfig::default().ocr_min_chars_per_page;
+
+        match ocr_pages(path, &pdf_text, page_count, min_chars_per_page).await {
+            Some(pages) => Ok(pages.join("\n\n")),
+            None => pdf_text,
+        }
+    }
 }
 
-async fn extract_pdf_text(path: &Path) -> ChunkerResult<String> {
+/// Extracts the text layer of the PDF along with its page count
+/// The page count is None when the file couldn't be loaded as a PDF at all
+async fn extract_pdf_text(path: &Path) -> (ChunkerResult<String>, Option<usize>) {
     // Use blocking operation in a spawn_blocking task since PDF processing can be intensive
-    let path_str = path.to_string_lossy().to_string();
-
-    let text = tokio::task::spawn_blocking(move || match extract_text(&path_str) {
-        Ok(text) => Ok(text),
-        Err(e) => Err(ChunkerError::PdFilefError(format!(
-            "Failed to extract PDF text: {}",
-            e
-        ))),
+    let path = path.to_path_buf();
+
+    tokio::task::spawn_blocking(move || {
+        // the document is parsed once for both its text and its page count
+        let mut document = match Document::load(&path) {
+            Ok(document) => document,
+            Err(e) => return (Err(pdf_text_error(e)), None),
+        };
+        let page_count = document.get_pages().len();
+
+        if document.is_encrypted() {
+            if let Err(e) = document.decrypt("") {
+                return (Err(pdf_text_error(e)), Some(page_count));
+            }
+        }
+
+        let mut text = String::new();
+        let result = output_doc(&document, &mut PlainTextOutput::new(&mut text));
+        (
+            result.map(|_| text).map_err(pdf_text_error),
+            Some(page_count),
+        )
     })
     .await
-    .map_err(|e| ChunkerError::PdFilefError(format!("Thread error: {:?}", e)))??;
+    .unwrap_or_else(|e| {
+        (
+            Err(ChunkerError::PdFilefError(format!("Thread error: {:?}", e))),
+            None,
+        )
+    })
+}
+
+fn pdf_text_error(e: impl std::fmt::Display) -> ChunkerError {
+    ChunkerError::PdFilefError(format!("Failed to extract PDF text: {}", e))
+}
+
+/// Recognizes the text of a scanned PDF, whose text layer is missing or too thin to be its real text
+/// None when the text layer is good enough or OCR found nothing, then the extracted text is used
+async fn ocr_pages(
+    path: &Path,
+    pdf_text: &ChunkerResult<String>,
+    page_count: Option<usize>,
+    min_chars_per_page: usize,
+) -> Option<Vec<String>> {
+    // a file that couldn't be loaded has no pages to OCR
+    let page_count = page_count?;
+    if min_chars_per_page == 0 {
+        return None;
+    }
+
+    let text = pdf_text.as_deref().unwrap_or_default();
+    if !ocr::needs_ocr(text, page_count, min_chars_per_page) {
+        return None;
+    }
 
-    Ok(text)
+    debug!("Running OCR on {:?}, its text layer is too thin", path);
+    match ocr::recognize_pages(path).await {
+        Ok(pages) if pages.iter().any(|page| !page.is_empty()) => Some(pages),
+        Ok(_) => None,
+        Err(e) => {
+            warn!("OCR of {:?} failed: {}", path, e);
+            None
+        }
+    }
 }
 
 async fn chunk_pdf_text(
@@ -97,7 +169,7 @@ async fn chunk_pdf_text(
     };
 
     // Create text chunks using the same function as for TXT files
-    let text_chunks = util::chunk_text(&processed_content, config.chunk_size, config.chunk_overlap);
+    let text_chunks = util::chunk_text(&processed_content, config);
 
     if text_chunks.is_empty() {
         return Ok(Vec::new());
@@ -117,6 +189,8 @@ async fn chunk_pdf_text(
                 page_number: None,
                 section: None,
                 mime_type: "application/pdf".to_string(),
+                row_range: None,
+                time_range: None,
             },
         })
         .collect();
@@ -0,0 +1,130 @@
+use async_trait::async_trait;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::embedder::Embedder;
+use crate::file_processor::FileMetadata;
+use crate::plugins::{Plugin, PluginChunk};
+
+use super::common::{Chunk, ChunkMetadata, ChunkerConfig, ChunkerResult};
+use super::Chunker;
+use super::ChunkerError;
+
+/// Chunker provided by a WASM plugin for the extensions in its manifest
+pub struct PluginChunker {
+    plugin: Arc<Plugin>,
+}
+
+impl PluginChunker {
+    pub fn new(plugin: Arc<Plugin>) -> Self {
+        Self { plugin }
+    }
+
+    /// Runs the plugin on a blocking thread, plugins can take a while on large files
+    async fn plugin_chunks(
+        &self,
+        path: &Path,
+        config: &ChunkerConfig,
+    ) -> ChunkerResult<Vec<PluginChunk>> {
+        let plugin = Arc::clone(&self.plugin);
+        let path = path.to_path_buf();
+        let (chunk_size, chunk_overlap) = (config.chunk_size, config.chunk_overlap);
+
+        tokio::task::spawn_blocking(move || plugin.chunk(&path, chunk_size, chunk_overlap))
+            .await
+            .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?
+            .map_err(|e| ChunkerError::Other(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl Chunker for PluginChunker {
+    fn name(&self) -> &str {
+        &self.plugin.manifest.name
+    }
+
+    fn supported_mime_types(&self) -> Vec<&str> {
+        Vec::new()
+    }
+
+    fn extensions(&self) -> Vec<&str> {
+        self.plugin
+            .manifest
+            .extensions
+            .iter()
+            .map(String::as_str)
+            .collect()
+    }
+
+    fn can_chunk_file_type(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|ext| {
+            let ext = ext.to_string_lossy().to_lowercase();
+            self.plugin
+                .manifest
+                .extensions
+                .iter()
+                .any(|plugin_ext| plugin_ext.eq_ignore_ascii_case(&ext))
+        })
+    }
+
+    async fn chunk_file(
+        &self,
+        file: &FileMetadata,
+        config: &ChunkerConfig,
+        embedder: Arc<Embedder>,
+    ) -> ChunkerResult<Vec<(Chunk, Vec<f32>)>> {
+        let path = Path::new(&file.base.path);
+        let plugin_chunks = self.plugin_chunks(path, config).await?;
+
+        let total_chunks = plugin_chunks.len();
+        let chunks: Vec<Chunk> = plugin_chunks
+            .into_iter()
+            .filter(|chunk| !chunk.content.trim().is_empty())
+            .enumerate()
+            .map(|(chunk_index, chunk)| Chunk {
+                content: chunk.content,
+                metadata: ChunkMetadata {
+                    source_path: path.to_path_buf(),
+                    chunk_index,
+                    total_chunks: Some(total_chunks),
+                    page_number: chunk.page_number,
+                    section: chunk.section,
+                    mime_type: format!("application/x-kita-plugin-{}", self.plugin.manifest.name),
+                    row_range: None,
+                    time_range: None,
+                },
+            })
+            .collect();
+
+        if chunks.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        tokio::task::spawn_blocking(move || {
+            let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
+
+            match embedder.model.embed(texts, embedder.batch_size()) {
+                Ok(embeddings) => Ok(chunks
+                    .into_iter()
+                    .zip(embeddings)
+                    .filter(|(_, embedding)| !embedding.is_empty())
+                    .collect()),
+                Err(_) => Err(ChunkerError::Other(
+                    "Failed to generate embeddings".to_string(),
+                )),
+            }
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?
+    }
+
+    async fn extract_text(&self, path: &Path) -> ChunkerResult<String> {
+        let chunks = self.plugin_chunks(path, &ChunkerConfig::default()).await?;
+
+        Ok(chunks
+            .into_iter()
+            .map(|chunk| chunk.content)
+            .collect::<Vec<_>>()
+            .join("\n\n"))
+    }
+}
@@ -0,0 +1,345 @@
+use async_trait::async_trait;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{debug, warn};
+use zip::ZipArchive;
+
+use crate::embedder::Embedder;
+use crate::file_processor::FileMetadata;
+
+use super::common::{Chunk, ChunkMetadata, ChunkerConfig, ChunkerResult};
+use super::Chunker;
+use super::{util, ChunkerError};
+
+const PPTX_MIME_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.presentationml.presentation";
+
+/// Text of one slide, numbered from 1 in the order of the deck
+struct Slide {
+    number: usize,
+    text: String,
+    notes: String,
+}
+
+impl Slide {
+    fn content(&self) -> String {
+        if self.notes.is_empty() {
+            self.text.clone()
+        } else {
+            format!("{}\nNotes: {}", self.text, self.notes)
+        }
+    }
+}
+
+/// Parser for PowerPoint decks, every slide is chunked with its speaker notes
+#[derive(Default)]
+pub struct PptxChunker;
+
+#[async_trait]
+impl Chunker for PptxChunker {
+    fn name(&self) -> &str {
+        "pptx"
+    }
+
+    fn supported_mime_types(&self) -> Vec<&str> {
+        vec![PPTX_MIME_TYPE]
+    }
+
+    fn can_chunk_file_type(&self, path: &Path) -> bool {
+        match util::detect_mime_type(path) {
+            Ok(mime) => mime == PPTX_MIME_TYPE,
+            Err(_) => false,
+        }
+    }
+
+    async fn chunk_file(
+        &self,
+        file: &FileMetadata,
+        config: &ChunkerConfig,
+        embedder: Arc<Embedder>,
+    ) -> ChunkerResult<Vec<(Chunk, Vec<f32>)>> {
+        debug!("Creating PPTX chunks for file {:?}", file.base.path);
+
+        let path = PathBuf::from(&file.base.path);
+        let buffer = tokio::fs::read(&path).await?;
+        let config = config.clone();
+
+        let chunks = tokio::task::spawn_blocking(move || {
+            let slides = read_slides(buffer)?;
+            Ok::<_, ChunkerError>(slides_to_chunks(&slides, &path, &config))
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))??;
+
+        if chunks.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        tokio::task::spawn_blocking(move || {
+            let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
+
+            match embedder.model.embed(texts, embedder.batch_size()) {
+                Ok(embeddings) => {
+                    let chunk_embeddings: Vec<(Chunk, Vec<f32>)> = chunks
+                        .into_iter()
+                        .zip(embeddings)
+                        .filter(|(_, embedding)| !embedding.is_empty())
+                        .collect();
+
+                    Ok(chunk_embeddings)
+                }
+                Err(_) => Err(ChunkerError::Other(
+                    "Failed to generate embeddings".to_string(),
+                )),
+            }
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?
+    }
+
+    async fn extract_text(&self, path: &Path) -> ChunkerResult<String> {
+        let buffer = tokio::fs::read(path).await?;
+
+        tokio::task::spawn_blocking(move || -> ChunkerResult<String> {
+            Ok(read_slides(buffer)?
+                .iter()
+                .map(Slide::content)
+                .collect::<Vec<_>>()
+                .join("\n\n"))
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?
+    }
+}
+
+fn pptx_error(e: impl std::fmt::Display) -> ChunkerError {
+    ChunkerError::Other(format!("Failed to parse PPTX: {}", e))
+}
+
+fn read_entry(archive: &mut ZipArchive<Cursor<Vec<u8>>>, name: &str) -> ChunkerResult<String> {
+    let mut entry = archive.by_name(name).map_err(pptx_error)?;
+    let mut xml = String::new();
+    entry.read_to_string(&mut xml)?;
+    Ok(xml)
+}
+
+/// Id to target of every relationship in a .rels file, targets are resolved against `base_dir`
+fn read_relationships(xml: &str, base_dir: &str) -> HashMap<String, String> {
+    let mut relationships = HashMap::new();
+    let mut reader = Reader::from_str(xml);
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e))
+                if e.local_name().as_ref() == b"Relationship" =>
+            {
+                let mut id = None;
+                let mut target = None;
+                for attribute in e.attributes().flatten() {
+                    let value = String::from_utf8_lossy(&attribute.value).to_string();
+                    match attribute.key.local_name().as_ref() {
+                        b"Id" => id = Some(value),
+                        b"Target" => target = Some(value),
+                        _ => {}
+                    }
+                }
+                if let (Some(id), Some(target)) = (id, target) {
+                    relationships.insert(id, resolve_target(base_dir, &target));
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    relationships
+}
+
+/// Resolves a relationship target like `../notesSlides/notesSlide1.xml` to its path in the archive
+fn resolve_target(base_dir: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    }
+
+    let mut parts: Vec<&str> = base_dir
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect();
+    for part in target.split('/') {
+        match part {
+            ".." => {
+                parts.pop();
+            }
+            "." | "" => {}
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// Paths of the slides in the order of the deck, which can differ from the numbers in their names
+fn slide_paths(archive: &mut ZipArchive<Cursor<Vec<u8>>>) -> Vec<String> {
+    let ordered = (|| -> ChunkerResult<Vec<String>> {
+        let presentation = read_entry(archive, "ppt/presentation.xml")?;
+        let relationships = read_relationships(
+            &read_entry(archive, "ppt/_rels/presentation.xml.rels")?,
+            "ppt",
+        );
+
+        let mut paths = Vec::new();
+        let mut reader = Reader::from_str(&presentation);
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(e)) | Ok(Event::Empty(e))
+                    if e.local_name().as_ref() == b"sldId" =>
+                {
+                    let relationship_id = e
+                        .attributes()
+                        .flatten()
+                        .find(|attribute| attribute.key.local_name().as_ref() == b"id")
+                        .map(|attribute| String::from_utf8_lossy(&attribute.value).to_string());
+                    if let Some(path) = relationship_id.and_then(|id| relationships.get(&id)) {
+                        paths.push(path.clone());
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(pptx_error(e)),
+                _ => {}
+            }
+        }
+        Ok(paths)
+    })();
+
+    match ordered {
+        Ok(paths) if !paths.is_empty() => paths,
+        _ => {
+            // fall back to the numbers in the slide file names
+            let mut numbered: Vec<(usize, String)> = archive
+                .file_names()
+                .filter_map(|name| {
+                    let number = name
+                        .strip_prefix("ppt/slides/slide")?
+                        .strip_suffix(".xml")?
+                        .parse()
+                        .ok()?;
+                    Some((number, name.to_string()))
+                })
+                .collect();
+            numbered.sort();
+            numbered.into_iter().map(|(_, name)| name).collect()
+        }
+    }
+}
+
+/// Text of the `a:t` runs of a slide or notes slide, a line per paragraph
+/// Fields like the slide number on notes pages are left out
+fn extract_xml_text(xml: &str) -> ChunkerResult<String> {
+    let mut reader = Reader::from_str(xml);
+    let mut text = String::new();
+    let mut in_text = false;
+    let mut field_depth = 0;
+
+    loop {
+        match reader.read_event().map_err(pptx_error)? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"t" => in_text = true,
+                b"fld" => field_depth += 1,
+                _ => {}
+            },
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"fld" => field_depth -= 1,
+                b"p" if !text.is_empty() && !text.ends_with('\n') => text.push('\n'),
+                _ => {}
+            },
+            Event::Text(e) if in_text && field_depth == 0 => {
+                text.push_str(&e.unescape().map_err(pptx_error)?);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(text.trim().to_string())
+}
+
+fn read_slides(buffer: Vec<u8>) -> ChunkerResult<Vec<Slide>> {
+    let mut archive = ZipArchive::new(Cursor::new(buffer)).map_err(pptx_error)?;
+    let mut slides = Vec::new();
+
+    for (index, slide_path) in slide_paths(&mut archive).into_iter().enumerate() {
+        let text =
+            match read_entry(&mut archive, &slide_path).and_then(|xml| extract_xml_text(&xml)) {
+                Ok(text) => text,
+                Err(e) => {
+                    warn!("Skipping slide {}: {}", slide_path, e);
+                    continue;
+                }
+            };
+
+        // the notes slide is linked from the slide's relationships
+        let (slide_dir, slide_file) = slide_path.rsplit_once('/').unwrap_or(("", &slide_path));
+        let rels_path = format!("{}/_rels/{}.rels", slide_dir, slide_file);
+        let notes = read_entry(&mut archive, &rels_path)
+            .ok()
+            .and_then(|rels| {
+                read_relationships(&rels, slide_dir)
+                    .into_values()
+                    .find(|target| target.contains("notesSlides/"))
+            })
+            .and_then(|notes_path| read_entry(&mut archive, &notes_path).ok())
+            .and_then(|xml| extract_xml_text(&xml).ok())
+            .unwrap_or_default();
+
+        slides.push(Slide {
+            number: index + 1,
+            text,
+            notes,
+        });
+    }
+
+    Ok(slides)
+}
+
+/// Chunks every slide on its own, long slides are split but keep their slide number
+fn slides_to_chunks(slides: &[Slide], path: &Path, config: &ChunkerConfig) -> Vec<Chunk> {
+    let mut chunks: Vec<Chunk> = Vec::new();
+
+    for slide in slides {
+        let content = slide.content();
+        let content = if config.normalize_text {
+            util::normalize_text(&content)
+        } else {
+            content
+        };
+
+        for text in util::chunk_text(&content, config) {
+            if text.trim().is_empty() {
+                continue;
+            }
+            chunks.push(Chunk {
+                content: text,
+                metadata: ChunkMetadata {
+                    source_path: path.to_path_buf(),
+                    chunk_index: chunks.len(),
+                    total_chunks: None,
+                    page_number: Some(slide.number),
+                    section: None,
+                    mime_type: PPTX_MIME_TYPE.to_string(),
+                    row_range: None,
+                    time_range: None,
+                },
+            });
+        }
+    }
+
+    let total_chunks = chunks.len();
+    for chunk in &mut chunks {
+        chunk.metadata.total_chunks = Some(total_chunks);
+    }
+    chunks
+}
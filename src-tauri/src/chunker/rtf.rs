@@ -0,0 +1,303 @@
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::debug;
+
+use crate::embedder::Embedder;
+use crate::file_processor::FileMetadata;
+
+use super::common::{Chunk, ChunkMetadata, ChunkerConfig, ChunkerResult};
+use super::Chunker;
+use super::{util, ChunkerError};
+
+const RTF_MIME_TYPE: &str = "application/rtf";
+
+/// Destinations that hold formatting tables or embedded data instead of document text
+const SKIPPED_DESTINATIONS: [&str; 18] = [
+    "fonttbl",
+    "colortbl",
+    "stylesheet",
+    "info",
+    "pict",
+    "object",
+    "fldinst",
+    "themedata",
+    "colorschememapping",
+    "datastore",
+    "latentstyles",
+    "listtable",
+    "listoverridetable",
+    "rsidtbl",
+    "generator",
+    "xmlnstbl",
+    "filetbl",
+    "revtbl",
+];
+
+/// Parser for RTF documents, the control words are stripped and the text is chunked like a plain text file
+#[derive(Default)]
+pub struct RtfChunker;
+
+#[async_trait]
+impl Chunker for RtfChunker {
+    fn name(&self) -> &str {
+        "rtf"
+    }
+
+    fn supported_mime_types(&self) -> Vec<&str> {
+        vec![RTF_MIME_TYPE, "text/rtf"]
+    }
+
+    fn extensions(&self) -> Vec<&str> {
+        vec!["rtf"]
+    }
+
+    fn can_chunk_file_type(&self, path: &Path) -> bool {
+        match util::detect_mime_type(path) {
+            Ok(mime) => mime == RTF_MIME_TYPE || mime == "text/rtf",
+            Err(_) => false,
+        }
+    }
+
+    async fn chunk_file(
+        &self,
+        file: &FileMetadata,
+        config: &ChunkerConfig,
+        embedder: Arc<Embedder>,
+    ) -> ChunkerResult<Vec<(Chunk, Vec<f32>)>> {
+        debug!("Creating RTF chunks for file {:?}", file.base.path);
+
+        let path = PathBuf::from(&file.base.path);
+        let buffer = tokio::fs::read(&path).await?;
+        let config = config.clone();
+
+        let chunks = tokio::task::spawn_blocking(move || {
+            text_to_chunks(&rtf_to_text(&buffer), &path, &config, RTF_MIME_TYPE)
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?;
+
+        if chunks.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        tokio::task::spawn_blocking(move || {
+            let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
+
+            match embedder.model.embed(texts, embedder.batch_size()) {
+                Ok(embeddings) => {
+                    let chunk_embeddings: Vec<(Chunk, Vec<f32>)> = chunks
+                        .into_iter()
+                        .zip(embeddings)
+                        .filter(|(_, embedding)| !embedding.is_empty())
+                        .collect();
+
+                    Ok(chunk_embeddings)
+                }
+                Err(_) => Err(ChunkerError::Other(
+                    "Failed to generate embeddings".to_string(),
+                )),
+            }
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?
+    }
+
+    async fn extract_text(&self, path: &Path) -> ChunkerResult<String> {
+        let buffer = tokio::fs::read(path).await?;
+
+        tokio::task::spawn_blocking(move || rtf_to_text(&buffer))
+            .await
+            .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))
+    }
+}
+
+/// Splits the extracted text of a document into chunks with the given MIME type
+pub(super) fn text_to_chunks(
+    text: &str,
+    path: &Path,
+    config: &ChunkerConfig,
+    mime_type: &str,
+) -> Vec<Chunk> {
+    let text = if config.normalize_text {
+        util::normalize_text(text)
+    } else {
+        text.to_string()
+    };
+
+    let text_chunks = util::chunk_text(&text, config);
+    let total_chunks = text_chunks.len();
+
+    text_chunks
+        .into_iter()
+        .enumerate()
+        .map(|(idx, content)| Chunk {
+            content,
+            metadata: ChunkMetadata {
+                source_path: path.to_path_buf(),
+                chunk_index: idx,
+                total_chunks: Some(total_chunks),
+                page_number: None,
+                section: None,
+                mime_type: mime_type.to_string(),
+                row_range: None,
+                time_range: None,
+            },
+        })
+        .collect()
+}
+
+/// True when the bytes are an RTF document, whatever the extension of the file says
+pub(super) fn is_rtf(buffer: &[u8]) -> bool {
+    buffer.starts_with(b"{\\rtf")
+}
+
+#[derive(Clone, Copy)]
+struct GroupState {
+    /// Inside a destination whose text isn't part of the document
+    skip: bool,
+    /// Number of fallback characters that follow a \u character
+    unicode_skip: usize,
+}
+
+/// Reads the text of an RTF document
+/// Only what's needed for indexing is handled, paragraphs, tabs, escaped and unicode characters
+pub(super) fn rtf_to_text(buffer: &[u8]) -> String {
+    let mut text = String::new();
+    let mut state = GroupState {
+        skip: false,
+        unicode_skip: 1,
+    };
+    let mut stack: Vec<GroupState> = Vec::new();
+    // fallback characters still to drop after a \u character
+    let mut pending_skip = 0;
+    let mut i = 0;
+
+    while i < buffer.len() {
+        let byte = buffer[i];
+        i += 1;
+
+        match byte {
+            b'{' => stack.push(state),
+            b'}' => {
+                state = stack.pop().unwrap_or(state);
+                pending_skip = 0;
+            }
+            b'\r' | b'\n' => {}
+            b'\\' => {
+                let Some(&next) = buffer.get(i) else {
+                    break;
+                };
+
+                if !next.is_ascii_alphabetic() {
+                    i += 1;
+                    match next {
+                        b'\\' | b'{' | b'}' => {
+                            push(&mut text, &mut pending_skip, state.skip, next as char)
+                        }
+                        b'~' => push(&mut text, &mut pending_skip, state.skip, ' '),
+                        b'_' => push(&mut text, &mut pending_skip, state.skip, '-'),
+                        b'*' => state.skip = true,
+                        b'\'' => {
+                            let hex = buffer.get(i..i + 2).unwrap_or_default();
+                            i += hex.len();
+                            if let Some(byte) = std::str::from_utf8(hex)
+                                .ok()
+                                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                            {
+                                push(&mut text, &mut pending_skip, state.skip, cp1252(byte));
+                            }
+                        }
+                        b'\r' | b'\n' => push(&mut text, &mut pending_skip, state.skip, '\n'),
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                let start = i;
+                while i < buffer.len() && buffer[i].is_ascii_alphabetic() {
+                    i += 1;
+                }
+                let word = String::from_utf8_lossy(&buffer[start..i]).to_string();
+
+                let param_start = i;
+                if i < buffer.len() && buffer[i] == b'-' {
+                    i += 1;
+                }
+                while i < buffer.len() && buffer[i].is_ascii_digit() {
+                    i += 1;
+                }
+                let param: Option<i64> = std::str::from_utf8(&buffer[param_start..i])
+                    .ok()
+                    .and_then(|param| param.parse().ok());
+
+                // a single space ends the control word and isn't part of the text
+                if i < buffer.len() && buffer[i] == b' ' {
+                    i += 1;
+                }
+
+                match word.as_str() {
+                    "par" | "line" | "sect" | "page" | "row" => {
+                        push(&mut text, &mut pending_skip, state.skip, '\n')
+                    }
+                    "tab" | "cell" => push(&mut text, &mut pending_skip, state.skip, '\t'),
+                    "emdash" => push(&mut text, &mut pending_skip, state.skip, '—'),
+                    "endash" => push(&mut text, &mut pending_skip, state.skip, '–'),
+                    "bullet" => push(&mut text, &mut pending_skip, state.skip, '•'),
+                    "lquote" => push(&mut text, &mut pending_skip, state.skip, '‘'),
+                    "rquote" => push(&mut text, &mut pending_skip, state.skip, '’'),
+                    "ldblquote" => push(&mut text, &mut pending_skip, state.skip, '“'),
+                    "rdblquote" => push(&mut text, &mut pending_skip, state.skip, '”'),
+                    "uc" => state.unicode_skip = param.unwrap_or(1).max(0) as usize,
+                    "u" => {
+                        if let Some(code) = param {
+                            // code points above 32767 are written as negative numbers
+                            let code = if code < 0 { code + 65536 } else { code };
+                            let c = char::from_u32(code as u32).unwrap_or('\u{FFFD}');
+                            pending_skip = 0;
+                            push(&mut text, &mut pending_skip, state.skip, c);
+                            pending_skip = state.unicode_skip;
+                        }
+                    }
+                    "bin" => {
+                        // binary data is skipped without looking at it
+                        i += param.unwrap_or(0).max(0) as usize;
+                    }
+                    word if SKIPPED_DESTINATIONS.contains(&word) => state.skip = true,
+                    _ => {}
+                }
+            }
+            byte => push(&mut text, &mut pending_skip, state.skip, cp1252(byte)),
+        }
+    }
+
+    text.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Adds a character of the document, unless it's the fallback of a \u character or in a skipped destination
+fn push(text: &mut String, pending_skip: &mut usize, skip: bool, c: char) {
+    if *pending_skip > 0 {
+        *pending_skip -= 1;
+    } else if !skip {
+        text.push(c);
+    }
+}
+
+/// Decodes a byte of the Windows-1252 code page, the default of RTF documents
+fn cp1252(byte: u8) -> char {
+    const HIGH: [char; 32] = [
+        '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž',
+        '\u{8F}', '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}',
+        'ž', 'Ÿ',
+    ];
+
+    match byte {
+        0x80..=0x9F => HIGH[(byte - 0x80) as usize],
+        byte => byte as char,
+    }
+}
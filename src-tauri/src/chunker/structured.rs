@@ -0,0 +1,254 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::debug;
+
+use crate::embedder::Embedder;
+use crate::file_processor::FileMetadata;
+
+use super::common::{Chunk, ChunkMetadata, ChunkerConfig, ChunkerResult};
+use super::Chunker;
+use super::ChunkerError;
+
+#[derive(Debug, Clone, Copy)]
+enum Format {
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_string_lossy().to_lowercase();
+        match ext.as_str() {
+            "yaml" | "yml" => Some(Format::Yaml),
+            "toml" => Some(Format::Toml),
+            _ => None,
+        }
+    }
+
+    fn mime_type(self) -> &'static str {
+        match self {
+            Format::Yaml => "application/yaml",
+            Format::Toml => "application/toml",
+        }
+    }
+}
+
+/// Parser for YAML and TOML files, every value is written on a line with its key path like `server.hosts[0]: example.com`
+/// Lines are grouped by their top level key so a chunk stays within one part of the file
+#[derive(Default)]
+pub struct StructuredChunker;
+
+#[async_trait]
+impl Chunker for StructuredChunker {
+    fn name(&self) -> &str {
+        "structured"
+    }
+
+    fn supported_mime_types(&self) -> Vec<&str> {
+        vec!["application/yaml", "application/toml"]
+    }
+
+    fn extensions(&self) -> Vec<&str> {
+        vec!["yaml", "yml", "toml"]
+    }
+
+    fn can_chunk_file_type(&self, path: &Path) -> bool {
+        Format::from_path(path).is_some()
+    }
+
+    async fn chunk_file(
+        &self,
+        file: &FileMetadata,
+        config: &ChunkerConfig,
+        embedder: Arc<Embedder>,
+    ) -> ChunkerResult<Vec<(Chunk, Vec<f32>)>> {
+        debug!("Creating structured chunks for file {:?}", file.base.path);
+
+        let path = PathBuf::from(&file.base.path);
+        let format = Format::from_path(&path).ok_or_else(|| {
+            ChunkerError::UnsupportedType(format!("Not a YAML or TOML file: {:?}", path))
+        })?;
+        let content = tokio::fs::read_to_string(&path).await?;
+
+        let entries = flatten_file(&content, format)?;
+        let chunks = entries_to_chunks(&entries, &path, config, format);
+
+        if chunks.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        tokio::task::spawn_blocking(move || {
+            let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
+
+            match embedder.model.embed(texts, embedder.batch_size()) {
+                Ok(embeddings) => {
+                    let chunk_embeddings: Vec<(Chunk, Vec<f32>)> = chunks
+                        .into_iter()
+                        .zip(embeddings)
+                        .filter(|(_, embedding)| !embedding.is_empty())
+                        .collect();
+
+                    Ok(chunk_embeddings)
+                }
+                Err(_) => Err(ChunkerError::Other(
+                    "Failed to generate embeddings".to_string(),
+                )),
+            }
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?
+    }
+}
+
+/// Parses the file into the same values as JSON and flattens them into key path and value pairs
+fn flatten_file(content: &str, format: Format) -> ChunkerResult<Vec<(String, String)>> {
+    let value = match format {
+        Format::Yaml => {
+            // a YAML file can hold several documents, like Kubernetes manifests
+            let mut documents = Vec::new();
+            for document in serde_yaml::Deserializer::from_str(content) {
+                let value = Value::deserialize(document)
+                    .map_err(|e| ChunkerError::Other(format!("Failed to parse YAML: {}", e)))?;
+                if !value.is_null() {
+                    documents.push(value);
+                }
+            }
+
+            if documents.len() == 1 {
+                documents.remove(0)
+            } else {
+                Value::Array(documents)
+            }
+        }
+        Format::Toml => {
+            let table: toml::Table = content
+                .parse()
+                .map_err(|e| ChunkerError::Other(format!("Failed to parse TOML: {}", e)))?;
+            toml_to_json(toml::Value::Table(table))
+        }
+    };
+
+    let mut entries = Vec::new();
+    flatten(&value, String::new(), &mut entries);
+    Ok(entries)
+}
+
+/// TOML dates have no JSON type, they're kept as the text they were written as
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Value::from(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(d) => Value::String(d.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_to_json(value)))
+                .collect::<Map<String, Value>>(),
+        ),
+    }
+}
+
+fn flatten(value: &Value, key_path: String, entries: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                let child_path = if key_path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", key_path, key)
+                };
+                flatten(child, child_path, entries);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (index, item) in items.iter().enumerate() {
+                flatten(item, format!("{}[{}]", key_path, index), entries);
+            }
+        }
+        Value::String(s) => entries.push((key_path, s.clone())),
+        other => entries.push((key_path, other.to_string())),
+    }
+}
+
+/// First key of the path, `server` for `server.hosts[0]` and `[1]` for the items of a top level list
+fn top_level_key(key_path: &str) -> &str {
+    let end = if key_path.starts_with('[') {
+        key_path.find(']').map(|index| index + 1)
+    } else {
+        key_path.find(['.', '['])
+    };
+    &key_path[..end.unwrap_or(key_path.len())]
+}
+
+/// Packs the lines of each top level key into chunks of up to chunk_size words
+fn entries_to_chunks(
+    entries: &[(String, String)],
+    path: &Path,
+    config: &ChunkerConfig,
+    format: Format,
+) -> Vec<Chunk> {
+    let mut chunks: Vec<Chunk> = Vec::new();
+    let mut content = String::new();
+    let mut words = 0;
+    let mut section: Option<&str> = None;
+
+    for (key_path, value) in entries {
+        let line = if key_path.is_empty() {
+            value.clone()
+        } else {
+            format!("{}: {}", key_path, value)
+        };
+        let line_words = line.split_whitespace().count();
+        let key = top_level_key(key_path);
+
+        if words > 0 && (section != Some(key) || words + line_words > config.chunk_size) {
+            push_chunk(&mut chunks, &mut content, section, path, format);
+            words = 0;
+        }
+
+        section = Some(key);
+        content.push_str(&line);
+        content.push('\n');
+        words += line_words;
+    }
+    push_chunk(&mut chunks, &mut content, section, path, format);
+
+    let total_chunks = chunks.len();
+    for chunk in &mut chunks {
+        chunk.metadata.total_chunks = Some(total_chunks);
+    }
+    chunks
+}
+
+fn push_chunk(
+    chunks: &mut Vec<Chunk>,
+    content: &mut String,
+    section: Option<&str>,
+    path: &Path,
+    format: Format,
+) {
+    if content.trim().is_empty() {
+        content.clear();
+        return;
+    }
+
+    chunks.push(Chunk {
+        content: std::mem::take(content),
+        metadata: ChunkMetadata {
+            source_path: path.to_path_buf(),
+            chunk_index: chunks.len(),
+            total_chunks: None,
+            page_number: None,
+            section: section.filter(|key| !key.is_empty()).map(str::to_string),
+            mime_type: format.mime_type().to_string(),
+            row_range: None,
+            time_range: None,
+        },
+    });
+}
@@ -18,6 +18,10 @@ pub struct TxtChunker;
 
 #[async_trait]
 impl Chunker for TxtChunker {
+    fn name(&self) -> &str {
+        "txt"
+    }
+
     fn supported_mime_types(&self) -> Vec<&str> {
         vec!["text/plain"]
     }
@@ -56,7 +60,7 @@ impl Chunker for TxtChunker {
             let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
 
             // Generate embeddings in one batch call
-            match embedder.model.embed(texts, None) {
+            match embedder.model.embed(texts, embedder.batch_size()) {
                 Ok(embeddings) => {
                     // Pair chunks with their embeddings
                     let chunk_embeddings: Vec<(Chunk, Vec<f32>)> = chunks
@@ -117,6 +121,8 @@ async fn get_chunks_from_large_file(
                     page_number: None,
                     section: None,
                     mime_type: "text/plain".to_string(),
+                    row_range: None,
+                    time_range: None,
                 },
             });
 
@@ -164,6 +170,8 @@ async fn get_chunks_from_large_file(
                 page_number: None,
                 section: None,
                 mime_type: "text/plain".to_string(),
+                row_range: None,
+                time_range: None,
             },
         });
     }
@@ -195,7 +203,7 @@ async fn get_chunks_from_small_file(
     };
 
     // Create text chunks
-    let text_chunks = util::chunk_text(&processed_content, config.chunk_size, config.chunk_overlap);
+    let text_chunks = util::chunk_text(&processed_content, config);
 
     if text_chunks.is_empty() {
         return Ok(Vec::new());
@@ -215,6 +223,8 @@ async fn get_chunks_from_small_file(
                 page_number: None,
                 section: None,
                 mime_type: "text/plain".to_string(),
+                row_range: None,
+                time_range: None,
             },
         })
         .collect();
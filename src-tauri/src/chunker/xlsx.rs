@@ -0,0 +1,371 @@
+use async_trait::async_trait;
+use calamine::{open_workbook_auto, Data, Reader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use crate::embedder::Embedder;
+use crate::file_processor::FileMetadata;
+
+use super::common::{Chunk, ChunkMetadata, ChunkerConfig, ChunkerResult};
+use super::Chunker;
+use super::ChunkerError;
+
+const XLSX_MIME_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+const CSV_MIME_TYPE: &str = "text/csv";
+
+/// Rows of one sheet, the number is the row's position in the sheet counted from 1
+struct Sheet {
+    name: String,
+    rows: Vec<(usize, Vec<String>)>,
+}
+
+/// Parser for Excel and OpenDocument spreadsheets, every sheet is chunked on its own
+#[derive(Default)]
+pub struct XlsxChunker;
+
+/// Parser for CSV files, chunked like a spreadsheet with a single sheet
+#[derive(Default)]
+pub struct CsvChunker;
+
+#[async_trait]
+impl Chunker for XlsxChunker {
+    fn name(&self) -> &str {
+        "xlsx"
+    }
+
+    fn supported_mime_types(&self) -> Vec<&str> {
+        vec![XLSX_MIME_TYPE]
+    }
+
+    /// calamine reads the older Excel and OpenDocument formats too
+    fn extensions(&self) -> Vec<&str> {
+        vec!["xls", "xlsm", "xlsb", "ods"]
+    }
+
+    fn can_chunk_file_type(&self, path: &Path) -> bool {
+        has_extension(path, &["xlsx", "xls", "xlsm", "xlsb", "ods"])
+    }
+
+    async fn chunk_file(
+        &self,
+        file: &FileMetadata,
+        config: &ChunkerConfig,
+        embedder: Arc<Embedder>,
+    ) -> ChunkerResult<Vec<(Chunk, Vec<f32>)>> {
+        debug!("Creating spreadsheet chunks for file {:?}", file.base.path);
+
+        let path = PathBuf::from(&file.base.path);
+        let config = config.clone();
+
+        let chunks = tokio::task::spawn_blocking(move || {
+            let sheets = read_workbook(&path)?;
+            Ok::<_, ChunkerError>(sheets_to_chunks(&sheets, &path, &config, XLSX_MIME_TYPE))
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))??;
+
+        embed_chunks(chunks, embedder).await
+    }
+
+    async fn extract_text(&self, path: &Path) -> ChunkerResult<String> {
+        let path = path.to_path_buf();
+
+        tokio::task::spawn_blocking(move || -> ChunkerResult<String> {
+            Ok(sheets_to_text(&read_workbook(&path)?))
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?
+    }
+}
+
+#[async_trait]
+impl Chunker for CsvChunker {
+    fn name(&self) -> &str {
+        "csv"
+    }
+
+    fn supported_mime_types(&self) -> Vec<&str> {
+        vec![CSV_MIME_TYPE]
+    }
+
+    fn extensions(&self) -> Vec<&str> {
+        vec!["tsv"]
+    }
+
+    fn can_chunk_file_type(&self, path: &Path) -> bool {
+        has_extension(path, &["csv", "tsv"])
+    }
+
+    async fn chunk_file(
+        &self,
+        file: &FileMetadata,
+        config: &ChunkerConfig,
+        embedder: Arc<Embedder>,
+    ) -> ChunkerResult<Vec<(Chunk, Vec<f32>)>> {
+        debug!("Creating CSV chunks for file {:?}", file.base.path);
+
+        let path = PathBuf::from(&file.base.path);
+        let config = config.clone();
+
+        let chunks = tokio::task::spawn_blocking(move || {
+            let sheet = read_csv(&path)?;
+            Ok::<_, ChunkerError>(sheets_to_chunks(&[sheet], &path, &config, CSV_MIME_TYPE))
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))??;
+
+        embed_chunks(chunks, embedder).await
+    }
+
+    async fn extract_text(&self, path: &Path) -> ChunkerResult<String> {
+        let path = path.to_path_buf();
+
+        tokio::task::spawn_blocking(move || -> ChunkerResult<String> {
+            Ok(sheets_to_text(&[read_csv(&path)?]))
+        })
+        .await
+        .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?
+    }
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| extensions.contains(&ext.as_str()))
+}
+
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        other => other.to_string().trim().to_string(),
+    }
+}
+
+/// Reads every sheet of the workbook, sheets that fail to load are skipped
+fn read_workbook(path: &Path) -> ChunkerResult<Vec<Sheet>> {
+    let mut workbook = open_workbook_auto(path)
+        .map_err(|e| ChunkerError::Other(format!("Failed to open spreadsheet: {}", e)))?;
+
+    let mut sheets = Vec::new();
+    for name in workbook.sheet_names() {
+        let range = match workbook.worksheet_range(&name) {
+            Ok(range) => range,
+            Err(e) => {
+                warn!("Skipping sheet {} of {:?}: {}", name, path, e);
+                continue;
+            }
+        };
+
+        // the range starts at the first used cell, which isn't always A1
+        let first_row = range.start().map(|(row, _)| row as usize).unwrap_or(0);
+        let rows = range
+            .rows()
+            .enumerate()
+            .map(|(offset, cells)| {
+                (
+                    first_row + offset + 1,
+                    cells.iter().map(cell_text).collect(),
+                )
+            })
+            .collect();
+
+        sheets.push(Sheet { name, rows });
+    }
+
+    Ok(sheets)
+}
+
+fn read_csv(path: &Path) -> ChunkerResult<Sheet> {
+    let delimiter = if has_extension(path, &["tsv"]) {
+        b'\t'
+    } else {
+        b','
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_path(path)
+        .map_err(|e| ChunkerError::Other(format!("Failed to open CSV: {}", e)))?;
+
+    let mut rows = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let record =
+            record.map_err(|e| ChunkerError::Other(format!("Failed to parse CSV: {}", e)))?;
+        rows.push((
+            index + 1,
+            record
+                .iter()
+                .map(|field| field.trim().to_string())
+                .collect(),
+        ));
+    }
+
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    Ok(Sheet { name, rows })
+}
+
+/// Writes a row as `header: value` pairs so every chunk carries the meaning of its columns
+/// Columns without a header are named by their letter, empty cells are left out
+fn row_text(headers: &[String], cells: &[String]) -> String {
+    cells
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| !cell.is_empty())
+        .map(
+            |(column, cell)| match headers.get(column).filter(|h| !h.is_empty()) {
+                Some(header) => format!("{}: {}", header, cell),
+                None => format!("{}: {}", column_name(column), cell),
+            },
+        )
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Spreadsheet name of the column, A for the first one
+fn column_name(mut column: usize) -> String {
+    let mut name = String::new();
+    loop {
+        name.insert(0, (b'A' + (column % 26) as u8) as char);
+        if column < 26 {
+            break;
+        }
+        column = column / 26 - 1;
+    }
+    name
+}
+
+/// Splits the rows of each sheet into chunks of whole rows, up to chunk_size words each
+/// The first non-empty row of a sheet is taken as its header row
+fn sheets_to_chunks(
+    sheets: &[Sheet],
+    path: &Path,
+    config: &ChunkerConfig,
+    mime_type: &str,
+) -> Vec<Chunk> {
+    let mut chunks: Vec<Chunk> = Vec::new();
+
+    for sheet in sheets {
+        let mut rows = sheet
+            .rows
+            .iter()
+            .filter(|(_, cells)| cells.iter().any(|cell| !cell.is_empty()));
+
+        let Some((_, headers)) = rows.next() else {
+            continue;
+        };
+
+        let mut content = String::new();
+        let mut words = 0;
+        let mut range: Option<(usize, usize)> = None;
+
+        for (row_number, cells) in rows {
+            let line = row_text(headers, cells);
+            let line_words = line.split_whitespace().count();
+
+            if words > 0 && words + line_words > config.chunk_size {
+                push_chunk(
+                    &mut chunks,
+                    &mut content,
+                    range.take(),
+                    sheet,
+                    path,
+                    mime_type,
+                );
+                words = 0;
+            }
+
+            content.push_str(&line);
+            content.push('\n');
+            words += line_words;
+            range = Some(match range {
+                Some((start, _)) => (start, *row_number),
+                None => (*row_number, *row_number),
+            });
+        }
+
+        push_chunk(&mut chunks, &mut content, range, sheet, path, mime_type);
+    }
+
+    let total_chunks = chunks.len();
+    for chunk in &mut chunks {
+        chunk.metadata.total_chunks = Some(total_chunks);
+    }
+    chunks
+}
+
+fn push_chunk(
+    chunks: &mut Vec<Chunk>,
+    content: &mut String,
+    row_range: Option<(usize, usize)>,
+    sheet: &Sheet,
+    path: &Path,
+    mime_type: &str,
+) {
+    if content.trim().is_empty() {
+        content.clear();
+        return;
+    }
+
+    chunks.push(Chunk {
+        content: std::mem::take(content),
+        metadata: ChunkMetadata {
+            source_path: path.to_path_buf(),
+            chunk_index: chunks.len(),
+            total_chunks: None,
+            page_number: None,
+            section: Some(sheet.name.clone()),
+            mime_type: mime_type.to_string(),
+            row_range,
+            time_range: None,
+        },
+    });
+}
+
+fn sheets_to_text(sheets: &[Sheet]) -> String {
+    sheets
+        .iter()
+        .map(|sheet| {
+            let rows = sheet
+                .rows
+                .iter()
+                .map(|(_, cells)| cells.join("\t"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!("{}\n{}", sheet.name, rows)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+async fn embed_chunks(
+    chunks: Vec<Chunk>,
+    embedder: Arc<Embedder>,
+) -> ChunkerResult<Vec<(Chunk, Vec<f32>)>> {
+    if chunks.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
+
+        match embedder.model.embed(texts, embedder.batch_size()) {
+            Ok(embeddings) => Ok(chunks
+                .into_iter()
+                .zip(embeddings)
+                .filter(|(_, embedding)| !embedding.is_empty())
+                .collect()),
+            Err(_) => Err(ChunkerError::Other(
+                "Failed to generate embeddings".to_string(),
+            )),
+        }
+    })
+    .await
+    .map_err(|e| ChunkerError::Other(format!("Thread error: {:?}", e)))?
+}
@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
+#[cfg(target_os = "macos")]
 use std::ffi::{c_char, CStr};
+#[cfg(target_os = "macos")]
 use std::os::raw::c_int;
-use std::str;
 use thiserror::Error;
+#[cfg(target_os = "macos")]
+use tracing::debug;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Contact {
@@ -20,6 +23,7 @@ pub struct ContactPhone {
 }
 
 #[derive(Debug, Error)]
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
 pub enum ContactError {
     #[error("Permission denied to access contacts")]
     PermissionDenied,
@@ -34,6 +38,7 @@ pub enum ContactError {
     JsonError(String),
 }
 
+#[cfg(target_os = "macos")]
 extern "C" {
     fn check_contacts_permission_swift() -> c_int;
     fn request_contacts_permission_swift() -> c_int;
@@ -41,6 +46,7 @@ extern "C" {
     fn free_string_swift(pointer: *mut c_char);
 }
 
+#[cfg(target_os = "macos")]
 pub fn check_contacts_permission() -> Result<bool, ContactError> {
     // CNAuthorizationStatus: NotDetermined = 0, Restricted = 1, Denied = 2, Authorized = 3
     let status = unsafe { check_contacts_permission_swift() };
@@ -50,6 +56,7 @@ pub fn check_contacts_permission() -> Result<bool, ContactError> {
     }
 }
 
+#[cfg(target_os = "macos")]
 pub fn request_contacts_permission() -> Result<bool, ContactError> {
     let status = unsafe { request_contacts_permission_swift() };
     match status {
@@ -58,8 +65,9 @@ pub fn request_contacts_permission() -> Result<bool, ContactError> {
     }
 }
 
+#[cfg(target_os = "macos")]
 pub fn get_contacts() -> Result<Vec<Contact>, ContactError> {
-    println!("getting contacts...");
+    debug!("getting contacts...");
     if !check_contacts_permission()? {
         if !request_contacts_permission()? {
             return Err(ContactError::PermissionDenied);
@@ -105,6 +113,14 @@ pub fn get_contacts() -> Result<Vec<Contact>, ContactError> {
     Ok(contacts)
 }
 
+/// Contacts are read from the Contacts framework, other platforms have no address book to read
+#[cfg(not(target_os = "macos"))]
+pub fn get_contacts() -> Result<Vec<Contact>, ContactError> {
+    Err(ContactError::AccessError(
+        "Contacts are only available on macOS".to_string(),
+    ))
+}
+
 #[tauri::command]
 pub async fn get_contacts_command() -> Result<Vec<Contact>, String> {
     match get_contacts() {
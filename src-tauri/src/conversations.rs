@@ -0,0 +1,386 @@
+/*
+This file contains the chat conversations. Every conversation and its messages are stored in the index database, so the chat window can host several of them and restore them after a relaunch
+*/
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, State};
+use tokio::task;
+
+use crate::database_handler::open_connection;
+use crate::file_processor::{get_processor, FileProcessor, FileProcessorError, FileProcessorState};
+
+const DEFAULT_CONVERSATION_TITLE: &str = "New conversation";
+
+/// Longest title taken from the first question of a conversation
+const MAX_TITLE_CHARS: usize = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageRole {
+    User,
+    Assistant,
+}
+
+impl MessageRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageRole::User => "user",
+            MessageRole::Assistant => "assistant",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Option<Self> {
+        match value {
+            "user" => Some(MessageRole::User),
+            "assistant" => Some(MessageRole::Assistant),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conversation {
+    pub id: i64,
+    pub title: String,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationMessage {
+    pub id: i64,
+    pub conversation_id: i64,
+    pub role: MessageRole,
+    pub content: String,
+    pub sources: Vec<String>,
+    pub created_at: Option<String>,
+}
+
+/// Title of a conversation started with the question, cut at a word boundary
+fn title_from_question(question: &str) -> String {
+    let question = question.split_whitespace().collect::<Vec<_>>().join(" ");
+    if question.chars().count() <= MAX_TITLE_CHARS {
+        return question;
+    }
+
+    let truncated: String = question.chars().take(MAX_TITLE_CHARS).collect();
+    let cut = truncated.rfind(' ').unwrap_or(truncated.len());
+    format!("{}…", &truncated[..cut])
+}
+
+fn load_conversation(conn: &Connection, id: i64) -> rusqlite::Result<Option<Conversation>> {
+    conn.query_row(
+        "SELECT id, title, created_at, updated_at FROM conversations WHERE id = ?1",
+        [id],
+        |row| {
+            Ok(Conversation {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                created_at: row.get(2).ok(),
+                updated_at: row.get(3).ok(),
+            })
+        },
+    )
+    .optional()
+}
+
+async fn insert_conversation(
+    db_path: PathBuf,
+    title: String,
+) -> Result<Conversation, FileProcessorError> {
+    task::spawn_blocking(move || -> Result<Conversation, FileProcessorError> {
+        let conn = open_connection(db_path)?;
+
+        conn.execute("INSERT INTO conversations (title) VALUES (?1)", [&title])?;
+
+        load_conversation(&conn, conn.last_insert_rowid())?
+            .ok_or_else(|| FileProcessorError::Other("Conversation was not saved".to_string()))
+    })
+    .await
+    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
+}
+
+async fn update_conversation_title(
+    db_path: PathBuf,
+    id: i64,
+    title: String,
+) -> Result<(), FileProcessorError> {
+    task::spawn_blocking(move || -> Result<(), FileProcessorError> {
+        let conn = open_connection(db_path)?;
+
+        conn.execute(
+            "UPDATE conversations SET title = ?1 WHERE id = ?2",
+            params![title, id],
+        )?;
+
+        Ok(())
+    })
+    .await
+    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
+}
+
+/// Deletes the conversation with its messages
+async fn delete_conversation_rows(db_path: PathBuf, id: i64) -> Result<(), FileProcessorError> {
+    task::spawn_blocking(move || -> Result<(), FileProcessorError> {
+        let mut conn = open_connection(db_path)?;
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "DELETE FROM conversation_messages WHERE conversation_id = ?1",
+            [id],
+        )?;
+        tx.execute("DELETE FROM conversations WHERE id = ?1", [id])?;
+
+        tx.commit()?;
+        Ok(())
+    })
+    .await
+    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
+}
+
+/// Appends a message and moves the conversation to the top of the list
+/// A conversation that still has the default title is named after its first question
+async fn insert_message(
+    db_path: PathBuf,
+    conversation_id: i64,
+    role: MessageRole,
+    content: String,
+    sources: Vec<String>,
+) -> Result<ConversationMessage, FileProcessorError> {
+    task::spawn_blocking(move || -> Result<ConversationMessage, FileProcessorError> {
+        let mut conn = open_connection(db_path)?;
+        let tx = conn.transaction()?;
+
+        let title: Option<String> = tx
+            .query_row(
+                "SELECT title FROM conversations WHERE id = ?1",
+                [conversation_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(title) = title else {
+            return Err(FileProcessorError::Other(format!(
+                "Conversation {} not found",
+                conversation_id
+            )));
+        };
+
+        let sources_json = serde_json::to_string(&sources)
+            .map_err(|e| FileProcessorError::Other(e.to_string()))?;
+        tx.execute(
+            r#"
+            INSERT INTO conversation_messages (conversation_id, role, content, sources)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            params![conversation_id, role.as_str(), content, sources_json],
+        )?;
+        let id = tx.last_insert_rowid();
+
+        if role == MessageRole::User && title == DEFAULT_CONVERSATION_TITLE {
+            tx.execute(
+                "UPDATE conversations SET title = ?1 WHERE id = ?2",
+                params![title_from_question(&content), conversation_id],
+            )?;
+        }
+        tx.execute(
+            "UPDATE conversations SET updated_at = CURRENT_TIMESTAMP WHERE id = ?1",
+            [conversation_id],
+        )?;
+
+        let created_at: Option<String> = tx
+            .query_row(
+                "SELECT created_at FROM conversation_messages WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .ok();
+
+        tx.commit()?;
+
+        Ok(ConversationMessage {
+            id,
+            conversation_id,
+            role,
+            content,
+            sources,
+            created_at,
+        })
+    })
+    .await
+    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
+}
+
+/// Returns the conversations, the most recently active first
+#[tauri::command]
+pub async fn list_conversations(
+    state: State<'_, FileProcessorState>,
+) -> Result<Vec<Conversation>, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let conn = processor.get_read_connection()?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT id, title, created_at, updated_at
+            FROM conversations
+            ORDER BY updated_at DESC, id DESC
+            "#,
+        )
+        .map_err(|e| format!("Failed to prepare statement: {e}"))?;
+
+    let conversations = stmt
+        .query_map([], |row| {
+            Ok(Conversation {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                created_at: row.get(2).ok(),
+                updated_at: row.get(3).ok(),
+            })
+        })
+        .map_err(|e| format!("Query error: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Row error: {e}"))?;
+
+    Ok(conversations)
+}
+
+#[tauri::command]
+pub async fn create_conversation(
+    title: Option<String>,
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<Conversation, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let title = title
+        .map(|title| title.trim().to_string())
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| DEFAULT_CONVERSATION_TITLE.to_string());
+
+    let conversation = insert_conversation(processor.db_path.clone(), title)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let _ = app_handle.emit("conversations-updated", ());
+    Ok(conversation)
+}
+
+#[tauri::command]
+pub async fn rename_conversation(
+    conversation_id: i64,
+    title: String,
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let title = title.trim().to_string();
+    if title.is_empty() {
+        return Err("The title can't be empty".to_string());
+    }
+
+    update_conversation_title(processor.db_path.clone(), conversation_id, title)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let _ = app_handle.emit("conversations-updated", ());
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_conversation(
+    conversation_id: i64,
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    delete_conversation_rows(processor.db_path.clone(), conversation_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let _ = app_handle.emit("conversations-updated", ());
+    Ok(())
+}
+
+/// Returns the messages of the conversation, oldest first
+#[tauri::command]
+pub async fn get_conversation_messages(
+    conversation_id: i64,
+    state: State<'_, FileProcessorState>,
+) -> Result<Vec<ConversationMessage>, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let conn = processor.get_read_connection()?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT id, role, content, sources, created_at
+            FROM conversation_messages
+            WHERE conversation_id = ?1
+            ORDER BY id
+            "#,
+        )
+        .map_err(|e| format!("Failed to prepare statement: {e}"))?;
+
+    let rows = stmt
+        .query_map([conversation_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4).ok().flatten(),
+            ))
+        })
+        .map_err(|e| format!("Query error: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Row error: {e}"))?;
+
+    let messages = rows
+        .into_iter()
+        .filter_map(|(id, role, content, sources, created_at)| {
+            Some(ConversationMessage {
+                id,
+                conversation_id,
+                role: MessageRole::from_db(&role)?,
+                content,
+                sources: sources
+                    .and_then(|json| serde_json::from_str(&json).ok())
+                    .unwrap_or_default(),
+                created_at,
+            })
+        })
+        .collect();
+
+    Ok(messages)
+}
+
+#[tauri::command]
+pub async fn add_conversation_message(
+    conversation_id: i64,
+    role: MessageRole,
+    content: String,
+    sources: Option<Vec<String>>,
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<ConversationMessage, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let message = insert_message(
+        processor.db_path.clone(),
+        conversation_id,
+        role,
+        content,
+        sources.unwrap_or_default(),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let _ = app_handle.emit("conversations-updated", ());
+    Ok(message)
+}
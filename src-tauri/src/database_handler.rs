@@ -1,42 +1,230 @@
-use rusqlite::Connection;
+use rusqlite::{params, Connection, Transaction};
 use std::io::{Error, ErrorKind};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::AppHandle;
 use tauri::Manager;
+use tracing::{error, info};
 
+use crate::encryption::{apply_database_key, prepare_encryption};
+use crate::profiles::prepare_active_profile;
 use crate::AppResult;
 
+/// A schema change that is applied once, in order of version, and recorded in the schema_version table
+/// Migrations are never edited after they ship, later changes get a new migration with the next version
+struct Migration {
+    version: u32,
+    description: &'static str,
+    apply: fn(&Transaction) -> rusqlite::Result<()>,
+}
+
+const MIGRATIONS: [Migration; 13] = [
+    Migration {
+        version: 1,
+        description: "create the initial tables",
+        apply: create_initial_tables,
+    },
+    Migration {
+        version: 2,
+        description: "add content hash and file identity columns",
+        apply: add_file_identity_columns,
+    },
+    Migration {
+        version: 3,
+        description: "add the search index config table",
+        apply: create_fts_config_table,
+    },
+    Migration {
+        version: 4,
+        description: "add the tag tables",
+        apply: create_tag_tables,
+    },
+    Migration {
+        version: 5,
+        description: "add the favorites table",
+        apply: create_favorites_table,
+    },
+    Migration {
+        version: 6,
+        description: "add the open history table",
+        apply: create_open_history_table,
+    },
+    Migration {
+        version: 7,
+        description: "add the maintenance runs table",
+        apply: create_maintenance_runs_table,
+    },
+    Migration {
+        version: 8,
+        description: "mark the indexed root directories",
+        apply: add_directory_root_column,
+    },
+    Migration {
+        version: 9,
+        description: "add per directory index settings",
+        apply: add_directory_settings_column,
+    },
+    Migration {
+        version: 10,
+        description: "add the resource samples table",
+        apply: create_resource_samples_table,
+    },
+    Migration {
+        version: 11,
+        description: "add the conversation tables",
+        apply: create_conversation_tables,
+    },
+    Migration {
+        version: 12,
+        description: "add the usage analytics tables",
+        apply: create_usage_tables,
+    },
+    Migration {
+        version: 13,
+        description: "add the message of index errors",
+        apply: add_index_error_message_columns,
+    },
+];
+
 /// Initialize the database and return the path to the created database file
 pub fn init_database(app_handle: AppHandle) -> AppResult<std::path::PathBuf> {
     let app_data_dir: PathBuf = match app_handle.path().app_data_dir() {
         Ok(dir) => dir,
         Err(_) => {
             let error_msg = "Failed to get app data directory";
-            eprintln!("{}", error_msg);
+            error!("{}", error_msg);
             return Err(Box::new(Error::new(ErrorKind::NotFound, error_msg)));
         }
     };
 
-    let db_path: PathBuf = app_data_dir.join("kita-database.sqlite");
+    // every profile has its own database, the default profile keeps it in the app data directory
+    let profile_dir: PathBuf = match prepare_active_profile(&app_data_dir) {
+        Ok(dir) => dir,
+        Err(e) => {
+            let error_msg = format!("Failed to prepare profile: {}", e);
+            error!("{}", error_msg);
+            return Err(Box::new(Error::new(ErrorKind::Other, error_msg)));
+        }
+    };
+
+    let db_path: PathBuf = profile_dir.join("kita-database.sqlite");
+
+    // has to run before the first connection, it may convert the database and unlocks it
+    if let Err(e) = prepare_encryption(&profile_dir, &db_path) {
+        let error_msg = format!("Failed to prepare index encryption: {}", e);
+        error!("{}", error_msg);
+        return Err(Box::new(Error::new(ErrorKind::Other, error_msg)));
+    }
 
-    let conn: Connection = match Connection::open(&db_path) {
+    let mut conn: Connection = match open_connection(&db_path) {
         Ok(conn) => conn,
         Err(e) => {
             let error_msg = format!("Failed to open database connection: {}", e);
-            eprintln!("{}", error_msg);
+            error!("{}", error_msg);
             return Err(Box::new(Error::new(ErrorKind::Other, error_msg)));
         }
     };
 
-    let directories_table = r#"
-    CREATE TABLE IF NOT EXISTS directories (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        path TEXT UNIQUE,
-        created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-        updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
-    );"#;
+    if let Err(e) = run_migrations(&mut conn) {
+        let error_msg = format!("Failed to migrate database: {}", e);
+        error!("{}", error_msg);
+        return Err(Box::new(Error::new(ErrorKind::Other, error_msg)));
+    }
+
+    info!("Database initialized");
+    Ok(db_path)
+}
+
+/// Opens a connection to the index database, keyed when the index is encrypted
+/// Every connection to the index has to be opened through this
+pub fn open_connection<P: AsRef<Path>>(db_path: P) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(db_path)?;
+    apply_database_key(&conn)?;
+    Ok(conn)
+}
+
+/// Pragmas for connections that write to the index
+/// journal_size_limit truncates the WAL back to 64MB after each checkpoint, otherwise it keeps the size of the largest batch
+pub const WRITER_PRAGMAS: &str = r#"
+    PRAGMA journal_mode = WAL;
+    PRAGMA synchronous = NORMAL;
+    PRAGMA wal_autocheckpoint = 1000;
+    PRAGMA journal_size_limit = 67108864;
+"#;
+
+/// Copies the WAL into the database and truncates it to zero bytes
+/// Waits for readers up to the busy timeout, a checkpoint that can't finish is retried the next time
+pub fn checkpoint_wal<P: AsRef<Path>>(db_path: P) -> rusqlite::Result<()> {
+    let conn = open_connection(db_path)?;
+    conn.busy_timeout(std::time::Duration::from_secs(5))?;
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+}
+
+/// Applies the migrations that haven't run on this database yet
+/// Each migration runs in its own transaction so a failure leaves the database at the last good version
+pub fn run_migrations(conn: &mut Connection) -> AppResult<()> {
+    conn.execute(
+        r#"CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );"#,
+        [],
+    )?;
+
+    let current_version = schema_version(conn)?;
+    let latest_version = MIGRATIONS.last().map(|m| m.version).unwrap_or(0);
 
-    let files_table = r#"CREATE TABLE IF NOT EXISTS files (
+    if current_version > latest_version {
+        // the database was written by a newer version of the app, running against it could lose data
+        return Err(Box::new(Error::new(
+            ErrorKind::Other,
+            format!(
+                "database schema version {} is newer than the latest supported version {}",
+                current_version, latest_version
+            ),
+        )));
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current_version) {
+        let tx = conn.transaction()?;
+        (migration.apply)(&tx)?;
+        tx.execute(
+            "INSERT INTO schema_version (version, description) VALUES (?1, ?2)",
+            params![migration.version, migration.description],
+        )?;
+        tx.commit()?;
+
+        info!(
+            "Applied database migration {}: {}",
+            migration.version, migration.description
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns the version of the last applied migration, 0 for a new database
+pub fn schema_version(conn: &Connection) -> rusqlite::Result<u32> {
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// The tables as they were before versioned migrations
+/// Uses IF NOT EXISTS so databases created before the schema_version table are picked up as version 1
+fn create_initial_tables(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS directories (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            path TEXT UNIQUE,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE TABLE IF NOT EXISTS files (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
             directory_id INTEGER NOT NULL,
             path TEXT UNIQUE,
@@ -46,31 +234,253 @@ pub fn init_database(app_handle: AppHandle) -> AppResult<std::path::PathBuf> {
             category TEXT,
             created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
             updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-             FOREIGN KEY (directory_id) REFERENCES directories (id)
-        );"#;
+            FOREIGN KEY (directory_id) REFERENCES directories (id)
+        );
 
-    let settings_table = r#"CREATE TABLE IF NOT EXISTS settings (
-                id INTEGER PRIMARY KEY CHECK (id = 1), 
-                data TEXT NOT NULL,               
-                updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
-            );"#;
+        CREATE TABLE IF NOT EXISTS settings (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            data TEXT NOT NULL,
+            updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        );
 
-    let fts_table = r#"CREATE VIRTUAL TABLE IF NOT EXISTS files_fts
+        CREATE VIRTUAL TABLE IF NOT EXISTS files_fts
         USING fts5 (
             doc_text,
             content=''
-        );"#;
+        );
+
+        CREATE TABLE IF NOT EXISTS index_errors (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            path TEXT UNIQUE NOT NULL,
+            chunker TEXT,
+            error TEXT NOT NULL,
+            failed_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+        "#,
+    )
+}
 
-    let statements = vec![directories_table, files_table, settings_table, fts_table];
+/// Columns used to detect duplicate, changed and moved files
+/// Some databases already have them from before versioned migrations, so they are only added when missing
+fn add_file_identity_columns(tx: &Transaction) -> rusqlite::Result<()> {
+    add_column_if_missing(tx, "files", "content_hash", "TEXT")?;
+    add_column_if_missing(tx, "files", "device", "INTEGER")?;
+    add_column_if_missing(tx, "files", "inode", "INTEGER")?;
 
-    for (i, stmt) in statements.iter().enumerate() {
-        if let Err(e) = conn.execute(stmt, []) {
-            let error_msg = format!("Error executing statement #{}: {}", i + 1, e);
-            eprintln!("{}", error_msg);
-            return Err(Box::new(Error::new(ErrorKind::Other, error_msg)));
+    tx.execute_batch(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_files_content_hash ON files (content_hash);
+        CREATE INDEX IF NOT EXISTS idx_files_updated_at ON files (updated_at);
+        CREATE INDEX IF NOT EXISTS idx_files_identity ON files (device, inode);
+        "#,
+    )
+}
+
+/// Stores the tokenizer options files_fts was built with so a settings change can be detected
+fn create_fts_config_table(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS fts_config (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            data TEXT NOT NULL,
+            updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        );
+        "#,
+    )
+}
+
+/// Tag names are unique regardless of case so "Work" and "work" are the same tag
+fn create_tag_tables(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE TABLE IF NOT EXISTS file_tags (
+            file_id INTEGER NOT NULL,
+            tag_id INTEGER NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (file_id, tag_id),
+            FOREIGN KEY (file_id) REFERENCES files (id),
+            FOREIGN KEY (tag_id) REFERENCES tags (id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_file_tags_tag_id ON file_tags (tag_id);
+        "#,
+    )
+}
+
+/// Pinned files and apps, position is the order they are shown in
+fn create_favorites_table(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS favorites (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL,
+            path TEXT NOT NULL,
+            name TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (kind, path)
+        );
+        "#,
+    )
+}
+
+/// One row per opened result, the query is the search that led to it and is empty for results opened without searching
+fn create_open_history_table(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS open_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL,
+            path TEXT NOT NULL,
+            name TEXT NOT NULL,
+            query TEXT,
+            opened_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_open_history_item ON open_history (kind, path);
+        CREATE INDEX IF NOT EXISTS idx_open_history_opened_at ON open_history (opened_at);
+        "#,
+    )
+}
+
+/// Log of database maintenance runs, the scheduler uses the last one to decide when to run again
+fn create_maintenance_runs_table(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS maintenance_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            size_before_bytes INTEGER NOT NULL,
+            size_after_bytes INTEGER NOT NULL,
+            duration_ms INTEGER NOT NULL,
+            ran_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+        "#,
+    )
+}
+
+/// Marks the directories the user added to the index, the other rows are their subdirectories
+/// Existing databases don't know which directories were added, so every directory without an indexed parent becomes a root
+fn add_directory_root_column(tx: &Transaction) -> rusqlite::Result<()> {
+    add_column_if_missing(tx, "directories", "is_root", "INTEGER NOT NULL DEFAULT 0")?;
+
+    tx.execute_batch(
+        r#"
+        UPDATE directories SET is_root = 1
+        WHERE NOT EXISTS (
+            SELECT 1 FROM directories AS parent
+            WHERE substr(directories.path, 1, length(parent.path) + 1) = parent.path || '/'
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_directories_is_root ON directories (is_root);
+        "#,
+    )
+}
+
+/// JSON with the indexing settings of a root directory that override the global ones
+fn add_directory_settings_column(tx: &Transaction) -> rusqlite::Result<()> {
+    add_column_if_missing(tx, "directories", "index_settings", "TEXT")
+}
+
+/// Per process CPU and memory samples, only written when the user turns on persist_resource_history
+fn create_resource_samples_table(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS resource_samples (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            pid INTEGER NOT NULL,
+            sampled_at INTEGER NOT NULL,
+            cpu_usage REAL NOT NULL,
+            memory_bytes INTEGER NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_resource_samples_pid ON resource_samples (pid, sampled_at);
+        CREATE INDEX IF NOT EXISTS idx_resource_samples_sampled_at ON resource_samples (sampled_at);
+        "#,
+    )
+}
+
+/// Chat conversations and their messages, the sources of an answer are stored as a JSON list of paths
+fn create_conversation_tables(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS conversations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            title TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE TABLE IF NOT EXISTS conversation_messages (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            conversation_id INTEGER NOT NULL,
+            role TEXT NOT NULL,
+            content TEXT NOT NULL,
+            sources TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (conversation_id) REFERENCES conversations (id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_conversation_messages_conversation ON conversation_messages (conversation_id, id);
+        CREATE INDEX IF NOT EXISTS idx_conversations_updated_at ON conversations (updated_at);
+        "#,
+    )
+}
+
+/// Feature use counts and operation timings, only written when usage analytics are turned on
+fn create_usage_tables(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS usage_counts (
+            feature TEXT PRIMARY KEY,
+            count INTEGER NOT NULL,
+            last_used_at DATETIME
+        );
+
+        CREATE TABLE IF NOT EXISTS usage_timings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            metric TEXT NOT NULL,
+            duration_ms REAL NOT NULL,
+            items INTEGER,
+            recorded_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_usage_timings_metric ON usage_timings (metric, recorded_at);
+        "#,
+    )
+}
+
+/// Code and JSON params of the message shown for the error, errors stored before them have neither
+fn add_index_error_message_columns(tx: &Transaction) -> rusqlite::Result<()> {
+    add_column_if_missing(tx, "index_errors", "code", "TEXT")?;
+    add_column_if_missing(tx, "index_errors", "params", "TEXT")
+}
+
+/// Adds a column to an existing table unless it is already there
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+
+    for existing in columns {
+        if existing? == column {
+            return Ok(());
         }
     }
 
-    println!("Database initialized");
-    Ok(db_path)
+    conn.execute(
+        &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+        [],
+    )?;
+
+    Ok(())
 }
@@ -0,0 +1,176 @@
+/*
+This file contains database maintenance. Long lived indexes fragment and their FTS segments pile up, so this vacuums and analyzes the database, merges the files_fts segments and truncates the WAL. It runs on demand and on a schedule during the night
+*/
+
+use chrono::{Local, Timelike};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::task;
+use tracing::{error, info};
+
+use crate::database_handler::open_connection;
+use crate::file_processor::{get_processor, FileProcessor, FileProcessorError, FileProcessorState};
+use crate::settings::SettingsManagerState;
+
+/// Used when the user hasn't configured how often maintenance runs, 0 turns the schedule off
+const DEFAULT_MAINTENANCE_INTERVAL_DAYS: u64 = 7;
+
+/// Scheduled maintenance only starts between these local hours, VACUUM blocks indexing while it runs
+const OFF_HOURS_START: u32 = 2;
+const OFF_HOURS_END: u32 = 5;
+
+/// How often the scheduler checks whether maintenance is due
+const SCHEDULER_CHECK_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    pub size_before_bytes: u64,
+    pub size_after_bytes: u64,
+    pub reclaimed_bytes: u64,
+    pub duration_ms: u64,
+}
+
+/// Size of the database file together with its WAL, the WAL can be larger than the database itself
+fn database_size(db_path: &Path) -> u64 {
+    let wal_path = PathBuf::from(format!("{}-wal", db_path.to_string_lossy()));
+
+    [db_path.to_path_buf(), wal_path]
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Runs the maintenance steps and records the run in maintenance_runs
+async fn run_maintenance(db_path: PathBuf) -> Result<MaintenanceReport, FileProcessorError> {
+    task::spawn_blocking(move || -> Result<MaintenanceReport, FileProcessorError> {
+        let started = Instant::now();
+        let size_before_bytes = database_size(&db_path);
+
+        let conn = open_connection(&db_path)?;
+        conn.busy_timeout(Duration::from_secs(30))?;
+
+        // merge the FTS segments into one so searches don't have to read all of them
+        conn.execute("INSERT INTO files_fts(files_fts) VALUES ('optimize')", [])?;
+        conn.execute_batch("ANALYZE;")?;
+        conn.execute_batch("VACUUM;")?;
+        // VACUUM writes the whole database through the WAL, truncate it afterwards to give the space back
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+
+        let size_after_bytes = database_size(&db_path);
+        let duration_ms = started.elapsed().as_millis() as u64;
+
+        conn.execute(
+            r#"
+            INSERT INTO maintenance_runs (size_before_bytes, size_after_bytes, duration_ms)
+            VALUES (?1, ?2, ?3)
+            "#,
+            params![
+                size_before_bytes as i64,
+                size_after_bytes as i64,
+                duration_ms as i64
+            ],
+        )?;
+
+        Ok(MaintenanceReport {
+            size_before_bytes,
+            size_after_bytes,
+            reclaimed_bytes: size_before_bytes.saturating_sub(size_after_bytes),
+            duration_ms,
+        })
+    })
+    .await
+    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
+}
+
+/// Returns true if the last run is older than the interval, or if maintenance never ran
+fn is_maintenance_due(db_path: &Path, interval_days: u64) -> Result<bool, FileProcessorError> {
+    let conn = open_connection(db_path)?;
+
+    let days_since_last_run: Option<f64> = conn
+        .query_row(
+            "SELECT julianday('now') - julianday(MAX(ran_at)) FROM maintenance_runs",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?
+        .flatten();
+
+    Ok(days_since_last_run
+        .map(|days| days >= interval_days as f64)
+        .unwrap_or(true))
+}
+
+/// Starts the background task that runs maintenance during the night once the interval has passed
+pub fn start_maintenance_scheduler(db_path: PathBuf, app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SCHEDULER_CHECK_INTERVAL).await;
+
+            let interval_days = app_handle
+                .try_state::<SettingsManagerState>()
+                .and_then(|state| state.0.get_settings().ok())
+                .and_then(|settings| settings.db_maintenance_interval_days)
+                .unwrap_or(DEFAULT_MAINTENANCE_INTERVAL_DAYS);
+
+            if interval_days == 0 {
+                continue;
+            }
+
+            let hour = Local::now().hour();
+            if !(OFF_HOURS_START..OFF_HOURS_END).contains(&hour) {
+                continue;
+            }
+
+            let check_path = db_path.clone();
+            let is_due =
+                task::spawn_blocking(move || is_maintenance_due(&check_path, interval_days))
+                    .await
+                    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))
+                    .and_then(|result| result);
+
+            match is_due {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    error!("Failed to check the last database maintenance: {}", e);
+                    continue;
+                }
+            }
+
+            match run_maintenance(db_path.clone()).await {
+                Ok(report) => {
+                    info!(
+                        "Scheduled database maintenance reclaimed {} bytes in {}ms",
+                        report.reclaimed_bytes, report.duration_ms
+                    );
+                    let _ = app_handle.emit("db-maintenance-complete", &report);
+                }
+                Err(e) => error!("Scheduled database maintenance failed: {}", e),
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn run_db_maintenance(
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<MaintenanceReport, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let report = run_maintenance(processor.db_path.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    info!(
+        "Database maintenance reclaimed {} bytes in {}ms",
+        report.reclaimed_bytes, report.duration_ms
+    );
+    let _ = app_handle.emit("db-maintenance-complete", &report);
+
+    Ok(report)
+}
@@ -1,5 +1,12 @@
 use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
 
+use crate::resource_monitor::{system_pressure, SystemPressure};
+
+/// Texts embedded per batch on battery, smaller batches keep the CPU bursts of indexing short
+const REDUCED_BATCH_SIZE: usize = 32;
+/// Texts embedded per batch when the machine is throttled or short on memory
+const CRITICAL_BATCH_SIZE: usize = 8;
+
 /// Holds embedding model
 pub struct Embedder {
     pub model: TextEmbedding,
@@ -14,6 +21,15 @@ impl Embedder {
         Ok(Self { model })
     } 
 
+    /// Batch size to pass to embed for the current system pressure, None uses fastembed's default
+    pub fn batch_size(&self) -> Option<usize> {
+        match system_pressure() {
+            SystemPressure::Normal => None,
+            SystemPressure::Reduced => Some(REDUCED_BATCH_SIZE),
+            SystemPressure::Critical => Some(CRITICAL_BATCH_SIZE),
+        }
+    }
+
     /// Get embeddings for a single chunk of text
     /// If there is an error this will return back an empty vector
     pub fn embed_single_text(&self, text: &str) -> Vec<f32> {
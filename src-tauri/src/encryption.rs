@@ -0,0 +1,405 @@
+/*
+This file contains the optional encryption of the index at rest. The SQLite database is encrypted with SQLCipher and the vector store is kept in an encrypted disk image, both with a key that is stored in the macOS keychain.
+
+Whether encryption is on has to be known before the database can be opened, so it is stored in encryption.json next to the database and not in the settings table. Turning it on or off takes effect on the next start, when the database and the vector store are converted before anything opens them
+*/
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Error, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager, State};
+use tracing::info;
+
+use crate::profiles::active_profile_dir;
+use crate::settings::SettingsManagerState;
+use crate::AppResult;
+
+#[cfg(target_os = "macos")]
+use std::ffi::{CStr, CString};
+#[cfg(target_os = "macos")]
+use std::os::raw::c_char;
+
+#[cfg(target_os = "macos")]
+extern "C" {
+    fn load_keychain_password_swift(
+        service: *const c_char,
+        account: *const c_char,
+        status: *mut i32,
+    ) -> *mut c_char;
+    fn store_keychain_password_swift(
+        service: *const c_char,
+        account: *const c_char,
+        password: *const c_char,
+    ) -> i32;
+    fn free_string_swift(pointer: *mut c_char);
+}
+
+const ENCRYPTION_CONFIG_FILE_NAME: &str = "encryption.json";
+
+/// Keychain item that holds the key for the database and the vector store image
+#[cfg(target_os = "macos")]
+const KEYCHAIN_SERVICE: &str = "kita-index";
+#[cfg(target_os = "macos")]
+const KEYCHAIN_ACCOUNT: &str = "index-key";
+
+/// OSStatus of a keychain lookup when there is no such item
+#[cfg(target_os = "macos")]
+const ERR_SEC_ITEM_NOT_FOUND: i32 = -25300;
+
+const VECTOR_STORE_DIR_NAME: &str = "vector_db";
+const VECTOR_STORE_IMAGE_NAME: &str = "vector_db.sparsebundle";
+const VECTOR_STORE_MOUNT_DIR_NAME: &str = "vector_db_encrypted";
+
+/// Upper bound of the vector store image, sparse bundles only take the space that is used
+const VECTOR_STORE_IMAGE_SIZE: &str = "100g";
+
+/// First bytes of every unencrypted SQLite database
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// Key of the open database, only set when the database is encrypted
+static DATABASE_KEY: OnceLock<String> = OnceLock::new();
+
+/// Where the vector store lives for this run, inside the mounted image when encryption is on
+static VECTOR_STORE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct EncryptionConfig {
+    enabled: bool,
+}
+
+fn encryption_error(message: impl Into<String>) -> Box<dyn std::error::Error> {
+    Box::new(Error::new(ErrorKind::Other, message.into()))
+}
+
+fn load_config(app_data_dir: &Path) -> EncryptionConfig {
+    fs::read_to_string(app_data_dir.join(ENCRYPTION_CONFIG_FILE_NAME))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_config(app_data_dir: &Path, config: &EncryptionConfig) -> std::io::Result<()> {
+    let json = serde_json::to_string(config)?;
+    fs::write(app_data_dir.join(ENCRYPTION_CONFIG_FILE_NAME), json)
+}
+
+/// Keys a newly opened connection, has to run before any other statement on it
+pub fn apply_database_key(conn: &Connection) -> rusqlite::Result<()> {
+    if let Some(key) = DATABASE_KEY.get() {
+        conn.pragma_update(None, "key", key)?;
+    }
+    Ok(())
+}
+
+/// True when the database of this run is encrypted
+pub fn is_database_encrypted() -> bool {
+    DATABASE_KEY.get().is_some()
+}
+
+/// Directory of the vector store for this run
+pub fn vector_store_dir(app_data_dir: &Path) -> PathBuf {
+    VECTOR_STORE_DIR
+        .get()
+        .cloned()
+        .unwrap_or_else(|| app_data_dir.join(VECTOR_STORE_DIR_NAME))
+}
+
+/// Encryption needs SQLCipher, which is only compiled in with the encryption feature, and the keychain
+fn is_encryption_supported() -> bool {
+    cfg!(all(feature = "encryption", target_os = "macos"))
+}
+
+/// Converts the database and the vector store to match encryption.json and unlocks them
+/// Called once at startup before the database is opened
+pub fn prepare_encryption(app_data_dir: &Path, db_path: &Path) -> AppResult<()> {
+    let config = load_config(app_data_dir);
+    let database_encrypted = db_path.exists() && !is_plaintext_database(db_path)?;
+
+    if !config.enabled
+        && !database_encrypted
+        && !app_data_dir.join(VECTOR_STORE_IMAGE_NAME).exists()
+    {
+        return Ok(());
+    }
+
+    if !is_encryption_supported() {
+        return Err(encryption_error(
+            "The index is encrypted but this build doesn't support encryption",
+        ));
+    }
+
+    let key = load_or_create_key()?;
+
+    if config.enabled {
+        if db_path.exists() && !database_encrypted {
+            info!("Encrypting the index database");
+            export_database(db_path, None, Some(&key))?;
+        }
+        let _ = DATABASE_KEY.set(key.clone());
+        let _ = VECTOR_STORE_DIR.set(mount_encrypted_vector_store(app_data_dir, &key)?);
+    } else {
+        if database_encrypted {
+            info!("Decrypting the index database");
+            export_database(db_path, Some(&key), None)?;
+        }
+        unmount_encrypted_vector_store(app_data_dir, &key)?;
+    }
+
+    Ok(())
+}
+
+fn is_plaintext_database(db_path: &Path) -> AppResult<bool> {
+    let mut header = [0u8; 16];
+    let mut file = fs::File::open(db_path)?;
+
+    // an empty file is a database that hasn't been written yet
+    match file.read_exact(&mut header) {
+        Ok(()) => Ok(&header == SQLITE_HEADER),
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(true),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+/// Rewrites the database with a different key using sqlcipher_export
+/// A key of None means unencrypted, the converted copy replaces the original once it is complete
+fn export_database(db_path: &Path, from_key: Option<&str>, to_key: Option<&str>) -> AppResult<()> {
+    let converted_path = PathBuf::from(format!("{}.converting", db_path.to_string_lossy()));
+    let _ = fs::remove_file(&converted_path);
+
+    {
+        let conn = Connection::open(db_path)?;
+        if let Some(key) = from_key {
+            conn.pragma_update(None, "key", key)?;
+        }
+        // move everything out of the WAL so the export sees the whole database
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+
+        conn.execute(
+            "ATTACH DATABASE ?1 AS converted KEY ?2",
+            [
+                converted_path.to_string_lossy().to_string(),
+                to_key.unwrap_or_default().to_string(),
+            ],
+        )?;
+        conn.query_row("SELECT sqlcipher_export('converted')", [], |_| Ok(()))?;
+        conn.execute("DETACH DATABASE converted", [])?;
+    }
+
+    for suffix in ["-wal", "-shm"] {
+        let _ = fs::remove_file(format!("{}{}", db_path.to_string_lossy(), suffix));
+    }
+    fs::rename(&converted_path, db_path)?;
+
+    Ok(())
+}
+
+/// Creates the encrypted vector store image on first use, moves the existing vector store into it and mounts it
+fn mount_encrypted_vector_store(app_data_dir: &Path, key: &str) -> AppResult<PathBuf> {
+    let image_path = app_data_dir.join(VECTOR_STORE_IMAGE_NAME);
+    let mount_dir = app_data_dir.join(VECTOR_STORE_MOUNT_DIR_NAME);
+    let vector_store_dir = mount_dir.join(VECTOR_STORE_DIR_NAME);
+
+    let is_new_image = !image_path.exists();
+    if is_new_image {
+        run_hdiutil(
+            &[
+                "create",
+                "-size",
+                VECTOR_STORE_IMAGE_SIZE,
+                "-type",
+                "SPARSEBUNDLE",
+                "-fs",
+                "APFS",
+                "-encryption",
+                "AES-256",
+                "-stdinpass",
+                "-volname",
+                "Kita Vector Store",
+                &image_path.to_string_lossy(),
+            ],
+            Some(key),
+        )?;
+    }
+
+    if !vector_store_dir.exists() {
+        fs::create_dir_all(&mount_dir)?;
+        run_hdiutil(
+            &[
+                "attach",
+                &image_path.to_string_lossy(),
+                "-stdinpass",
+                "-nobrowse",
+                "-mountpoint",
+                &mount_dir.to_string_lossy(),
+            ],
+            Some(key),
+        )?;
+    }
+
+    let plaintext_dir = app_data_dir.join(VECTOR_STORE_DIR_NAME);
+    if plaintext_dir.exists() {
+        info!("Moving the vector store into the encrypted image");
+        crate::utils::copy_dir_all(&plaintext_dir, &vector_store_dir)?;
+        fs::remove_dir_all(&plaintext_dir)?;
+    } else {
+        fs::create_dir_all(&vector_store_dir)?;
+    }
+
+    Ok(vector_store_dir)
+}
+
+/// Moves the vector store out of the encrypted image and deletes the image
+fn unmount_encrypted_vector_store(app_data_dir: &Path, key: &str) -> AppResult<()> {
+    let image_path = app_data_dir.join(VECTOR_STORE_IMAGE_NAME);
+    if !image_path.exists() {
+        return Ok(());
+    }
+
+    let mount_dir = app_data_dir.join(VECTOR_STORE_MOUNT_DIR_NAME);
+    let encrypted_dir = mount_dir.join(VECTOR_STORE_DIR_NAME);
+
+    if !encrypted_dir.exists() {
+        fs::create_dir_all(&mount_dir)?;
+        run_hdiutil(
+            &[
+                "attach",
+                &image_path.to_string_lossy(),
+                "-stdinpass",
+                "-nobrowse",
+                "-mountpoint",
+                &mount_dir.to_string_lossy(),
+            ],
+            Some(key),
+        )?;
+    }
+
+    info!("Moving the vector store out of the encrypted image");
+    crate::utils::copy_dir_all(&encrypted_dir, &app_data_dir.join(VECTOR_STORE_DIR_NAME))?;
+
+    run_hdiutil(&["detach", &mount_dir.to_string_lossy()], None)?;
+    fs::remove_dir_all(&image_path)?;
+    let _ = fs::remove_dir(&mount_dir);
+
+    Ok(())
+}
+
+/// hdiutil reads the passphrase from stdin with -stdinpass so it never shows up in the process list
+fn run_hdiutil(args: &[&str], passphrase: Option<&str>) -> AppResult<()> {
+    let mut child = Command::new("hdiutil")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    if let (Some(passphrase), Some(mut stdin)) = (passphrase, child.stdin.take()) {
+        stdin.write_all(passphrase.as_bytes())?;
+        stdin.write_all(b"\0")?;
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(encryption_error(format!(
+            "hdiutil {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Returns the key from the keychain, a new random key is created and stored the first time
+/// Any other failure, like a locked keychain or denied access, is an error since a new key would
+/// replace the one the existing database and image are encrypted with
+#[cfg(target_os = "macos")]
+fn load_or_create_key() -> AppResult<String> {
+    let service = CString::new(KEYCHAIN_SERVICE)?;
+    let account = CString::new(KEYCHAIN_ACCOUNT)?;
+
+    let mut status: i32 = 0;
+    let key_ptr =
+        unsafe { load_keychain_password_swift(service.as_ptr(), account.as_ptr(), &mut status) };
+
+    if !key_ptr.is_null() {
+        let key = unsafe {
+            let key = CStr::from_ptr(key_ptr).to_string_lossy().trim().to_string();
+            free_string_swift(key_ptr);
+            key
+        };
+        if key.is_empty() {
+            return Err(encryption_error("The index key in the keychain is empty"));
+        }
+        return Ok(key);
+    }
+
+    if status != ERR_SEC_ITEM_NOT_FOUND {
+        return Err(encryption_error(format!(
+            "Failed to read the index key from the keychain (OSStatus {})",
+            status
+        )));
+    }
+
+    let mut bytes = [0u8; 32];
+    fs::File::open("/dev/urandom")?.read_exact(&mut bytes)?;
+    let key: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+
+    // stored through the Security framework so the key never shows up in the process list
+    let key_cstring = CString::new(key.as_str())?;
+    let status = unsafe {
+        store_keychain_password_swift(service.as_ptr(), account.as_ptr(), key_cstring.as_ptr())
+    };
+
+    if status != 0 {
+        return Err(encryption_error(format!(
+            "Failed to store the index key in the keychain (OSStatus {})",
+            status
+        )));
+    }
+
+    Ok(key)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn load_or_create_key() -> AppResult<String> {
+    Err(encryption_error(
+        "The index key is kept in the macOS keychain, encryption isn't available on this platform",
+    ))
+}
+
+/// Turns encryption of the index on or off, the change is applied on the next start
+#[tauri::command]
+pub fn set_index_encryption(
+    enabled: bool,
+    settings_manager: State<'_, SettingsManagerState>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    if enabled && !is_encryption_supported() {
+        return Err("Index encryption isn't supported by this build".to_string());
+    }
+
+    let app_data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|_| "Failed to get app data directory".to_string())?;
+
+    save_config(
+        &active_profile_dir(&app_data_dir),
+        &EncryptionConfig { enabled },
+    )
+    .map_err(|e| format!("Failed to save encryption config: {}", e))?;
+
+    let mut settings = settings_manager
+        .0
+        .get_settings()
+        .map_err(|e| format!("Failed to get settings: {}", e))?;
+    settings.encrypt_index = Some(enabled);
+    settings_manager
+        .0
+        .update(settings)
+        .map_err(|e| format!("Failed to update settings: {}", e))
+}
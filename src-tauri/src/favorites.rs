@@ -0,0 +1,218 @@
+/*
+This file contains pinned files and apps. Pins are stored in the favorites table and are shown above the other results when the search query is empty
+*/
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, State};
+use tokio::task;
+
+use crate::database_handler::open_connection;
+use crate::file_processor::{get_processor, FileProcessor, FileProcessorError, FileProcessorState};
+
+/// Whether a pinned or opened item is a file or an app
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemKind {
+    File,
+    App,
+}
+
+impl ItemKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemKind::File => "file",
+            ItemKind::App => "app",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Option<Self> {
+        match value {
+            "file" => Some(ItemKind::File),
+            "app" => Some(ItemKind::App),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Favorite {
+    pub kind: ItemKind,
+    pub path: String,
+    pub name: String,
+    pub position: i64,
+}
+
+/// Pins an item after the existing pins, pinning an item twice keeps its position
+async fn save_favorite(
+    db_path: PathBuf,
+    kind: ItemKind,
+    path: String,
+    name: String,
+) -> Result<(), FileProcessorError> {
+    task::spawn_blocking(move || -> Result<(), FileProcessorError> {
+        let conn = open_connection(db_path)?;
+
+        conn.execute(
+            r#"
+            INSERT OR IGNORE INTO favorites (kind, path, name, position)
+            VALUES (?1, ?2, ?3, (SELECT COALESCE(MAX(position), -1) + 1 FROM favorites))
+            "#,
+            params![kind.as_str(), path, name],
+        )?;
+
+        Ok(())
+    })
+    .await
+    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
+}
+
+async fn delete_favorite(
+    db_path: PathBuf,
+    kind: ItemKind,
+    path: String,
+) -> Result<(), FileProcessorError> {
+    task::spawn_blocking(move || -> Result<(), FileProcessorError> {
+        let conn = open_connection(db_path)?;
+
+        conn.execute(
+            "DELETE FROM favorites WHERE kind = ?1 AND path = ?2",
+            params![kind.as_str(), path],
+        )?;
+
+        Ok(())
+    })
+    .await
+    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
+}
+
+/// Stores the new order of the pins, pins that aren't in the list keep their relative order after the listed ones
+async fn save_favorite_order(
+    db_path: PathBuf,
+    paths: Vec<String>,
+) -> Result<(), FileProcessorError> {
+    task::spawn_blocking(move || -> Result<(), FileProcessorError> {
+        let mut conn = open_connection(db_path)?;
+        let tx = conn.transaction()?;
+
+        let offset = paths.len() as i64;
+        tx.execute("UPDATE favorites SET position = position + ?1", [offset])?;
+
+        for (position, path) in paths.iter().enumerate() {
+            tx.execute(
+                "UPDATE favorites SET position = ?1 WHERE path = ?2",
+                params![position as i64, path],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    })
+    .await
+    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
+}
+
+#[tauri::command]
+pub async fn pin_item(
+    kind: ItemKind,
+    path: String,
+    name: String,
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    save_favorite(processor.db_path.clone(), kind, path, name)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let _ = app_handle.emit("favorites-updated", ());
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn unpin_item(
+    kind: ItemKind,
+    path: String,
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    delete_favorite(processor.db_path.clone(), kind, path)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let _ = app_handle.emit("favorites-updated", ());
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn reorder_pinned_items(
+    paths: Vec<String>,
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    save_favorite_order(processor.db_path.clone(), paths)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let _ = app_handle.emit("favorites-updated", ());
+    Ok(())
+}
+
+/// Returns the pinned items in the order they should be shown
+/// Pinned files that aren't on disk are left out but stay pinned, they may be on a volume that isn't mounted
+#[tauri::command]
+pub async fn get_pinned_items(
+    state: State<'_, FileProcessorState>,
+) -> Result<Vec<Favorite>, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let conn = processor.get_read_connection()?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT kind, path, name, position
+            FROM favorites
+            ORDER BY position, id
+            "#,
+        )
+        .map_err(|e| format!("Failed to prepare statement: {e}"))?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)?,
+            ))
+        })
+        .map_err(|e| format!("Query error: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Row error: {e}"))?;
+
+    let favorites = rows
+        .into_iter()
+        .filter_map(|(kind, path, name, position)| {
+            let kind = ItemKind::from_db(&kind)?;
+            if kind == ItemKind::File && !Path::new(&path).exists() {
+                return None;
+            }
+
+            Some(Favorite {
+                kind,
+                path,
+                name,
+                position,
+            })
+        })
+        .collect();
+
+    Ok(favorites)
+}
@@ -0,0 +1,83 @@
+/*
+This file contains the file previews for search results. On macOS the file is shown with Quick Look, otherwise (or when the frontend asks for it) the text of the indexed formats is extracted with the chunkers so it can be shown inline
+*/
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tracing::warn;
+
+use crate::chunker::{ChunkerConfig, ChunkerOrchestrator};
+
+/// Longest text preview sent to the frontend, the rest of the file is cut off
+const MAX_PREVIEW_CHARS: usize = 20_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FilePreview {
+    /// The file is shown in a Quick Look panel, there is nothing to render
+    QuickLook,
+    Text {
+        content: String,
+        truncated: bool,
+    },
+}
+
+/// Opens the Quick Look panel for the file, qlmanage keeps running until the panel is closed
+#[cfg(target_os = "macos")]
+fn show_quick_look(path: &Path) -> Result<(), String> {
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new("qlmanage")
+        .arg("-p")
+        .arg(path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start Quick Look: {}", e))?;
+
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn show_quick_look(_path: &Path) -> Result<(), String> {
+    Err("Quick Look is only available on macOS".to_string())
+}
+
+async fn text_preview(path: &Path) -> Result<FilePreview, String> {
+    let orchestrator = ChunkerOrchestrator::new(ChunkerConfig::default());
+    let text = orchestrator
+        .extract_text(path)
+        .await
+        .map_err(|e| format!("Failed to preview file: {}", e))?;
+
+    let truncated = text.chars().count() > MAX_PREVIEW_CHARS;
+    let content = if truncated {
+        text.chars().take(MAX_PREVIEW_CHARS).collect()
+    } else {
+        text
+    };
+
+    Ok(FilePreview::Text { content, truncated })
+}
+
+/// Previews the file with Quick Look, or returns its text when text_only is set or Quick Look isn't available
+#[tauri::command]
+pub async fn preview_file(path: String, text_only: Option<bool>) -> Result<FilePreview, String> {
+    let path = Path::new(&path);
+    if !path.exists() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+
+    if !text_only.unwrap_or(false) {
+        match show_quick_look(path) {
+            Ok(()) => return Ok(FilePreview::QuickLook),
+            Err(e) => warn!("Falling back to a text preview: {}", e),
+        }
+    }
+
+    text_preview(path).await
+}
@@ -1,26 +1,52 @@
 use crate::AppResult;
 use arrow_array::{Array, RecordBatch};
-use rusqlite::{params, Connection, Rows};
+use chrono::{DateTime, Utc};
+use futures::stream::{FuturesUnordered, StreamExt};
+use ignore::{ParallelVisitor, ParallelVisitorBuilder, WalkBuilder, WalkState};
+use r2d2::{Pool, PooledConnection};
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{params, Connection, OptionalExtension, Rows};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::{HashMap, HashSet};
+#[cfg(target_os = "macos")]
+use std::ffi::CString;
 use std::io::{Error, ErrorKind};
+#[cfg(target_os = "macos")]
+use std::os::raw::c_char;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 use tauri::{AppHandle, Emitter, Manager, State};
-use tokio::sync::mpsc::UnboundedSender;
-use tokio::sync::Semaphore;
 use tokio::task;
-use tracing::error;
-use walkdir::WalkDir;
+use tracing::{debug, error, info, instrument, warn};
 
 use crate::chunker::{ChunkerConfig, ChunkerOrchestrator};
+use crate::database_handler::{open_connection, WRITER_PRAGMAS};
 use crate::embedder::Embedder;
-use crate::tokenizer::{build_doc_text, build_trigrams};
+use crate::encryption::apply_database_key;
+use crate::file_watcher::unwatch_paths;
+use crate::fts_index::active_fts_options;
+use crate::index_errors::{clear_index_errors, record_index_error};
+use crate::index_filter::{is_cloud_placeholder, IndexFilter, KITA_IGNORE_FILE_NAME};
+use crate::index_queue::{IndexPriority, IndexQueue};
+use crate::indexed_directories::directory_settings_for;
+use crate::messages::{ErrorCode, UserMessage};
+use crate::settings::{SearchSettings, SettingsManagerState};
+use crate::spotlight::with_spotlight_results;
+use crate::tags::load_file_tags;
+use crate::thumbnails::with_thumbnails;
+use crate::tokenizer::{build_doc_text, build_match_query};
+use crate::usage_analytics::{record_feature, record_timing};
 use crate::utils::get_category_from_extension;
 use crate::vectordb_manager::VectorDbManager;
 
+#[cfg(target_os = "macos")]
+extern "C" {
+    fn trash_file_swift(path: *const c_char) -> bool;
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum SearchSectionType {
@@ -48,6 +74,19 @@ pub struct FileMetadata {
     pub size: i64,
     pub updated_at: Option<String>,
     pub created_at: Option<String>,
+    /// PNG data URL of the file's thumbnail, only set for images, PDFs and videos in search results
+    pub thumbnail: Option<String>,
+    #[serde(default)]
+    pub source: FileSource,
+}
+
+/// Where a file result comes from, files found outside the index are labeled in the launcher
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileSource {
+    #[default]
+    Index,
+    Spotlight,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -81,6 +120,145 @@ pub struct ProcessingStatus {
     pub total: usize,
     pub processed: usize,
     pub percentage: usize,
+    pub current_file: Option<String>,
+    pub bytes_processed: u64,
+    pub total_bytes: u64,
+    pub failed: usize,
+    pub eta_seconds: Option<u64>,
+}
+
+/// Minimum time between two progress events, the final event is always sent
+const PROGRESS_EMIT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Tracks the progress of a batch of files and decides when a progress event should be sent
+struct ProgressTracker {
+    total_files: usize,
+    total_bytes: u64,
+    processed: usize,
+    failed: usize,
+    bytes_processed: u64,
+    started_at: Instant,
+    last_emit: Option<Instant>,
+}
+
+impl ProgressTracker {
+    fn new(total_files: usize, total_bytes: u64) -> Self {
+        Self {
+            total_files,
+            total_bytes,
+            processed: 0,
+            failed: 0,
+            bytes_processed: 0,
+            started_at: Instant::now(),
+            last_emit: None,
+        }
+    }
+
+    /// Records a finished file and returns the status to emit, or None if the last event was sent too recently
+    fn record(&mut self, file_path: &str, size: u64, success: bool) -> Option<ProcessingStatus> {
+        if success {
+            self.processed += 1;
+        } else {
+            self.failed += 1;
+        }
+        self.bytes_processed += size;
+
+        let completed = self.processed + self.failed;
+        let is_last = completed >= self.total_files;
+        let now = Instant::now();
+
+        if let Some(last_emit) = self.last_emit {
+            if !is_last && now.duration_since(last_emit) < PROGRESS_EMIT_INTERVAL {
+                return None;
+            }
+        }
+        self.last_emit = Some(now);
+
+        Some(self.status(completed, Some(file_path.to_string())))
+    }
+
+    fn status(&self, completed: usize, current_file: Option<String>) -> ProcessingStatus {
+        let percentage: usize = if self.total_files == 0 {
+            100
+        } else {
+            ((completed as f64 / self.total_files as f64) * 100.0).round() as usize
+        };
+
+        // estimate from the average time per file so far
+        let remaining = self.total_files.saturating_sub(completed);
+        let eta_seconds = if completed == 0 {
+            None
+        } else {
+            let per_file = self.started_at.elapsed().as_secs_f64() / completed as f64;
+            Some((per_file * remaining as f64).round() as u64)
+        };
+
+        ProcessingStatus {
+            total: self.total_files,
+            processed: self.processed,
+            percentage,
+            current_file,
+            bytes_processed: self.bytes_processed,
+            total_bytes: self.total_bytes,
+            failed: self.failed,
+            eta_seconds,
+        }
+    }
+}
+
+/// A file that was found while collecting files but won't be indexed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedFile {
+    pub path: String,
+    pub size: u64,
+    pub reason: String,
+}
+
+/// Files and directories found by walking the paths that are being indexed
+#[derive(Debug, Default)]
+pub struct CollectedFiles {
+    pub files: Vec<FileMetadata>,
+    pub directories: HashSet<PathBuf>,
+    pub skipped_files: Vec<SkippedFile>,
+}
+
+impl CollectedFiles {
+    fn extend(&mut self, other: CollectedFiles) {
+        self.files.extend(other.files);
+        self.directories.extend(other.directories);
+        self.skipped_files.extend(other.skipped_files);
+    }
+}
+
+/// Number of files and bytes of one extension in an index preview
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtensionBreakdown {
+    pub extension: String,
+    pub count: usize,
+    pub bytes: u64,
+}
+
+/// What indexing the given paths would do, returned without indexing anything
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexPreview {
+    pub total_files: usize,
+    pub total_bytes: u64,
+    pub total_directories: usize,
+    pub estimated_seconds: u64,
+    pub extensions: Vec<ExtensionBreakdown>,
+    pub skipped_files: Vec<SkippedFile>,
+}
+
+// rough per-worker throughput of chunking and embedding, used to estimate indexing time
+const ESTIMATED_SECONDS_PER_FILE: f64 = 0.25;
+const ESTIMATED_BYTES_PER_SECOND: f64 = 2.0 * 1024.0 * 1024.0;
+
+/// Estimates how long indexing would take with the given number of workers
+fn estimate_index_seconds(total_files: usize, total_bytes: u64, workers: usize) -> u64 {
+    let single_worker_seconds = total_files as f64 * ESTIMATED_SECONDS_PER_FILE
+        + total_bytes as f64 / ESTIMATED_BYTES_PER_SECOND;
+
+    (single_worker_seconds / workers.max(1) as f64).ceil() as u64
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -99,32 +277,54 @@ pub enum FileProcessorError {
 pub struct FileProcessor {
     pub db_path: PathBuf,
     pub concurrency_limit: usize,
+    pub index_queue: Arc<IndexQueue>,
+    pub read_pool: Pool<SqliteConnectionManager>,
 }
 
+/// Number of pooled connections used by the search and browse commands
+const READ_POOL_SIZE: u32 = 4;
+
 impl FileProcessor {
+    /// Gets a connection from the pool used by query commands so they don't open a new connection on every keystroke
+    pub fn get_read_connection(&self) -> Result<PooledConnection<SqliteConnectionManager>, String> {
+        self.read_pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {e}"))
+    }
+
     /// Main async method to process all the given paths:
     /// 1) collect files
-    /// 2) spawn tasks with concurrency limit
+    /// 2) queue the files with the given priority, the queue workers limit concurrency
     /// 3) process files by storing them, creating chunks, embeddings and storing in vectordb
     /// 4) track progress and emit Tauri events
     /// If successful then this function doesn't return anything
     /// If error, then it returns the number of errors, the file path that caused it and the error
+    #[instrument(skip_all, fields(paths = paths.len()))]
     pub async fn process_paths(
         &self,
         paths: Vec<String>,
+        priority: IndexPriority,
         on_progress: impl Fn(ProcessingStatus) + Send + Sync + Clone + 'static,
         app_handle: AppHandle,
     ) -> Result<serde_json::Value, FileProcessorError> {
-        println!("Processing paths: {:?}", paths);
+        debug!("Processing paths: {:?}", paths);
+        let started = Instant::now();
 
         // Get all file paths and directories that need to be processed
-        let (files, unique_directories) = self.collect_all_files(&paths).await?;
+        let index_filter = IndexFilter::from_app_handle(&app_handle);
+        let CollectedFiles {
+            files,
+            directories: unique_directories,
+            skipped_files,
+        } = self.collect_all_files(&paths, index_filter).await?;
         let total_files: usize = files.len();
         let total_directories: usize = unique_directories.len();
 
-        println!(
-            "Found {} files and {} unique directories",
-            total_files, total_directories
+        info!(
+            "Found {} files and {} unique directories, skipped {} files",
+            total_files,
+            total_directories,
+            skipped_files.len()
         );
 
         // Early return if no files
@@ -132,13 +332,14 @@ impl FileProcessor {
             return Ok(serde_json::json!({
                 "success": true,
                 "totalFiles": 0,
-                "errors": []
+                "errors": [],
+                "skippedFiles": skipped_files
             }));
         }
 
         // First, save all directories to the database (as a batch for efficiency)
         if !unique_directories.is_empty() {
-            println!(
+            debug!(
                 "Saving {} directories to database",
                 unique_directories.len()
             );
@@ -151,60 +352,48 @@ impl FileProcessor {
             }
         }
 
-        // Create new semaphore to handle concurrency limits
-        let sem = Arc::new(Semaphore::new(self.concurrency_limit));
-        let num_processed_files = Arc::new(AtomicUsize::new(0));
+        // Queue every file, the workers pick them up in priority order
+        let total_bytes: u64 = files.iter().map(|file| file.size.max(0) as u64).sum();
+        let mut pending_jobs: FuturesUnordered<_> = files
+            .into_iter()
+            .map(|file| {
+                let file_path = file.base.path.clone();
+                let file_size = file.size.max(0) as u64;
+                let receiver = self.index_queue.enqueue(file, priority);
+                async move { (file_path, file_size, receiver.await) }
+            })
+            .collect();
+
+        let mut progress = ProgressTracker::new(total_files, total_bytes);
+        let mut detailed_errors = Vec::new();
 
-        // Channel to collect errors
-        let (err_tx, mut err_rx) = tokio::sync::mpsc::unbounded_channel();
-        let mut task_handles = Vec::with_capacity(total_files);
+        // Wait for the files to be indexed and track progress as they complete
+        while let Some((file_path, file_size, result)) = pending_jobs.next().await {
+            let error_msg = match result {
+                Ok(Ok(())) => None,
+                Ok(Err(e)) => Some(e),
+                Err(_) => Some("Indexing job was dropped before completing".to_string()),
+            };
 
-        // Now process files with concurrency
-        for file in &files {
-            // Semaphore is shared but each task needs its own reference for concurrency limit
-            let permit = sem.clone();
-            // Each task needs a reference to the current process files so it can update it
-            let pc = num_processed_files.clone();
-            // Task needs its own channel sender for errors
-            let err_sender: UnboundedSender<(String, String)> = err_tx.clone();
-            // Each task needs a reference to the processor object to call process function
-            let this = self.clone();
-            // Each task needs its own reference to the progress function to update it
-            let progress_fn = on_progress.clone();
-
-            let task_handle: task::JoinHandle<()> = create_path_embedding(
-                this.db_path,
-                file,
-                permit,
-                err_sender,
-                total_files,
-                pc,
-                progress_fn,
-                app_handle.clone(),
-            );
+            if let Some(status) = progress.record(&file_path, file_size, error_msg.is_none()) {
+                on_progress(status);
+            }
 
-            task_handles.push(task_handle);
+            if let Some(error_msg) = error_msg {
+                detailed_errors.push(serde_json::json!({
+                    "path": file_path,
+                    "error": error_msg
+                }));
+            }
         }
 
-        // Wait for all tasks and process results
-        drop(err_tx);
-        futures::future::join_all(task_handles).await;
-
-        // Collect errors with file paths
-        let mut detailed_errors = Vec::new();
-        while let Ok((file_path, error_msg)) = err_rx.try_recv() {
-            detailed_errors.push(serde_json::json!({
-                "path": file_path,
-                "error": error_msg
-            }));
-        }
+        let processed_count = progress.processed;
 
         let success = detailed_errors.is_empty();
-        let processed_count = num_processed_files.load(Ordering::SeqCst);
 
         // When process is complete, emit an event with the paths to watch
         if success {
-            println!("successfully processed all files during index");
+            info!("successfully processed all files during index");
             // Convert the directory paths to strings for the event payload
             let dir_paths: Vec<String> = unique_directories
                 .iter()
@@ -214,225 +403,547 @@ impl FileProcessor {
             // Emit the indexing_complete event with directory paths
             // Don't serialize the vector again - Tauri will handle that
             if let Err(e) = app_handle.emit("indexing_complete", &dir_paths) {
-                println!("Warning: Failed to emit indexing_complete event: {}", e);
+                warn!("Failed to emit indexing_complete event: {}", e);
             } else {
-                println!(
+                debug!(
                     "Successfully emitted indexing_complete event with {} paths",
                     dir_paths.len()
                 );
             }
 
-            println!("successfully emitted indexing_complete event");
+            debug!("successfully emitted indexing_complete event");
         }
 
+        record_timing(
+            &app_handle,
+            "indexing",
+            started.elapsed(),
+            Some(processed_count as u64),
+        );
+
         let result = serde_json::json!({
             "success": success,
             "totalFiles": total_files,
             "processedFiles": processed_count,
             "totalDirectories": total_directories,
-            "errors": detailed_errors
+            "errors": detailed_errors,
+            "skippedFiles": skipped_files
         });
 
         Ok(result)
     }
 
+    /// Walks the given paths with the same filters as process_paths without indexing anything
+    pub async fn preview_paths(
+        &self,
+        paths: Vec<String>,
+        app_handle: &AppHandle,
+    ) -> Result<IndexPreview, FileProcessorError> {
+        let index_filter = IndexFilter::from_app_handle(app_handle);
+        let CollectedFiles {
+            files,
+            directories,
+            skipped_files,
+        } = self.collect_all_files(&paths, index_filter).await?;
+
+        let mut extensions: HashMap<String, ExtensionBreakdown> = HashMap::new();
+        for file in &files {
+            let extension = file.extension.to_lowercase();
+            let entry = extensions
+                .entry(extension.clone())
+                .or_insert_with(|| ExtensionBreakdown {
+                    extension,
+                    count: 0,
+                    bytes: 0,
+                });
+            entry.count += 1;
+            entry.bytes += file.size.max(0) as u64;
+        }
+
+        let mut extensions: Vec<ExtensionBreakdown> = extensions.into_values().collect();
+        extensions.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+
+        let total_files = files.len();
+        let total_bytes: u64 = extensions.iter().map(|e| e.bytes).sum();
+
+        Ok(IndexPreview {
+            total_files,
+            total_bytes,
+            total_directories: directories.len(),
+            estimated_seconds: estimate_index_seconds(
+                total_files,
+                total_bytes,
+                self.concurrency_limit,
+            ),
+            extensions,
+            skipped_files,
+        })
+    }
+
+    /// Drops the stored index entries for the given paths and processes them again
+    #[instrument(skip_all, fields(paths = paths.len()))]
+    pub async fn reindex_paths(
+        &self,
+        paths: Vec<String>,
+        priority: IndexPriority,
+        on_progress: impl Fn(ProcessingStatus) + Send + Sync + Clone + 'static,
+        app_handle: AppHandle,
+    ) -> Result<serde_json::Value, FileProcessorError> {
+        debug!("Re-indexing paths: {:?}", paths);
+
+        let invalidated_files =
+            delete_paths_from_db(self.db_path.clone(), paths.clone(), false).await?;
+
+        for (file_id, file_path) in &invalidated_files {
+            if let Err(e) =
+                VectorDbManager::delete_embedding(&app_handle, &file_id.to_string()).await
+            {
+                error!("Failed to delete embeddings for {}: {}", file_path, e);
+            }
+        }
+
+        self.process_paths(paths, priority, on_progress, app_handle)
+            .await
+    }
+
+    /// Brings the index in line with the disk for the given directories without touching unchanged files
+    /// Files whose modification time differs from the stored one are indexed again and entries for files that are gone are removed
+    pub async fn reconcile_paths(
+        &self,
+        roots: Vec<String>,
+        app_handle: AppHandle,
+    ) -> Result<serde_json::Value, FileProcessorError> {
+        let index_filter = IndexFilter::from_app_handle(&app_handle);
+        let collected = self.collect_all_files(&roots, index_filter).await?;
+
+        let db_path = self.db_path.clone();
+        let roots_for_query = roots.clone();
+        let indexed: HashMap<String, Option<String>> =
+            task::spawn_blocking(move || -> Result<_, FileProcessorError> {
+                let conn = open_connection(db_path)?;
+                let mut stmt = conn.prepare(
+                    r#"
+                    SELECT path, updated_at FROM files
+                    WHERE path = ?1 OR path LIKE ?2 ESCAPE '\'
+                    "#,
+                )?;
+
+                let mut indexed = HashMap::new();
+                for root in &roots_for_query {
+                    let root = root.trim_end_matches('/');
+                    let rows = stmt
+                        .query_map(params![root, descendants_like_pattern(root)], |row| {
+                            Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+                        })?;
+                    for row in rows {
+                        let (path, updated_at) = row?;
+                        indexed.insert(path, updated_at);
+                    }
+                }
+                Ok(indexed)
+            })
+            .await
+            .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))??;
+
+        let on_disk: HashSet<&str> = collected
+            .files
+            .iter()
+            .map(|file| file.base.path.as_str())
+            .collect();
+
+        let changed: Vec<String> = collected
+            .files
+            .iter()
+            .filter(|file| match indexed.get(&file.base.path) {
+                Some(updated_at) => *updated_at != file.updated_at,
+                None => true,
+            })
+            .map(|file| file.base.path.clone())
+            .collect();
+
+        let missing: Vec<String> = indexed
+            .keys()
+            .filter(|path| !on_disk.contains(path.as_str()) && !Path::new(path).exists())
+            .cloned()
+            .collect();
+
+        info!(
+            "Reconciled {:?}: {} changed files, {} missing files",
+            roots,
+            changed.len(),
+            missing.len()
+        );
+
+        if !missing.is_empty() {
+            let removed_files = delete_paths_from_db(self.db_path.clone(), missing, false).await?;
+            for (file_id, file_path) in &removed_files {
+                if let Err(e) =
+                    VectorDbManager::delete_embedding(&app_handle, &file_id.to_string()).await
+                {
+                    error!("Failed to delete embeddings for {}: {}", file_path, e);
+                }
+            }
+        }
+
+        if changed.is_empty() {
+            return Ok(serde_json::json!({
+                "success": true,
+                "totalFiles": 0,
+                "errors": []
+            }));
+        }
+
+        self.process_paths(
+            changed,
+            IndexPriority::Backfill,
+            |_status: ProcessingStatus| {},
+            app_handle,
+        )
+        .await
+    }
+
     /// Given a vector of paths, this walks the tree and collects all children paths and their parent directories
+    /// Paths matching the exclusion patterns or ignored by .gitignore/.kitaignore files are skipped
+    /// Files over the size limit are returned as skipped files so they can be reported back to the user
     async fn collect_all_files(
         &self,
         paths: &[String],
-    ) -> Result<(Vec<FileMetadata>, HashSet<PathBuf>), FileProcessorError> {
+        index_filter: IndexFilter,
+    ) -> Result<CollectedFiles, FileProcessorError> {
         let path_vec: Vec<String> = paths.to_vec();
 
         task::spawn_blocking(move || {
-            let mut all_files: Vec<FileMetadata> = Vec::new();
-            let mut unique_directories: HashSet<PathBuf> = HashSet::new();
+            let mut collected = CollectedFiles::default();
 
             for path_str in path_vec {
                 let path: &Path = Path::new(&path_str);
+
+                if index_filter.should_skip(path) {
+                    continue;
+                }
+
                 if path.is_dir() {
                     // Add the root directory itself
-                    unique_directories.insert(PathBuf::from(path));
-
-                    for entry in WalkDir::new(path) {
-                        let entry: walkdir::DirEntry = match entry {
-                            Ok(e) => e,
-                            Err(e) => {
-                                eprintln!("Error walking dir: {e}");
-                                continue;
-                            }
-                        };
-
-                        // Skip hidden files
-                        if let Some(file_name) = entry.file_name().to_str() {
-                            if file_name.starts_with(".") {
-                                continue;
-                            }
-                        }
+                    collected.directories.insert(PathBuf::from(path));
+
+                    let mut walk_builder = WalkBuilder::new(path);
+                    walk_builder
+                        .standard_filters(false)
+                        .hidden(!index_filter.include_hidden_files())
+                        .git_ignore(index_filter.respect_ignore_files())
+                        .parents(index_filter.respect_ignore_files())
+                        .require_git(false);
+
+                    if index_filter.respect_ignore_files() {
+                        walk_builder.add_custom_ignore_filename(KITA_IGNORE_FILE_NAME);
+                    }
 
-                        if entry.file_type().is_file() {
-                            // Check if the file has a valid extension before processing
-                            if is_valid_file_extension(entry.path()) {
-                                // Add the parent directory
-                                if let Some(parent) = entry.path().parent() {
-                                    unique_directories.insert(PathBuf::from(parent));
-                                }
+                    // prune excluded directories and the ones below the depth limit so we never descend into them
+                    let entry_filter = index_filter.clone();
+                    walk_builder.filter_entry(move |entry| {
+                        !entry_filter.matches_exclude_pattern(entry.path())
+                            && !entry_filter.exceeds_max_depth(entry.path())
+                    });
 
-                                let _ = get_file_metadata(entry.path(), &mut all_files);
-                            }
-                        } else if entry.file_type().is_dir() {
-                            // Add all directories to our set
-                            unique_directories.insert(entry.path().to_path_buf());
-                        }
-                    }
+                    // walk the tree on multiple threads, each thread collects into its own
+                    // CollectedFiles and merges them into the shared one when it finishes
+                    let shared = Mutex::new(CollectedFiles::default());
+                    let mut visitor_builder = CollectVisitorBuilder {
+                        index_filter: &index_filter,
+                        shared: &shared,
+                    };
+                    walk_builder.build_parallel().visit(&mut visitor_builder);
+
+                    let walked = shared
+                        .into_inner()
+                        .map_err(|e| FileProcessorError::Other(e.to_string()))?;
+                    collected.extend(walked);
                 } else {
                     // Handle single file case
                     if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-                        if file_name.starts_with(".") {
+                        if file_name.starts_with(".") && !index_filter.include_hidden_files() {
                             continue;
                         }
                     }
 
                     // Check if the file has a valid extension before processing
-                    if is_valid_file_extension(path) {
+                    if index_filter.has_indexable_extension(path) {
                         // Add the parent directory
                         if let Some(parent) = path.parent() {
-                            unique_directories.insert(PathBuf::from(parent));
+                            collected.directories.insert(PathBuf::from(parent));
                         }
 
-                        let _ = get_file_metadata(path, &mut all_files);
+                        collect_file(
+                            path,
+                            &index_filter,
+                            &mut collected.files,
+                            &mut collected.skipped_files,
+                        );
                     }
                 }
             }
-            Ok::<_, FileProcessorError>((all_files, unique_directories))
+            Ok::<_, FileProcessorError>(collected)
         })
         .await
         .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
     }
 }
 
-fn create_path_embedding(
-    db_path: PathBuf,
-    file_metadata: &FileMetadata,
-    permit: Arc<Semaphore>,
-    err_sender: UnboundedSender<(String, String)>,
-    total_files: usize,
-    pc: Arc<AtomicUsize>,
-    progress_fn: impl Fn(ProcessingStatus) + Send + Sync + Clone + 'static,
-    app_handle: AppHandle,
-) -> tokio::task::JoinHandle<()> {
-    let fm_clone = file_metadata.clone();
-    let file_path = fm_clone.base.path.clone();
+/// Creates a CollectVisitor for every thread of the parallel directory walk
+struct CollectVisitorBuilder<'a> {
+    index_filter: &'a IndexFilter,
+    shared: &'a Mutex<CollectedFiles>,
+}
 
-    println!(
-        "saving the path to db and creating embedding: {}",
-        file_metadata.base.path
-    );
+impl<'a> ParallelVisitorBuilder<'a> for CollectVisitorBuilder<'a> {
+    fn build(&mut self) -> Box<dyn ParallelVisitor + 'a> {
+        Box::new(CollectVisitor {
+            index_filter: self.index_filter,
+            collected: CollectedFiles::default(),
+            shared: self.shared,
+        })
+    }
+}
 
-    tokio::spawn(async move {
-        // Acquire concurrency permit
-        let _permit = match permit.acquire().await {
-            Ok(permit) => permit,
-            Err(_) => {
-                let _ =
-                    err_sender.send((file_path, "Failed to acquire semaphore permit".to_string()));
-                return;
-            }
-        };
+/// Collects the files and directories seen by one thread of the parallel directory walk
+struct CollectVisitor<'a> {
+    index_filter: &'a IndexFilter,
+    collected: CollectedFiles,
+    shared: &'a Mutex<CollectedFiles>,
+}
 
-        let saved_file_id: String = match save_file_to_db(db_path.clone(), &fm_clone).await {
-            Ok(file_id) => file_id,
+impl ParallelVisitor for CollectVisitor<'_> {
+    fn visit(&mut self, entry: Result<ignore::DirEntry, ignore::Error>) -> WalkState {
+        let entry: ignore::DirEntry = match entry {
+            Ok(e) => e,
             Err(e) => {
-                let _ = err_sender.send((file_path, format!("File processing error: {:?}", e)));
-                return;
+                error!("Error walking dir: {e}");
+                return WalkState::Continue;
             }
         };
 
-        // Skip empty files
-        if fm_clone.size == 0 {
+        let Some(file_type) = entry.file_type() else {
+            return WalkState::Continue;
+        };
+
+        if file_type.is_file() {
+            // Check if the file has a valid extension before processing
+            if self.index_filter.has_indexable_extension(entry.path()) {
+                // Add the parent directory
+                if let Some(parent) = entry.path().parent() {
+                    self.collected.directories.insert(PathBuf::from(parent));
+                }
+
+                collect_file(
+                    entry.path(),
+                    self.index_filter,
+                    &mut self.collected.files,
+                    &mut self.collected.skipped_files,
+                );
+            }
+        } else if file_type.is_dir() {
+            // Add all directories to our set
+            self.collected
+                .directories
+                .insert(entry.path().to_path_buf());
+        }
+
+        WalkState::Continue
+    }
+}
+
+impl Drop for CollectVisitor<'_> {
+    fn drop(&mut self) {
+        let collected = std::mem::take(&mut self.collected);
+        match self.shared.lock() {
+            Ok(mut shared) => shared.extend(collected),
+            Err(e) => error!("Failed to merge collected files: {}", e),
+        }
+    }
+}
+
+/// Adds the file's metadata to the collected files unless it is over the size limit
+fn collect_file(
+    path: &Path,
+    index_filter: &IndexFilter,
+    all_files: &mut Vec<FileMetadata>,
+    skipped_files: &mut Vec<SkippedFile>,
+) {
+    let meta = match std::fs::metadata(path) {
+        Ok(meta) => meta,
+        Err(e) => {
+            warn!("Unable to read metadata for {:?}: {}", path, e);
             return;
         }
+    };
+    let size = meta.len();
 
-        let config = ChunkerConfig {
-            chunk_size: 100,
-            chunk_overlap: 2,
-            normalize_text: true,
-            extract_metadata: true,
-            max_concurrent_files: 4,
-            use_gpu_acceleration: true,
-        };
+    // indexing a placeholder would either index an empty stub or download the file from the cloud
+    if !index_filter.download_cloud_placeholders() && is_cloud_placeholder(path, &meta) {
+        skipped_files.push(SkippedFile {
+            path: path.to_string_lossy().into_owned(),
+            size,
+            reason: "File is stored in the cloud and hasn't been downloaded".to_string(),
+        });
+        return;
+    }
 
-        let orchestrator = ChunkerOrchestrator::new(config);
+    if index_filter.exceeds_max_file_size(path, size) {
+        skipped_files.push(SkippedFile {
+            path: path.to_string_lossy().into_owned(),
+            size,
+            reason: format!(
+                "File is larger than the {} byte limit",
+                index_filter.max_file_size_bytes_for(path)
+            ),
+        });
+        return;
+    }
 
-        let embedder_state: State<'_, Arc<Embedder>> = app_handle.state::<Arc<Embedder>>();
+    let _ = get_file_metadata(path, all_files);
+}
 
-        let embedder: Arc<Embedder> = Arc::clone(&embedder_state.inner());
+/// Indexes a single file by saving it to the db, chunking it and storing the embeddings in the vectordb
+/// Called by the index queue workers, failures are stored in the index_errors table so they can be retried
+pub async fn index_file(
+    db_path: PathBuf,
+    file_metadata: FileMetadata,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let file_path = file_metadata.base.path.clone();
 
-        match orchestrator.chunk_file(&fm_clone, embedder).await {
-            Ok(chunk_embeddings) => {
-                if chunk_embeddings.is_empty() {
-                    let _ =
-                        err_sender.send((file_path, "No valid embeddings generated".to_string()));
-                } else {
-                    VectorDbManager::insert_embeddings(
-                        &app_handle,
-                        &saved_file_id,
-                        chunk_embeddings,
-                    )
-                    .await
-                    .unwrap_or_else(|e| {
-                        let _ = err_sender.send((
-                            file_path.clone(),
-                            format!("Failed to insert embeddings: {}", e),
-                        ));
-                    });
-                    // Update progress
-                    let processed: usize = pc.fetch_add(1, Ordering::SeqCst) + 1;
-                    let percentage: usize =
-                        ((processed as f64 / total_files as f64) * 100.0).round() as usize;
-                    progress_fn(ProcessingStatus {
-                        total: total_files,
-                        processed,
-                        percentage,
-                    });
-                }
+    match run_index_pipeline(db_path.clone(), &file_metadata, &app_handle).await {
+        Ok(()) => {
+            if let Err(e) = clear_index_errors(db_path, vec![file_path]).await {
+                error!("Failed to clear index errors: {}", e);
             }
-            Err(e) => {
-                let _ = err_sender.send((file_path, format!("Chunking/embedding error: {}", e)));
+            Ok(())
+        }
+        Err(failure) => {
+            let error = failure.message.message.clone();
+            if let Err(e) =
+                record_index_error(db_path, file_path, failure.chunker, failure.message).await
+            {
+                error!("Failed to record index error: {}", e);
             }
+            Err(error)
         }
-    })
+    }
+}
+
+/// Error from a single step of the indexing pipeline along with the chunker that was used, if any
+struct IndexFailure {
+    chunker: Option<String>,
+    message: UserMessage,
+}
+
+impl IndexFailure {
+    fn new(chunker: Option<String>, code: ErrorCode, error: impl ToString) -> Self {
+        Self {
+            chunker,
+            message: UserMessage::new(code).with_param("error", error),
+        }
+    }
+}
+
+async fn run_index_pipeline(
+    db_path: PathBuf,
+    file_metadata: &FileMetadata,
+    app_handle: &AppHandle,
+) -> Result<(), IndexFailure> {
+    debug!(
+        "saving the path to db and creating embedding: {}",
+        file_metadata.base.path
+    );
+
+    let saved_file = save_file_to_db(db_path, file_metadata)
+        .await
+        .map_err(|e| IndexFailure::new(None, ErrorCode::IndexSaveFailed, format!("{:?}", e)))?;
+    let saved_file_id = saved_file.file_id;
+
+    // Moved files keep their id, so the embeddings stored for them are still correct
+    if saved_file.reuse_embeddings {
+        debug!(
+            "Reusing embeddings for moved file: {}",
+            file_metadata.base.path
+        );
+        return Ok(());
+    }
+
+    // Skip empty files
+    if file_metadata.size == 0 {
+        return Ok(());
+    }
+
+    // directories with semantic indexing turned off only get the full-text index
+    let semantic_indexing = directory_settings_for(Path::new(&file_metadata.base.path))
+        .and_then(|(_, settings)| settings.semantic_indexing)
+        .unwrap_or(true);
+    if !semantic_indexing {
+        return VectorDbManager::delete_embedding(app_handle, &saved_file_id)
+            .await
+            .map_err(|e| IndexFailure::new(None, ErrorCode::IndexEmbeddingsFailed, e));
+    }
+
+    let config = ChunkerConfig::default();
+
+    let orchestrator = ChunkerOrchestrator::new(config);
+
+    let embedder_state: State<'_, Arc<Embedder>> = app_handle.state::<Arc<Embedder>>();
+
+    let embedder: Arc<Embedder> = Arc::clone(&embedder_state.inner());
+
+    let chunker_name = || orchestrator.chunker_name_for_file(Path::new(&file_metadata.base.path));
+
+    let chunk_embeddings = orchestrator
+        .chunk_file(file_metadata, embedder)
+        .await
+        .map_err(|e| IndexFailure::new(chunker_name(), ErrorCode::IndexChunkingFailed, e))?;
+
+    if chunk_embeddings.is_empty() {
+        return Err(IndexFailure {
+            chunker: chunker_name(),
+            message: UserMessage::new(ErrorCode::IndexNoEmbeddings),
+        });
+    }
+
+    // files that were indexed before still have their old chunks in the vectordb
+    VectorDbManager::delete_embedding(app_handle, &saved_file_id)
+        .await
+        .map_err(|e| IndexFailure::new(chunker_name(), ErrorCode::IndexEmbeddingsFailed, e))?;
+
+    VectorDbManager::insert_embeddings(app_handle, &saved_file_id, chunk_embeddings)
+        .await
+        .map_err(|e| IndexFailure::new(chunker_name(), ErrorCode::IndexEmbeddingsFailed, e))
+}
+
+/// Result of saving a file to the db
+struct SavedFile {
+    file_id: String,
+    // the file was moved without changing its contents, so its existing embeddings still apply
+    reuse_embeddings: bool,
 }
 
 /// Saves a single file to the db and to fts
-/// returns the stringified file id on success
+/// Files that were moved from a path that no longer exists keep their row, so their id and embeddings are kept
 async fn save_file_to_db(
     db_path: PathBuf,
     file: &FileMetadata,
-) -> Result<String, FileProcessorError> {
+) -> Result<SavedFile, FileProcessorError> {
     let file = file.clone();
 
-    println!("saving the file in the db:{:?}", file.base.path);
+    debug!("saving the file in the db:{:?}", file.base.path);
 
     task::spawn_blocking({
         let db_path = db_path;
-        move || -> Result<String, FileProcessorError> {
+        move || -> Result<SavedFile, FileProcessorError> {
             // Fixed error handling with map_err instead of map
-            let conn = Connection::open(db_path).map_err(|e| FileProcessorError::Db(e))?;
+            let conn = open_connection(db_path).map_err(|e| FileProcessorError::Db(e))?;
 
             // Set pragmas for better performance
-            conn.execute_batch(
-                r#"
-                PRAGMA journal_mode = WAL;
-                PRAGMA synchronous = NORMAL;
-                "#,
-            )?;
+            conn.execute_batch(WRITER_PRAGMAS)?;
 
-            // Get the filename part
             let path = Path::new(&file.base.path);
-            let filename = path
-                .file_name()
-                .map(|f| f.to_string_lossy().to_string())
-                .unwrap_or_else(|| file.base.name.clone());
 
             // Get the parent directory
             let parent_path = path
@@ -441,36 +952,56 @@ async fn save_file_to_db(
                 .unwrap_or_else(|| String::from(""));
 
             // Get directory_id (it should already exist from the batch insert)
-            let directory_id: i64 = match conn.query_row(
-                "SELECT id FROM directories WHERE path = ?1",
-                [&parent_path],
-                |row| row.get(0),
-            ) {
-                Ok(id) => id,
-                Err(rusqlite::Error::QueryReturnedNoRows) => {
-                    // Directory not found - insert it as a fallback
-                    conn.execute(
-                        r#"
-                        INSERT OR IGNORE INTO directories (path)
-                        VALUES (?1);
-                        "#,
-                        params![parent_path],
-                    )?;
-
-                    conn.query_row(
-                        "SELECT id FROM directories WHERE path = ?1",
-                        [&parent_path],
-                        |row| row.get(0),
-                    )?
+            let directory_id = get_or_create_directory_id(&conn, &parent_path)?;
+
+            // Hash the contents so identical files in different folders can be found
+            let content_hash = compute_content_hash(path)
+                .map_err(|e| error!("Failed to hash {}: {}", file.base.path, e))
+                .ok();
+
+            let identity = std::fs::metadata(path)
+                .ok()
+                .and_then(|meta| get_file_identity(&meta));
+
+            // Files that were indexed before already have their fts entry
+            let mut existing_file_id: Option<i64> = conn
+                .query_row(
+                    "SELECT id FROM files WHERE path = ?1",
+                    [&file.base.path],
+                    |row| row.get(0),
+                )
+                .optional()?;
+
+            // A new path with the identity of an indexed file whose path is gone means the file was moved
+            let mut reuse_embeddings = false;
+            if let (None, Some((device, inode))) = (existing_file_id, identity) {
+                if let Some(moved) = find_moved_file(&conn, device, inode)? {
+                    info!("Detected move of {} to {}", moved.path, file.base.path);
+                    update_indexed_file_path(&conn, &moved, path, directory_id)?;
+
+                    reuse_embeddings = content_hash.is_some() && moved.content_hash == content_hash;
+                    existing_file_id = Some(moved.id);
                 }
-                Err(e) => return Err(FileProcessorError::Db(e)),
-            };
+            }
+
+            let (device, inode) = identity.unzip();
 
-            // Insert file metadata with directory_id
+            // Insert file metadata with directory_id, re-indexed files get their metadata refreshed
             conn.execute(
                 r#"
-                INSERT OR IGNORE INTO files (directory_id, path, name, extension, size, category)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6);
+                INSERT INTO files (directory_id, path, name, extension, size, category, content_hash, created_at, updated_at, device, inode)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, COALESCE(?8, CURRENT_TIMESTAMP), COALESCE(?9, CURRENT_TIMESTAMP), ?10, ?11)
+                ON CONFLICT(path) DO UPDATE SET
+                    directory_id = excluded.directory_id,
+                    name = excluded.name,
+                    extension = excluded.extension,
+                    size = excluded.size,
+                    category = excluded.category,
+                    content_hash = excluded.content_hash,
+                    created_at = excluded.created_at,
+                    updated_at = excluded.updated_at,
+                    device = excluded.device,
+                    inode = excluded.inode;
                 "#,
                 params![
                     directory_id,
@@ -478,84 +1009,567 @@ async fn save_file_to_db(
                     file.base.name,
                     file.extension,
                     file.size,
-                    get_category_from_extension(&file.extension)
+                    get_category_from_extension(&file.extension),
+                    content_hash,
+                    file.created_at,
+                    file.updated_at,
+                    device,
+                    inode
                 ],
             )?;
 
-            // Get the file ID for FTS insertion
-            let file_id: i64 = conn.query_row(
-                "SELECT id FROM files WHERE path = ?1",
-                [file.base.path.clone()],
-                |row| row.get(0),
-            )?;
+            // Get the file ID for FTS insertion
+            let file_id: i64 = conn.query_row(
+                "SELECT id FROM files WHERE path = ?1",
+                [file.base.path.clone()],
+                |row| row.get(0),
+            )?;
+
+            if existing_file_id.is_none() {
+                // Build document text from file metadata for search indexing
+                let doc_text = build_doc_text(
+                    &file.base.name,
+                    &file.base.path,
+                    &file.extension,
+                    &[],
+                    &active_fts_options(),
+                );
+
+                // Insert into full-text search table
+                conn.execute(
+                    r#"
+                    INSERT INTO files_fts(rowid, doc_text)
+                    VALUES (?1, ?2)
+                    "#,
+                    params![file_id, doc_text],
+                )?;
+            }
+
+            Ok(SavedFile {
+                file_id: file_id.to_string(),
+                reuse_embeddings,
+            })
+        }
+    })
+    .await
+    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
+}
+
+/// Returns the id of the directory, inserting it if it doesn't exist yet
+fn get_or_create_directory_id(
+    conn: &Connection,
+    parent_path: &str,
+) -> Result<i64, FileProcessorError> {
+    match conn.query_row(
+        "SELECT id FROM directories WHERE path = ?1",
+        [parent_path],
+        |row| row.get(0),
+    ) {
+        Ok(id) => Ok(id),
+        Err(rusqlite::Error::QueryReturnedNoRows) => {
+            // Directory not found - insert it as a fallback
+            conn.execute(
+                r#"
+                INSERT OR IGNORE INTO directories (path)
+                VALUES (?1);
+                "#,
+                params![parent_path],
+            )?;
+
+            let id = conn.query_row(
+                "SELECT id FROM directories WHERE path = ?1",
+                [parent_path],
+                |row| row.get(0),
+            )?;
+            Ok(id)
+        }
+        Err(e) => Err(FileProcessorError::Db(e)),
+    }
+}
+
+/// Device and inode of a file, these stay the same when the file is renamed or moved within a volume
+pub fn get_file_identity(meta: &std::fs::Metadata) -> Option<(i64, i64)> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some((meta.dev() as i64, meta.ino() as i64))
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = meta;
+        None
+    }
+}
+
+/// A file row as it is stored in the index
+pub struct IndexedFile {
+    pub id: i64,
+    pub path: String,
+    pub name: String,
+    pub extension: String,
+    pub content_hash: Option<String>,
+}
+
+/// Finds an indexed file with the given identity whose stored path no longer exists on disk
+fn find_moved_file(
+    conn: &Connection,
+    device: i64,
+    inode: i64,
+) -> Result<Option<IndexedFile>, FileProcessorError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT id, path, name, extension, content_hash
+        FROM files
+        WHERE device = ?1 AND inode = ?2
+        "#,
+    )?;
+
+    let candidates = stmt.query_map(params![device, inode], |row| {
+        Ok(IndexedFile {
+            id: row.get(0)?,
+            path: row.get(1)?,
+            name: row.get(2)?,
+            extension: row.get(3)?,
+            content_hash: row.get(4)?,
+        })
+    })?;
+
+    for candidate in candidates {
+        let candidate = candidate?;
+        // inodes are reused after a file is deleted, so only a missing old path counts as a move
+        if !Path::new(&candidate.path).exists() {
+            return Ok(Some(candidate));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Points an indexed file at its new path, keeping its id so the vectors stored for it stay valid
+pub fn update_indexed_file_path(
+    conn: &Connection,
+    file: &IndexedFile,
+    new_path: &Path,
+    directory_id: i64,
+) -> Result<(), FileProcessorError> {
+    let new_path_str = new_path.to_string_lossy().to_string();
+    let new_name = new_path
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_else(|| file.name.clone());
+    let new_extension = new_path
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    conn.execute(
+        r#"
+        UPDATE files
+        SET path = ?1, name = ?2, extension = ?3, category = ?4, directory_id = ?5
+        WHERE id = ?6
+        "#,
+        params![
+            new_path_str,
+            new_name,
+            new_extension,
+            get_category_from_extension(&new_extension),
+            directory_id,
+            file.id
+        ],
+    )?;
+
+    delete_from_fts(conn, file.id, &file.name, &file.path, &file.extension)?;
+
+    let tags = load_file_tags(conn, file.id)?;
+    let new_doc_text = build_doc_text(
+        &new_name,
+        &new_path_str,
+        &new_extension,
+        &tags,
+        &active_fts_options(),
+    );
+    conn.execute(
+        "INSERT INTO files_fts(rowid, doc_text) VALUES (?1, ?2)",
+        params![file.id, new_doc_text],
+    )?;
+
+    Ok(())
+}
+
+/// Removes a file's entry from files_fts
+/// files_fts is contentless so the entry can only be deleted with the text it was indexed with,
+/// the file's tags have to be removed after this and not before
+pub fn delete_from_fts(
+    conn: &Connection,
+    file_id: i64,
+    name: &str,
+    path: &str,
+    extension: &str,
+) -> Result<(), FileProcessorError> {
+    let tags = load_file_tags(conn, file_id)?;
+    let doc_text = build_doc_text(name, path, extension, &tags, &active_fts_options());
+    conn.execute(
+        "INSERT INTO files_fts(files_fts, rowid, doc_text) VALUES ('delete', ?1, ?2)",
+        params![file_id, doc_text],
+    )?;
+    Ok(())
+}
+
+/// Points every indexed file at or below `from` at the same place below `to`, used when a file or folder is renamed
+/// File ids don't change so the embeddings stored for them stay valid, returns the number of files that were updated
+pub async fn rename_indexed_paths(
+    db_path: PathBuf,
+    from: PathBuf,
+    to: PathBuf,
+) -> Result<usize, FileProcessorError> {
+    task::spawn_blocking(move || -> Result<usize, FileProcessorError> {
+        let mut conn = open_connection(db_path)?;
+        let tx = conn.transaction()?;
+
+        let from_str = from.to_string_lossy().trim_end_matches('/').to_string();
+        let to_str = to.to_string_lossy().trim_end_matches('/').to_string();
+        let pattern = descendants_like_pattern(&from_str);
+
+        let files: Vec<IndexedFile> = {
+            let mut stmt = tx.prepare(
+                r#"
+                SELECT id, path, name, extension, content_hash
+                FROM files
+                WHERE path = ?1 OR path LIKE ?2 ESCAPE '\'
+                "#,
+            )?;
+            let rows = stmt.query_map(params![from_str, pattern], |row| {
+                Ok(IndexedFile {
+                    id: row.get(0)?,
+                    path: row.get(1)?,
+                    name: row.get(2)?,
+                    extension: row.get(3)?,
+                    content_hash: row.get(4)?,
+                })
+            })?;
+            rows.collect::<Result<_, _>>()?
+        };
+
+        // renamed folders keep their rows so they are still watched on the next start
+        tx.execute(
+            r#"
+            UPDATE OR IGNORE directories
+            SET path = ?1 || substr(path, ?2)
+            WHERE path = ?3 OR path LIKE ?4 ESCAPE '\'
+            "#,
+            params![
+                to_str,
+                from_str.chars().count() as i64 + 1,
+                from_str,
+                pattern
+            ],
+        )?;
+
+        for file in &files {
+            let new_path = match Path::new(&file.path).strip_prefix(&from_str) {
+                Ok(relative) if !relative.as_os_str().is_empty() => to.join(relative),
+                _ => to.clone(),
+            };
+
+            let parent_path = new_path
+                .parent()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let directory_id = get_or_create_directory_id(&tx, &parent_path)?;
+
+            update_indexed_file_path(&tx, file, &new_path, directory_id)?;
+        }
+
+        tx.commit()?;
+        Ok(files.len())
+    })
+    .await
+    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
+}
+
+/// Returns the hex encoded SHA-256 of the file's contents
+fn compute_content_hash(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Formats a filesystem timestamp the same way sqlite's CURRENT_TIMESTAMP does, in UTC
+fn format_file_time(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+/// Get metadata for a given file path
+pub fn get_file_metadata(
+    path: &Path,
+    all_files: &mut Vec<FileMetadata>,
+) -> Result<(), FileProcessorError> {
+    let meta = std::fs::metadata(path)?;
+    let size = meta.len() as i64;
+    // not every filesystem records a creation time
+    let created_at = meta.created().ok().map(format_file_time);
+    let updated_at = meta.modified().ok().map(format_file_time);
+    let ext = path
+        .extension()
+        .map(|os| os.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    all_files.push(FileMetadata {
+        base: BaseMetadata {
+            id: None,
+            name: path
+                .file_name()
+                .map(|f| f.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "unknown".into()),
+            path: path.to_string_lossy().into_owned(),
+        },
+        file_type: SearchSectionType::Files,
+        extension: ext,
+        size,
+        updated_at,
+        created_at,
+        thumbnail: None,
+        source: FileSource::Index,
+    });
+
+    Ok(())
+}
+
+#[derive(Default)]
+pub struct FileProcessorState(pub Mutex<Option<FileProcessor>>);
+
+#[tauri::command]
+pub async fn process_paths_command(
+    paths: Vec<String>,
+    state: tauri::State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<serde_json::Value, String> {
+    let processor: FileProcessor = {
+        let guard: std::sync::MutexGuard<'_, Option<FileProcessor>> =
+            state.0.lock().map_err(|e| e.to_string())?;
+        match guard.as_ref() {
+            Some(p) => p.clone(),
+            None => return Err("File processor not initialized".to_string()),
+        }
+    };
+
+    let app_handle_for_progress = app_handle.clone();
+
+    let progress_handler = move |status: ProcessingStatus| {
+        let _ = app_handle_for_progress.emit("file-processing-progress", &status);
+    };
+
+    processor
+        .process_paths(paths, IndexPriority::Backfill, progress_handler, app_handle)
+        .await
+        .map_err(|e: FileProcessorError| e.to_string())
+}
+
+/// Indexes a single file ahead of any bulk indexing that is already queued
+#[tauri::command]
+pub async fn index_file_now(
+    path: String,
+    state: tauri::State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<serde_json::Value, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let app_handle_for_progress = app_handle.clone();
+
+    let progress_handler = move |status: ProcessingStatus| {
+        let _ = app_handle_for_progress.emit("file-processing-progress", &status);
+    };
+
+    processor
+        .process_paths(
+            vec![path],
+            IndexPriority::Immediate,
+            progress_handler,
+            app_handle,
+        )
+        .await
+        .map_err(|e: FileProcessorError| e.to_string())
+}
+
+/// Previews an indexing operation so the user can see its size before committing to it
+#[tauri::command]
+pub async fn preview_index(
+    paths: Vec<String>,
+    state: tauri::State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<IndexPreview, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    processor
+        .preview_paths(paths, &app_handle)
+        .await
+        .map_err(|e: FileProcessorError| e.to_string())
+}
+
+/// Removes every indexed file under the given paths from the db, fts and vectordb and stops watching them
+#[tauri::command]
+#[instrument(skip_all, fields(paths = paths.len()))]
+pub async fn remove_paths_from_index(
+    paths: Vec<String>,
+    state: tauri::State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<serde_json::Value, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    debug!("Removing paths from index: {:?}", paths);
+
+    let removed_files = delete_paths_from_db(processor.db_path.clone(), paths.clone(), true)
+        .await
+        .map_err(|e: FileProcessorError| e.to_string())?;
+    let total_files = removed_files.len();
 
-            // Build document text from file metadata for search indexing
-            let doc_text = build_doc_text(&file.base.name, &file.base.path, &file.extension);
+    let mut detailed_errors = Vec::new();
+    let mut progress = ProgressTracker::new(total_files, 0);
 
-            // Insert into full-text search table
-            conn.execute(
-                r#"
-                INSERT INTO files_fts(rowid, doc_text)
-                VALUES (?1, ?2)
-                "#,
-                params![file_id, doc_text],
-            )?;
+    for (file_id, file_path) in &removed_files {
+        let result = VectorDbManager::delete_embedding(&app_handle, &file_id.to_string()).await;
 
-            Ok(file_id.to_string())
+        if let Some(status) = progress.record(file_path, 0, result.is_ok()) {
+            let _ = app_handle.emit("unindex-progress", status);
         }
-    })
-    .await
-    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
+
+        if let Err(e) = result {
+            detailed_errors.push(serde_json::json!({
+                "path": file_path,
+                "error": format!("Failed to delete embeddings: {}", e)
+            }));
+        }
+    }
+
+    let roots: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+    unwatch_paths(&app_handle, &roots);
+
+    if let Err(e) = app_handle.emit("files-updated", ()) {
+        warn!("Failed to emit files-updated event: {}", e);
+    }
+
+    Ok(serde_json::json!({
+        "success": detailed_errors.is_empty(),
+        "removedFiles": total_files,
+        "errors": detailed_errors
+    }))
 }
 
-/// Get metadata for a given file path
-pub fn get_file_metadata(
-    path: &Path,
-    all_files: &mut Vec<FileMetadata>,
-) -> Result<(), FileProcessorError> {
-    let meta = std::fs::metadata(path)?;
-    let size = meta.len() as i64;
-    let ext = path
-        .extension()
-        .map(|os| os.to_string_lossy().into_owned())
-        .unwrap_or_default();
+#[cfg(target_os = "macos")]
+fn move_to_trash(path: &str) -> Result<(), String> {
+    let path_cstring = CString::new(path).map_err(|_| "Failed to create C string".to_string())?;
 
-    all_files.push(FileMetadata {
-        base: BaseMetadata {
-            id: None,
-            name: path
-                .file_name()
-                .map(|f| f.to_string_lossy().into_owned())
-                .unwrap_or_else(|| "unknown".into()),
-            path: path.to_string_lossy().into_owned(),
-        },
-        file_type: SearchSectionType::Files,
-        extension: ext,
-        size,
-        updated_at: None,
-        created_at: None,
-    });
+    let trashed = unsafe { trash_file_swift(path_cstring.as_ptr()) };
+    if !trashed {
+        return Err(format!("Failed to move {} to the Trash", path));
+    }
+    Ok(())
+}
 
+/// Uses the freedesktop trash through gio, which file managers on Linux share
+#[cfg(target_os = "linux")]
+fn move_to_trash(path: &str) -> Result<(), String> {
+    let output = Command::new("gio")
+        .args(["trash", "--"])
+        .arg(path)
+        .output()
+        .map_err(|e| format!("Failed to run gio to move {} to the Trash: {}", path, e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to move {} to the Trash: {}",
+            path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
     Ok(())
 }
 
-#[derive(Default)]
-pub struct FileProcessorState(pub Mutex<Option<FileProcessor>>);
+/// Sends the path to the Recycle Bin through the VisualBasic file system API
+/// The path is passed in an environment variable so it doesn't have to be quoted for PowerShell
+#[cfg(windows)]
+fn move_to_trash(path: &str) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+
+    /// Keeps PowerShell from flashing a console window
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+    const SCRIPT: &str = "Add-Type -AssemblyName Microsoft.VisualBasic; \
+        if (Test-Path -LiteralPath $env:KITA_TRASH_PATH -PathType Container) { \
+        [Microsoft.VisualBasic.FileIO.FileSystem]::DeleteDirectory($env:KITA_TRASH_PATH, 'OnlyErrorDialogs', 'SendToRecycleBin') \
+        } else { \
+        [Microsoft.VisualBasic.FileIO.FileSystem]::DeleteFile($env:KITA_TRASH_PATH, 'OnlyErrorDialogs', 'SendToRecycleBin') }";
+
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .env("KITA_TRASH_PATH", path)
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| {
+            format!(
+                "Failed to run PowerShell to move {} to the Recycle Bin: {}",
+                path, e
+            )
+        })?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to move {} to the Recycle Bin: {}",
+            path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
 
+/// Moves a file or folder to the Trash and removes it from the db, fts and vectordb
 #[tauri::command]
-pub async fn process_paths_command(
+pub async fn trash_file(
+    path: String,
+    state: tauri::State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let is_dir = Path::new(&path).is_dir();
+    move_to_trash(&path)?;
+
+    let removed_files = delete_paths_from_db(processor.db_path.clone(), vec![path.clone()], is_dir)
+        .await
+        .map_err(|e: FileProcessorError| e.to_string())?;
+
+    for (file_id, file_path) in &removed_files {
+        if let Err(e) = VectorDbManager::delete_embedding(&app_handle, &file_id.to_string()).await {
+            error!("Failed to delete embeddings for {}: {}", file_path, e);
+        }
+    }
+
+    if is_dir {
+        unwatch_paths(&app_handle, &[PathBuf::from(&path)]);
+    }
+
+    info!("Moved {} to the Trash", path);
+
+    if let Err(e) = app_handle.emit("files-updated", ()) {
+        warn!("Failed to emit files-updated event: {}", e);
+    }
+
+    Ok(())
+}
+
+/// Drops the stored index entries for the given paths and runs them through the indexing pipeline again
+/// Useful after changing chunking settings or when a file was indexed badly
+#[tauri::command]
+pub async fn reindex_paths(
     paths: Vec<String>,
     state: tauri::State<'_, FileProcessorState>,
     app_handle: AppHandle,
 ) -> Result<serde_json::Value, String> {
-    let processor: FileProcessor = {
-        let guard: std::sync::MutexGuard<'_, Option<FileProcessor>> =
-            state.0.lock().map_err(|e| e.to_string())?;
-        match guard.as_ref() {
-            Some(p) => p.clone(),
-            None => return Err("File processor not initialized".to_string()),
-        }
-    };
+    let processor: FileProcessor = get_processor(&state)?;
 
     let app_handle_for_progress = app_handle.clone();
 
@@ -564,7 +1578,12 @@ pub async fn process_paths_command(
     };
 
     processor
-        .process_paths(paths, progress_handler, app_handle)
+        .reindex_paths(
+            paths,
+            IndexPriority::Immediate,
+            progress_handler,
+            app_handle,
+        )
         .await
         .map_err(|e: FileProcessorError| e.to_string())
 }
@@ -576,17 +1595,24 @@ pub async fn get_semantic_files_data(
     app_handle: AppHandle,
 ) -> Result<Vec<SemanticMetadata>, String> {
     let processor: FileProcessor = get_processor(&state)?;
+    record_feature(&app_handle, "search.semantic");
+    let started = Instant::now();
+
+    let conn = processor.get_read_connection()?;
 
-    let conn: Connection = Connection::open(&processor.db_path)
-        .map_err(|e| format!("Failed to open database: {e}"))?;
+    let search_settings = SearchSettings::from_app_handle(&app_handle);
 
     // Do a vector similarity search
-    let semantic_files: Vec<SemanticMetadata> =
+    let mut semantic_files: Vec<SemanticMetadata> =
         match VectorDbManager::search_similar(&app_handle, &query).await {
-            Ok(results) => convert_search_results_to_metadata(results, &conn)?,
+            Ok(results) => convert_search_results_to_metadata(
+                results,
+                &conn,
+                search_settings.semantic_distance_threshold(),
+            )?,
             Err(e) => {
                 // Log the error but continue with just FTS results
-                eprintln!(
+                warn!(
                     "Semantic search error (continuing with text search only): {}",
                     e
                 );
@@ -594,31 +1620,344 @@ pub async fn get_semantic_files_data(
             }
         };
 
+    if search_settings.rerank() {
+        semantic_files = rerank_semantic_results(&conn, &query, semantic_files, &search_settings);
+    }
+
+    let collapse_duplicates = app_handle
+        .try_state::<SettingsManagerState>()
+        .and_then(|state| state.0.get_settings().ok())
+        .and_then(|settings| settings.collapse_duplicate_results)
+        .unwrap_or(true);
+
+    let semantic_files = if collapse_duplicates {
+        collapse_duplicate_results(&conn, semantic_files)?
+    } else {
+        semantic_files
+    };
+
+    record_timing(&app_handle, "search.semantic", started.elapsed(), None);
     Ok(semantic_files)
 }
 
+/// Orders semantic results by their weighted similarity plus a bonus for files whose name, path or tags match the query
+fn rerank_semantic_results(
+    conn: &Connection,
+    query: &str,
+    results: Vec<SemanticMetadata>,
+    search_settings: &SearchSettings,
+) -> Vec<SemanticMetadata> {
+    let (semantic_weight, keyword_weight) = search_settings.hybrid_weights();
+    let match_query = build_match_query(query, &active_fts_options());
+
+    let keyword_match = |file_id: Option<i64>| -> bool {
+        file_id
+            .and_then(|id| {
+                conn.query_row(
+                    "SELECT 1 FROM files_fts WHERE doc_text MATCH ?1 AND rowid = ?2",
+                    params![match_query, id],
+                    |_| Ok(()),
+                )
+                .optional()
+                // queries the tokenizer can't match count as no match
+                .unwrap_or(None)
+            })
+            .is_some()
+    };
+
+    let mut scored: Vec<(f32, SemanticMetadata)> = results
+        .into_iter()
+        .map(|result| {
+            let keyword_score = if keyword_match(result.base.id) {
+                1.0
+            } else {
+                0.0
+            };
+            let score = semantic_weight * (1.0 - result.distance) + keyword_weight * keyword_score;
+            (score, result)
+        })
+        .collect();
+
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Keeps only the closest match for files with identical contents
+fn collapse_duplicate_results(
+    conn: &Connection,
+    mut results: Vec<SemanticMetadata>,
+) -> Result<Vec<SemanticMetadata>, String> {
+    if results.len() < 2 {
+        return Ok(results);
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT content_hash FROM files WHERE id = ?1")
+        .map_err(|e| format!("Failed to prepare statement: {e}"))?;
+
+    results.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+
+    let mut seen_hashes: HashSet<String> = HashSet::new();
+    let mut collapsed: Vec<SemanticMetadata> = Vec::with_capacity(results.len());
+
+    for result in results {
+        let content_hash: Option<String> = match result.base.id {
+            Some(id) => stmt
+                .query_row([id], |row| row.get(0))
+                .map_err(|e| format!("Query error: {e}"))?,
+            None => None,
+        };
+
+        // files without a hash can't be compared so they are always kept
+        if let Some(hash) = content_hash {
+            if !seen_hashes.insert(hash) {
+                continue;
+            }
+        }
+        collapsed.push(result);
+    }
+
+    Ok(collapsed)
+}
+
+/// Files that have identical contents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateFileGroup {
+    pub content_hash: String,
+    pub size: i64,
+    pub files: Vec<FileMetadata>,
+}
+
+/// Groups the indexed files that have identical contents
+#[tauri::command]
+pub async fn find_duplicate_files(
+    state: State<'_, FileProcessorState>,
+) -> Result<Vec<DuplicateFileGroup>, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let conn = processor.get_read_connection()?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT
+              content_hash,
+              id,
+              name,
+              path,
+              extension,
+              size,
+              created_at,
+              updated_at
+            FROM files
+            WHERE content_hash IN (
+              SELECT content_hash FROM files
+              WHERE content_hash IS NOT NULL
+              GROUP BY content_hash
+              HAVING COUNT(*) > 1
+            )
+            ORDER BY size DESC, content_hash, path
+            "#,
+        )
+        .map_err(|e| format!("Failed to prepare statement: {e}"))?;
+
+    let mut rows = stmt.query([]).map_err(|e| format!("Query error: {e}"))?;
+    let mut groups: Vec<DuplicateFileGroup> = Vec::new();
+
+    while let Some(row) = rows.next().map_err(|e| format!("Row error: {e}"))? {
+        let content_hash: String = row.get(0).map_err(|e| e.to_string())?;
+        let file = FileMetadata {
+            base: BaseMetadata {
+                id: Some(row.get(1).map_err(|e| e.to_string())?),
+                name: row.get(2).map_err(|e| e.to_string())?,
+                path: row.get(3).map_err(|e| e.to_string())?,
+            },
+            file_type: SearchSectionType::Files,
+            extension: row.get(4).map_err(|e| e.to_string())?,
+            size: row.get(5).map_err(|e| e.to_string())?,
+            created_at: row.get(6).ok(),
+            updated_at: row.get(7).ok(),
+            thumbnail: None,
+            source: FileSource::Index,
+        };
+
+        // rows are ordered by hash so each group is contiguous
+        match groups.last_mut() {
+            Some(group) if group.content_hash == content_hash => group.files.push(file),
+            _ => groups.push(DuplicateFileGroup {
+                content_hash,
+                size: file.size,
+                files: vec![file],
+            }),
+        }
+    }
+
+    Ok(groups)
+}
+
 #[tauri::command]
 pub async fn get_files_data(
     query: String,
+    app_handle: AppHandle,
     state: State<'_, FileProcessorState>,
 ) -> Result<Vec<FileMetadata>, String> {
     let processor: FileProcessor = get_processor(&state)?;
+    record_feature(&app_handle, "search.keyword");
+    let started = Instant::now();
+
+    let files = search_files(&processor, &query)?;
+    let files = with_spotlight_results(&app_handle, &processor, &query, files).await;
+    record_timing(&app_handle, "search.keyword", started.elapsed(), None);
+
+    Ok(with_thumbnails(&app_handle, files).await)
+}
 
-    let conn: Connection = Connection::open(&processor.db_path)
-        .map_err(|e| format!("Failed to open database: {e}"))?;
+/// Keyword search over the indexed files, without thumbnails
+pub fn search_files(processor: &FileProcessor, query: &str) -> Result<Vec<FileMetadata>, String> {
+    let conn = processor.get_read_connection()?;
 
     // Handle short que
     if query.len() < 3 {
-        return search_files_by_like(&conn, &query);
+        return search_files_by_like(&conn, query);
     }
 
     // For queries with >3 characters, first do an FTS search
-    let files = search_files_by_fts(&conn, &query)?;
+    search_files_by_fts(&conn, query)
+}
 
-    Ok(files)
+/// Returns every indexed file in the given category, most recently updated first
+#[tauri::command]
+pub async fn get_files_by_category(
+    category: String,
+    app_handle: AppHandle,
+    state: State<'_, FileProcessorState>,
+) -> Result<Vec<FileMetadata>, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let files = {
+        let conn = processor.get_read_connection()?;
+
+        let mut stmt = conn
+            .prepare(
+                r#"
+                SELECT
+                  id,
+                  name,
+                  path,
+                  extension,
+                  size,
+                  created_at,
+                  updated_at
+                FROM files
+                WHERE category = ?1
+                ORDER BY updated_at DESC
+                "#,
+            )
+            .map_err(|e| format!("Failed to prepare statement: {e}"))?;
+
+        let rows = stmt
+            .query([category.to_lowercase()])
+            .map_err(|e| format!("Query error: {e}"))?;
+
+        rows_to_file_metadata(rows)?
+    };
+
+    Ok(with_thumbnails(&app_handle, files).await)
+}
+
+/// Number of files in a category, used for the category facets in search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryCount {
+    pub category: String,
+    pub count: i64,
+}
+
+/// Counts the files matching the query per category, an empty query counts every indexed file
+/// Uses the same matching as get_files_data so the counts line up with the search results
+#[tauri::command]
+pub async fn get_category_counts(
+    query: String,
+    state: State<'_, FileProcessorState>,
+) -> Result<Vec<CategoryCount>, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let conn = processor.get_read_connection()?;
+
+    let (sql, params): (&str, Vec<String>) = if query.is_empty() {
+        (
+            r#"
+            SELECT COALESCE(category, 'other'), COUNT(*)
+            FROM files
+            GROUP BY 1
+            ORDER BY 2 DESC
+            "#,
+            Vec::new(),
+        )
+    } else if query.len() < 3 {
+        let like_pattern = format!("%{}%", query);
+        (
+            r#"
+            SELECT COALESCE(category, 'other'), COUNT(*)
+            FROM files
+            WHERE name LIKE ?1 OR path LIKE ?1 OR extension LIKE ?1
+            GROUP BY 1
+            ORDER BY 2 DESC
+            "#,
+            vec![like_pattern],
+        )
+    } else {
+        (
+            r#"
+            SELECT COALESCE(f.category, 'other'), COUNT(*)
+            FROM files_fts ft
+            JOIN files f ON ft.rowid = f.id
+            WHERE ft.doc_text MATCH ?1
+            GROUP BY 1
+            ORDER BY 2 DESC
+            "#,
+            vec![build_match_query(&query, &active_fts_options())],
+        )
+    };
+
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| format!("Failed to prepare statement: {e}"))?;
+
+    let counts = stmt
+        .query_map(rusqlite::params_from_iter(params), |row| {
+            Ok(CategoryCount {
+                category: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })
+        .map_err(|e| format!("Query error: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Row error: {e}"))?;
+
+    Ok(counts)
+}
+
+/// Pauses or resumes indexing, the tray menu calls this too
+#[tauri::command]
+pub fn set_indexing_paused(
+    paused: bool,
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let processor: FileProcessor = get_processor(&state)?;
+    processor.index_queue.set_paused(paused);
+
+    let _ = app_handle.emit("indexing-paused-changed", paused);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn is_indexing_paused(state: State<'_, FileProcessorState>) -> Result<bool, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+    Ok(processor.index_queue.is_paused())
 }
 
-fn get_processor(state: &State<'_, FileProcessorState>) -> Result<FileProcessor, String> {
+pub fn get_processor(state: &State<'_, FileProcessorState>) -> Result<FileProcessor, String> {
     let processor: FileProcessor = {
         let guard: std::sync::MutexGuard<'_, Option<FileProcessor>> =
             state.0.lock().map_err(|e| e.to_string())?;
@@ -662,7 +2001,7 @@ fn search_files_by_like(conn: &Connection, query: &str) -> Result<Vec<FileMetada
 
 // Search files using full-text search
 fn search_files_by_fts(conn: &Connection, query: &str) -> Result<Vec<FileMetadata>, String> {
-    let search_trigrams = build_trigrams(query);
+    let match_query = build_match_query(query, &active_fts_options());
 
     let mut stmt = conn
         .prepare(
@@ -684,14 +2023,14 @@ fn search_files_by_fts(conn: &Connection, query: &str) -> Result<Vec<FileMetadat
         .map_err(|e| format!("Failed to prepare statement: {e}"))?;
 
     let rows = stmt
-        .query([search_trigrams.as_str()])
+        .query([match_query.as_str()])
         .map_err(|e| format!("Query error: {e}"))?;
 
     rows_to_file_metadata(rows)
 }
 
 // convert sqlite rows to FileMetadata type
-fn rows_to_file_metadata(mut rows: Rows) -> Result<Vec<FileMetadata>, String> {
+pub fn rows_to_file_metadata(mut rows: Rows) -> Result<Vec<FileMetadata>, String> {
     let mut files: Vec<FileMetadata> = Vec::new();
 
     while let Some(row) = rows.next().map_err(|e| format!("Row error: {e}"))? {
@@ -706,6 +2045,8 @@ fn rows_to_file_metadata(mut rows: Rows) -> Result<Vec<FileMetadata>, String> {
             size: row.get(4).map_err(|e| e.to_string())?,
             created_at: row.get(5).ok(),
             updated_at: row.get(6).ok(),
+            thumbnail: None,
+            source: FileSource::Index,
         });
     }
 
@@ -743,6 +2084,7 @@ fn rows_to_semantic_metadata(
 fn convert_search_results_to_metadata(
     results: Vec<RecordBatch>,
     conn: &Connection,
+    distance_threshold: f32,
 ) -> Result<Vec<SemanticMetadata>, String> {
     // If no results, return empty vector
     if results.is_empty() {
@@ -767,13 +2109,13 @@ fn convert_search_results_to_metadata(
                     for i in 0..distance_array.len() {
                         if !distance_array.is_null(i) {
                             let distance = distance_array.value(i);
-                            if distance < 0.85 {
+                            if distance < distance_threshold {
                                 let file_id = file_id_array.value(i);
                                 if !file_id_distances.contains_key(file_id)
                                     || file_id_distances[file_id] > distance
                                 {
                                     file_id_distances.insert(file_id.to_string(), distance);
-                                    println!(
+                                    debug!(
                                         "Relevant match: file_id={}, distance={}",
                                         file_id, distance
                                     );
@@ -827,9 +2169,15 @@ fn convert_search_results_to_metadata(
     rows_to_semantic_metadata(rows, &file_id_distances)
 }
 
+/// Command that opens a file with its default app
+#[cfg(target_os = "linux")]
+const OPEN_COMMAND: &str = "xdg-open";
+#[cfg(not(target_os = "linux"))]
+const OPEN_COMMAND: &str = "open";
+
 #[tauri::command]
 pub fn open_file(file_path: &str) -> Result<(), String> {
-    let status = Command::new("open")
+    let status = Command::new(OPEN_COMMAND)
         .arg(file_path)
         .status()
         .map_err(|e| format!("Failed to open file: {}", e))?;
@@ -844,6 +2192,23 @@ pub fn open_file(file_path: &str) -> Result<(), String> {
     }
 }
 
+/// Creates the connection pool for the query commands
+/// WAL mode lets these readers run alongside the indexer writes, the busy timeout covers checkpoints
+fn create_read_pool(db_path: &str) -> AppResult<Pool<SqliteConnectionManager>> {
+    let manager = SqliteConnectionManager::file(db_path).with_init(|conn| {
+        apply_database_key(conn)?;
+        conn.execute_batch(
+            r#"
+            PRAGMA journal_mode = WAL;
+            PRAGMA busy_timeout = 5000;
+            "#,
+        )
+    });
+
+    let pool = Pool::builder().max_size(READ_POOL_SIZE).build(manager)?;
+    Ok(pool)
+}
+
 pub fn init_file_processor(
     db_path: &str,
     concurrency: usize,
@@ -854,28 +2219,45 @@ pub fn init_file_processor(
 
     match lock_result {
         Ok(mut processor_guard) => {
+            let read_pool = create_read_pool(db_path)?;
+
+            // the configured concurrency is the upper bound, the controller scales down from it under load
+            let concurrency = app_handle
+                .try_state::<SettingsManagerState>()
+                .and_then(|state| state.0.get_settings().ok())
+                .and_then(|settings| settings.index_concurrency)
+                .unwrap_or(concurrency);
+
+            // the workers share one queue so bulk indexing can't starve watcher or user requests
+            let index_queue = Arc::new(IndexQueue::new());
+            index_queue.start_workers(concurrency, PathBuf::from(db_path), app_handle.clone());
+            index_queue.start_concurrency_controller(app_handle.clone());
+
             *processor_guard = Some(FileProcessor {
                 db_path: PathBuf::from(db_path),
                 concurrency_limit: concurrency,
+                index_queue,
+                read_pool,
             });
 
-            println!("File processor initialized.");
+            info!("File processor initialized.");
             Ok(())
         }
         Err(e) => {
             let error_msg = format!("Failed to initialize file processor: {}", e);
-            eprintln!("{}", error_msg);
+            error!("{}", error_msg);
             Err(Box::new(Error::new(ErrorKind::Other, error_msg)))
         }
     }
 }
 
-pub fn is_valid_file_extension(path: &Path) -> bool {
-    let valid_extensions: HashSet<&str> = ["txt", "pdf", "docx", "md", "yaml", "yml"]
-        .iter()
-        .cloned()
-        .collect();
+/// Extensions that are indexed when the user hasn't configured their own list
+pub const DEFAULT_INDEXABLE_EXTENSIONS: [&str; 27] = [
+    "txt", "pdf", "docx", "md", "yaml", "yml", "xlsx", "csv", "pptx", "doc", "rtf", "toml", "html",
+    "htm", "xhtml", "zip", "tar", "tgz", "rs", "py", "js", "ts", "jsx", "tsx", "m4a", "mp3", "wav",
+];
 
+pub fn is_valid_file_extension(path: &Path, valid_extensions: &HashSet<String>) -> bool {
     if let Some(extension) = path.extension() {
         if let Some(ext_str) = extension.to_str() {
             return valid_extensions.contains(ext_str.to_lowercase().as_str());
@@ -884,6 +2266,79 @@ pub fn is_valid_file_extension(path: &Path) -> bool {
     false
 }
 
+/// Builds a LIKE pattern that matches everything below the given directory
+pub fn descendants_like_pattern(root: &str) -> String {
+    let escaped = root
+        .trim_end_matches('/')
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+
+    format!("{}/%", escaped)
+}
+
+/// Deletes the given paths and everything below them from files and files_fts, and from directories if requested
+/// Returns the id and path of every deleted file so their embeddings can be removed as well
+pub async fn delete_paths_from_db(
+    db_path: PathBuf,
+    paths: Vec<String>,
+    remove_directories: bool,
+) -> Result<Vec<(i64, String)>, FileProcessorError> {
+    task::spawn_blocking(move || -> Result<Vec<(i64, String)>, FileProcessorError> {
+        let mut conn = open_connection(db_path)?;
+        let tx = conn.transaction()?;
+        let mut removed_files: Vec<(i64, String)> = Vec::new();
+
+        for root in &paths {
+            let root = root.trim_end_matches('/');
+            let pattern = descendants_like_pattern(root);
+
+            let files: Vec<(i64, String, String, String)> = {
+                let mut stmt = tx.prepare(
+                    r#"
+                    SELECT id, path, name, extension FROM files
+                    WHERE path = ?1 OR path LIKE ?2 ESCAPE '\'
+                    "#,
+                )?;
+                let rows = stmt.query_map(params![root, pattern], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                })?;
+                rows.collect::<Result<_, _>>()?
+            };
+
+            for (file_id, path, name, extension) in &files {
+                delete_from_fts(&tx, *file_id, name, path, extension)?;
+                tx.execute("DELETE FROM file_tags WHERE file_id = ?1", [file_id])?;
+                tx.execute("DELETE FROM files WHERE id = ?1", [file_id])?;
+            }
+
+            // drop the directories too so they aren't watched again on the next start
+            if remove_directories {
+                tx.execute(
+                    r#"
+                    DELETE FROM directories
+                    WHERE path = ?1 OR path LIKE ?2 ESCAPE '\'
+                    "#,
+                    params![root, pattern],
+                )?;
+            }
+
+            removed_files.extend(files.into_iter().map(|(id, path, _, _)| (id, path)));
+        }
+
+        // tags only exist while they are on at least one file
+        tx.execute(
+            "DELETE FROM tags WHERE NOT EXISTS (SELECT 1 FROM file_tags WHERE tag_id = tags.id)",
+            [],
+        )?;
+
+        tx.commit()?;
+        Ok(removed_files)
+    })
+    .await
+    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
+}
+
 /// Saves directories to the database, handling duplicates via the UNIQUE constraint
 async fn save_directories_to_db(
     db_path: PathBuf,
@@ -903,15 +2358,10 @@ async fn save_directories_to_db(
         let dirs = directories_vec.clone();
 
         move || -> Result<(), FileProcessorError> {
-            let mut conn = Connection::open(db_path).map_err(|e| FileProcessorError::Db(e))?;
+            let mut conn = open_connection(db_path).map_err(|e| FileProcessorError::Db(e))?;
 
             // Set pragmas for better performance
-            conn.execute_batch(
-                r#"
-                PRAGMA journal_mode = WAL;
-                PRAGMA synchronous = NORMAL;
-                "#,
-            )?;
+            conn.execute_batch(WRITER_PRAGMAS)?;
 
             let tx = conn.transaction()?;
 
@@ -1,40 +1,140 @@
+use crate::database_handler::open_connection;
 use crate::file_processor::{
-    is_valid_file_extension, FileProcessor, FileProcessorError, FileProcessorState,
-    ProcessingStatus,
+    delete_paths_from_db, descendants_like_pattern, get_processor, rename_indexed_paths,
+    FileProcessor, FileProcessorError, FileProcessorState, ProcessingStatus,
 };
+use crate::index_filter::IndexFilter;
+use crate::index_queue::IndexPriority;
+use crate::settings::SettingsManagerState;
+use crate::supervisor::supervise;
 use crate::vectordb_manager::VectorDbManager;
 use crate::AppResult;
+use notify::event::{ModifyKind, RenameMode};
 use notify::{
-    Config, Error as NotifyError, Event as NotifyEvent, EventKind, RecommendedWatcher,
+    Config, Error as NotifyError, Event as NotifyEvent, EventKind, PollWatcher, RecommendedWatcher,
     RecursiveMode, Watcher,
 };
-use rusqlite::Connection;
-use std::collections::HashSet;
+use rusqlite::params;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+#[cfg(target_os = "macos")]
+use std::sync::OnceLock;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tauri::{AppHandle, Emitter, Listener, Manager};
 use tokio::select;
 use tokio::sync::mpsc::Receiver;
 use tokio::task;
-use tracing::error;
+use tracing::{debug, error, info, warn};
 
-const DEBOUNCE_TIMEOUT_MS: u64 = 1000;
+#[cfg(target_os = "macos")]
+extern "C" {
+    fn register_wake_callback_swift(callback: extern "C" fn());
+}
+
+/// App handle for the wake callback, the Swift bridge can only call a plain C function
+#[cfg(target_os = "macos")]
+static WAKE_APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+/// Used when the user hasn't configured their own debounce
+const DEFAULT_DEBOUNCE_TIMEOUT_MS: u64 = 1000;
+
+/// Largest number of paths indexed in one job after a debounce, bigger batches are split up
+const DEFAULT_MAX_BATCH_SIZE: usize = 200;
+
+/// How often directories on network and external volumes are scanned for changes
+const DEFAULT_POLL_INTERVAL_SECS: u64 = 30;
 
 #[derive(Debug, Default, Clone)]
 pub struct WatcherState {
     pub watched_roots: HashSet<PathBuf>,
+    // number of filesystem events received for each watched root
+    pub event_counts: HashMap<PathBuf, u64>,
+    // paths waiting for the debounce to finish
+    pub pending_events: usize,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchedRootStatus {
+    pub path: String,
+    pub event_count: u64,
+    pub polled: bool,
+}
+
+/// Snapshot of the watcher, sent with the watcher-status event and returned by get_watcher_status
+#[derive(Debug, Clone, Serialize)]
+pub struct WatcherStatus {
+    pub is_running: bool,
+    pub watched_roots: Vec<WatchedRootStatus>,
+    pub pending_events: usize,
+    pub last_error: Option<String>,
+}
+
+/// Managed handle that tracks whether live index updates are running
+/// The watchers stay alive while stopped, stopping only removes their watches
+#[derive(Default)]
+pub struct FileWatcherHandle {
+    is_watching: Arc<Mutex<bool>>,
+}
+
+impl FileWatcherHandle {
+    pub fn is_watching(&self) -> bool {
+        self.is_watching.lock().map(|flag| *flag).unwrap_or(false)
+    }
+
+    fn set_watching(&self, watching: bool) {
+        if let Ok(mut flag) = self.is_watching.lock() {
+            *flag = watching;
+        }
+    }
+}
+
+/// Watcher for directories on volumes where native events don't fire reliably, e.g. SMB/NFS mounts and FAT/exFAT disks
+pub struct PollingWatcher {
+    watcher: PollWatcher,
+    // directories that are polled, the watches are recursive so their subdirectories don't need their own
+    roots: HashSet<PathBuf>,
+}
+
+/// Debounce and batching settings, read every time they are needed so changes apply right away
+struct WatcherConfig {
+    debounce: Duration,
+    max_batch_size: usize,
+}
+
+impl WatcherConfig {
+    fn from_app_handle(app_handle: &AppHandle) -> Self {
+        let settings = app_handle
+            .try_state::<SettingsManagerState>()
+            .and_then(|state| state.0.get_settings().ok())
+            .unwrap_or_default();
+
+        Self {
+            debounce: Duration::from_millis(
+                settings
+                    .watcher_debounce_ms
+                    .unwrap_or(DEFAULT_DEBOUNCE_TIMEOUT_MS),
+            ),
+            // a batch size of 0 would never make progress
+            max_batch_size: settings
+                .watcher_max_batch_size
+                .unwrap_or(DEFAULT_MAX_BATCH_SIZE)
+                .max(1),
+        }
+    }
 }
 
 // inits the file wastcher and gets the parent directories from the db to watch
 pub fn init_file_watcher(app: &tauri::App, db_path: &Path) -> AppResult<()> {
-    println!("Initializing file watcher service...");
+    info!("Initializing file watcher service...");
 
     let app_clone = app.handle().clone();
 
     let watched_roots = match extract_watch_directories_from_db(db_path) {
         Ok(dirs) => {
-            println!("Found {} directories to watch from database", dirs.len());
+            info!("Found {} directories to watch from database", dirs.len());
             dirs
         }
         Err(e) => {
@@ -45,12 +145,14 @@ pub fn init_file_watcher(app: &tauri::App, db_path: &Path) -> AppResult<()> {
 
     let initial_state = Arc::new(Mutex::new(Some(WatcherState {
         watched_roots: watched_roots.clone(),
+        ..Default::default()
     })));
 
     // store the initial state in the app state as well
     app.manage(initial_state);
+    app.manage(FileWatcherHandle::default());
 
-    println!(
+    info!(
         "File watcher initialized with {} watched directories",
         watched_roots.len()
     );
@@ -66,31 +168,53 @@ pub fn init_file_watcher(app: &tauri::App, db_path: &Path) -> AppResult<()> {
 
 // gets the parent directories from the db to watch
 fn extract_watch_directories_from_db(db_path: &Path) -> Result<HashSet<PathBuf>, rusqlite::Error> {
-    let conn = Connection::open(db_path)?;
+    let conn = open_connection(db_path)?;
 
     // extract unique parent directories
     let mut stmt = conn.prepare(
         "
-        SELECT path FROM directories
+        SELECT id, path FROM directories
     ",
     )?;
 
-    let dirs = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    let dirs = stmt.query_map([], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+    })?;
 
     // insert parent directories into watcher state
     let mut watch_dirs = HashSet::new();
+    let mut missing_dirs = Vec::new();
     for dir_result in dirs {
-        if let Ok(dir_str) = dir_result {
+        if let Ok((dir_id, dir_str)) = dir_result {
             let path = PathBuf::from(dir_str);
-            watch_dirs.insert(path);
+            if path.is_dir() {
+                watch_dirs.insert(path);
+            } else {
+                missing_dirs.push(dir_id);
+            }
         }
     }
 
+    // drop orphaned directories that were deleted from disk and have nothing indexed in them anymore
+    let mut orphan_count = 0;
+    for dir_id in missing_dirs {
+        orphan_count += conn.execute(
+            "DELETE FROM directories WHERE id = ?1 AND NOT EXISTS (SELECT 1 FROM files WHERE directory_id = ?1)",
+            [dir_id],
+        )?;
+    }
+    if orphan_count > 0 {
+        info!(
+            "Removed {} orphaned directories from database",
+            orphan_count
+        );
+    }
+
     Ok(watch_dirs)
 }
 
 pub fn start_watcher_service(app_handle: AppHandle) -> AppResult<()> {
-    println!("Starting File Watcher Service...");
+    info!("Starting File Watcher Service...");
 
     // channel for filesystem events
     let (fs_event_sender, fs_event_receiver) = tokio::sync::mpsc::channel(100);
@@ -110,54 +234,55 @@ pub fn start_watcher_service(app_handle: AppHandle) -> AppResult<()> {
     let watcher_mutex = Arc::new(std::sync::Mutex::new(watcher));
     app_handle.manage(watcher_mutex.clone());
 
-    // Set up watches for all directories in the WatcherState
-    let watcher_state = app_handle.state::<Arc<Mutex<Option<WatcherState>>>>();
-    let watch_roots = {
-        let guard = watcher_state.lock().unwrap();
-        match &*guard {
-            Some(state) => state.watched_roots.clone(),
-            None => {
-                error!("WatcherState not initialized correctly.");
-                HashSet::new()
-            }
-        }
-    };
+    // network and external volumes are polled instead, they send their events to the same channel
+    let poll_interval = app_handle
+        .try_state::<SettingsManagerState>()
+        .and_then(|state| state.0.get_settings().ok())
+        .and_then(|settings| settings.watcher_poll_interval_secs)
+        .unwrap_or(DEFAULT_POLL_INTERVAL_SECS);
 
-    //iterate through the directories and start watching them
-    let mut success_count = 0;
-    {
-        let mut watcher_guard = watcher_mutex.lock().unwrap();
-        for root in &watch_roots {
-            match watcher_guard.watch(root, RecursiveMode::Recursive) {
-                Ok(_) => {
-                    println!("Started watching directory: {:?}", root);
-                    success_count += 1;
-                }
-                Err(e) => {
-                    error!("Failed to watch directory {:?}: {}", root, e);
-                    // We don't remove from watched_roots here as the directory might
-                    // become available later
-                }
+    let poll_tx = fs_event_sender.clone();
+    let poll_watcher = PollWatcher::new(
+        move |res: Result<NotifyEvent, NotifyError>| {
+            if poll_tx.try_send(res).is_err() {
+                error!(
+                    "FS Event processing channel error (full or closed). Poll watcher might stop."
+                );
             }
-        }
+        },
+        Config::default().with_poll_interval(Duration::from_secs(poll_interval.max(1))),
+    )?;
+    app_handle.manage(Arc::new(std::sync::Mutex::new(PollingWatcher {
+        watcher: poll_watcher,
+        roots: HashSet::new(),
+    })));
+
+    // live indexing can be turned off with stop_file_watching, the watchers are still created so it can be started again
+    let live_indexing = app_handle
+        .try_state::<SettingsManagerState>()
+        .and_then(|state| state.0.get_settings().ok())
+        .and_then(|settings| settings.live_indexing)
+        .unwrap_or(true);
+
+    if live_indexing {
+        watch_all_roots(&app_handle);
+    } else {
+        info!("Live indexing is turned off, not watching any directories");
     }
 
-    println!(
-        "Successfully started watching {}/{} directories",
-        success_count,
-        watch_roots.len()
-    );
+    #[cfg(target_os = "macos")]
+    register_wake_handler(app_handle.clone());
 
     let (app_event_tx, app_event_rx) = tokio::sync::mpsc::channel::<Vec<String>>(5);
 
     // Listen for Tauri "indexing_complete" events
     let app_event_tx_clone = app_event_tx.clone();
     app_handle.listen("indexing_complete", move |event| {
-        println!("Received 'indexing_complete' Tauri event.");
+        debug!("Received 'indexing_complete' Tauri event.");
         let paths_str = event.payload();
         match serde_json::from_str::<Vec<String>>(paths_str) {
             Ok(paths) => {
-                println!("Forwarding {} indexed paths to watcher task.", paths.len());
+                debug!("Forwarding {} indexed paths to watcher task.", paths.len());
                 if let Err(e) = app_event_tx_clone.try_send(paths) {
                     error!(
                         "Failed to send indexing_complete payload to watcher task: {}",
@@ -169,32 +294,38 @@ pub fn start_watcher_service(app_handle: AppHandle) -> AppResult<()> {
         }
     });
 
-    // Spawn the main event processing loop
+    // Spawn the main event processing loop, the receivers are shared so a restarted loop keeps receiving the events
+    let receivers = Arc::new(tokio::sync::Mutex::new((fs_event_receiver, app_event_rx)));
     let app_handle_clone = app_handle.clone();
-    tokio::spawn(async move {
-        println!("Watcher event processing task started.");
-        process_combined_events(
-            fs_event_receiver,
-            app_event_rx,
-            app_handle_clone,
-            watcher_mutex,
-        )
-        .await;
-        println!("Watcher event processing task finished.");
+    supervise(app_handle.clone(), "file_watcher", true, move || {
+        let receivers = Arc::clone(&receivers);
+        let app_handle = app_handle_clone.clone();
+        let watcher_mutex = Arc::clone(&watcher_mutex);
+        async move {
+            debug!("Watcher event processing task started.");
+            let mut receivers = receivers.lock().await;
+            let (fs_event_rx, app_event_rx) = &mut *receivers;
+            process_combined_events(fs_event_rx, app_event_rx, app_handle, watcher_mutex).await;
+            debug!("Watcher event processing task finished.");
+        }
     });
 
-    println!("File Watcher Service started.");
+    info!("File Watcher Service started.");
     Ok(())
 }
 
 async fn process_combined_events(
-    mut fs_event_rx: Receiver<notify::Result<NotifyEvent>>, // Filesystem events
-    mut app_event_rx: Receiver<Vec<String>>,                // App events ("indexing_complete")
+    fs_event_rx: &mut Receiver<notify::Result<NotifyEvent>>, // Filesystem events
+    app_event_rx: &mut Receiver<Vec<String>>,                // App events ("indexing_complete")
     app_handle: AppHandle,
     watcher_mutex: Arc<std::sync::Mutex<RecommendedWatcher>>, // Watcher instance
 ) {
     let mut pending_reindex: HashSet<PathBuf> = HashSet::new();
     let mut pending_new: HashSet<PathBuf> = HashSet::new();
+    // paths that disappeared through a rename, removed at the end of the debounce unless a move claims them first
+    let mut pending_removals: HashSet<PathBuf> = HashSet::new();
+    // the From side of the last rename and its tracker, waiting for the matching To event
+    let mut last_rename_from: Option<(PathBuf, Option<usize>)> = None;
     let mut debounce_timer = Option::<tokio::time::Sleep>::None;
 
     // Get the DB path from the FileProcessorState
@@ -232,51 +363,90 @@ async fn process_combined_events(
             biased;
 
             // Timer fires: Process debounced Create/Modify
-            _ = async { debounce_timer.as_mut().unwrap() }, if debounce_timer.is_some() && (!pending_reindex.is_empty() || !pending_new.is_empty()) => {
+            _ = async { debounce_timer.as_mut().unwrap() }, if debounce_timer.is_some() && (!pending_reindex.is_empty() || !pending_new.is_empty() || !pending_removals.is_empty()) => {
                 let paths_to_reindex: Vec<PathBuf> = pending_reindex.drain().collect();
                 let paths_to_index_new: Vec<PathBuf> = pending_new.drain().collect();
-                debounce_timer = None;
-
-                let mut all_paths_to_process = paths_to_reindex;
-                all_paths_to_process.extend(paths_to_index_new);
 
-                if !all_paths_to_process.is_empty() {
-                    println!("Debounce finished. Processing changes/additions for: {:?}", all_paths_to_process);
+                // watched folders can disappear without a Remove event for every file inside them
+                let vanished_roots: Vec<PathBuf> = match watcher_state.lock() {
+                    Ok(guard) => guard.as_ref()
+                        .map(|state| state.watched_roots.iter().filter(|root| !root.exists()).cloned().collect())
+                        .unwrap_or_default(),
+                    Err(_) => Vec::new(),
+                };
+                for root in vanished_roots {
+                    add_pending_removal(&mut pending_removals, root);
+                }
+                let paths_to_remove: Vec<PathBuf> = pending_removals.drain().collect();
+                last_rename_from = None;
+                debounce_timer = None;
+                set_pending_events(&watcher_state, 0);
+                emit_watcher_status(&app_handle);
+
+                // a path can be queued as both new and changed during a burst, and temp files are usually gone by now
+                let all_paths_to_process: Vec<PathBuf> = paths_to_reindex
+                    .into_iter()
+                    .chain(paths_to_index_new)
+                    .collect::<HashSet<PathBuf>>()
+                    .into_iter()
+                    .filter(|path| path.exists())
+                    .collect();
+
+                if all_paths_to_process.is_empty() && !paths_to_remove.is_empty() {
+                    let db_path_clone = db_path.clone();
+                    let app_handle_clone = app_handle.clone();
+
+                    tokio::spawn(async move {
+                        remove_missing_paths_from_index(db_path_clone, paths_to_remove, &app_handle_clone).await;
+                    });
+                } else if !all_paths_to_process.is_empty() {
+                    debug!("Debounce finished. Processing changes/additions for: {:?}", all_paths_to_process);
 
                     let processor_state_handle = app_handle.state::<FileProcessorState>();
-                    let maybe_processor_info = {
+                    let maybe_processor = {
                         match processor_state_handle.0.lock() {
-                            Ok(guard) => guard.as_ref().map(|p| (p.db_path.clone(), p.concurrency_limit)),
+                            Ok(guard) => guard.as_ref().cloned(),
                             Err(e) => { error!("Mutex poisoned (debounce processing): {}", e); None }
                         }
                     };
 
-                    if let Some((db_path, concurrency_limit)) = maybe_processor_info {
+                    if let Some(processor) = maybe_processor {
                         let app_handle_clone = app_handle.clone();
+                        let db_path_clone = db_path.clone();
+                        let max_batch_size = WatcherConfig::from_app_handle(&app_handle).max_batch_size;
 
                         tokio::spawn(async move {
-                            let processor = FileProcessor { db_path, concurrency_limit };
-                            let progress_handler = move |_status: ProcessingStatus| { /* do nothing */ };
                             let paths_str: Vec<String> = all_paths_to_process
                                 .iter()
                                 .map(|p| p.to_string_lossy().to_string())
                                 .collect();
 
-                            println!("the path str in the events: {:?}", paths_str);
-                            match processor.process_paths(
-                                paths_str.clone(),
-                                progress_handler,
-                                app_handle_clone.clone(),
-                            ).await {
-                                Ok(_) => {
-                                    println!("Successfully processed batch: {:?}", all_paths_to_process);
-                                    if let Err(e) = app_handle_clone.emit("files-updated", ()) {
-                                        error!("Failed to emit files-updaede event: {}", e);
-                                    } else{
-                                        println!("Emitted files-updated event");
-                                    }
-                                },
-                                Err(e) => error!("Error processing batch {:?}: {:?}", all_paths_to_process, e),
+                            // large batches are indexed one after another so a huge tree doesn't flood the queue at once
+                            for batch in paths_str.chunks(max_batch_size) {
+                                let progress_handler = move |_status: ProcessingStatus| { /* do nothing */ };
+
+                                debug!("the path str in the events: {:?}", batch);
+                                match processor.process_paths(
+                                    batch.to_vec(),
+                                    IndexPriority::Watcher,
+                                    progress_handler,
+                                    app_handle_clone.clone(),
+                                ).await {
+                                    Ok(_) => {
+                                        debug!("Successfully processed batch: {:?}", batch);
+                                        if let Err(e) = app_handle_clone.emit("files-updated", ()) {
+                                            error!("Failed to emit files-updaede event: {}", e);
+                                        } else{
+                                            debug!("Emitted files-updated event");
+                                        }
+                                    },
+                                    Err(e) => error!("Error processing batch {:?}: {:?}", batch, e),
+                                }
+                            }
+
+                            // removed after indexing so that moved files can be matched to their old rows first
+                            if !paths_to_remove.is_empty() {
+                                remove_missing_paths_from_index(db_path_clone, paths_to_remove, &app_handle_clone).await;
                             }
                         });
                     } else {
@@ -289,89 +459,138 @@ async fn process_combined_events(
             maybe_fs_event_res = fs_event_rx.recv() => {
                 match maybe_fs_event_res {
                     Some(Ok(event)) => {
-                        println!("Received FS event: {:?}", event);
+                        debug!("Received FS event: {:?}", event);
+                        record_watcher_event(&watcher_state, &event);
                         let mut needs_debounce_reset = false;
-
-                        for path in &event.paths {
-                            if !is_relevant_file_event(&event, path) { continue; }
-
-                            let path_clone = path.clone();
-
-                            // Check database to see if file is indexed
-                            let db_path_clone = db_path.clone();
-                            let path_str = path_clone.to_string_lossy().to_string();
-
-                            // Use tokio::task for database operations
-                            let is_indexed = tokio::task::spawn_blocking(move || -> bool {
-                                if let Ok(conn) = Connection::open(db_path_clone) {
-                                    let result: Result<i32, _> = conn.query_row(
-                                        "SELECT 1 FROM files WHERE path = ?1 LIMIT 1",
-                                        [&path_str],
-                                        |row| row.get(0)
-                                    );
-                                    result.is_ok()
-                                } else {
-                                    false
-                                }
-                            }).await.unwrap_or(false);
-
-                            match event.kind {
-                                EventKind::Create(_) => {
-                                    if !is_indexed {
-                                        if pending_new.insert(path_clone) { needs_debounce_reset = true; }
+                        // rebuilt per event so that changes to the exclusion settings apply right away
+                        let index_filter = IndexFilter::from_app_handle(&app_handle);
+
+                        if let EventKind::Modify(ModifyKind::Name(rename_mode)) = event.kind {
+                            match (rename_mode, event.paths.as_slice()) {
+                                (RenameMode::Both, [from, to]) => {
+                                    pending_removals.remove(from);
+                                    last_rename_from = None;
+
+                                    // atomic saves move a temp file onto the real file, or the real file aside as a backup
+                                    if let Some(saved_file) = resolve_atomic_save(from, to) {
+                                        if is_indexable_path(&saved_file, &index_filter) && pending_reindex.insert(saved_file) { needs_debounce_reset = true; }
                                     } else {
-                                        if pending_reindex.insert(path_clone) { needs_debounce_reset = true; }
+                                        spawn_rename(from.clone(), to.clone(), db_path.clone(), index_filter.clone(), app_handle.clone());
                                     }
                                 },
-                                EventKind::Modify(_) => {
-                                    if is_indexed {
-                                        if pending_reindex.insert(path_clone) { needs_debounce_reset = true; }
-                                    } else {
-                                        if pending_new.insert(path_clone) { needs_debounce_reset = true; }
+                                (RenameMode::From, [from]) => {
+                                    // a move out of the watched folders never gets a To event, so the path is removed unless one arrives in time
+                                    if add_pending_removal(&mut pending_removals, from.clone()) { needs_debounce_reset = true; }
+                                    last_rename_from = Some((from.clone(), event.attrs.tracker()));
+                                },
+                                (RenameMode::To, [to]) => {
+                                    match last_rename_from.take() {
+                                        // inotify follows a matched To with a Both event that carries both paths
+                                        Some((from, Some(tracker))) if event.attrs.tracker() == Some(tracker) => {
+                                            last_rename_from = Some((from, Some(tracker)));
+                                        },
+                                        Some((from, None)) if event.attrs.tracker().is_none() => {
+                                            pending_removals.remove(&from);
+                                            spawn_rename(from, to.clone(), db_path.clone(), index_filter.clone(), app_handle.clone());
+                                        },
+                                        // moved in from outside the watched folders
+                                        _ => {
+                                            if is_indexable_path(to, &index_filter) && pending_new.insert(to.clone()) { needs_debounce_reset = true; }
+                                        },
                                     }
                                 },
-                                EventKind::Remove(_) => {
-                                    if is_indexed {
-                                        pending_reindex.remove(&path_clone);
-                                        pending_new.remove(&path_clone);
-
-                                        // Trigger immediate removal from database
-                                        let db_path_clone = db_path.clone();
-                                        let path_string = path_clone.to_string_lossy().to_string();
-
-                                        let app_handle_clone = app_handle.clone();
-
-                                        tokio::spawn(async move {
-                                            if let Err(e) = remove_file_from_index(
-                                                path_string.clone(), db_path_clone,
-                                            ).await {
-                                                error!("Failed removal process for {}: {:?}", path_string, e);
-                                            } else {
-                                                // Emit event after successful file removal
-                                                if let Err(e) = app_handle_clone.clone().emit("files-updated", ()) {
-                                                    error!("Failed to emit files-updated event after removal: {}", e);
-                                                }
-                                            }
-                                        });
+                                (_, paths) => {
+                                    // FSEvents and kqueue don't say which side of the rename a path is on,
+                                    // new paths are indexed and their move is detected by inode when the old row is found
+                                    for path in paths {
+                                        if let Some(target) = resolve_temp_file_target(path) {
+                                            if target.is_file() && is_indexable_path(&target, &index_filter) && pending_reindex.insert(target) { needs_debounce_reset = true; }
+                                        } else if path.exists() {
+                                            if is_indexable_path(path, &index_filter) && pending_new.insert(path.clone()) { needs_debounce_reset = true; }
+                                        } else if add_pending_removal(&mut pending_removals, path.clone()) {
+                                            needs_debounce_reset = true;
+                                        }
                                     }
                                 },
-                                _ => {}
-                            } // end match event.kind
-                        } // end for path
+                            }
+                        } else {
+
+                            for path in &event.paths {
+                                // events for an editor's temp and backup files count as a change to the file being saved
+                                if let Some(target) = resolve_temp_file_target(path) {
+                                    if target.is_file() && is_indexable_path(&target, &index_filter) && !pending_new.contains(&target) && pending_reindex.insert(target) {
+                                        needs_debounce_reset = true;
+                                    }
+                                    continue;
+                                }
+
+                                if !is_relevant_file_event(&event, path, &index_filter) { continue; }
+
+                                let path_clone = path.clone();
+
+                                // Check database to see if file is indexed
+                                let db_path_clone = db_path.clone();
+                                let path_str = path_clone.to_string_lossy().to_string();
+
+                                // Use tokio::task for database operations
+                                let is_indexed = tokio::task::spawn_blocking(move || -> bool {
+                                    if let Ok(conn) = open_connection(db_path_clone) {
+                                        let result: Result<i32, _> = conn.query_row(
+                                            "SELECT 1 FROM files WHERE path = ?1 LIMIT 1",
+                                            [&path_str],
+                                            |row| row.get(0)
+                                        );
+                                        result.is_ok()
+                                    } else {
+                                        false
+                                    }
+                                }).await.unwrap_or(false);
+
+                                match event.kind {
+                                    EventKind::Create(_) => {
+                                        if !is_indexed {
+                                            if pending_new.insert(path_clone) { needs_debounce_reset = true; }
+                                        } else {
+                                            if pending_reindex.insert(path_clone) { needs_debounce_reset = true; }
+                                        }
+                                    },
+                                    EventKind::Modify(_) => {
+                                        if is_indexed {
+                                            if pending_reindex.insert(path_clone) { needs_debounce_reset = true; }
+                                        } else {
+                                            if pending_new.insert(path_clone) { needs_debounce_reset = true; }
+                                        }
+                                    },
+                                    EventKind::Remove(_) => {
+                                        // recursive deletes often only report some of the files, so the whole missing folder is removed at once
+                                        let removal_root = highest_missing_ancestor(&path_clone);
+                                        pending_reindex.retain(|path| !path.starts_with(&removal_root));
+                                        pending_new.retain(|path| !path.starts_with(&removal_root));
+
+                                        if add_pending_removal(&mut pending_removals, removal_root) { needs_debounce_reset = true; }
+                                    },
+                                    _ => {}
+                                } // end match event.kind
+                            } // end for path
+                        }
 
                         if needs_debounce_reset {
-                            debounce_timer = Some(tokio::time::sleep(Duration::from_millis(DEBOUNCE_TIMEOUT_MS)));
+                            debounce_timer = Some(tokio::time::sleep(WatcherConfig::from_app_handle(&app_handle).debounce));
                         }
+                        set_pending_events(&watcher_state, pending_reindex.len() + pending_new.len() + pending_removals.len());
+                    },
+                    Some(Err(e)) => {
+                        error!("Error receiving FS event: {:?}", e);
+                        record_watcher_error(&app_handle, format!("Error receiving FS event: {}", e));
                     },
-                    Some(Err(e)) => error!("Error receiving FS event: {:?}", e),
-                    None => { println!("FS Event channel closed."); break; } // Filesystem watcher stopped
+                    None => { info!("FS Event channel closed."); break; } // Filesystem watcher stopped
                 }
             } // End fs_event_rx arm
 
             // Receive application event ("indexing_complete")
             maybe_app_event = app_event_rx.recv() => {
                 if let Some(newly_indexed_paths) = maybe_app_event {
-                    println!("Received indexing_complete event with {} paths.", newly_indexed_paths.len());
+                    debug!("Received indexing_complete event with {} paths.", newly_indexed_paths.len());
 
                     // Extract new parent directories to watch
                     let mut new_roots_to_check = HashSet::new();
@@ -384,7 +603,14 @@ async fn process_combined_events(
                     }
 
                     // Update watched directories
-                    if let Ok(mut watcher_guard) = watcher_mutex.lock() {
+                    let is_watching = app_handle
+                        .try_state::<FileWatcherHandle>()
+                        .map(|handle| handle.is_watching())
+                        .unwrap_or(false);
+
+                    if !is_watching {
+                        info!("Live indexing is stopped, not watching the newly indexed directories.");
+                    } else if let Ok(mut watcher_guard) = watcher_mutex.lock() {
                         let watcher = &mut *watcher_guard;
 
                         // Get current watched roots
@@ -405,13 +631,14 @@ async fn process_combined_events(
                                 .any(|r| root_dir.starts_with(r));
 
                             if !already_covered {
-                                match watcher.watch(&root_dir, RecursiveMode::Recursive) {
+                                match watch_directory(&app_handle, watcher, &root_dir) {
                                     Ok(_) => {
-                                        println!("Started watching new directory root: {:?}", root_dir);
+                                        info!("Started watching new directory root: {:?}", root_dir);
                                         current_watched_roots.insert(root_dir);
                                     },
                                     Err(e) => {
                                         error!("Failed to watch new directory {:?}: {}", root_dir, e);
+                                        record_watcher_error(&app_handle, format!("Failed to watch directory {:?}: {}", root_dir, e));
                                     }
                                 }
                             }
@@ -424,64 +651,868 @@ async fn process_combined_events(
                                 state.watched_roots = current_watched_roots;
                             }
                         }
+                        emit_watcher_status(&app_handle);
                     } else {
                         error!("Watcher mutex poisoned during indexing_complete handling.");
                     }
                 } else {
-                    println!("App event channel closed."); // Should not happen if listener is alive
+                    info!("App event channel closed."); // Should not happen if listener is alive
                 }
             } // End app_event_rx arm
         } // end select!
     } // end loop
 } // end process_combined_events
 
-async fn remove_file_from_index(
-    file_path: String,
+/// Counts the event against the watched root that contains its paths
+fn record_watcher_event(watcher_state: &Mutex<Option<WatcherState>>, event: &NotifyEvent) {
+    let Ok(mut guard) = watcher_state.lock() else {
+        return;
+    };
+    let Some(state) = guard.as_mut() else {
+        return;
+    };
+
+    for path in &event.paths {
+        // the deepest root wins since parents and their subdirectories are both watched
+        let root = state
+            .watched_roots
+            .iter()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .cloned();
+
+        if let Some(root) = root {
+            *state.event_counts.entry(root).or_insert(0) += 1;
+        }
+    }
+}
+
+fn set_pending_events(watcher_state: &Mutex<Option<WatcherState>>, pending_events: usize) {
+    if let Ok(mut guard) = watcher_state.lock() {
+        if let Some(state) = guard.as_mut() {
+            state.pending_events = pending_events;
+        }
+    }
+}
+
+/// Stores the error so it shows up in the watcher status and notifies the frontend
+fn record_watcher_error(app_handle: &AppHandle, message: String) {
+    if let Some(watcher_state) = app_handle.try_state::<Arc<Mutex<Option<WatcherState>>>>() {
+        if let Ok(mut guard) = watcher_state.lock() {
+            if let Some(state) = guard.as_mut() {
+                state.last_error = Some(message);
+            }
+        }
+    }
+
+    emit_watcher_status(app_handle);
+}
+
+fn build_watcher_status(app_handle: &AppHandle) -> WatcherStatus {
+    let state = app_handle
+        .try_state::<Arc<Mutex<Option<WatcherState>>>>()
+        .and_then(|watcher_state| watcher_state.lock().ok().and_then(|guard| guard.clone()))
+        .unwrap_or_default();
+
+    let polled_roots: HashSet<PathBuf> = app_handle
+        .try_state::<Arc<std::sync::Mutex<PollingWatcher>>>()
+        .and_then(|polling_watcher| {
+            polling_watcher
+                .lock()
+                .ok()
+                .map(|polling_watcher| polling_watcher.roots.clone())
+        })
+        .unwrap_or_default();
+
+    let mut watched_roots: Vec<WatchedRootStatus> = state
+        .watched_roots
+        .iter()
+        .map(|root| WatchedRootStatus {
+            path: root.to_string_lossy().to_string(),
+            event_count: state.event_counts.get(root).copied().unwrap_or(0),
+            polled: polled_roots.iter().any(|polled| root.starts_with(polled)),
+        })
+        .collect();
+    watched_roots.sort_by(|a, b| a.path.cmp(&b.path));
+
+    WatcherStatus {
+        is_running: app_handle
+            .try_state::<FileWatcherHandle>()
+            .map(|handle| handle.is_watching())
+            .unwrap_or(false),
+        watched_roots,
+        pending_events: state.pending_events,
+        last_error: state.last_error,
+    }
+}
+
+fn emit_watcher_status(app_handle: &AppHandle) {
+    if let Err(e) = app_handle.emit("watcher-status", build_watcher_status(app_handle)) {
+        error!("Failed to emit watcher-status event: {}", e);
+    }
+}
+
+/// Returns the watched directories with their event counts, the debounce queue size and the last watcher error
+#[tauri::command]
+pub fn get_watcher_status(app_handle: AppHandle) -> WatcherStatus {
+    build_watcher_status(&app_handle)
+}
+
+/// Registers with the Swift bridge so the watches are re-armed every time the Mac wakes from sleep
+#[cfg(target_os = "macos")]
+fn register_wake_handler(app_handle: AppHandle) {
+    if WAKE_APP_HANDLE.set(app_handle).is_err() {
+        // already registered
+        return;
+    }
+
+    unsafe { register_wake_callback_swift(on_system_wake) };
+}
+
+#[cfg(target_os = "macos")]
+extern "C" fn on_system_wake() {
+    let Some(app_handle) = WAKE_APP_HANDLE.get() else {
+        return;
+    };
+    let app_handle = app_handle.clone();
+
+    tauri::async_runtime::spawn(async move {
+        rearm_watches(app_handle).await;
+    });
+}
+
+/// Re-creates the native watches after sleep, FSEvents streams and network mounts can be invalidated while asleep
+/// Then scans the watched folders for changes that were made in the meantime
+#[cfg(target_os = "macos")]
+async fn rearm_watches(app_handle: AppHandle) {
+    let is_watching = app_handle
+        .try_state::<FileWatcherHandle>()
+        .map(|handle| handle.is_watching())
+        .unwrap_or(false);
+    if !is_watching {
+        return;
+    }
+
+    info!("System woke from sleep, re-arming file watches");
+
+    let watched_roots: Vec<PathBuf> = app_handle
+        .try_state::<Arc<Mutex<Option<WatcherState>>>>()
+        .and_then(|watcher_state| {
+            watcher_state
+                .lock()
+                .ok()
+                .and_then(|guard| guard.as_ref().map(|state| state.watched_roots.clone()))
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+
+    let mut existing_roots: Vec<PathBuf> = watched_roots
+        .iter()
+        .filter(|root| root.exists())
+        .cloned()
+        .collect();
+    // parents first, so polled directories cover their subdirectories
+    existing_roots.sort_by_key(|root| root.components().count());
+
+    if let Some(watcher_mutex) = app_handle.try_state::<Arc<std::sync::Mutex<RecommendedWatcher>>>()
+    {
+        if let Ok(mut watcher) = watcher_mutex.lock() {
+            for root in &existing_roots {
+                // unwatching fails for polled directories, which keep working across sleep
+                if watcher.unwatch(root).is_err() {
+                    continue;
+                }
+
+                if let Err(e) = watch_directory(&app_handle, &mut watcher, root) {
+                    error!("Failed to re-arm watch for {:?}: {}", root, e);
+                    record_watcher_error(
+                        &app_handle,
+                        format!("Failed to watch directory {:?}: {}", root, e),
+                    );
+                }
+            }
+        }
+    }
+
+    reconcile_watched_roots(&app_handle, watched_roots).await;
+}
+
+/// Removes the watched directories that are gone from disk and indexes the changes in the rest
+/// Used when changes may have been missed, after sleep or while watching was stopped
+async fn reconcile_watched_roots(app_handle: &AppHandle, watched_roots: Vec<PathBuf>) {
+    let maybe_processor: Option<FileProcessor> = app_handle
+        .state::<FileProcessorState>()
+        .0
+        .lock()
+        .ok()
+        .and_then(|guard| guard.as_ref().cloned());
+
+    let Some(processor) = maybe_processor else {
+        error!("FileProcessor not available (reconciliation).");
+        return;
+    };
+
+    let (existing_roots, vanished_roots): (Vec<PathBuf>, Vec<PathBuf>) =
+        watched_roots.into_iter().partition(|root| root.exists());
+
+    if !vanished_roots.is_empty() {
+        remove_missing_paths_from_index(processor.db_path.clone(), vanished_roots, app_handle)
+            .await;
+    }
+
+    // the roots include every subdirectory, scanning the topmost ones covers the rest
+    let top_roots: Vec<String> = existing_roots
+        .iter()
+        .filter(|root| {
+            !existing_roots
+                .iter()
+                .any(|other| other != *root && root.starts_with(other))
+        })
+        .map(|root| root.to_string_lossy().to_string())
+        .collect();
+
+    match processor
+        .reconcile_paths(top_roots, app_handle.clone())
+        .await
+    {
+        Ok(_) => {
+            if let Err(e) = app_handle.emit("files-updated", ()) {
+                error!(
+                    "Failed to emit files-updated event after reconciliation: {}",
+                    e
+                );
+            }
+        }
+        Err(e) => error!("Failed to reconcile watched directories: {:?}", e),
+    }
+
+    emit_watcher_status(app_handle);
+}
+
+/// Watches every directory in the WatcherState and marks live indexing as running
+/// Returns the number of directories that are being watched
+fn watch_all_roots(app_handle: &AppHandle) -> usize {
+    let Some(watcher_mutex) = app_handle.try_state::<Arc<std::sync::Mutex<RecommendedWatcher>>>()
+    else {
+        error!("Watcher not initialized, cannot start watching directories.");
+        return 0;
+    };
+
+    // Set up watches for all directories in the WatcherState
+    let watcher_state = app_handle.state::<Arc<Mutex<Option<WatcherState>>>>();
+    let watch_roots = {
+        let guard = watcher_state.lock().unwrap();
+        match &*guard {
+            Some(state) => state.watched_roots.clone(),
+            None => {
+                error!("WatcherState not initialized correctly.");
+                HashSet::new()
+            }
+        }
+    };
+
+    // parents first, so polled directories cover their subdirectories
+    let mut sorted_roots: Vec<&PathBuf> = watch_roots.iter().collect();
+    sorted_roots.sort_by_key(|root| root.components().count());
+
+    //iterate through the directories and start watching them
+    let mut success_count = 0;
+    {
+        let Ok(mut watcher_guard) = watcher_mutex.lock() else {
+            error!("Watcher mutex poisoned while starting watches.");
+            return 0;
+        };
+        for root in sorted_roots {
+            match watch_directory(app_handle, &mut watcher_guard, root) {
+                Ok(_) => {
+                    info!("Started watching directory: {:?}", root);
+                    success_count += 1;
+                }
+                Err(e) => {
+                    error!("Failed to watch directory {:?}: {}", root, e);
+                    record_watcher_error(
+                        app_handle,
+                        format!("Failed to watch directory {:?}: {}", root, e),
+                    );
+                    // We don't remove from watched_roots here as the directory might
+                    // become available later
+                }
+            }
+        }
+    }
+
+    info!(
+        "Successfully started watching {}/{} directories",
+        success_count,
+        watch_roots.len()
+    );
+
+    if let Some(handle) = app_handle.try_state::<FileWatcherHandle>() {
+        handle.set_watching(true);
+    }
+
+    success_count
+}
+
+/// Removes every native and polled watch but keeps the watched roots, so watching can be started again
+pub fn unwatch_all_roots(app_handle: &AppHandle) {
+    let watched_roots: Vec<PathBuf> = app_handle
+        .try_state::<Arc<Mutex<Option<WatcherState>>>>()
+        .and_then(|watcher_state| {
+            watcher_state
+                .lock()
+                .ok()
+                .and_then(|guard| guard.as_ref().map(|state| state.watched_roots.clone()))
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+
+    if let Some(polling_watcher) = app_handle.try_state::<Arc<std::sync::Mutex<PollingWatcher>>>() {
+        if let Ok(mut polling_watcher) = polling_watcher.lock() {
+            let polled_roots: Vec<PathBuf> = polling_watcher.roots.drain().collect();
+            for root in polled_roots {
+                let _ = polling_watcher.watcher.unwatch(&root);
+            }
+        }
+    }
+
+    if let Some(watcher_mutex) = app_handle.try_state::<Arc<std::sync::Mutex<RecommendedWatcher>>>()
+    {
+        if let Ok(mut watcher) = watcher_mutex.lock() {
+            for root in &watched_roots {
+                // roots that were polled or failed to watch have no native watch
+                let _ = watcher.unwatch(root);
+            }
+        }
+    }
+
+    if let Some(handle) = app_handle.try_state::<FileWatcherHandle>() {
+        handle.set_watching(false);
+    }
+
+    info!("Stopped watching {} directories", watched_roots.len());
+}
+
+/// Starts live index updates for every indexed directory and indexes the changes made while they were stopped
+#[tauri::command]
+pub async fn start_file_watching(
+    state: tauri::State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<WatcherStatus, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    set_live_indexing_setting(&app_handle, true)?;
+
+    let is_watching = app_handle
+        .try_state::<FileWatcherHandle>()
+        .map(|handle| handle.is_watching())
+        .unwrap_or(false);
+    if is_watching {
+        return Ok(build_watcher_status(&app_handle));
+    }
+
+    watch_indexed_roots(&app_handle, processor.db_path.clone()).await?;
+
+    let status = build_watcher_status(&app_handle);
+    emit_watcher_status(&app_handle);
+    Ok(status)
+}
+
+/// Reloads the watched directories from the database, watches them and indexes the changes that were missed
+/// Directories may have been added or the index replaced while watching was stopped
+pub async fn watch_indexed_roots(app_handle: &AppHandle, db_path: PathBuf) -> Result<(), String> {
+    let watched_roots = task::spawn_blocking(move || extract_watch_directories_from_db(&db_path))
+        .await
+        .map_err(|e| format!("spawn_blocking error: {e}"))?
+        .map_err(|e| e.to_string())?;
+
+    if let Some(watcher_state) = app_handle.try_state::<Arc<Mutex<Option<WatcherState>>>>() {
+        if let Ok(mut guard) = watcher_state.lock() {
+            if let Some(state) = guard.as_mut() {
+                state.watched_roots = watched_roots.clone();
+            }
+        }
+    }
+
+    watch_all_roots(app_handle);
+
+    let app_handle_clone = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        reconcile_watched_roots(&app_handle_clone, watched_roots.into_iter().collect()).await;
+    });
+
+    Ok(())
+}
+
+/// Stops live index updates, the index keeps its current contents
+#[tauri::command]
+pub fn stop_file_watching(app_handle: AppHandle) -> Result<WatcherStatus, String> {
+    set_live_indexing_setting(&app_handle, false)?;
+    unwatch_all_roots(&app_handle);
+
+    let status = build_watcher_status(&app_handle);
+    emit_watcher_status(&app_handle);
+    Ok(status)
+}
+
+/// Persists the choice so live indexing stays in the same state after a restart
+fn set_live_indexing_setting(app_handle: &AppHandle, enabled: bool) -> Result<(), String> {
+    let Some(settings_state) = app_handle.try_state::<SettingsManagerState>() else {
+        return Ok(());
+    };
+
+    let mut settings = settings_state.0.get_settings().map_err(|e| e.to_string())?;
+    settings.live_indexing = Some(enabled);
+    settings_state.0.update(settings).map_err(|e| e.to_string())
+}
+
+/// Starts watching a directory recursively
+/// Directories on network and external volumes are polled because native events don't fire reliably there
+fn watch_directory(
+    app_handle: &AppHandle,
+    native_watcher: &mut RecommendedWatcher,
+    dir: &Path,
+) -> notify::Result<()> {
+    if !needs_polling(dir) {
+        return native_watcher.watch(dir, RecursiveMode::Recursive);
+    }
+
+    let Some(polling_watcher) = app_handle.try_state::<Arc<std::sync::Mutex<PollingWatcher>>>()
+    else {
+        return native_watcher.watch(dir, RecursiveMode::Recursive);
+    };
+    let mut polling_watcher = polling_watcher
+        .lock()
+        .map_err(|e| NotifyError::generic(&format!("Poll watcher mutex poisoned: {}", e)))?;
+
+    if polling_watcher
+        .roots
+        .iter()
+        .any(|root| dir.starts_with(root))
+    {
+        return Ok(());
+    }
+
+    polling_watcher
+        .watcher
+        .watch(dir, RecursiveMode::Recursive)?;
+    polling_watcher.roots.insert(dir.to_path_buf());
+    info!(
+        "Polling {:?} since it is on a network or external volume",
+        dir
+    );
+    Ok(())
+}
+
+/// Returns true if the directory is on a network share or on a removable file system that the native watcher can't follow
+#[cfg(target_os = "macos")]
+fn needs_polling(dir: &Path) -> bool {
+    use std::ffi::{CStr, CString};
+    use std::os::unix::ffi::OsStrExt;
+
+    // FSEvents doesn't report changes made by other machines or on FAT and exFAT disks
+    const POLLED_FS_TYPES: [&str; 7] =
+        ["smbfs", "nfs", "afpfs", "webdav", "msdos", "exfat", "ntfs"];
+
+    let Ok(c_path) = CString::new(dir.as_os_str().as_bytes()) else {
+        return false;
+    };
+
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return false;
+    }
+
+    let fs_type = unsafe { CStr::from_ptr(stat.f_fstypename.as_ptr()) }.to_string_lossy();
+    let is_local = stat.f_flags & libc::MNT_LOCAL as u32 != 0;
+
+    !is_local || POLLED_FS_TYPES.contains(&fs_type.as_ref())
+}
+
+#[cfg(target_os = "linux")]
+fn needs_polling(dir: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    // inotify only sees changes made through the local kernel, which misses edits from other machines
+    const POLLED_FS_MAGIC: [u32; 7] = [
+        0x6969,     // NFS
+        0x517b,     // SMB
+        0xff534d42, // CIFS
+        0xfe534d42, // SMB2
+        0x65735546, // FUSE, e.g. sshfs
+        0x4d44,     // FAT
+        0x2011bab0, // exFAT
+    ];
+
+    let Ok(c_path) = CString::new(dir.as_os_str().as_bytes()) else {
+        return false;
+    };
+
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return false;
+    }
+
+    POLLED_FS_MAGIC.contains(&(stat.f_type as u32))
+}
+
+#[cfg(windows)]
+fn needs_polling(dir: &Path) -> bool {
+    // UNC paths like \\server\share are network shares
+    dir.to_string_lossy().starts_with(r"\\")
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+fn needs_polling(_dir: &Path) -> bool {
+    false
+}
+
+/// Stops watching every watched directory that is inside one of the given roots
+pub fn unwatch_paths(app_handle: &AppHandle, roots: &[PathBuf]) {
+    let Some(watcher_state) = app_handle.try_state::<Arc<Mutex<Option<WatcherState>>>>() else {
+        return;
+    };
+    let watcher_mutex = app_handle.try_state::<Arc<std::sync::Mutex<RecommendedWatcher>>>();
+
+    let mut state_guard = match watcher_state.lock() {
+        Ok(guard) => guard,
+        Err(e) => {
+            error!("Watcher state mutex poisoned while unwatching paths: {}", e);
+            return;
+        }
+    };
+    let Some(state) = state_guard.as_mut() else {
+        return;
+    };
+
+    let dirs_to_unwatch: Vec<PathBuf> = state
+        .watched_roots
+        .iter()
+        .filter(|dir| roots.iter().any(|root| dir.starts_with(root)))
+        .cloned()
+        .collect();
+
+    let polling_watcher = app_handle.try_state::<Arc<std::sync::Mutex<PollingWatcher>>>();
+
+    for dir in dirs_to_unwatch {
+        let was_polled = polling_watcher
+            .as_ref()
+            .and_then(|polling_watcher| polling_watcher.lock().ok())
+            .map(|mut polling_watcher| {
+                let was_polled = polling_watcher.roots.remove(&dir);
+                if was_polled {
+                    if let Err(e) = polling_watcher.watcher.unwatch(&dir) {
+                        warn!("Could not stop polling directory {:?}: {}", dir, e);
+                    }
+                }
+                was_polled
+            })
+            .unwrap_or(false);
+
+        // polled directories don't have a native watch
+        if !was_polled {
+            if let Some(watcher_mutex) = &watcher_mutex {
+                if let Ok(mut watcher) = watcher_mutex.lock() {
+                    // the directory may already be gone from disk, which is fine
+                    if let Err(e) = watcher.unwatch(&dir) {
+                        warn!("Could not unwatch directory {:?}: {}", dir, e);
+                    }
+                }
+            }
+        }
+
+        state.watched_roots.remove(&dir);
+        info!("Stopped watching directory: {:?}", dir);
+    }
+}
+
+/// Stops watching a directory and everything below it and drops it from the directories table
+/// With `unindex_files` the files under it are removed from the db and vectordb as well, otherwise they stay searchable
+#[tauri::command]
+pub async fn stop_watching_directory(
+    path: String,
+    unindex_files: bool,
+    state: tauri::State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<serde_json::Value, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+    let root = PathBuf::from(path.trim_end_matches('/'));
+
+    // notify watches are recursive, so a watched parent would keep reporting changes in this directory
+    let watched_parent = app_handle
+        .try_state::<Arc<Mutex<Option<WatcherState>>>>()
+        .and_then(|watcher_state| {
+            let guard = watcher_state.lock().ok()?;
+            guard.as_ref().and_then(|state| {
+                state
+                    .watched_roots
+                    .iter()
+                    .find(|dir| !dir.starts_with(&root) && root.starts_with(dir))
+                    .cloned()
+            })
+        });
+
+    if let Some(parent) = watched_parent {
+        return Err(format!(
+            "{} is inside the watched directory {}, stop watching that directory instead",
+            root.display(),
+            parent.display()
+        ));
+    }
+
+    unwatch_paths(&app_handle, &[root.clone()]);
+
+    let root_str = root.to_string_lossy().to_string();
+    let mut removed_files = 0;
+
+    if unindex_files {
+        let removed = delete_paths_from_db(processor.db_path.clone(), vec![root_str], true)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        for (file_id, file_path) in &removed {
+            if let Err(e) =
+                VectorDbManager::delete_embedding(&app_handle, &file_id.to_string()).await
+            {
+                error!("Failed to delete embeddings for {}: {}", file_path, e);
+            }
+        }
+
+        removed_files = removed.len();
+    } else {
+        let db_path = processor.db_path.clone();
+        task::spawn_blocking(move || -> Result<(), FileProcessorError> {
+            let conn = open_connection(db_path)?;
+            conn.execute(
+                "DELETE FROM directories WHERE path = ?1 OR path LIKE ?2 ESCAPE '\\'",
+                params![root_str, descendants_like_pattern(&root_str)],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| format!("spawn_blocking error: {e}"))?
+        .map_err(|e| e.to_string())?;
+    }
+
+    info!("Stopped watching directory {}", root.display());
+
+    if let Err(e) = app_handle.emit("files-updated", ()) {
+        error!("Failed to emit files-updated event: {}", e);
+    }
+
+    Ok(serde_json::json!({
+        "success": true,
+        "removedFiles": removed_files
+    }))
+}
+
+fn spawn_rename(
+    from: PathBuf,
+    to: PathBuf,
+    db_path: PathBuf,
+    index_filter: IndexFilter,
+    app_handle: AppHandle,
+) {
+    tokio::spawn(async move {
+        match apply_rename(&from, &to, db_path, &index_filter, &app_handle).await {
+            Ok(_) => {
+                if let Err(e) = app_handle.emit("files-updated", ()) {
+                    error!("Failed to emit files-updated event after rename: {}", e);
+                }
+            }
+            Err(e) => error!("Failed to handle rename of {:?} to {:?}: {:?}", from, to, e),
+        }
+    });
+}
+
+/// Moves the index entries of a renamed file or folder to its new path without embedding it again
+/// If the new path is out of scope (excluded, hidden or an unsupported extension) the old entries are removed instead
+async fn apply_rename(
+    from: &Path,
+    to: &Path,
     db_path: PathBuf,
+    index_filter: &IndexFilter,
+    app_handle: &AppHandle,
 ) -> Result<(), FileProcessorError> {
-    let file_path_clone_log = file_path.clone();
-
-    let db_result = task::spawn_blocking(move || -> Result<bool, FileProcessorError> {
-        let mut conn = Connection::open(db_path)?;
-        let tx = conn.transaction()?;
-
-        let file_id: Option<i64> = tx
-            .query_row(
-                "SELECT id FROM files WHERE path = ?1",
-                [&file_path],
-                |row| row.get(0),
-            )
-            .ok();
-
-        let mut deleted_from_sqlite = false;
-        if let Some(id) = file_id {
-            tx.execute("DELETE FROM files_fts WHERE rowid = ?1", [id])?;
-            let files_deleted_count = tx.execute("DELETE FROM files WHERE id = ?1", [id])?;
-            deleted_from_sqlite = files_deleted_count > 0;
-        }
-
-        tx.commit()?;
-        Ok(deleted_from_sqlite)
-    })
-    .await
-    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking JoinError: {e}")))?;
-
-    let was_deleted_from_sqlite = db_result?;
-
-    if was_deleted_from_sqlite {
-        // Handle vector DB deletion if needed
-        // VectorDbManager::delete_embedding(file_path);
-        println!(
-            "Successfully removed file {} from index",
-            file_path_clone_log
+    if !is_indexable_path(to, index_filter) {
+        info!("{:?} was renamed out of the index to {:?}", from, to);
+        remove_missing_paths_from_index(db_path, vec![from.to_path_buf()], app_handle).await;
+        return Ok(());
+    }
+
+    // the rename replaced whatever was at the new path, e.g. an editor saving through a temp file
+    let to_str = to.to_string_lossy().to_string();
+    let replaced_files = delete_paths_from_db(db_path.clone(), vec![to_str.clone()], false).await?;
+    for (file_id, file_path) in &replaced_files {
+        if let Err(e) = VectorDbManager::delete_embedding(app_handle, &file_id.to_string()).await {
+            error!("Failed to delete embeddings for {}: {}", file_path, e);
+        }
+    }
+
+    let renamed_count = rename_indexed_paths(db_path, from.to_path_buf(), to.to_path_buf()).await?;
+
+    if renamed_count > 0 {
+        info!(
+            "Moved {} indexed files from {:?} to {:?}",
+            renamed_count, from, to
         );
-    } else {
-        println!("File {} was not found in the database", file_path_clone_log);
+        return Ok(());
     }
 
+    // the old path wasn't indexed, so the new one has to be indexed from scratch
+    let maybe_processor: Option<FileProcessor> = app_handle
+        .state::<FileProcessorState>()
+        .0
+        .lock()
+        .ok()
+        .and_then(|guard| guard.as_ref().cloned());
+
+    let Some(processor) = maybe_processor else {
+        return Err(FileProcessorError::Other(
+            "FileProcessor not available (rename processing)".to_string(),
+        ));
+    };
+
+    processor
+        .process_paths(
+            vec![to_str],
+            IndexPriority::Watcher,
+            |_status: ProcessingStatus| {},
+            app_handle.clone(),
+        )
+        .await?;
+
     Ok(())
 }
 
+/// Markers editors put after the real file name when saving through a temp file
+/// e.g. "notes.md.tmp.1234", "notes.md.sb-1a2b3c-XYZ" (TextEdit) and "notes.md___jb_tmp___" (JetBrains)
+const TEMP_FILE_MARKERS: [&str; 3] = [".tmp", ".sb-", "___jb_"];
+
+/// Maps the temp, backup and swap files editors write while saving to the file being saved
+/// Returns None for regular files
+fn resolve_temp_file_target(path: &Path) -> Option<PathBuf> {
+    let file_name = path.file_name()?.to_str()?;
+
+    // emacs writes "#notes.md#" auto-saves and ".#notes.md" lock files
+    let mut target = file_name
+        .strip_prefix('#')
+        .and_then(|name| name.strip_suffix('#'))
+        .or_else(|| file_name.strip_prefix(".#"))
+        .unwrap_or(file_name);
+
+    for marker in TEMP_FILE_MARKERS {
+        if let Some(index) = target.find(marker) {
+            target = &target[..index];
+        }
+    }
+
+    target = target.strip_suffix('~').unwrap_or(target);
+
+    // vim swap files are hidden, ".notes.md.swp"
+    if let Some(swapped) = target.strip_suffix(".swp") {
+        target = swapped.strip_prefix('.').unwrap_or(swapped);
+    }
+
+    if target.is_empty() || target == file_name {
+        return None;
+    }
+
+    Some(path.with_file_name(target))
+}
+
+/// Returns the saved file if the rename is one step of an editor's atomic save rather than a real rename
+fn resolve_atomic_save(from: &Path, to: &Path) -> Option<PathBuf> {
+    // the temp file replaces the real file
+    if resolve_temp_file_target(from).as_deref() == Some(to) {
+        return Some(to.to_path_buf());
+    }
+
+    // the real file is moved aside as a backup before the new contents are written
+    if resolve_temp_file_target(to).as_deref() == Some(from) {
+        return Some(from.to_path_buf());
+    }
+
+    None
+}
+
+/// Queues a missing path for removal, returns false if it's already covered by a queued parent
+/// Queued paths below the new one are dropped since removing the parent removes them too
+fn add_pending_removal(pending_removals: &mut HashSet<PathBuf>, path: PathBuf) -> bool {
+    if pending_removals
+        .iter()
+        .any(|queued| path.starts_with(queued))
+    {
+        return false;
+    }
+
+    pending_removals.retain(|queued| !queued.starts_with(&path));
+    pending_removals.insert(path)
+}
+
+/// Walks up from a removed path to the topmost ancestor that is gone from disk as well
+fn highest_missing_ancestor(path: &Path) -> PathBuf {
+    let mut highest = path.to_path_buf();
+
+    for ancestor in path.ancestors().skip(1) {
+        if ancestor.as_os_str().is_empty() || ancestor.exists() {
+            break;
+        }
+        highest = ancestor.to_path_buf();
+    }
+
+    highest
+}
+
+/// Removes the paths that no longer exist on disk, and everything below them, from the db and vectordb and stops watching them
+/// All paths are deleted in a single transaction so a deleted folder disappears from the index at once
+async fn remove_missing_paths_from_index(
+    db_path: PathBuf,
+    paths: Vec<PathBuf>,
+    app_handle: &AppHandle,
+) {
+    let missing: Vec<PathBuf> = paths.into_iter().filter(|path| !path.exists()).collect();
+    if missing.is_empty() {
+        return;
+    }
+
+    let missing_str: Vec<String> = missing
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+
+    // directories are dropped too so a deleted folder isn't watched again on the next start
+    let removed_files = match delete_paths_from_db(db_path, missing_str, true).await {
+        Ok(files) => files,
+        Err(e) => {
+            error!("Failed removal process for {:?}: {:?}", missing, e);
+            return;
+        }
+    };
+
+    for (file_id, removed_path) in &removed_files {
+        if let Err(e) = VectorDbManager::delete_embedding(app_handle, &file_id.to_string()).await {
+            error!("Failed to delete embeddings for {}: {}", removed_path, e);
+        }
+    }
+
+    unwatch_paths(app_handle, &missing);
+
+    if !removed_files.is_empty() {
+        info!(
+            "Removed {} files under {:?} from index",
+            removed_files.len(),
+            missing
+        );
+        if let Err(e) = app_handle.emit("files-updated", ()) {
+            error!("Failed to emit files-updated event after removal: {}", e);
+        }
+    }
+}
+
 // async fn process_combined_events(
 //     mut fs_event_rx: Receiver<notify::Result<NotifyEvent>>, // Filesystem events
 //     mut app_event_rx: Receiver<Vec<String>>,                // App events ("indexing_complete")
@@ -661,7 +1692,7 @@ async fn remove_file_from_index(
 //                                     let already_covered = state_guard.watched_roots.iter().any(|r| root_dir.starts_with(r));
 //                                     if !already_covered {
 //                                         if state_guard.watched_roots.insert(root_dir.clone()) {
-//                                              match watcher.watch(&root_dir, RecursiveMode::Recursive) {
+//                                              match watch_directory(&app_handle, watcher, &root_dir) {
 //                                                  Ok(_) => println!("Started watching new directory root: {:?}", root_dir),
 //                                                  Err(e) => {
 //                                                      error!("Failed to watch new directory {:?}: {}", root_dir, e);
@@ -744,11 +1775,30 @@ async fn remove_file_from_index(
 //     Ok(())
 // }
 
-fn is_relevant_file_event(event: &NotifyEvent, path: &Path) -> bool {
+fn is_relevant_file_event(event: &NotifyEvent, path: &Path, index_filter: &IndexFilter) -> bool {
+    match event.kind {
+        EventKind::Create(_) | EventKind::Modify(_) => {
+            // Only care about real files with valid extensions
+            path.is_file() && is_indexable_path(path, index_filter)
+        }
+        // removed paths are gone from disk, so there is no telling whether they were a file or a folder
+        EventKind::Remove(_) => is_in_index_scope(path, index_filter),
+        _ => false,
+    }
+}
+
+/// Returns true if the file or folder at the path belongs in the index
+fn is_indexable_path(path: &Path, index_filter: &IndexFilter) -> bool {
+    is_in_index_scope(path, index_filter)
+        && (path.is_dir() || index_filter.has_indexable_extension(path))
+}
+
+/// Returns true unless the path is hidden, temporary or excluded by the user
+fn is_in_index_scope(path: &Path, index_filter: &IndexFilter) -> bool {
     // Skip temporary files and hidden files
     if let Some(file_name) = path.file_name() {
         let file_name_str = file_name.to_string_lossy();
-        if file_name_str.starts_with('.')
+        if (file_name_str.starts_with('.') && !index_filter.include_hidden_files())
             || file_name_str.ends_with('~')
             || file_name_str.starts_with('#')
             || file_name_str.contains(".tmp")
@@ -757,11 +1807,6 @@ fn is_relevant_file_event(event: &NotifyEvent, path: &Path) -> bool {
         }
     }
 
-    match event.kind {
-        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => {
-            // Only care about real files with valid extensions
-            path.is_file() && is_valid_file_extension(path)
-        }
-        _ => false,
-    }
+    // Skip paths the user excluded or that are listed in .gitignore/.kitaignore files
+    !index_filter.should_skip(path)
 }
@@ -0,0 +1,170 @@
+/*
+This file keeps the files_fts table in sync with the user's tokenizer settings. The options the table was built with are stored in the fts_config table, and the table is rebuilt from the files table when the settings no longer match
+*/
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::task;
+use tracing::{error, info};
+
+use crate::database_handler::open_connection;
+use crate::file_processor::{get_processor, FileProcessor, FileProcessorError, FileProcessorState};
+use crate::settings::SettingsManagerState;
+use crate::tags::load_file_tags;
+use crate::tokenizer::{build_doc_text, FtsOptions, FtsTokenizer};
+use crate::AppResult;
+
+/// The options files_fts is currently built with, every insert, delete and search has to use the same ones
+static ACTIVE_FTS_OPTIONS: RwLock<FtsOptions> = RwLock::new(FtsOptions {
+    tokenizer: FtsTokenizer::Trigram,
+    prefix_indexes: false,
+    split_identifiers: false,
+});
+
+pub fn active_fts_options() -> FtsOptions {
+    ACTIVE_FTS_OPTIONS
+        .read()
+        .map(|options| *options)
+        .unwrap_or_default()
+}
+
+fn set_active_fts_options(options: FtsOptions) {
+    if let Ok(mut active) = ACTIVE_FTS_OPTIONS.write() {
+        *active = options;
+    }
+}
+
+/// Loads the options files_fts was built with and rebuilds it if the settings changed since
+pub fn init_fts_index(db_path: &Path, app_handle: AppHandle) -> AppResult<()> {
+    let conn = open_connection(db_path)?;
+    set_active_fts_options(load_built_options(&conn)?);
+
+    let wanted = options_from_app_handle(&app_handle);
+    if wanted != active_fts_options() {
+        let db_path = db_path.to_path_buf();
+        tauri::async_runtime::spawn(async move {
+            match rebuild_fts_table(db_path, wanted).await {
+                Ok(count) => {
+                    info!("Rebuilt the search index for {} files", count);
+                    let _ = app_handle.emit("files-updated", ());
+                }
+                Err(e) => error!("Failed to rebuild the search index: {}", e),
+            }
+        });
+    }
+
+    info!("Search index initialized");
+    Ok(())
+}
+
+pub fn options_from_app_handle(app_handle: &AppHandle) -> FtsOptions {
+    let settings = app_handle
+        .try_state::<SettingsManagerState>()
+        .and_then(|state| state.0.get_settings().ok())
+        .unwrap_or_default();
+
+    FtsOptions::from_settings(&settings)
+}
+
+/// Databases without a stored config were built with the defaults
+fn load_built_options(conn: &Connection) -> Result<FtsOptions, FileProcessorError> {
+    let json: Option<String> = conn
+        .query_row("SELECT data FROM fts_config WHERE id = 1", [], |row| {
+            row.get(0)
+        })
+        .optional()?;
+
+    match json {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| FileProcessorError::Other(format!("Invalid search index config: {e}"))),
+        None => Ok(FtsOptions::default()),
+    }
+}
+
+/// Recreates files_fts with the given options and re-adds every indexed file
+/// Only file names, paths and tags are in the table so nothing has to be read from disk
+pub async fn rebuild_fts_table(
+    db_path: PathBuf,
+    options: FtsOptions,
+) -> Result<usize, FileProcessorError> {
+    task::spawn_blocking(move || -> Result<usize, FileProcessorError> {
+        let mut conn = open_connection(db_path)?;
+        let tx = conn.transaction()?;
+
+        let mut create_sql = format!(
+            "CREATE VIRTUAL TABLE files_fts USING fts5 (doc_text, content='', tokenize='{}'",
+            options.tokenizer.fts5_tokenize()
+        );
+        if options.prefix_indexes {
+            create_sql.push_str(", prefix='2 3'");
+        }
+        create_sql.push_str(");");
+
+        tx.execute("DROP TABLE IF EXISTS files_fts", [])?;
+        tx.execute(&create_sql, [])?;
+
+        let mut count = 0;
+        {
+            let mut select = tx.prepare("SELECT id, name, path, extension FROM files")?;
+            let mut insert = tx.prepare("INSERT INTO files_fts(rowid, doc_text) VALUES (?1, ?2)")?;
+
+            let rows = select.query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                    row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                    row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                ))
+            })?;
+
+            for row in rows {
+                let (id, name, path, extension) = row?;
+                let tags = load_file_tags(&tx, id)?;
+                insert.execute(params![
+                    id,
+                    build_doc_text(&name, &path, &extension, &tags, &options)
+                ])?;
+                count += 1;
+            }
+        }
+
+        let json = serde_json::to_string(&options)
+            .map_err(|e| FileProcessorError::Other(e.to_string()))?;
+        tx.execute(
+            "INSERT OR REPLACE INTO fts_config (id, data, updated_at) VALUES (1, ?1, CURRENT_TIMESTAMP)",
+            params![json],
+        )?;
+
+        tx.commit()?;
+        set_active_fts_options(options);
+
+        Ok(count)
+    })
+    .await
+    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
+}
+
+/// Rebuilds the search index if the tokenizer settings changed, called after the settings are saved
+/// Returns whether the index was rebuilt
+#[tauri::command]
+pub async fn apply_fts_settings(
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<bool, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let wanted = options_from_app_handle(&app_handle);
+    if wanted == active_fts_options() {
+        return Ok(false);
+    }
+
+    let count = rebuild_fts_table(processor.db_path.clone(), wanted)
+        .await
+        .map_err(|e| e.to_string())?;
+    info!("Rebuilt the search index for {} files", count);
+
+    let _ = app_handle.emit("files-updated", ());
+    Ok(true)
+}
@@ -0,0 +1,117 @@
+/*
+This file contains the disk cache for app icons. Extracting an icon goes through the platform's app handler and is the slowest part of listing the apps, so the data URLs are stored in the app cache directory keyed by the app's path and modification time. An app that is updated gets a new modification time, which invalidates its icon
+*/
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Manager};
+use tracing::error;
+
+const ICON_CACHE_FILE_NAME: &str = "app_icons.json";
+
+static ICON_CACHE: OnceLock<Mutex<IconCache>> = OnceLock::new();
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CachedIcon {
+    modified_secs: u64,
+    icon: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct IconCache {
+    file_path: Option<PathBuf>,
+    icons: HashMap<String, CachedIcon>,
+    dirty: bool,
+}
+
+impl IconCache {
+    fn load(file_path: Option<PathBuf>) -> Self {
+        let icons = file_path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        Self {
+            file_path,
+            icons,
+            dirty: false,
+        }
+    }
+
+    /// Returns the cached icon if the app hasn't changed since it was cached
+    pub fn get(&self, app_path: &str, modified_secs: u64) -> Option<Option<String>> {
+        self.icons
+            .get(app_path)
+            .filter(|cached| cached.modified_secs == modified_secs)
+            .map(|cached| cached.icon.clone())
+    }
+
+    pub fn insert(&mut self, app_path: &str, modified_secs: u64, icon: Option<String>) {
+        self.icons.insert(
+            app_path.to_string(),
+            CachedIcon {
+                modified_secs,
+                icon,
+            },
+        );
+        self.dirty = true;
+    }
+
+    /// Drops the icons of apps that are no longer installed
+    pub fn retain_paths(&mut self, app_paths: &HashSet<&str>) {
+        let before = self.icons.len();
+        self.icons
+            .retain(|path, _| app_paths.contains(path.as_str()));
+        self.dirty |= self.icons.len() != before;
+    }
+
+    /// Writes the cache to disk if it changed, the file is replaced atomically so a crash can't leave half a cache
+    pub fn save(&mut self) {
+        let Some(file_path) = &self.file_path else {
+            return;
+        };
+        if !self.dirty {
+            return;
+        }
+
+        let result = (|| -> std::io::Result<()> {
+            if let Some(parent) = file_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let tmp_path = file_path.with_extension("json.tmp");
+            fs::write(&tmp_path, serde_json::to_string(&self.icons)?)?;
+            fs::rename(&tmp_path, file_path)
+        })();
+
+        match result {
+            Ok(()) => self.dirty = false,
+            Err(e) => error!("Failed to save the icon cache: {}", e),
+        }
+    }
+}
+
+/// Returns the icon cache, it is read from disk the first time it is used
+pub fn icon_cache(app_handle: &AppHandle) -> &'static Mutex<IconCache> {
+    ICON_CACHE.get_or_init(|| {
+        let file_path = app_handle
+            .path()
+            .app_cache_dir()
+            .ok()
+            .map(|dir| dir.join(ICON_CACHE_FILE_NAME));
+        Mutex::new(IconCache::load(file_path))
+    })
+}
+
+/// Modification time of the app in seconds, part of the cache key
+pub fn modified_secs(app_path: &str) -> Option<u64> {
+    fs::metadata(Path::new(app_path))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+}
@@ -0,0 +1,317 @@
+/*
+This file contains the export and import of the index so it can be moved to another machine without indexing everything again. An archive is a directory with a copy of the SQLite database, a copy of the vector store and a manifest. File ids are kept on import so the imported embeddings still belong to the same files
+*/
+
+use chrono::Local;
+use rusqlite::{params, Connection, Transaction};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::task;
+use tracing::info;
+
+use crate::database_handler::{open_connection, run_migrations, schema_version};
+use crate::encryption::is_database_encrypted;
+use crate::file_processor::{
+    descendants_like_pattern, get_processor, FileProcessor, FileProcessorError, FileProcessorState,
+};
+use crate::file_watcher::{unwatch_all_roots, watch_indexed_roots, FileWatcherHandle};
+use crate::fts_index::{options_from_app_handle, rebuild_fts_table};
+use crate::settings::{AppSettings, SettingsManagerState};
+use crate::vectordb_manager::VectorDbManager;
+
+/// Bumped when the layout of the archive directory changes
+const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+const DATABASE_FILE_NAME: &str = "index.sqlite";
+const VECTOR_STORE_DIR_NAME: &str = "vector_db";
+
+/// Tables that are copied on import, in an order that keeps the references between them valid
+const IMPORTED_TABLES: [&str; 4] = ["directories", "files", "tags", "file_tags"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchiveManifest {
+    format_version: u32,
+    schema_version: u32,
+    exported_at: String,
+    /// Home directory of the exporting machine, indexed paths below it are moved to the importing user's home
+    home_dir: Option<String>,
+    file_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexArchiveReport {
+    pub archive_path: String,
+    pub file_count: i64,
+}
+
+fn archive_error(message: impl Into<String>) -> FileProcessorError {
+    FileProcessorError::Other(message.into())
+}
+
+fn home_dir_string() -> Option<String> {
+    dirs::home_dir().map(|home| home.to_string_lossy().to_string())
+}
+
+/// Writes a consistent copy of the database and the manifest into the archive directory
+fn export_database(db_path: &Path, archive_dir: &Path) -> Result<i64, FileProcessorError> {
+    let conn = open_connection(db_path)?;
+
+    let archive_db_path = archive_dir.join(DATABASE_FILE_NAME);
+    if is_database_encrypted() {
+        // archives are always unencrypted so they can be imported with another key
+        conn.execute(
+            "ATTACH DATABASE ?1 AS archive KEY ''",
+            [archive_db_path.to_string_lossy().to_string()],
+        )?;
+        conn.query_row("SELECT sqlcipher_export('archive')", [], |_| Ok(()))?;
+        conn.execute("DETACH DATABASE archive", [])?;
+    } else {
+        // VACUUM INTO takes a consistent snapshot even while the indexer is writing
+        conn.execute(
+            "VACUUM INTO ?1",
+            [archive_db_path.to_string_lossy().to_string()],
+        )?;
+    }
+
+    let file_count: i64 = conn.query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))?;
+
+    let manifest = ArchiveManifest {
+        format_version: ARCHIVE_FORMAT_VERSION,
+        schema_version: schema_version(&conn)?,
+        exported_at: Local::now().to_rfc3339(),
+        home_dir: home_dir_string(),
+        file_count,
+    };
+
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| archive_error(e.to_string()))?;
+    fs::write(archive_dir.join(MANIFEST_FILE_NAME), json)?;
+
+    Ok(file_count)
+}
+
+fn read_manifest(archive_dir: &Path) -> Result<ArchiveManifest, FileProcessorError> {
+    let json = fs::read_to_string(archive_dir.join(MANIFEST_FILE_NAME))
+        .map_err(|e| archive_error(format!("{:?} is not an index archive: {}", archive_dir, e)))?;
+
+    let manifest: ArchiveManifest =
+        serde_json::from_str(&json).map_err(|e| archive_error(format!("Invalid manifest: {e}")))?;
+
+    if manifest.format_version > ARCHIVE_FORMAT_VERSION {
+        return Err(archive_error(format!(
+            "The archive was exported by a newer version of the app (format {})",
+            manifest.format_version
+        )));
+    }
+
+    Ok(manifest)
+}
+
+/// Copies a table from the attached import database, by column name because
+/// databases migrated from older versions can have the same columns in a different order
+fn copy_table(tx: &Transaction, table: &str) -> Result<(), FileProcessorError> {
+    let columns: Vec<String> = {
+        let mut stmt = tx.prepare(&format!("PRAGMA main.table_info({})", table))?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(1))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    let column_list = columns.join(", ");
+
+    tx.execute(&format!("DELETE FROM main.{}", table), [])?;
+    tx.execute(
+        &format!(
+            "INSERT INTO main.{table} ({column_list}) SELECT {column_list} FROM import.{table}"
+        ),
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Points every path below `from` at the same place below `to`
+fn rewrite_path_prefix(tx: &Transaction, from: &str, to: &str) -> Result<(), FileProcessorError> {
+    let from = from.trim_end_matches('/');
+    let to = to.trim_end_matches('/');
+    let pattern = descendants_like_pattern(from);
+
+    for table in ["files", "directories"] {
+        tx.execute(
+            &format!(
+                r#"
+                UPDATE OR IGNORE {table}
+                SET path = ?1 || substr(path, ?2)
+                WHERE path = ?3 OR path LIKE ?4 ESCAPE '\'
+                "#
+            ),
+            params![to, from.len() as i64 + 1, from, pattern],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Replaces the indexed files, directories and tags with the ones in the archive
+/// Returns the settings stored in the archive so they can be applied through the settings manager
+fn import_database(
+    db_path: &Path,
+    archive_dir: &Path,
+    manifest: &ArchiveManifest,
+) -> Result<Option<AppSettings>, FileProcessorError> {
+    // migrate a copy so the archive itself stays untouched and older archives match the current schema
+    let staging_path = PathBuf::from(format!("{}.import", db_path.to_string_lossy()));
+    fs::copy(archive_dir.join(DATABASE_FILE_NAME), &staging_path)?;
+
+    let result = import_staged_database(db_path, &staging_path, manifest);
+
+    let _ = fs::remove_file(&staging_path);
+    result
+}
+
+fn import_staged_database(
+    db_path: &Path,
+    staging_path: &Path,
+    manifest: &ArchiveManifest,
+) -> Result<Option<AppSettings>, FileProcessorError> {
+    // the archive is unencrypted, so the staging copy is opened without the index key
+    {
+        let mut staging = Connection::open(staging_path)?;
+        run_migrations(&mut staging).map_err(|e| archive_error(e.to_string()))?;
+    }
+
+    let mut conn = open_connection(db_path)?;
+    let attach_sql = if is_database_encrypted() {
+        "ATTACH DATABASE ?1 AS import KEY ''"
+    } else {
+        "ATTACH DATABASE ?1 AS import"
+    };
+    conn.execute(attach_sql, [staging_path.to_string_lossy().to_string()])?;
+
+    let settings_json: Option<String> = conn
+        .query_row("SELECT data FROM import.settings WHERE id = 1", [], |row| {
+            row.get(0)
+        })
+        .ok();
+
+    let tx = conn.transaction()?;
+    for table in IMPORTED_TABLES {
+        copy_table(&tx, table)?;
+    }
+
+    if let (Some(from), Some(to)) = (&manifest.home_dir, home_dir_string()) {
+        if *from != to {
+            rewrite_path_prefix(&tx, from, &to)?;
+        }
+    }
+    tx.commit()?;
+
+    conn.execute("DETACH DATABASE import", [])?;
+
+    Ok(settings_json.and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+/// Exports the index into a new directory inside `destination`
+#[tauri::command]
+pub async fn export_index(
+    destination: String,
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<IndexArchiveReport, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let archive_dir = PathBuf::from(destination).join(format!(
+        "kita-index-{}",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::create_dir_all(&archive_dir).map_err(|e| e.to_string())?;
+
+    let db_path = processor.db_path.clone();
+    let export_dir = archive_dir.clone();
+    let file_count = task::spawn_blocking(move || export_database(&db_path, &export_dir))
+        .await
+        .map_err(|e| format!("spawn_blocking error: {e}"))?
+        .map_err(|e| e.to_string())?;
+
+    VectorDbManager::export_store(&app_handle, &archive_dir.join(VECTOR_STORE_DIR_NAME))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    info!("Exported {} files to {:?}", file_count, archive_dir);
+
+    Ok(IndexArchiveReport {
+        archive_path: archive_dir.to_string_lossy().to_string(),
+        file_count,
+    })
+}
+
+/// Replaces the index with an exported one
+/// Paths below the exporting user's home directory are moved to this user's home directory
+#[tauri::command]
+pub async fn import_index(
+    archive_path: String,
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<IndexArchiveReport, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+    let archive_dir = PathBuf::from(&archive_path);
+
+    let manifest = read_manifest(&archive_dir).map_err(|e| e.to_string())?;
+
+    // the watched directories change with the index, stop watching until it is replaced
+    let was_watching = app_handle
+        .try_state::<FileWatcherHandle>()
+        .map(|handle| handle.is_watching())
+        .unwrap_or(false);
+    if was_watching {
+        unwatch_all_roots(&app_handle);
+    }
+
+    let db_path = processor.db_path.clone();
+    let import_dir = archive_dir.clone();
+    let import_manifest = manifest.clone();
+    let settings =
+        task::spawn_blocking(move || import_database(&db_path, &import_dir, &import_manifest))
+            .await
+            .map_err(|e| format!("spawn_blocking error: {e}"))?
+            .map_err(|e| e.to_string())?;
+
+    if let (Some(settings), Some(settings_state)) =
+        (settings, app_handle.try_state::<SettingsManagerState>())
+    {
+        settings_state
+            .0
+            .update(settings)
+            .map_err(|e| format!("Failed to import settings: {}", e))?;
+    }
+
+    // files_fts is contentless so it can't be copied, build it from the imported files instead
+    rebuild_fts_table(
+        processor.db_path.clone(),
+        options_from_app_handle(&app_handle),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let vector_store_dir = archive_dir.join(VECTOR_STORE_DIR_NAME);
+    if vector_store_dir.is_dir() {
+        VectorDbManager::import_store(&app_handle, &vector_store_dir)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    if was_watching {
+        watch_indexed_roots(&app_handle, processor.db_path.clone()).await?;
+    }
+
+    info!(
+        "Imported {} files from {:?}",
+        manifest.file_count, archive_dir
+    );
+    let _ = app_handle.emit("files-updated", ());
+
+    Ok(IndexArchiveReport {
+        archive_path,
+        file_count: manifest.file_count,
+    })
+}
@@ -0,0 +1,192 @@
+/*
+This file contains the persistent log of files that failed to index. Errors are kept in the index_errors table until the file indexes successfully so that they can be reviewed and retried later
+*/
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, State};
+use tokio::task;
+use tracing::info;
+
+use crate::database_handler::open_connection;
+use crate::file_processor::{
+    get_processor, FileProcessor, FileProcessorError, FileProcessorState, ProcessingStatus,
+};
+use crate::index_queue::IndexPriority;
+use crate::messages::{ErrorCode, UserMessage};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexErrorRecord {
+    pub path: String,
+    pub chunker: Option<String>,
+    /// The error in English
+    pub error: String,
+    /// Missing for errors stored by older versions
+    pub message: Option<UserMessage>,
+    pub failed_at: String,
+}
+
+/// Stores the latest indexing error for a file, replacing any earlier error for the same path
+pub async fn record_index_error(
+    db_path: PathBuf,
+    path: String,
+    chunker: Option<String>,
+    message: UserMessage,
+) -> Result<(), FileProcessorError> {
+    task::spawn_blocking(move || -> Result<(), FileProcessorError> {
+        let conn = open_connection(db_path)?;
+        let params_json = serde_json::to_string(&message.params)
+            .map_err(|e| FileProcessorError::Other(e.to_string()))?;
+
+        conn.execute(
+            r#"
+            INSERT INTO index_errors (path, chunker, error, code, params, failed_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, CURRENT_TIMESTAMP)
+            ON CONFLICT(path) DO UPDATE SET
+                chunker = excluded.chunker,
+                error = excluded.error,
+                code = excluded.code,
+                params = excluded.params,
+                failed_at = CURRENT_TIMESTAMP;
+            "#,
+            params![
+                path,
+                chunker,
+                message.message,
+                message.code.as_str(),
+                params_json
+            ],
+        )?;
+
+        Ok(())
+    })
+    .await
+    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
+}
+
+/// Removes the stored errors for the given paths, called once a file indexes successfully
+pub async fn clear_index_errors(
+    db_path: PathBuf,
+    paths: Vec<String>,
+) -> Result<(), FileProcessorError> {
+    task::spawn_blocking(move || -> Result<(), FileProcessorError> {
+        let conn = open_connection(db_path)?;
+
+        for path in paths {
+            conn.execute("DELETE FROM index_errors WHERE path = ?1", [path])?;
+        }
+
+        Ok(())
+    })
+    .await
+    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
+}
+
+/// Rebuilds the message of a stored error, None when it has no code or the code is unknown to this version
+fn stored_message(code: Option<String>, params_json: Option<String>) -> Option<UserMessage> {
+    let code = ErrorCode::parse(&code?)?;
+    let params: BTreeMap<String, String> = params_json
+        .and_then(|params_json| serde_json::from_str(&params_json).ok())
+        .unwrap_or_default();
+
+    Some(
+        params
+            .iter()
+            .fold(UserMessage::new(code), |message, (name, value)| {
+                message.with_param(name, value)
+            }),
+    )
+}
+
+fn load_index_errors(db_path: &Path) -> Result<Vec<IndexErrorRecord>, FileProcessorError> {
+    let conn = open_connection(db_path)?;
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT path, chunker, error, failed_at, code, params
+        FROM index_errors
+        ORDER BY failed_at DESC
+        "#,
+    )?;
+
+    let rows = stmt.query_map([], |row| {
+        Ok(IndexErrorRecord {
+            path: row.get(0)?,
+            chunker: row.get(1)?,
+            error: row.get(2)?,
+            failed_at: row.get(3)?,
+            message: stored_message(row.get(4)?, row.get(5)?),
+        })
+    })?;
+
+    let errors = rows.collect::<Result<Vec<_>, _>>()?;
+    Ok(errors)
+}
+
+#[tauri::command]
+pub async fn get_index_errors(
+    state: State<'_, FileProcessorState>,
+) -> Result<Vec<IndexErrorRecord>, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    task::spawn_blocking(move || load_index_errors(&processor.db_path))
+        .await
+        .map_err(|e| format!("spawn_blocking error: {e}"))?
+        .map_err(|e| e.to_string())
+}
+
+/// Re-indexes every file that has a stored error
+/// Errors for files that no longer exist on disk are dropped instead of retried
+#[tauri::command]
+pub async fn retry_failed_files(
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<serde_json::Value, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let db_path = processor.db_path.clone();
+    let failed_files = task::spawn_blocking(move || load_index_errors(&db_path))
+        .await
+        .map_err(|e| format!("spawn_blocking error: {e}"))?
+        .map_err(|e| e.to_string())?;
+
+    let (existing, missing): (Vec<String>, Vec<String>) = failed_files
+        .into_iter()
+        .map(|record| record.path)
+        .partition(|path| Path::new(path).exists());
+
+    if !missing.is_empty() {
+        info!("Dropping errors for {} missing files", missing.len());
+        clear_index_errors(processor.db_path.clone(), missing)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    if existing.is_empty() {
+        return Ok(serde_json::json!({
+            "success": true,
+            "totalFiles": 0,
+            "errors": []
+        }));
+    }
+
+    info!("Retrying {} failed files", existing.len());
+
+    let app_handle_for_progress = app_handle.clone();
+
+    let progress_handler = move |status: ProcessingStatus| {
+        let _ = app_handle_for_progress.emit("file-processing-progress", &status);
+    };
+
+    processor
+        .reindex_paths(
+            existing,
+            IndexPriority::Immediate,
+            progress_handler,
+            app_handle,
+        )
+        .await
+        .map_err(|e: FileProcessorError| e.to_string())
+}
@@ -0,0 +1,309 @@
+/*
+This file contains the rules that decide whether a path should be indexed. They are shared by the file processor and the file watcher so that both skip the same files
+*/
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::Gitignore;
+use ignore::Match;
+use std::collections::HashSet;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use tracing::{error, warn};
+
+use crate::file_processor::is_valid_file_extension;
+use crate::indexed_directories::all_directory_settings;
+use crate::settings::{AppSettings, SettingsManagerState};
+
+/// Glob patterns that are excluded when the user hasn't configured their own
+pub const DEFAULT_EXCLUDE_PATTERNS: [&str; 2] = ["**/.git", "**/build"];
+
+/// System, cache and build directories that are always skipped unless the user turns the deny list off
+/// These are applied on top of the user's exclusion patterns
+pub const DEFAULT_DENY_LIST: [&str; 8] = [
+    "**/Library/Caches",
+    "**/.Trash",
+    "**/.Trashes",
+    "**/node_modules",
+    "**/target",
+    "**/DerivedData",
+    "**/*.photoslibrary",
+    "**/*.photolibrary",
+];
+
+/// Files larger than this are skipped unless the user configures a different limit
+pub const DEFAULT_MAX_FILE_SIZE_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Extension of the stub files iCloud Drive leaves in place of files that haven't been downloaded
+const ICLOUD_STUB_EXTENSION: &str = "icloud";
+
+/// SF_DATALESS from sys/stat.h, set on files whose contents are only in the cloud
+/// Used by iCloud Drive and by Dropbox and OneDrive online-only files through File Provider
+#[cfg(target_os = "macos")]
+const SF_DATALESS: u32 = 0x40000000;
+
+// Windows attributes set on cloud files (OneDrive, Dropbox) whose contents aren't on disk
+#[cfg(windows)]
+const FILE_ATTRIBUTE_OFFLINE: u32 = 0x1000;
+#[cfg(windows)]
+const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x400000;
+
+pub const GIT_IGNORE_FILE_NAME: &str = ".gitignore";
+pub const KITA_IGNORE_FILE_NAME: &str = ".kitaignore";
+
+/// Overrides of an indexed directory, see indexed_directories::DirectorySettings
+#[derive(Debug, Clone)]
+struct DirectoryRules {
+    root: PathBuf,
+    indexable_extensions: Option<HashSet<String>>,
+    max_file_size_bytes: Option<u64>,
+    max_depth: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexFilter {
+    exclude_set: GlobSet,
+    respect_ignore_files: bool,
+    indexable_extensions: HashSet<String>,
+    max_file_size_bytes: u64,
+    download_cloud_placeholders: bool,
+    include_hidden_files: bool,
+    /// Deepest roots first so the closest root wins
+    directory_rules: Vec<DirectoryRules>,
+}
+
+impl IndexFilter {
+    pub fn new(
+        exclude_patterns: &[String],
+        respect_ignore_files: bool,
+        indexable_extensions: &[String],
+        max_file_size_bytes: u64,
+        download_cloud_placeholders: bool,
+        include_hidden_files: bool,
+    ) -> Self {
+        let mut builder = GlobSetBuilder::new();
+
+        for pattern in exclude_patterns {
+            match Glob::new(pattern) {
+                Ok(glob) => {
+                    builder.add(glob);
+                }
+                Err(e) => warn!("Skipping invalid exclusion pattern {}: {}", pattern, e),
+            }
+        }
+
+        let exclude_set = builder.build().unwrap_or_else(|e| {
+            error!("Failed to build exclusion patterns: {}", e);
+            GlobSet::empty()
+        });
+
+        let indexable_extensions: HashSet<String> = indexable_extensions
+            .iter()
+            .map(|ext| ext.to_lowercase())
+            .collect();
+
+        Self {
+            exclude_set,
+            respect_ignore_files,
+            indexable_extensions,
+            max_file_size_bytes,
+            download_cloud_placeholders,
+            include_hidden_files,
+            directory_rules: Vec::new(),
+        }
+    }
+
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        let mut exclude_patterns: Vec<String> = match &settings.exclude_patterns {
+            Some(patterns) => patterns.clone(),
+            None => DEFAULT_EXCLUDE_PATTERNS
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+        };
+
+        if settings.use_default_deny_list.unwrap_or(true) {
+            exclude_patterns.extend(DEFAULT_DENY_LIST.iter().map(|p| p.to_string()));
+        }
+
+        let mut filter = Self::new(
+            &exclude_patterns,
+            settings.respect_ignore_files.unwrap_or(true),
+            &settings.get_indexable_extensions(),
+            settings
+                .max_file_size_bytes
+                .unwrap_or(DEFAULT_MAX_FILE_SIZE_BYTES),
+            settings.download_cloud_placeholders.unwrap_or(false),
+            settings.show_hidden_files.unwrap_or(false),
+        );
+
+        filter.directory_rules = all_directory_settings()
+            .into_iter()
+            .map(|(root, directory_settings)| DirectoryRules {
+                root,
+                indexable_extensions: directory_settings
+                    .indexable_extensions
+                    .map(|extensions| extensions.iter().map(|ext| ext.to_lowercase()).collect()),
+                max_file_size_bytes: directory_settings.max_file_size_bytes,
+                max_depth: directory_settings.max_depth,
+            })
+            .collect();
+
+        filter
+    }
+
+    /// Builds the filter from the current settings, falls back to the defaults if settings aren't available
+    pub fn from_app_handle(app_handle: &AppHandle) -> Self {
+        let settings = app_handle
+            .try_state::<SettingsManagerState>()
+            .and_then(|state| state.0.get_settings().ok())
+            .unwrap_or_default();
+
+        Self::from_settings(&settings)
+    }
+
+    pub fn respect_ignore_files(&self) -> bool {
+        self.respect_ignore_files
+    }
+
+    fn directory_rules_for(&self, path: &Path) -> Option<&DirectoryRules> {
+        self.directory_rules
+            .iter()
+            .find(|rules| path.starts_with(&rules.root))
+    }
+
+    /// Checks the path's extension against the indexable extensions of its indexed directory
+    /// or the user's indexable extensions
+    pub fn has_indexable_extension(&self, path: &Path) -> bool {
+        let extensions = self
+            .directory_rules_for(path)
+            .and_then(|rules| rules.indexable_extensions.as_ref())
+            .unwrap_or(&self.indexable_extensions);
+        is_valid_file_extension(path, extensions)
+    }
+
+    /// Size limit for the path, its indexed directory can override the user's limit
+    pub fn max_file_size_bytes_for(&self, path: &Path) -> u64 {
+        self.directory_rules_for(path)
+            .and_then(|rules| rules.max_file_size_bytes)
+            .unwrap_or(self.max_file_size_bytes)
+    }
+
+    /// Cloud files that aren't downloaded are skipped unless the user allows downloading them,
+    /// reading a placeholder makes the cloud provider download the whole file
+    pub fn download_cloud_placeholders(&self) -> bool {
+        self.download_cloud_placeholders
+    }
+
+    /// Hidden files and folders are skipped unless the user turned on show hidden files
+    pub fn include_hidden_files(&self) -> bool {
+        self.include_hidden_files
+    }
+
+    /// A limit of 0 disables the size check
+    pub fn exceeds_max_file_size(&self, path: &Path, size: u64) -> bool {
+        let max_file_size_bytes = self.max_file_size_bytes_for(path);
+        max_file_size_bytes > 0 && size > max_file_size_bytes
+    }
+
+    /// Checks the path against the depth limit of its indexed directory, the root itself is depth 0
+    pub fn exceeds_max_depth(&self, path: &Path) -> bool {
+        self.directory_rules_for(path)
+            .and_then(|rules| {
+                let max_depth = rules.max_depth?;
+                let depth = path.strip_prefix(&rules.root).ok()?.components().count();
+                Some(depth > max_depth)
+            })
+            .unwrap_or(false)
+    }
+
+    /// Checks only the given path against the exclusion patterns, used to prune directories while walking
+    pub fn matches_exclude_pattern(&self, path: &Path) -> bool {
+        self.exclude_set.is_match(path)
+    }
+
+    /// Checks the given path and all of its parents against the exclusion patterns
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        path.ancestors()
+            .any(|ancestor| self.matches_exclude_pattern(ancestor))
+    }
+
+    /// Checks the .gitignore and .kitaignore files in the path's parent directories
+    /// The ignore file closest to the path wins, the same way git resolves nested ignore files
+    pub fn is_ignored_by_ignore_files(&self, path: &Path) -> bool {
+        if !self.respect_ignore_files {
+            return false;
+        }
+
+        let is_dir = path.is_dir();
+
+        for dir in path.ancestors().skip(1) {
+            // .kitaignore takes precedence over .gitignore in the same directory
+            for ignore_file_name in [KITA_IGNORE_FILE_NAME, GIT_IGNORE_FILE_NAME] {
+                let ignore_file = dir.join(ignore_file_name);
+                if !ignore_file.is_file() {
+                    continue;
+                }
+
+                let (gitignore, _) = Gitignore::new(&ignore_file);
+                match gitignore.matched_path_or_any_parents(path, is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Returns true if the path should not be indexed
+    pub fn should_skip(&self, path: &Path) -> bool {
+        self.is_excluded(path)
+            || self.exceeds_max_depth(path)
+            || self.is_ignored_by_ignore_files(path)
+    }
+}
+
+/// Returns true for iCloud stubs and for files whose contents are only stored in the cloud
+pub fn is_cloud_placeholder(path: &Path, metadata: &Metadata) -> bool {
+    is_icloud_stub(path) || is_dataless(metadata)
+}
+
+/// iCloud stubs are named ".<file name>.icloud"
+fn is_icloud_stub(path: &Path) -> bool {
+    let is_hidden = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.starts_with('.'))
+        .unwrap_or(false);
+
+    is_hidden
+        && path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case(ICLOUD_STUB_EXTENSION))
+            .unwrap_or(false)
+}
+
+#[cfg(target_os = "macos")]
+fn is_dataless(metadata: &Metadata) -> bool {
+    use std::os::macos::fs::MetadataExt;
+    metadata.st_flags() & SF_DATALESS != 0
+}
+
+#[cfg(windows)]
+fn is_dataless(metadata: &Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    metadata.file_attributes() & (FILE_ATTRIBUTE_OFFLINE | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS)
+        != 0
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn is_dataless(_metadata: &Metadata) -> bool {
+    false
+}
+
+#[tauri::command]
+pub fn get_default_deny_list() -> Vec<String> {
+    DEFAULT_DENY_LIST.iter().map(|p| p.to_string()).collect()
+}
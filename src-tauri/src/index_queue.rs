@@ -0,0 +1,350 @@
+/*
+This file contains the indexing work queue. Files are indexed by a pool of workers that always pick the highest priority job first, so that watcher changes and "index this file now" requests don't wait behind a large backfill
+The number of active workers is adjusted to the load of the machine so indexing backs off while the user is busy
+*/
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::{oneshot, Notify};
+use tracing::{error, info};
+
+use crate::database_handler::checkpoint_wal;
+use crate::file_processor::{index_file, FileMetadata};
+use crate::resource_monitor::{latest_system_load, SystemLoad};
+use crate::settings::SettingsManagerState;
+use crate::supervisor::supervise;
+
+/// How often the number of active workers is adjusted to the published system load
+const LOAD_SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Number of indexed files after which the WAL is checkpointed and truncated
+const WAL_CHECKPOINT_EVERY_FILES: usize = 500;
+
+/// How often a paused worker checks whether it may resume
+const PAUSED_WORKER_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// above these the number of workers is reduced, below the lower ones it's increased
+const HIGH_CPU_USAGE: f32 = 85.0;
+const HIGH_MEMORY_USAGE: f32 = 90.0;
+const LOW_CPU_USAGE: f32 = 40.0;
+const LOW_MEMORY_USAGE: f32 = 75.0;
+
+/// Priority of an indexing job, higher priorities are always picked up first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexPriority {
+    /// Bulk indexing of folders the user added
+    Backfill,
+    /// Files that changed on disk while being watched
+    Watcher,
+    /// Files the user explicitly asked to index right away
+    Immediate,
+}
+
+impl IndexPriority {
+    const ALL_DESCENDING: [IndexPriority; 3] = [
+        IndexPriority::Immediate,
+        IndexPriority::Watcher,
+        IndexPriority::Backfill,
+    ];
+
+    fn queue_index(&self) -> usize {
+        match self {
+            IndexPriority::Backfill => 0,
+            IndexPriority::Watcher => 1,
+            IndexPriority::Immediate => 2,
+        }
+    }
+}
+
+/// Result of indexing a single file, the error is a message describing what failed
+pub type IndexJobResult = Result<(), String>;
+
+struct PendingJob {
+    file: FileMetadata,
+    priority: IndexPriority,
+    waiters: Vec<oneshot::Sender<IndexJobResult>>,
+}
+
+#[derive(Default)]
+struct QueueInner {
+    // one FIFO of file paths per priority, indexed by IndexPriority::queue_index
+    queues: [VecDeque<String>; 3],
+    // the job for each queued path, a path is only ever queued once
+    pending: HashMap<String, PendingJob>,
+}
+
+#[derive(Default)]
+pub struct IndexQueue {
+    inner: Mutex<QueueInner>,
+    notify: Notify,
+    // number of spawned workers
+    max_workers: AtomicUsize,
+    // workers with an index at or above this limit are paused
+    worker_limit: AtomicUsize,
+    // files indexed since the WAL was last truncated
+    indexed_since_checkpoint: AtomicUsize,
+    // set by the user, every worker waits until indexing is resumed
+    paused: AtomicBool,
+}
+
+impl IndexQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file to the queue and returns a receiver that resolves once the file has been indexed
+    /// If the file is already queued the jobs are merged and the higher priority is kept
+    pub fn enqueue(
+        &self,
+        file: FileMetadata,
+        priority: IndexPriority,
+    ) -> oneshot::Receiver<IndexJobResult> {
+        let (tx, rx) = oneshot::channel();
+        let path = file.base.path.clone();
+
+        {
+            let mut inner = self.inner.lock().unwrap();
+
+            match inner.pending.get_mut(&path) {
+                Some(job) => {
+                    job.waiters.push(tx);
+                    job.file = file;
+
+                    if priority > job.priority {
+                        // the old entry in the lower priority queue is skipped when it's popped
+                        job.priority = priority;
+                        inner.queues[priority.queue_index()].push_back(path);
+                    }
+                }
+                None => {
+                    inner.pending.insert(
+                        path.clone(),
+                        PendingJob {
+                            file,
+                            priority,
+                            waiters: vec![tx],
+                        },
+                    );
+                    inner.queues[priority.queue_index()].push_back(path);
+                }
+            }
+        }
+
+        self.notify.notify_one();
+        rx
+    }
+
+    /// Number of files waiting to be indexed
+    pub fn pending_count(&self) -> usize {
+        self.inner.lock().unwrap().pending.len()
+    }
+
+    /// Number of workers currently allowed to index files
+    pub fn worker_limit(&self) -> usize {
+        self.worker_limit.load(Ordering::Relaxed)
+    }
+
+    /// Changes the number of active workers, clamped between 1 and the number of spawned workers
+    /// Workers above the limit finish their current file and then pause
+    pub fn set_worker_limit(&self, limit: usize) {
+        let limit = limit.clamp(1, self.max_workers.load(Ordering::Relaxed).max(1));
+        let previous = self.worker_limit.swap(limit, Ordering::Relaxed);
+
+        if previous != limit {
+            info!(
+                "Indexing concurrency changed from {} to {}",
+                previous, limit
+            );
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Pauses or resumes every worker, files keep being queued while indexing is paused
+    pub fn set_paused(&self, paused: bool) {
+        if self.paused.swap(paused, Ordering::Relaxed) != paused {
+            info!("Indexing {}", if paused { "paused" } else { "resumed" });
+        }
+    }
+
+    fn try_pop(&self) -> Option<PendingJob> {
+        let mut inner = self.inner.lock().unwrap();
+
+        for priority in IndexPriority::ALL_DESCENDING {
+            while let Some(path) = inner.queues[priority.queue_index()].pop_front() {
+                let is_current = inner
+                    .pending
+                    .get(&path)
+                    .map(|job| job.priority == priority)
+                    .unwrap_or(false);
+
+                // stale entries are left behind when a job is moved to a higher priority
+                if is_current {
+                    return inner.pending.remove(&path);
+                }
+            }
+        }
+
+        None
+    }
+
+    async fn next_job(&self) -> PendingJob {
+        loop {
+            if let Some(job) = self.try_pop() {
+                return job;
+            }
+            self.notify.notified().await;
+        }
+    }
+
+    /// Spawns the workers that process the queue for the lifetime of the app
+    pub fn start_workers(
+        self: &Arc<Self>,
+        worker_count: usize,
+        db_path: PathBuf,
+        app_handle: AppHandle,
+    ) {
+        let worker_count = worker_count.max(1);
+        self.max_workers.store(worker_count, Ordering::Relaxed);
+        self.worker_limit.store(worker_count, Ordering::Relaxed);
+
+        for worker_index in 0..worker_count {
+            let queue = Arc::clone(self);
+            let db_path = db_path.clone();
+            let app_handle = app_handle.clone();
+
+            // a panic only loses the file being indexed, its waiters get an error and the restarted worker takes the next job
+            supervise(app_handle.clone(), "index_worker", true, move || {
+                let queue = Arc::clone(&queue);
+                let db_path = db_path.clone();
+                let app_handle = app_handle.clone();
+                async move {
+                    loop {
+                        if queue.is_paused() || worker_index >= queue.worker_limit() {
+                            tokio::time::sleep(PAUSED_WORKER_POLL_INTERVAL).await;
+                            continue;
+                        }
+
+                        let job = queue.next_job().await;
+                        let result =
+                            index_file(db_path.clone(), job.file, app_handle.clone()).await;
+
+                        for waiter in job.waiters {
+                            let _ = waiter.send(result.clone());
+                        }
+
+                        queue.checkpoint_if_needed(&db_path).await;
+
+                        // wake up another worker in case more jobs were queued while this one was busy
+                        if queue.pending_count() > 0 {
+                            queue.notify.notify_one();
+                        }
+                    }
+                }
+            });
+        }
+    }
+
+    /// Truncates the WAL every WAL_CHECKPOINT_EVERY_FILES files and once the queue runs empty
+    /// Automatic checkpoints never shrink the WAL, so without this a large backfill leaves it at hundreds of MB
+    async fn checkpoint_if_needed(&self, db_path: &Path) {
+        let indexed = self
+            .indexed_since_checkpoint
+            .fetch_add(1, Ordering::Relaxed)
+            + 1;
+        if indexed < WAL_CHECKPOINT_EVERY_FILES && self.pending_count() > 0 {
+            return;
+        }
+
+        // only the worker that resets the counter runs the checkpoint
+        if self
+            .indexed_since_checkpoint
+            .compare_exchange(indexed, 0, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+        {
+            return;
+        }
+
+        let db_path = db_path.to_path_buf();
+        match tokio::task::spawn_blocking(move || checkpoint_wal(db_path)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => error!("Failed to checkpoint the WAL: {}", e),
+            Err(e) => error!("Failed to checkpoint the WAL: {}", e),
+        }
+    }
+
+    /// Periodically reads the system load published by the resource monitor and scales the number of active workers up or down
+    /// Does nothing but keep every worker active while adaptive concurrency is turned off in the settings
+    pub fn start_concurrency_controller(self: &Arc<Self>, app_handle: AppHandle) {
+        let queue = Arc::clone(self);
+
+        supervise(
+            app_handle.clone(),
+            "concurrency_controller",
+            true,
+            move || {
+                let queue = Arc::clone(&queue);
+                let app_handle = app_handle.clone();
+                async move {
+                    let mut interval = tokio::time::interval(LOAD_SAMPLE_INTERVAL);
+
+                    loop {
+                        interval.tick().await;
+
+                        let max_workers = queue.max_workers.load(Ordering::Relaxed);
+
+                        let adaptive = app_handle
+                            .try_state::<SettingsManagerState>()
+                            .and_then(|state| state.0.get_settings().ok())
+                            .and_then(|settings| settings.adaptive_concurrency)
+                            .unwrap_or(true);
+
+                        if !adaptive {
+                            queue.set_worker_limit(max_workers);
+                            continue;
+                        }
+
+                        let Some(load) = latest_system_load() else {
+                            continue;
+                        };
+
+                        let limit = target_worker_limit(queue.worker_limit(), max_workers, &load);
+                        queue.set_worker_limit(limit);
+                    }
+                }
+            },
+        );
+    }
+}
+
+/// Picks the number of active workers for the given load, moving one step at a time so that short spikes don't stall indexing
+fn target_worker_limit(current: usize, max_workers: usize, load: &SystemLoad) -> usize {
+    if load.thermal_throttled {
+        return 1;
+    }
+
+    // leave half of the workers idle on battery to save power
+    let ceiling = if load.on_battery {
+        (max_workers / 2).max(1)
+    } else {
+        max_workers
+    };
+
+    let target = if load.cpu_usage > HIGH_CPU_USAGE || load.memory_usage > HIGH_MEMORY_USAGE {
+        current.saturating_sub(1)
+    } else if load.cpu_usage < LOW_CPU_USAGE && load.memory_usage < LOW_MEMORY_USAGE {
+        current + 1
+    } else {
+        current
+    };
+
+    target.clamp(1, ceiling)
+}
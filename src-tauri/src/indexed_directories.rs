@@ -0,0 +1,360 @@
+/*
+This file contains the management of the indexed directories. The directories the user added are marked as roots in the directories table, the other rows are their subdirectories. Adding a root indexes it and the indexing_complete event starts watching it, removing a root unindexes everything under it and stops watching it
+
+Roots can override the global indexing settings, the overrides are stored as JSON in the root's row and cached here so the index filter doesn't have to read them for every path
+*/
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tauri::{AppHandle, Emitter, State};
+use tokio::task;
+use tracing::{error, info, instrument, warn};
+
+use crate::database_handler::open_connection;
+use crate::file_processor::{
+    descendants_like_pattern, get_processor, remove_paths_from_index, FileProcessor,
+    FileProcessorError, FileProcessorState, ProcessingStatus,
+};
+use crate::index_queue::IndexPriority;
+use crate::settings::normalize_extension;
+use crate::usage_analytics::record_feature;
+
+/// Indexing settings of a root directory, unset fields use the global settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DirectorySettings {
+    pub indexable_extensions: Option<Vec<String>>,
+    pub max_file_size_bytes: Option<u64>,
+    /// Files are only added to the full-text index when this is off
+    pub semantic_indexing: Option<bool>,
+    /// How many levels below the root are indexed, 1 only indexes the files directly in the root
+    pub max_depth: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedDirectory {
+    pub path: String,
+    pub file_count: i64,
+    pub exists: bool,
+    pub added_at: Option<String>,
+    pub settings: DirectorySettings,
+}
+
+/// Settings of every root that has overrides, deepest roots first so the closest root wins
+static DIRECTORY_SETTINGS: RwLock<Vec<(PathBuf, DirectorySettings)>> = RwLock::new(Vec::new());
+
+/// Returns the root containing the path and its settings, if that root has overrides
+pub fn directory_settings_for(path: &Path) -> Option<(PathBuf, DirectorySettings)> {
+    DIRECTORY_SETTINGS
+        .read()
+        .ok()?
+        .iter()
+        .find(|(root, _)| path.starts_with(root))
+        .cloned()
+}
+
+/// Returns every root with overrides, deepest roots first
+pub fn all_directory_settings() -> Vec<(PathBuf, DirectorySettings)> {
+    DIRECTORY_SETTINGS
+        .read()
+        .map(|settings| settings.clone())
+        .unwrap_or_default()
+}
+
+/// Reads the overrides of every root into the cache, called on start and after they change
+pub fn load_directory_settings(db_path: &Path) -> Result<(), FileProcessorError> {
+    let conn = open_connection(db_path)?;
+    let mut stmt = conn.prepare(
+        "SELECT path, index_settings FROM directories WHERE is_root = 1 AND index_settings IS NOT NULL",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+
+    let mut loaded: Vec<(PathBuf, DirectorySettings)> = Vec::new();
+    for row in rows {
+        let (path, json) = row?;
+        match serde_json::from_str(&json) {
+            Ok(settings) => loaded.push((PathBuf::from(path), settings)),
+            Err(e) => warn!("Ignoring invalid settings for {}: {}", path, e),
+        }
+    }
+    loaded.sort_by_key(|(root, _)| std::cmp::Reverse(root.components().count()));
+
+    if let Ok(mut settings) = DIRECTORY_SETTINGS.write() {
+        *settings = loaded;
+    }
+    Ok(())
+}
+
+fn normalize_directory_settings(
+    mut settings: DirectorySettings,
+) -> Result<DirectorySettings, String> {
+    if let Some(extensions) = settings.indexable_extensions.take() {
+        let normalized = extensions
+            .iter()
+            .map(|extension| normalize_extension(extension).map_err(|e| e.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        settings.indexable_extensions = Some(normalized);
+    }
+
+    if settings.max_depth == Some(0) {
+        return Err("The depth must be at least 1".to_string());
+    }
+
+    Ok(settings)
+}
+
+fn normalize_directory_path(path: &str) -> String {
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() {
+        "/".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Marks the directory as a root, roots inside it become its subdirectories
+/// Fails if the directory is already covered by another root
+fn save_root_directory(db_path: &Path, root: &str) -> Result<(), FileProcessorError> {
+    let mut conn = open_connection(db_path)?;
+    let tx = conn.transaction()?;
+
+    let covering_root: Option<String> = {
+        let mut stmt = tx.prepare("SELECT path FROM directories WHERE is_root = 1")?;
+        let roots = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let covering = roots
+            .filter_map(Result::ok)
+            .find(|existing| Path::new(root).starts_with(existing));
+        covering
+    };
+
+    if let Some(existing) = covering_root {
+        return Err(FileProcessorError::Other(if existing == root {
+            format!("{} is already indexed", root)
+        } else {
+            format!("{} is already indexed as part of {}", root, existing)
+        }));
+    }
+
+    tx.execute(
+        "UPDATE directories SET is_root = 0 WHERE is_root = 1 AND path LIKE ?1 ESCAPE '\\'",
+        [descendants_like_pattern(root)],
+    )?;
+    tx.execute(
+        r#"
+        INSERT INTO directories (path, is_root, created_at, updated_at)
+        VALUES (?1, 1, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+        ON CONFLICT(path) DO UPDATE SET is_root = 1, updated_at = CURRENT_TIMESTAMP
+        "#,
+        [root],
+    )?;
+
+    tx.commit()?;
+    Ok(())
+}
+
+fn load_root_directories(db_path: &Path) -> Result<Vec<IndexedDirectory>, FileProcessorError> {
+    let conn = open_connection(db_path)?;
+
+    let roots: Vec<(String, Option<String>, Option<String>)> = {
+        let mut stmt = conn.prepare(
+            "SELECT path, created_at, index_settings FROM directories WHERE is_root = 1 ORDER BY path",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    let mut count_stmt =
+        conn.prepare("SELECT COUNT(*) FROM files WHERE path LIKE ?1 ESCAPE '\\'")?;
+
+    roots
+        .into_iter()
+        .map(
+            |(path, added_at, settings)| -> Result<IndexedDirectory, FileProcessorError> {
+                let file_count: i64 =
+                    count_stmt.query_row([descendants_like_pattern(&path)], |row| row.get(0))?;
+
+                Ok(IndexedDirectory {
+                    exists: Path::new(&path).is_dir(),
+                    path,
+                    file_count,
+                    added_at,
+                    settings: settings
+                        .and_then(|json| serde_json::from_str(&json).ok())
+                        .unwrap_or_default(),
+                })
+            },
+        )
+        .collect()
+}
+
+/// Adds a directory to the index and starts indexing it in the background
+/// Progress is reported with the file-processing-progress event like any other indexing
+#[tauri::command]
+#[instrument(skip_all, fields(path = %path))]
+pub async fn add_indexed_directory(
+    path: String,
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let processor: FileProcessor = get_processor(&state)?;
+    let root = normalize_directory_path(&path);
+
+    if !Path::new(&root).is_dir() {
+        return Err(format!("{} is not a directory", root));
+    }
+
+    let db_path = processor.db_path.clone();
+    let root_for_db = root.clone();
+    task::spawn_blocking(move || save_root_directory(&db_path, &root_for_db))
+        .await
+        .map_err(|e| format!("spawn_blocking error: {e}"))?
+        .map_err(|e| e.to_string())?;
+
+    info!("Added indexed directory {}", root);
+    record_feature(&app_handle, "index_directory");
+    let _ = app_handle.emit("indexed-directories-updated", ());
+
+    tauri::async_runtime::spawn(async move {
+        let app_handle_for_progress = app_handle.clone();
+        let progress_handler = move |status: ProcessingStatus| {
+            let _ = app_handle_for_progress.emit("file-processing-progress", &status);
+        };
+
+        if let Err(e) = processor
+            .process_paths(
+                vec![root.clone()],
+                IndexPriority::Backfill,
+                progress_handler,
+                app_handle.clone(),
+            )
+            .await
+        {
+            error!("Failed to index directory {}: {}", root, e);
+        }
+
+        let _ = app_handle.emit("files-updated", ());
+    });
+
+    Ok(())
+}
+
+/// Removes a directory that was added to the index, its files are unindexed and it is no longer watched
+#[tauri::command]
+#[instrument(skip_all, fields(path = %path))]
+pub async fn remove_indexed_directory(
+    path: String,
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<serde_json::Value, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+    let root = normalize_directory_path(&path);
+
+    let db_path = processor.db_path.clone();
+    let root_for_db = root.clone();
+    let is_root = task::spawn_blocking(move || -> Result<bool, FileProcessorError> {
+        let conn = open_connection(db_path)?;
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM directories WHERE path = ?1 AND is_root = 1",
+            [root_for_db],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking error: {e}"))?
+    .map_err(|e| e.to_string())?;
+
+    if !is_root {
+        return Err(format!("{} is not an indexed directory", root));
+    }
+
+    let result = remove_paths_from_index(vec![root.clone()], state, app_handle.clone()).await?;
+
+    if let Err(e) = load_directory_settings(&processor.db_path) {
+        error!("Failed to reload directory settings: {}", e);
+    }
+
+    info!("Removed indexed directory {}", root);
+    let _ = app_handle.emit("indexed-directories-updated", ());
+
+    Ok(result)
+}
+
+/// Returns the directories the user added to the index with the number of files indexed in each
+#[tauri::command]
+pub async fn list_indexed_directories(
+    state: State<'_, FileProcessorState>,
+) -> Result<Vec<IndexedDirectory>, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+    let db_path: PathBuf = processor.db_path.clone();
+
+    task::spawn_blocking(move || load_root_directories(&db_path))
+        .await
+        .map_err(|e| format!("spawn_blocking error: {e}"))?
+        .map_err(|e| e.to_string())
+}
+
+/// Saves the indexing settings of an indexed directory and reindexes it so they apply to the files already indexed
+#[tauri::command]
+pub async fn set_directory_settings(
+    path: String,
+    settings: DirectorySettings,
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let processor: FileProcessor = get_processor(&state)?;
+    let root = normalize_directory_path(&path);
+    let settings = normalize_directory_settings(settings)?;
+    let json = serde_json::to_string(&settings).map_err(|e| e.to_string())?;
+
+    let db_path = processor.db_path.clone();
+    let root_for_db = root.clone();
+    let updated = task::spawn_blocking(move || -> Result<usize, FileProcessorError> {
+        let conn = open_connection(&db_path)?;
+        let updated = conn.execute(
+            r#"
+            UPDATE directories SET index_settings = ?1, updated_at = CURRENT_TIMESTAMP
+            WHERE path = ?2 AND is_root = 1
+            "#,
+            [json, root_for_db],
+        )?;
+        load_directory_settings(&db_path)?;
+        Ok(updated)
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking error: {e}"))?
+    .map_err(|e| e.to_string())?;
+
+    if updated == 0 {
+        return Err(format!("{} is not an indexed directory", root));
+    }
+
+    info!("Updated index settings of {}", root);
+    let _ = app_handle.emit("indexed-directories-updated", ());
+
+    // files that are out of scope now are dropped and the rest is indexed with the new settings
+    tauri::async_runtime::spawn(async move {
+        let app_handle_for_progress = app_handle.clone();
+        let progress_handler = move |status: ProcessingStatus| {
+            let _ = app_handle_for_progress.emit("file-processing-progress", &status);
+        };
+
+        if let Err(e) = processor
+            .reindex_paths(
+                vec![root.clone()],
+                IndexPriority::Backfill,
+                progress_handler,
+                app_handle.clone(),
+            )
+            .await
+        {
+            error!("Failed to reindex directory {}: {}", root, e);
+        }
+
+        let _ = app_handle.emit("files-updated", ());
+    });
+
+    Ok(())
+}
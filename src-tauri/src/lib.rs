@@ -1,24 +1,67 @@
+mod api_server;
 mod app_handler;
+mod app_watcher;
+#[cfg(target_os = "linux")]
+mod apps_linux;
+#[cfg(target_os = "macos")]
+mod apps_macos;
+#[cfg(windows)]
+mod apps_windows;
+mod browser_tabs;
 mod chunker;
 mod contacts;
+mod conversations;
 mod database_handler;
+mod db_maintenance;
 mod embedder;
+mod encryption;
+mod favorites;
+mod file_preview;
 mod file_processor;
 mod file_watcher;
+mod fts_index;
+mod icon_cache;
+mod index_archive;
+mod index_errors;
+mod index_filter;
+mod index_queue;
+mod indexed_directories;
+mod logging;
+mod login_item;
+mod mcp;
+mod messages;
+mod model_benchmark;
 mod model_registry;
+mod open_history;
+mod plugins;
+mod profiles;
 mod resource_monitor;
 mod server;
 mod settings;
+mod settings_transfer;
+mod spotlight;
+mod supervisor;
+mod system_actions;
+mod tags;
+mod thumbnails;
 mod tokenizer;
+mod tray;
+mod usage_analytics;
 mod utils;
 mod vectordb_manager;
 mod window;
 
 use file_processor::FileProcessorState;
 use tauri::Manager;
+use tracing::error;
 
 type AppResult<T> = Result<T, Box<dyn std::error::Error>>;
 
+/// Runs the MCP stdio transport, it talks to the running app through its local API
+pub fn run_mcp_stdio() {
+    mcp::run_stdio_bridge()
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -26,13 +69,29 @@ pub fn run() {
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_dialog::init())
         .setup(|app| {
+            logging::init_logging(app)?;
+            supervisor::install_panic_hook();
             let db_path = database_handler::init_database(app.app_handle().clone())?;
             let db_path_str = &db_path.to_string_lossy();
+            if let Err(e) = indexed_directories::load_directory_settings(&db_path) {
+                error!("Failed to load directory settings: {}", e);
+            }
 
             settings::init_settings(&db_path_str, app.app_handle().clone())?;
+            login_item::sync_login_item(app.app_handle());
+            // chunker plugins have to be loaded before the first files are indexed
+            plugins::init_plugins(app)?;
+            chunker::audio::init_transcription(app);
             file_processor::init_file_processor(&db_path_str, 4, app.app_handle().clone())?;
+            fts_index::init_fts_index(&db_path, app.app_handle().clone())?;
+            db_maintenance::start_maintenance_scheduler(db_path.clone(), app.app_handle().clone());
+            usage_analytics::start_usage_flusher(db_path.clone(), app.app_handle().clone());
             file_watcher::init_file_watcher(app, &db_path)?;
             resource_monitor::init_resource_monitor(app)?;
+            resource_monitor::start_system_pressure_monitor(app.app_handle().clone());
+            app_watcher::start_app_watcher(app.app_handle().clone());
+            tray::init_tray(app)?;
+            api_server::init_api_server(app.app_handle().clone());
             vectordb_manager::init_vector_db(app)?;
             // server::init_server(app)?;
             // server::register_llm_commands(app)?;
@@ -44,22 +103,100 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             app_handler::get_apps_data,
             app_handler::force_quit_application,
+            app_handler::force_quit_applications,
+            app_handler::quit_heavy_applications,
+            app_handler::hide_application,
+            app_handler::minimize_application,
+            app_handler::get_recent_documents,
             app_handler::restart_application,
             app_handler::launch_or_switch_to_app,
-            resource_monitor::start_resource_monitoring,
-            resource_monitor::stop_resource_monitoring,
+            browser_tabs::get_browser_tabs,
+            browser_tabs::focus_browser_tab,
+            browser_tabs::search_browser_tabs,
+            system_actions::list_system_actions,
+            system_actions::run_system_action,
+            plugins::list_plugins,
+            plugins::reload_plugins,
+            plugins::get_plugin_actions,
+            plugins::run_plugin_action,
+            plugins::search_plugin_providers,
+            resource_monitor::subscribe_resource_usage,
+            resource_monitor::update_resource_subscription,
+            resource_monitor::unsubscribe_resource_usage,
+            resource_monitor::get_resource_history,
             file_processor::process_paths_command,
+            file_processor::set_indexing_paused,
+            file_processor::is_indexing_paused,
+            file_processor::index_file_now,
+            file_processor::preview_index,
+            file_processor::remove_paths_from_index,
+            indexed_directories::add_indexed_directory,
+            indexed_directories::remove_indexed_directory,
+            indexed_directories::list_indexed_directories,
+            indexed_directories::set_directory_settings,
+            file_watcher::stop_watching_directory,
+            file_watcher::get_watcher_status,
+            file_watcher::start_file_watching,
+            file_watcher::stop_file_watching,
+            fts_index::apply_fts_settings,
+            tags::add_tag,
+            tags::remove_tag,
+            tags::list_tags,
+            tags::get_files_by_tag,
+            favorites::pin_item,
+            favorites::unpin_item,
+            favorites::reorder_pinned_items,
+            favorites::get_pinned_items,
+            open_history::record_opened_item,
+            open_history::get_recent_items,
+            conversations::list_conversations,
+            conversations::create_conversation,
+            conversations::rename_conversation,
+            conversations::delete_conversation,
+            conversations::get_conversation_messages,
+            conversations::add_conversation_message,
+            db_maintenance::run_db_maintenance,
+            index_archive::export_index,
+            index_archive::import_index,
+            encryption::set_index_encryption,
+            profiles::list_profiles,
+            profiles::switch_profile,
+            file_processor::reindex_paths,
+            file_processor::trash_file,
+            index_errors::get_index_errors,
+            index_errors::retry_failed_files,
             file_processor::get_files_data,
             file_processor::get_semantic_files_data,
+            file_processor::find_duplicate_files,
+            file_processor::get_files_by_category,
+            file_processor::get_category_counts,
             file_processor::open_file,
+            file_preview::preview_file,
             model_registry::get_models,
             model_registry::get_downloaded_models,
             model_registry::start_model_download,
             model_registry::check_model_exists,
+            model_benchmark::benchmark_model,
             server::ask_llm,
+            server::ask_llm_stream,
             settings::get_settings,
             settings::update_settings,
+            settings::get_indexable_extensions,
+            settings::add_indexable_extension,
+            settings::remove_indexable_extension,
+            settings_transfer::export_settings,
+            settings_transfer::import_settings,
+            index_filter::get_default_deny_list,
             window::show_main_window,
+            window::toggle_quick_ask_window,
+            window::toggle_chat_window,
+            login_item::set_launch_at_login,
+            login_item::get_launch_at_login,
+            logging::get_recent_logs,
+            usage_analytics::get_usage_stats,
+            usage_analytics::clear_usage_stats,
+            api_server::get_api_server_status,
+            api_server::regenerate_api_token,
             contacts::get_contacts_command,
             // contacts::request_contacts_permission_command,
             // contacts::check_contacts_permission_command
@@ -0,0 +1,247 @@
+/*
+This file contains the logging. Events are written as JSON lines to a daily rotating file in the app data directory and to stdout, get_recent_logs reads them back so users can include them when they report a problem
+Errors are also sent to the frontend with the log-event event
+*/
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::AppResult;
+
+const LOG_DIR_NAME: &str = "logs";
+const LOG_FILE_PREFIX: &str = "kita";
+const LOG_FILE_SUFFIX: &str = "log";
+
+/// Days of logs kept, the oldest file is deleted when a new one is started
+const MAX_LOG_FILES: usize = 7;
+
+const DEFAULT_LOG_LIMIT: usize = 200;
+const MAX_LOG_LIMIT: usize = 5000;
+
+static LOG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    /// Module the event was logged from
+    pub target: String,
+    pub message: String,
+    /// Name of the span the event was logged in, like process_paths
+    pub span: Option<String>,
+}
+
+/// A line written by the JSON formatter
+#[derive(Debug, Deserialize)]
+struct LogLine {
+    timestamp: String,
+    level: String,
+    #[serde(default)]
+    target: String,
+    #[serde(default)]
+    fields: Map<String, Value>,
+    span: Option<Map<String, Value>>,
+}
+
+/// Joins the message with the other fields of the event, like `Indexed path=/a/b`
+fn format_message(mut fields: Map<String, Value>) -> String {
+    let mut message = match fields.remove("message") {
+        Some(Value::String(message)) => message,
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+
+    for (name, value) in fields {
+        let value = match value {
+            Value::String(value) => value,
+            other => other.to_string(),
+        };
+        message.push_str(&format!(" {}={}", name, value));
+    }
+
+    message
+}
+
+impl From<LogLine> for LogEntry {
+    fn from(line: LogLine) -> Self {
+        LogEntry {
+            timestamp: line.timestamp,
+            level: line.level,
+            target: line.target,
+            message: format_message(line.fields),
+            span: line
+                .span
+                .and_then(|span| span.get("name").and_then(Value::as_str).map(str::to_string)),
+        }
+    }
+}
+
+/// Collects the message and fields of an event
+#[derive(Default)]
+struct EventVisitor {
+    fields: Map<String, Value>,
+}
+
+impl Visit for EventVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.fields
+            .insert(field.name().to_string(), Value::String(value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.fields.insert(
+            field.name().to_string(),
+            Value::String(format!("{:?}", value)),
+        );
+    }
+}
+
+/// Sends every error to the frontend as it's logged
+struct LogEventLayer {
+    app_handle: AppHandle,
+}
+
+impl<S: Subscriber> Layer<S> for LogEventLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if *event.metadata().level() != Level::ERROR {
+            return;
+        }
+
+        let mut visitor = EventVisitor::default();
+        event.record(&mut visitor);
+
+        let entry = LogEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            level: Level::ERROR.to_string(),
+            target: event.metadata().target().to_string(),
+            message: format_message(visitor.fields),
+            span: None,
+        };
+        let _ = self.app_handle.emit("log-event", entry);
+    }
+}
+
+/// Starts writing logs, runs first in setup so the rest of the startup is logged
+pub fn init_logging(app: &tauri::App) -> AppResult<()> {
+    let log_dir = app.path().app_data_dir()?.join(LOG_DIR_NAME);
+    fs::create_dir_all(&log_dir)?;
+
+    let log_file = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(&log_dir)?;
+
+    // the app's own debug events are only kept in development builds, dependencies only log warnings
+    let app_level = if cfg!(debug_assertions) {
+        Level::DEBUG
+    } else {
+        Level::INFO
+    };
+    let filter = Targets::new()
+        .with_default(Level::WARN)
+        .with_target("kita_lib", app_level);
+
+    let subscriber = tracing_subscriber::registry()
+        .with(filter)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .json()
+                .with_current_span(true)
+                .with_span_list(false)
+                .with_writer(log_file),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .with(LogEventLayer {
+            app_handle: app.handle().clone(),
+        });
+
+    // logging isn't worth failing the startup for
+    if let Err(e) = subscriber.try_init() {
+        eprintln!("Failed to initialize logging: {}", e);
+    }
+
+    let _ = LOG_DIR.set(log_dir);
+    Ok(())
+}
+
+/// Log files, newest first, the date in their names sorts them
+fn log_files(log_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(log_dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(LOG_FILE_PREFIX))
+        })
+        .collect();
+    files.sort();
+    files.reverse();
+    files
+}
+
+/// Returns the most recent log entries at the level or more severe, newest first
+/// The level defaults to info, entries written by an older format are skipped
+#[tauri::command]
+pub async fn get_recent_logs(
+    level: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<LogEntry>, String> {
+    let min_level = match level {
+        Some(level) => level
+            .parse::<Level>()
+            .map_err(|_| format!("Unknown log level {}", level))?,
+        None => Level::INFO,
+    };
+    let limit = limit.unwrap_or(DEFAULT_LOG_LIMIT).clamp(1, MAX_LOG_LIMIT);
+
+    let Some(log_dir) = LOG_DIR.get().cloned() else {
+        return Err("Logging isn't initialized".to_string());
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let mut entries = Vec::new();
+
+        for path in log_files(&log_dir) {
+            let Ok(contents) = fs::read_to_string(&path) else {
+                continue;
+            };
+
+            for line in contents.lines().rev() {
+                let Ok(line) = serde_json::from_str::<LogLine>(line) else {
+                    continue;
+                };
+                // more severe levels compare as smaller
+                if line
+                    .level
+                    .parse::<Level>()
+                    .is_ok_and(|level| level <= min_level)
+                {
+                    entries.push(LogEntry::from(line));
+                    if entries.len() == limit {
+                        return entries;
+                    }
+                }
+            }
+        }
+
+        entries
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking error: {e}"))
+}
@@ -0,0 +1,96 @@
+/*
+This file contains launch at login. The app is registered as a login item through the Swift bridge with SMAppService, and the start_hidden setting keeps the window hidden on start so only the global shortcut is active
+*/
+
+use tauri::{AppHandle, Manager, State};
+use tracing::error;
+
+use crate::settings::SettingsManagerState;
+
+#[cfg(target_os = "macos")]
+extern "C" {
+    fn set_launch_at_login_swift(enabled: bool) -> bool;
+    fn is_launch_at_login_enabled_swift() -> bool;
+}
+
+#[cfg(target_os = "macos")]
+fn register_login_item(enabled: bool) -> Result<(), String> {
+    let changed = unsafe { set_launch_at_login_swift(enabled) };
+    if !changed {
+        return Err(if enabled {
+            "Failed to add the app to the login items".to_string()
+        } else {
+            "Failed to remove the app from the login items".to_string()
+        });
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn is_login_item_registered() -> bool {
+    unsafe { is_launch_at_login_enabled_swift() }
+}
+
+/// Login items are registered through SMAppService, which only exists on macOS
+#[cfg(not(target_os = "macos"))]
+fn register_login_item(_enabled: bool) -> Result<(), String> {
+    Err("Launch at login is only supported on macOS".to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn is_login_item_registered() -> bool {
+    false
+}
+
+/// Makes the login item match the launch_at_login setting
+/// The registration can be lost when the app is moved or reinstalled, so this runs on every start
+pub fn sync_login_item(app_handle: &AppHandle) {
+    let Some(enabled) = app_handle
+        .try_state::<SettingsManagerState>()
+        .and_then(|state| state.0.get_settings().ok())
+        .and_then(|settings| settings.launch_at_login)
+    else {
+        return;
+    };
+
+    let registered = is_login_item_registered();
+    if registered != enabled {
+        if let Err(e) = register_login_item(enabled) {
+            error!("{}", e);
+        }
+    }
+}
+
+/// Whether the main window should stay hidden when the app starts
+pub fn should_start_hidden(app_handle: &AppHandle) -> bool {
+    app_handle
+        .try_state::<SettingsManagerState>()
+        .and_then(|state| state.0.get_settings().ok())
+        .and_then(|settings| settings.start_hidden)
+        .unwrap_or(false)
+}
+
+/// Adds or removes the app from the login items and saves the choice in the settings
+#[tauri::command]
+pub fn set_launch_at_login(
+    enabled: bool,
+    settings_manager: State<'_, SettingsManagerState>,
+) -> Result<(), String> {
+    register_login_item(enabled)?;
+
+    let mut settings = settings_manager
+        .0
+        .get_settings()
+        .map_err(|e| format!("Failed to get settings: {}", e))?;
+    settings.launch_at_login = Some(enabled);
+    settings_manager
+        .0
+        .update(settings)
+        .map_err(|e| format!("Failed to update settings: {}", e))
+}
+
+/// Returns whether the app is currently registered as a login item
+#[tauri::command]
+pub fn get_launch_at_login() -> bool {
+    is_login_item_registered()
+}
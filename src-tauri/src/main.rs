@@ -2,5 +2,10 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // `kita --mcp` runs the MCP stdio transport for clients like Claude Desktop instead of the app
+    if std::env::args().any(|arg| arg == "--mcp") {
+        return kita_lib::run_mcp_stdio();
+    }
+
     kita_lib::run()
 }
@@ -0,0 +1,455 @@
+/*
+This file contains the Model Context Protocol server. It exposes the index as MCP tools to search files, search and fetch chunks and list the indexed directories, so Claude Desktop and other MCP clients can use the local files as a knowledge source
+Clients connect over SSE through the local HTTP API, or over stdio by launching the app binary with --mcp, which forwards every message to the running app. Both need the local API enabled in the settings
+*/
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use crate::api_server::{generate_token, ApiState, API_TOKEN_FILE_NAME};
+use crate::file_processor::{get_processor, search_files, FileProcessorState};
+use crate::indexed_directories::list_indexed_directories;
+use crate::settings::DEFAULT_API_SERVER_PORT;
+use crate::vectordb_manager::{chunk_records, VectorDbManager};
+
+/// Protocol revision answered when the client asks for one we don't know
+const PROTOCOL_VERSION: &str = "2024-11-05";
+const SUPPORTED_PROTOCOL_VERSIONS: [&str; 2] = ["2024-11-05", "2025-03-26"];
+
+/// Results returned when the tool call doesn't set a limit
+const DEFAULT_TOOL_LIMIT: usize = 10;
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_UNAVAILABLE: i64 = -32000;
+
+/// Identifier of the app, the stdio bridge finds the API token in the app data directory named after it
+const APP_IDENTIFIER: &str = "com.kita.app";
+
+/// Open SSE sessions, messages posted for a session are answered on its stream
+static SSE_SESSIONS: Mutex<BTreeMap<String, mpsc::UnboundedSender<Value>>> =
+    Mutex::new(BTreeMap::new());
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcRequest {
+    /// Missing on notifications, they get no response
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+fn success(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn failure(id: Value, code: i64, message: impl Into<String>) -> Value {
+    let error = JsonRpcError {
+        code,
+        message: message.into(),
+    };
+    json!({ "jsonrpc": "2.0", "id": id, "error": error })
+}
+
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "search_files",
+            "description": "Searches the names and contents of the files indexed by Kita and returns the matching files with their paths",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Words to search for" },
+                    "limit": { "type": "integer", "description": "Maximum number of files to return", "minimum": 1 }
+                },
+                "required": ["query"]
+            }
+        },
+        {
+            "name": "search_chunks",
+            "description": "Finds the passages of the indexed files closest in meaning to the query, use it to answer questions from the user's documents",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Question or topic to find passages about" },
+                    "limit": { "type": "integer", "description": "Maximum number of passages to return", "minimum": 1 }
+                },
+                "required": ["query"]
+            }
+        },
+        {
+            "name": "fetch_chunk",
+            "description": "Returns the full text of a passage by the id search_chunks returned for it",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string", "description": "Id of the passage" }
+                },
+                "required": ["id"]
+            }
+        },
+        {
+            "name": "list_directories",
+            "description": "Lists the directories Kita indexes with the number of files in each",
+            "inputSchema": { "type": "object", "properties": {} }
+        }
+    ])
+}
+
+fn string_argument(arguments: &Value, name: &str) -> Result<String, String> {
+    arguments
+        .get(name)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| format!("The {} argument is required", name))
+}
+
+fn limit_argument(arguments: &Value) -> usize {
+    arguments
+        .get("limit")
+        .and_then(Value::as_u64)
+        .map(|limit| limit.max(1) as usize)
+        .unwrap_or(DEFAULT_TOOL_LIMIT)
+}
+
+fn to_text<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| e.to_string())
+}
+
+/// Runs the tool and returns its output as text for the client
+async fn call_tool(
+    app_handle: &AppHandle,
+    name: &str,
+    arguments: &Value,
+) -> Result<String, String> {
+    match name {
+        "search_files" => {
+            let query = string_argument(arguments, "query")?;
+            let state = app_handle.state::<FileProcessorState>();
+            let processor = get_processor(&state)?;
+
+            let mut files = search_files(&processor, &query)?;
+            files.truncate(limit_argument(arguments));
+
+            let files: Vec<Value> = files
+                .into_iter()
+                .map(|file| {
+                    json!({
+                        "name": file.base.name,
+                        "path": file.base.path,
+                        "extension": file.extension,
+                        "size": file.size,
+                        "updated_at": file.updated_at,
+                    })
+                })
+                .collect();
+            to_text(&files)
+        }
+        "search_chunks" => {
+            let query = string_argument(arguments, "query")?;
+            let results = VectorDbManager::search_similar(app_handle, &query)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            let mut chunks = chunk_records(&results);
+            chunks.truncate(limit_argument(arguments));
+            to_text(&chunks)
+        }
+        "fetch_chunk" => {
+            let id = string_argument(arguments, "id")?;
+            match VectorDbManager::get_chunk(app_handle, &id)
+                .await
+                .map_err(|e| e.to_string())?
+            {
+                Some(chunk) => to_text(&chunk),
+                None => Err(format!("No passage with the id {}", id)),
+            }
+        }
+        "list_directories" => {
+            let directories = list_indexed_directories(app_handle.state()).await?;
+            to_text(&directories)
+        }
+        _ => Err(format!("Unknown tool {}", name)),
+    }
+}
+
+async fn handle_request(app_handle: &AppHandle, request: JsonRpcRequest) -> Option<Value> {
+    // notifications, like notifications/initialized, need no answer
+    let id = request.id?;
+
+    let response = match request.method.as_str() {
+        "initialize" => {
+            let requested = request
+                .params
+                .get("protocolVersion")
+                .and_then(Value::as_str)
+                .unwrap_or(PROTOCOL_VERSION);
+            let protocol_version = if SUPPORTED_PROTOCOL_VERSIONS.contains(&requested) {
+                requested
+            } else {
+                PROTOCOL_VERSION
+            };
+
+            success(
+                id,
+                json!({
+                    "protocolVersion": protocol_version,
+                    "capabilities": { "tools": {} },
+                    "serverInfo": { "name": "kita", "version": env!("CARGO_PKG_VERSION") },
+                }),
+            )
+        }
+        "ping" => success(id, json!({})),
+        "tools/list" => success(id, json!({ "tools": tool_definitions() })),
+        "tools/call" => {
+            let Some(name) = request.params.get("name").and_then(Value::as_str) else {
+                return Some(failure(id, INVALID_PARAMS, "The tool name is missing"));
+            };
+            let arguments = request
+                .params
+                .get("arguments")
+                .cloned()
+                .unwrap_or_else(|| json!({}));
+
+            if !tool_definitions()
+                .as_array()
+                .is_some_and(|tools| tools.iter().any(|tool| tool["name"] == name))
+            {
+                return Some(failure(
+                    id,
+                    INVALID_PARAMS,
+                    format!("Unknown tool {}", name),
+                ));
+            }
+
+            // tool failures are results so the model can see them
+            let (text, is_error) = match call_tool(app_handle, name, &arguments).await {
+                Ok(text) => (text, false),
+                Err(e) => (e, true),
+            };
+            success(
+                id,
+                json!({
+                    "content": [{ "type": "text", "text": text }],
+                    "isError": is_error,
+                }),
+            )
+        }
+        method => failure(id, METHOD_NOT_FOUND, format!("Unknown method {}", method)),
+    };
+
+    Some(response)
+}
+
+/// Answers one JSON-RPC message, None when it was a notification
+async fn handle_message(app_handle: &AppHandle, body: &str) -> Option<Value> {
+    let message: Value = match serde_json::from_str(body) {
+        Ok(message) => message,
+        Err(e) => return Some(failure(Value::Null, PARSE_ERROR, e.to_string())),
+    };
+
+    match serde_json::from_value::<JsonRpcRequest>(message) {
+        Ok(request) => handle_request(app_handle, request).await,
+        Err(e) => Some(failure(Value::Null, INVALID_REQUEST, e.to_string())),
+    }
+}
+
+/// Plain JSON-RPC over HTTP, one message per request, used by the stdio bridge
+async fn post_message(State(api): State<ApiState>, body: String) -> Response {
+    match handle_message(&api.app_handle, &body).await {
+        Some(response) => Json(response).into_response(),
+        None => StatusCode::ACCEPTED.into_response(),
+    }
+}
+
+/// Removes the session once its stream is dropped, when the client disconnects
+struct SessionGuard(String);
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        SSE_SESSIONS.lock().unwrap().remove(&self.0);
+    }
+}
+
+/// Opens an SSE session, the first event tells the client where to post its messages
+async fn open_sse_session() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let session_id = generate_token();
+    let (sender, receiver) = mpsc::unbounded_channel::<Value>();
+    SSE_SESSIONS
+        .lock()
+        .unwrap()
+        .insert(session_id.clone(), sender);
+
+    let endpoint = Event::default()
+        .event("endpoint")
+        .data(format!("/mcp/messages?sessionId={}", session_id));
+
+    let guard = SessionGuard(session_id);
+    let messages = UnboundedReceiverStream::new(receiver).map(move |message| {
+        let _session = &guard;
+        Event::default().event("message").data(message.to_string())
+    });
+
+    Sse::new(stream::once(async { endpoint }).chain(messages).map(Ok))
+        .keep_alive(KeepAlive::default())
+}
+
+#[derive(Debug, Deserialize)]
+struct SessionParams {
+    #[serde(rename = "sessionId")]
+    session_id: String,
+}
+
+/// Accepts a message for an SSE session, the answer is sent on the session's stream
+async fn post_session_message(
+    State(api): State<ApiState>,
+    Query(params): Query<SessionParams>,
+    body: String,
+) -> Response {
+    let Some(sender) = SSE_SESSIONS
+        .lock()
+        .unwrap()
+        .get(&params.session_id)
+        .cloned()
+    else {
+        return (StatusCode::NOT_FOUND, "Unknown session").into_response();
+    };
+
+    tauri::async_runtime::spawn(async move {
+        if let Some(response) = handle_message(&api.app_handle, &body).await {
+            let _ = sender.send(response);
+        }
+    });
+
+    StatusCode::ACCEPTED.into_response()
+}
+
+/// MCP routes, served by the local API behind its token
+pub fn routes() -> Router<ApiState> {
+    Router::new()
+        .route("/mcp", post(post_message))
+        .route("/mcp/sse", get(open_sse_session))
+        .route("/mcp/messages", post(post_session_message))
+}
+
+/// Port of the local API, from --port or the default one
+fn bridge_port() -> u16 {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|arg| arg == "--port")
+        .and_then(|index| args.get(index + 1))
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(DEFAULT_API_SERVER_PORT)
+}
+
+fn bridge_token() -> Option<String> {
+    let path: PathBuf = dirs::data_dir()?
+        .join(APP_IDENTIFIER)
+        .join(API_TOKEN_FILE_NAME);
+    let token = std::fs::read_to_string(path).ok()?;
+    Some(token.trim().to_string())
+}
+
+/// Error answer for a message the running app couldn't be reached for, notifications get none
+fn unavailable_response(line: &str, message: &str) -> Option<Value> {
+    let id = serde_json::from_str::<Value>(line).ok()?.get("id")?.clone();
+    Some(failure(id, SERVER_UNAVAILABLE, message))
+}
+
+/// Runs the stdio transport, every line read from stdin is forwarded to the running app and the answer written to stdout
+/// Logs go to stderr, stdout only carries protocol messages
+pub fn run_stdio_bridge() {
+    let url = format!("http://127.0.0.1:{}/mcp", bridge_port());
+    let token = bridge_token();
+    if token.is_none() {
+        eprintln!("No API token found, enable the local API in the Kita settings first");
+    }
+
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start the MCP bridge: {}", e);
+            return;
+        }
+    };
+    let client = reqwest::Client::new();
+
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let result = runtime.block_on(async {
+            let response = client
+                .post(&url)
+                .bearer_auth(token.as_deref().unwrap_or_default())
+                .header("Content-Type", "application/json")
+                .body(line.clone())
+                .send()
+                .await?;
+            let status = response.status();
+            let body = response.text().await?;
+            Ok::<_, reqwest::Error>((status, body))
+        });
+
+        let output = match result {
+            Ok((status, _)) if status == reqwest::StatusCode::ACCEPTED => None,
+            Ok((status, body)) if status.is_success() => Some(body),
+            Ok((status, body)) => {
+                eprintln!("The Kita API answered {}: {}", status, body);
+                unavailable_response(&line, "Kita refused the request, check the API token")
+                    .map(|response| response.to_string())
+            }
+            Err(e) => {
+                eprintln!("Failed to reach Kita on {}: {}", url, e);
+                unavailable_response(
+                    &line,
+                    "Kita isn't running or its local API is disabled in the settings",
+                )
+                .map(|response| response.to_string())
+            }
+        };
+
+        if let Some(output) = output {
+            if writeln!(stdout, "{}", output)
+                .and_then(|_| stdout.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+/*
+This file contains the error codes and message keys of the messages shown to the user. Commands and events send a UserMessage with a stable code, the key of its text in the frontend's catalog and the values to fill in, so the frontend can translate it and render every error the same way
+The English text is sent along for clients without the catalog, like the HTTP API, and for the logs
+*/
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    ModelSelectionRequired,
+    ModelDownloadRequired,
+    ModelNotFound,
+    ModelDownloadFailed,
+    LlmStarting,
+    LlmNotRunning,
+    LlmContextFailed,
+    LlmRequestFailed,
+    IndexSaveFailed,
+    IndexChunkingFailed,
+    IndexNoEmbeddings,
+    IndexEmbeddingsFailed,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::ModelSelectionRequired => "model_selection_required",
+            ErrorCode::ModelDownloadRequired => "model_download_required",
+            ErrorCode::ModelNotFound => "model_not_found",
+            ErrorCode::ModelDownloadFailed => "model_download_failed",
+            ErrorCode::LlmStarting => "llm_starting",
+            ErrorCode::LlmNotRunning => "llm_not_running",
+            ErrorCode::LlmContextFailed => "llm_context_failed",
+            ErrorCode::LlmRequestFailed => "llm_request_failed",
+            ErrorCode::IndexSaveFailed => "index_save_failed",
+            ErrorCode::IndexChunkingFailed => "index_chunking_failed",
+            ErrorCode::IndexNoEmbeddings => "index_no_embeddings",
+            ErrorCode::IndexEmbeddingsFailed => "index_embeddings_failed",
+        }
+    }
+
+    /// Parses a code stored with as_str, None for codes this version doesn't know
+    pub fn parse(code: &str) -> Option<Self> {
+        serde_json::from_value(serde_json::Value::String(code.to_string())).ok()
+    }
+
+    /// Key of the message in the frontend's catalog in src/lib/messages.ts
+    pub fn key(self) -> &'static str {
+        match self {
+            ErrorCode::ModelSelectionRequired => "model.selection_required",
+            ErrorCode::ModelDownloadRequired => "model.download_required",
+            ErrorCode::ModelNotFound => "model.not_found",
+            ErrorCode::ModelDownloadFailed => "model.download_failed",
+            ErrorCode::LlmStarting => "llm.starting",
+            ErrorCode::LlmNotRunning => "llm.not_running",
+            ErrorCode::LlmContextFailed => "llm.context_failed",
+            ErrorCode::LlmRequestFailed => "llm.request_failed",
+            ErrorCode::IndexSaveFailed => "indexing.save_failed",
+            ErrorCode::IndexChunkingFailed => "indexing.chunking_failed",
+            ErrorCode::IndexNoEmbeddings => "indexing.no_embeddings",
+            ErrorCode::IndexEmbeddingsFailed => "indexing.embeddings_failed",
+        }
+    }
+
+    /// English text, the names in braces are filled in with the params of the message
+    fn template(self) -> &'static str {
+        match self {
+            ErrorCode::ModelSelectionRequired => "Please select a model to use for AI features",
+            ErrorCode::ModelDownloadRequired => {
+                "The selected model '{model}' needs to be downloaded before use"
+            }
+            ErrorCode::ModelNotFound => {
+                "The previously selected model is no longer available. Please select a new model."
+            }
+            ErrorCode::ModelDownloadFailed => "Failed to download the model: {error}",
+            ErrorCode::LlmStarting => "The LLM server is starting, please try again in a moment.",
+            ErrorCode::LlmNotRunning => {
+                "No LLM server is currently running. Please select a model first."
+            }
+            ErrorCode::LlmContextFailed => "Failed to find the context for the question: {error}",
+            ErrorCode::LlmRequestFailed => "Failed to get response: {error}",
+            ErrorCode::IndexSaveFailed => "File processing error: {error}",
+            ErrorCode::IndexChunkingFailed => "Chunking/embedding error: {error}",
+            ErrorCode::IndexNoEmbeddings => "No valid embeddings generated",
+            ErrorCode::IndexEmbeddingsFailed => "Failed to store the embeddings: {error}",
+        }
+    }
+}
+
+/// A message for the user, sent as the error of commands and the payload of events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserMessage {
+    pub code: ErrorCode,
+    pub key: String,
+    /// Values for the placeholders of the message, like the model name
+    pub params: BTreeMap<String, String>,
+    /// The message in English
+    pub message: String,
+}
+
+impl UserMessage {
+    pub fn new(code: ErrorCode) -> Self {
+        UserMessage {
+            code,
+            key: code.key().to_string(),
+            params: BTreeMap::new(),
+            message: code.template().to_string(),
+        }
+    }
+
+    /// Sets the value of a placeholder, like `{model}`
+    pub fn with_param(mut self, name: &str, value: impl ToString) -> Self {
+        self.params.insert(name.to_string(), value.to_string());
+        self.message = self.params.iter().fold(
+            self.code.template().to_string(),
+            |message, (name, value)| message.replace(&format!("{{{}}}", name), value),
+        );
+        self
+    }
+}
+
+impl fmt::Display for UserMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<UserMessage> for String {
+    fn from(message: UserMessage) -> Self {
+        message.message
+    }
+}
@@ -0,0 +1,281 @@
+/*
+This file contains functions to benchmark a downloaded LLM model against a standard prompt set so users can compare quantizations on their own hardware
+*/
+
+use futures_util::StreamExt;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use sysinfo::{ProcessExt, System, SystemExt};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::model_registry::ModelRegistry;
+use crate::server::LLMServer;
+
+// runs on its own port so that it doesn't conflict with the main llm server
+const BENCHMARK_PORT: u16 = 8081;
+const BENCHMARK_N_PREDICT: i32 = 128;
+const MEMORY_SAMPLE_INTERVAL_MS: u64 = 250;
+
+const BENCHMARK_PROMPTS: [&str; 4] = [
+    "Summarize the main benefits of keeping personal documents organized in a few sentences.",
+    "Explain the difference between a process and a thread to a new programmer.",
+    "Write a short, friendly email asking a colleague to review a document by Friday.",
+    "List five practical tips for reducing battery usage on a laptop.",
+];
+
+#[derive(Debug, Serialize)]
+struct BenchmarkCompletionRequest {
+    prompt: String,
+    n_predict: i32,
+    temperature: f32,
+    stream: bool,
+    cache_prompt: bool,
+}
+
+/// Streamed chunk from the llama.cpp /completion endpoint
+#[derive(Debug, Deserialize)]
+struct StreamChunk {
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    stop: bool,
+    timings: Option<StreamTimings>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamTimings {
+    predicted_n: Option<u64>,
+    predicted_ms: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptBenchmark {
+    pub prompt: String,
+    pub tokens_generated: u64,
+    pub tokens_per_second: f64,
+    pub time_to_first_token_ms: f64,
+    pub total_time_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    pub model_id: String,
+    pub model_name: String,
+    pub quantization: String,
+    pub load_time_ms: f64,
+    pub avg_tokens_per_second: f64,
+    pub avg_time_to_first_token_ms: f64,
+    pub peak_memory_bytes: u64,
+    pub prompts: Vec<PromptBenchmark>,
+}
+
+/// Benchmark progress data structure
+#[derive(Clone, Serialize, Deserialize)]
+struct BenchmarkProgress {
+    model_id: String,
+    completed: usize,
+    total: usize,
+}
+
+/// Runs a single prompt against the server and measures time to first token and generation speed
+async fn run_prompt(client: &Client, port: u16, prompt: &str) -> Result<PromptBenchmark, String> {
+    let url = format!("http://127.0.0.1:{}/completion", port);
+
+    let request = BenchmarkCompletionRequest {
+        prompt: format!("<s>[INST] {} [/INST]", prompt),
+        n_predict: BENCHMARK_N_PREDICT,
+        temperature: 0.0,
+        stream: true,
+        cache_prompt: false,
+    };
+
+    let start = Instant::now();
+    let response = client
+        .post(&url)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| format!("Benchmark request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Server returned error {}", response.status()));
+    }
+
+    let mut first_token_at: Option<Duration> = None;
+    let mut streamed_tokens: u64 = 0;
+    let mut timings: Option<StreamTimings> = None;
+    let mut buffer = String::new();
+    let mut stream = response.bytes_stream();
+
+    // server-sent events are separated by newlines, each event is "data: {json}"
+    while let Some(item) = stream.next().await {
+        let bytes = item.map_err(|e| format!("Benchmark stream error: {}", e))?;
+        buffer.push_str(&String::from_utf8_lossy(&bytes));
+
+        while let Some(newline_idx) = buffer.find('\n') {
+            let line: String = buffer.drain(..=newline_idx).collect();
+            let Some(data) = line.trim().strip_prefix("data:") else {
+                continue;
+            };
+
+            let chunk: StreamChunk = match serde_json::from_str(data.trim()) {
+                Ok(chunk) => chunk,
+                Err(_) => continue,
+            };
+
+            if !chunk.content.is_empty() {
+                if first_token_at.is_none() {
+                    first_token_at = Some(start.elapsed());
+                }
+                streamed_tokens += 1;
+            }
+
+            if chunk.stop {
+                timings = chunk.timings;
+            }
+        }
+    }
+
+    let total_time = start.elapsed();
+    let time_to_first_token = first_token_at.unwrap_or(total_time);
+
+    // prefer the server's own timings, fall back to counting streamed tokens
+    let (tokens_generated, tokens_per_second) = match timings {
+        Some(StreamTimings {
+            predicted_n: Some(n),
+            predicted_ms: Some(ms),
+        }) if ms > 0.0 => (n, n as f64 / (ms / 1000.0)),
+        _ => {
+            let generation_secs = (total_time - time_to_first_token).as_secs_f64();
+            let tps = if generation_secs > 0.0 {
+                streamed_tokens as f64 / generation_secs
+            } else {
+                0.0
+            };
+            (streamed_tokens, tps)
+        }
+    };
+
+    Ok(PromptBenchmark {
+        prompt: prompt.to_string(),
+        tokens_generated,
+        tokens_per_second,
+        time_to_first_token_ms: time_to_first_token.as_secs_f64() * 1000.0,
+        total_time_ms: total_time.as_secs_f64() * 1000.0,
+    })
+}
+
+/// Samples the memory of the server process until stopped and keeps track of the peak value
+fn spawn_memory_sampler(pid: u32, peak: Arc<AtomicU64>, running: Arc<AtomicBool>) {
+    tokio::spawn(async move {
+        let mut system = System::new();
+        let sys_pid = sysinfo::Pid::from(pid as usize);
+
+        while running.load(Ordering::SeqCst) {
+            system.refresh_process(sys_pid);
+            if let Some(process) = system.process(sys_pid) {
+                peak.fetch_max(process.memory(), Ordering::SeqCst);
+            }
+            tokio::time::sleep(Duration::from_millis(MEMORY_SAMPLE_INTERVAL_MS)).await;
+        }
+    });
+}
+
+fn average(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0usize), |(sum, count), v| (sum + v, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f64
+    }
+}
+
+#[tauri::command]
+pub async fn benchmark_model(
+    app_handle: AppHandle,
+    model_id: String,
+) -> Result<BenchmarkResult, String> {
+    let registry = app_handle
+        .try_state::<ModelRegistry>()
+        .ok_or_else(|| "Model registry is not initialized".to_string())?;
+
+    let model = registry
+        .get_model(&model_id)
+        .ok_or_else(|| format!("Model {} not found", model_id))?;
+
+    if !model.is_downloaded {
+        return Err(format!(
+            "The model '{}' needs to be downloaded before it can be benchmarked",
+            model.name
+        ));
+    }
+
+    let mut server = LLMServer::with_port(app_handle.clone(), BENCHMARK_PORT)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    server
+        .set_model_path(&model.path)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // loading the model is part of the benchmark since it differs a lot between quantizations
+    let load_start = Instant::now();
+    server
+        .start(&model.name)
+        .await
+        .map_err(|e| format!("Failed to load model for benchmark: {}", e))?;
+    let load_time_ms = load_start.elapsed().as_secs_f64() * 1000.0;
+
+    let peak_memory = Arc::new(AtomicU64::new(0));
+    let sampler_running = Arc::new(AtomicBool::new(true));
+    if let Some(pid) = server.pid() {
+        spawn_memory_sampler(pid, peak_memory.clone(), sampler_running.clone());
+    }
+
+    let client = Client::new();
+    let mut prompt_results: Vec<PromptBenchmark> = Vec::with_capacity(BENCHMARK_PROMPTS.len());
+    let mut benchmark_error: Option<String> = None;
+
+    for (i, prompt) in BENCHMARK_PROMPTS.iter().enumerate() {
+        match run_prompt(&client, server.port(), prompt).await {
+            Ok(result) => prompt_results.push(result),
+            Err(e) => {
+                benchmark_error = Some(e);
+                break;
+            }
+        }
+
+        let _ = app_handle.emit(
+            "model-benchmark-progress",
+            BenchmarkProgress {
+                model_id: model_id.clone(),
+                completed: i + 1,
+                total: BENCHMARK_PROMPTS.len(),
+            },
+        );
+    }
+
+    sampler_running.store(false, Ordering::SeqCst);
+    let _ = server.stop().await;
+
+    if let Some(e) = benchmark_error {
+        return Err(e);
+    }
+
+    Ok(BenchmarkResult {
+        model_id: model.id,
+        model_name: model.name,
+        quantization: model.quantization,
+        load_time_ms,
+        avg_tokens_per_second: average(prompt_results.iter().map(|r| r.tokens_per_second)),
+        avg_time_to_first_token_ms: average(
+            prompt_results.iter().map(|r| r.time_to_first_token_ms),
+        ),
+        peak_memory_bytes: peak_memory.load(Ordering::SeqCst),
+        prompts: prompt_results,
+    })
+}
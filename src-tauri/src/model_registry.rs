@@ -11,8 +11,13 @@ use std::path::PathBuf;
 use std::sync::Mutex;
 use tauri::{AppHandle, Emitter, Manager, State};
 use thiserror::Error;
+use tracing::error;
+
+use crate::messages::{ErrorCode, UserMessage};
+use crate::settings::SettingsManagerState;
 
 const MODEL_FOLDER_NAME: &str = "models";
+const HF_DEFAULT_BASE_URL: &str = "https://huggingface.co";
 
 #[derive(Error, Debug)]
 pub enum ModelRegistryError {
@@ -244,11 +249,31 @@ fn get_models_dir(app_handle: &AppHandle, custom_path: Option<&str>) -> Result<P
 }
 
 /// Get HuggingFace download URL for a model
-fn get_hf_download_url(repo_id: &str, filename: &str) -> String {
-    format!(
-        "https://huggingface.co/{}/resolve/main/{}",
-        repo_id, filename
-    )
+/// If a mirror base url is configured (i.e. https://hf-mirror.com) it is used instead of huggingface.co
+fn get_hf_download_url(base_url: Option<&str>, repo_id: &str, filename: &str) -> String {
+    let base_url = base_url
+        .map(|url| url.trim().trim_end_matches('/'))
+        .filter(|url| !url.is_empty())
+        .unwrap_or(HF_DEFAULT_BASE_URL);
+
+    format!("{}/{}/resolve/main/{}", base_url, repo_id, filename)
+}
+
+/// Get the optional HuggingFace token and mirror url from the user's settings
+fn get_hf_download_settings(app_handle: &AppHandle) -> (Option<String>, Option<String>) {
+    match app_handle.try_state::<SettingsManagerState>() {
+        Some(settings_state) => match settings_state.0.get_settings() {
+            Ok(settings) => (
+                settings.hf_token.filter(|token| !token.trim().is_empty()),
+                settings.hf_mirror_url,
+            ),
+            Err(e) => {
+                error!("Unable to read HuggingFace settings: {}", e);
+                (None, None)
+            }
+        },
+        None => (None, None),
+    }
 }
 
 /// Model downloade Progress data structure
@@ -258,6 +283,14 @@ struct DownloadProgress {
     model_id: String,
 }
 
+/// Payload of model-download-error, `error` is the English text of the message
+#[derive(Clone, Serialize, Deserialize)]
+struct ModelDownloadError {
+    model_id: String,
+    error: String,
+    message: UserMessage,
+}
+
 /// Download a model from HuggingFace with option to place model in custom path
 async fn download_model_from_hf(
     app_handle: &AppHandle,
@@ -296,14 +329,32 @@ async fn download_model_from_hf(
         fs::remove_file(&temp_path)?;
     }
 
-    // Get download URL
-    let url = get_hf_download_url(&model_info.repo_id, &model_info.filename);
+    // Get download URL, gated models need the user's HuggingFace token
+    let (hf_token, hf_mirror_url) = get_hf_download_settings(app_handle);
+    let url = get_hf_download_url(
+        hf_mirror_url.as_deref(),
+        &model_info.repo_id,
+        &model_info.filename,
+    );
 
     // Start download
     let client = Client::new();
-    let res = client.get(&url).send().await?;
+    let mut request = client.get(&url);
+    if let Some(token) = hf_token {
+        request = request.bearer_auth(token.trim());
+    }
+    let res = request.send().await?;
 
     // Check response
+    if res.status() == reqwest::StatusCode::UNAUTHORIZED
+        || res.status() == reqwest::StatusCode::FORBIDDEN
+    {
+        return Err(ModelRegistryError::DownloadFailed(format!(
+            "Server returned: {}. This model may be gated, check your HuggingFace token",
+            res.status()
+        )));
+    }
+
     if !res.status().is_success() {
         return Err(ModelRegistryError::DownloadFailed(format!(
             "Server returned: {}",
@@ -423,14 +474,17 @@ pub async fn start_model_download(
                 let _ = app_handle_clone.emit("model-download-complete", model_id_clone);
             }
             Err(e) => {
-                eprintln!("Download error: {}", e);
+                error!("Download error: {}", e);
                 // Notify frontend of error
+                let message =
+                    UserMessage::new(ErrorCode::ModelDownloadFailed).with_param("error", &e);
                 let _ = app_handle_clone.emit(
                     "model-download-error",
-                    serde_json::json!({
-                        "model_id": model_id_clone,
-                        "error": e.to_string()
-                    }),
+                    ModelDownloadError {
+                        model_id: model_id_clone,
+                        error: message.message.clone(),
+                        message,
+                    },
                 );
             }
         }
@@ -0,0 +1,155 @@
+/*
+This file contains the history of results the user opened. Every open is stored in the open_history table together with the query that led to it, which is used for the recent items and to rank results the user opens often
+*/
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::State;
+use tokio::task;
+
+use crate::database_handler::open_connection;
+use crate::favorites::ItemKind;
+use crate::file_processor::{get_processor, FileProcessor, FileProcessorError, FileProcessorState};
+
+/// Number of recent items returned when the caller doesn't ask for a specific number
+const DEFAULT_RECENT_ITEMS_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentItem {
+    pub kind: ItemKind,
+    pub path: String,
+    pub name: String,
+    pub last_query: Option<String>,
+    pub last_opened_at: String,
+    pub open_count: i64,
+}
+
+/// Records an open, used by the commands that open results themselves like launching apps
+pub async fn save_open(
+    db_path: PathBuf,
+    kind: ItemKind,
+    path: String,
+    name: String,
+    query: Option<String>,
+) -> Result<(), FileProcessorError> {
+    // opening from the empty launcher isn't a search, store it without a query
+    let query = query
+        .map(|q| q.trim().to_string())
+        .filter(|q| !q.is_empty());
+
+    task::spawn_blocking(move || -> Result<(), FileProcessorError> {
+        let conn = open_connection(db_path)?;
+
+        conn.execute(
+            "INSERT INTO open_history (kind, path, name, query) VALUES (?1, ?2, ?3, ?4)",
+            params![kind.as_str(), path, name, query],
+        )?;
+
+        Ok(())
+    })
+    .await
+    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
+}
+
+/// Scores every app the user opened by how often and how recently they opened it
+/// Each open counts for less the older it is, so an app used a lot last year ranks below one used daily this week
+pub fn load_app_frecency(conn: &Connection) -> rusqlite::Result<HashMap<String, i64>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT path, SUM(
+            CASE
+                WHEN opened_at >= datetime('now', '-4 days') THEN 100
+                WHEN opened_at >= datetime('now', '-14 days') THEN 70
+                WHEN opened_at >= datetime('now', '-31 days') THEN 50
+                WHEN opened_at >= datetime('now', '-90 days') THEN 30
+                ELSE 10
+            END
+        )
+        FROM open_history
+        WHERE kind = ?1
+        GROUP BY path
+        "#,
+    )?;
+
+    let rows = stmt.query_map([ItemKind::App.as_str()], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+    })?;
+    rows.collect()
+}
+
+/// Records that the user opened a result, called by the frontend after the file was opened
+#[tauri::command]
+pub async fn record_opened_item(
+    kind: ItemKind,
+    path: String,
+    name: String,
+    query: Option<String>,
+    state: State<'_, FileProcessorState>,
+) -> Result<(), String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    save_open(processor.db_path.clone(), kind, path, name, query)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Returns the most recently opened items, each item once with the number of times it was opened
+#[tauri::command]
+pub async fn get_recent_items(
+    limit: Option<usize>,
+    state: State<'_, FileProcessorState>,
+) -> Result<Vec<RecentItem>, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let conn = processor.get_read_connection()?;
+
+    // the bare columns come from the row with MAX(id), which is the latest open of the item
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT kind, path, name, query, opened_at, MAX(id), COUNT(*)
+            FROM open_history
+            GROUP BY kind, path
+            ORDER BY MAX(id) DESC
+            LIMIT ?1
+            "#,
+        )
+        .map_err(|e| format!("Failed to prepare statement: {e}"))?;
+
+    let limit = limit.unwrap_or(DEFAULT_RECENT_ITEMS_LIMIT) as i64;
+
+    let rows = stmt
+        .query_map([limit], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, i64>(6)?,
+            ))
+        })
+        .map_err(|e| format!("Query error: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Row error: {e}"))?;
+
+    let items = rows
+        .into_iter()
+        .filter_map(
+            |(kind, path, name, last_query, last_opened_at, open_count)| {
+                Some(RecentItem {
+                    kind: ItemKind::from_db(&kind)?,
+                    path,
+                    name,
+                    last_query,
+                    last_opened_at,
+                    open_count,
+                })
+            },
+        )
+        .collect();
+
+    Ok(items)
+}
@@ -0,0 +1,572 @@
+/*
+This file contains the WASM plugin host. Plugins are loaded from the plugins directory in the app data directory and can add chunkers for new file types, actions on search results and launcher providers without changes to the Rust core
+Every plugin is a folder with a plugin.json manifest and a plugin.wasm module built for wasm32-wasip1. Plugins run under wasmtime with limited memory and fuel, and only see the directories their manifest asks for, read only, plus their own data directory at /data
+
+The interface, version 1, passes JSON through the plugin's memory:
+- the plugin exports `memory`, `kita_alloc(len: i32) -> i32` and every function of its capabilities
+- each function takes the pointer and length of the JSON input and returns the pointer of the JSON output in the high 32 bits and its length in the low 32 bits
+- chunker: `kita_chunk` gets `{"path", "chunk_size", "chunk_overlap"}` and returns `{"chunks": [{"content", "page_number"?, "section"?}]}`, the file's directory is readable while it runs
+- actions: `kita_actions` gets `{"path"}` and returns `[{"id", "title"}]`, `kita_run_action` gets `{"id", "path"}` and returns `{"message"?, "open_url"?}`
+- provider: `kita_provide` gets `{"query"}` and returns `[{"id", "title", "subtitle"?}]`, picking a result runs it with `kita_run_action` and no path
+*/
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use tauri::{AppHandle, Manager};
+use thiserror::Error;
+use tracing::{error, info, warn};
+use wasmtime::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+use wasmtime_wasi::preview1::{self, WasiP1Ctx};
+use wasmtime_wasi::{DirPerms, FilePerms, WasiCtxBuilder};
+
+use crate::AppResult;
+
+/// Version of the interface above, plugins built for another version aren't loaded
+pub const PLUGIN_API_VERSION: u32 = 1;
+
+const PLUGINS_DIR_NAME: &str = "plugins";
+const MANIFEST_FILE_NAME: &str = "plugin.json";
+const MODULE_FILE_NAME: &str = "plugin.wasm";
+const DATA_DIR_NAME: &str = "data";
+
+/// Memory a plugin can grow to during one call
+const MAX_PLUGIN_MEMORY_BYTES: usize = 256 * 1024 * 1024;
+
+/// Instructions a plugin can run during one call, stops plugins stuck in a loop
+const MAX_PLUGIN_FUEL: u64 = 10_000_000_000;
+
+/// Largest JSON output read back from a plugin
+const MAX_PLUGIN_OUTPUT_BYTES: usize = 64 * 1024 * 1024;
+
+#[derive(Error, Debug)]
+pub enum PluginError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid manifest: {0}")]
+    Manifest(String),
+
+    #[error("WASM error: {0}")]
+    Wasm(String),
+
+    #[error("Invalid plugin output: {0}")]
+    Output(String),
+
+    #[error("Plugin {0} isn't loaded")]
+    NotFound(String),
+}
+
+impl From<wasmtime::Error> for PluginError {
+    fn from(e: wasmtime::Error) -> Self {
+        PluginError::Wasm(format!("{e:#}"))
+    }
+}
+
+pub type PluginResult<T> = Result<T, PluginError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginCapability {
+    Chunker,
+    Actions,
+    Provider,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginPermissions {
+    /// Directories the plugin can read, `~` is the home directory
+    #[serde(default)]
+    pub read_dirs: Vec<String>,
+    /// Gives the plugin a writable directory at /data that is kept between calls
+    #[serde(default)]
+    pub data_dir: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub api_version: u32,
+    pub capabilities: Vec<PluginCapability>,
+    /// File extensions handled by the chunker, without the dot
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[serde(default)]
+    pub permissions: PluginPermissions,
+}
+
+impl PluginManifest {
+    fn validate(&self) -> PluginResult<()> {
+        if self.api_version != PLUGIN_API_VERSION {
+            return Err(PluginError::Manifest(format!(
+                "api_version {} isn't supported, expected {}",
+                self.api_version, PLUGIN_API_VERSION
+            )));
+        }
+        if self.name.trim().is_empty() {
+            return Err(PluginError::Manifest("name can't be empty".to_string()));
+        }
+        if self.capabilities.contains(&PluginCapability::Chunker) && self.extensions.is_empty() {
+            return Err(PluginError::Manifest(
+                "a chunker needs at least one extension".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn has_capability(&self, capability: PluginCapability) -> bool {
+        self.capabilities.contains(&capability)
+    }
+}
+
+/// A loaded plugin, the module is compiled once and instantiated for every call
+pub struct Plugin {
+    pub manifest: PluginManifest,
+    dir: PathBuf,
+    module: Module,
+}
+
+/// State of a plugin during one call
+struct PluginCtx {
+    wasi: WasiP1Ctx,
+    limits: StoreLimits,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginInfo {
+    /// Name of the plugin's folder
+    pub dir_name: String,
+    pub manifest: Option<PluginManifest>,
+    /// Why the plugin couldn't be loaded
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginChunk {
+    pub content: String,
+    #[serde(default)]
+    pub page_number: Option<usize>,
+    #[serde(default)]
+    pub section: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkOutput {
+    chunks: Vec<PluginChunk>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginAction {
+    pub plugin: String,
+    pub id: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginActionResult {
+    #[serde(default)]
+    pub message: Option<String>,
+    /// URL or file the frontend should open
+    #[serde(default)]
+    pub open_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginProviderResult {
+    pub plugin: String,
+    pub id: String,
+    pub title: String,
+    pub subtitle: Option<String>,
+}
+
+/// What the plugin returns for kita_actions and kita_provide, the plugin name is added by the host
+#[derive(Debug, Deserialize)]
+struct PluginItem {
+    id: String,
+    title: String,
+    #[serde(default)]
+    subtitle: Option<String>,
+}
+
+#[derive(Default)]
+struct PluginRegistry {
+    loaded: Vec<Arc<Plugin>>,
+    infos: Vec<PluginInfo>,
+}
+
+static PLUGINS: Mutex<PluginRegistry> = Mutex::new(PluginRegistry {
+    loaded: Vec::new(),
+    infos: Vec::new(),
+});
+
+static ENGINE: OnceLock<Engine> = OnceLock::new();
+static PLUGINS_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+fn engine() -> PluginResult<&'static Engine> {
+    if let Some(engine) = ENGINE.get() {
+        return Ok(engine);
+    }
+
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config)?;
+    Ok(ENGINE.get_or_init(|| engine))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+impl Plugin {
+    fn load(dir: &Path) -> PluginResult<Plugin> {
+        let manifest: PluginManifest =
+            serde_json::from_str(&fs::read_to_string(dir.join(MANIFEST_FILE_NAME))?)
+                .map_err(|e| PluginError::Manifest(e.to_string()))?;
+        manifest.validate()?;
+
+        let module = Module::from_file(engine()?, dir.join(MODULE_FILE_NAME))?;
+
+        Ok(Plugin {
+            manifest,
+            dir: dir.to_path_buf(),
+            module,
+        })
+    }
+
+    /// Builds the store of one call, the plugin sees the directories of its manifest and `extra_read_dirs`, all read only, and its data directory
+    fn store(&self, extra_read_dirs: &[PathBuf]) -> PluginResult<Store<PluginCtx>> {
+        let mut builder = WasiCtxBuilder::new();
+
+        let read_dirs = self
+            .manifest
+            .permissions
+            .read_dirs
+            .iter()
+            .map(|dir| expand_home(dir))
+            .chain(extra_read_dirs.iter().cloned());
+        for dir in read_dirs {
+            if !dir.is_dir() {
+                continue;
+            }
+            // the plugin sees the directory at its real path so the paths it gets are valid as is
+            builder.preopened_dir(&dir, dir.to_string_lossy(), DirPerms::READ, FilePerms::READ)?;
+        }
+
+        if self.manifest.permissions.data_dir {
+            let data_dir = self.dir.join(DATA_DIR_NAME);
+            fs::create_dir_all(&data_dir)?;
+            builder.preopened_dir(&data_dir, "/data", DirPerms::all(), FilePerms::all())?;
+        }
+
+        let mut store = Store::new(
+            self.module.engine(),
+            PluginCtx {
+                wasi: builder.build_p1(),
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(MAX_PLUGIN_MEMORY_BYTES)
+                    .build(),
+            },
+        );
+        store.limiter(|ctx| &mut ctx.limits);
+        store.set_fuel(MAX_PLUGIN_FUEL)?;
+        Ok(store)
+    }
+
+    /// Calls an exported function of the plugin with the JSON input and parses its JSON output
+    /// Blocks while the plugin runs, so it's called from spawn_blocking
+    fn call<I: Serialize, O: DeserializeOwned>(
+        &self,
+        function: &str,
+        input: &I,
+        extra_read_dirs: &[PathBuf],
+    ) -> PluginResult<O> {
+        let mut store = self.store(extra_read_dirs)?;
+
+        let mut linker: Linker<PluginCtx> = Linker::new(self.module.engine());
+        preview1::add_to_linker_sync(&mut linker, |ctx: &mut PluginCtx| &mut ctx.wasi)?;
+        let instance = linker.instantiate(&mut store, &self.module)?;
+
+        // reactor modules set up their runtime in _initialize
+        if let Ok(initialize) = instance.get_typed_func::<(), ()>(&mut store, "_initialize") {
+            initialize.call(&mut store, ())?;
+        }
+
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| PluginError::Wasm("the plugin doesn't export its memory".to_string()))?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "kita_alloc")?;
+        let func = instance.get_typed_func::<(i32, i32), i64>(&mut store, function)?;
+
+        let input = serde_json::to_vec(input).map_err(|e| PluginError::Output(e.to_string()))?;
+        let input_len = i32::try_from(input.len())
+            .map_err(|_| PluginError::Wasm("the input is too large".to_string()))?;
+        let input_ptr = alloc.call(&mut store, input_len)?;
+        memory
+            .write(&mut store, input_ptr as u32 as usize, &input)
+            .map_err(|e| PluginError::Wasm(e.to_string()))?;
+
+        let packed = func.call(&mut store, (input_ptr, input_len))? as u64;
+        let output_ptr = (packed >> 32) as usize;
+        let output_len = (packed & 0xFFFF_FFFF) as usize;
+        if output_len > MAX_PLUGIN_OUTPUT_BYTES {
+            return Err(PluginError::Output(format!(
+                "{} returned {} bytes, the limit is {}",
+                function, output_len, MAX_PLUGIN_OUTPUT_BYTES
+            )));
+        }
+
+        let mut output = vec![0u8; output_len];
+        memory
+            .read(&store, output_ptr, &mut output)
+            .map_err(|e| PluginError::Wasm(e.to_string()))?;
+
+        serde_json::from_slice(&output).map_err(|e| PluginError::Output(e.to_string()))
+    }
+
+    /// Chunks the file with the plugin, only the file's directory is readable besides the manifest's directories
+    pub fn chunk(
+        &self,
+        path: &Path,
+        chunk_size: usize,
+        chunk_overlap: usize,
+    ) -> PluginResult<Vec<PluginChunk>> {
+        let file_dir: Vec<PathBuf> = path.parent().map(Path::to_path_buf).into_iter().collect();
+
+        let output: ChunkOutput = self.call(
+            "kita_chunk",
+            &serde_json::json!({
+                "path": path,
+                "chunk_size": chunk_size,
+                "chunk_overlap": chunk_overlap,
+            }),
+            &file_dir,
+        )?;
+        Ok(output.chunks)
+    }
+
+    fn actions(&self, path: &str) -> PluginResult<Vec<PluginAction>> {
+        let items: Vec<PluginItem> =
+            self.call("kita_actions", &serde_json::json!({ "path": path }), &[])?;
+
+        Ok(items
+            .into_iter()
+            .map(|item| PluginAction {
+                plugin: self.manifest.name.clone(),
+                id: item.id,
+                title: item.title,
+            })
+            .collect())
+    }
+
+    fn run_action(&self, id: &str, path: Option<&str>) -> PluginResult<PluginActionResult> {
+        self.call(
+            "kita_run_action",
+            &serde_json::json!({ "id": id, "path": path }),
+            &[],
+        )
+    }
+
+    fn provide(&self, query: &str) -> PluginResult<Vec<PluginProviderResult>> {
+        let items: Vec<PluginItem> =
+            self.call("kita_provide", &serde_json::json!({ "query": query }), &[])?;
+
+        Ok(items
+            .into_iter()
+            .map(|item| PluginProviderResult {
+                plugin: self.manifest.name.clone(),
+                id: item.id,
+                title: item.title,
+                subtitle: item.subtitle,
+            })
+            .collect())
+    }
+}
+
+/// Loads every plugin in the plugins directory, replacing the plugins loaded before
+fn load_plugins(plugins_dir: &Path) -> PluginResult<()> {
+    fs::create_dir_all(plugins_dir)?;
+
+    let mut registry = PluginRegistry::default();
+    let mut dirs: Vec<PathBuf> = fs::read_dir(plugins_dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+
+    for dir in dirs {
+        let dir_name = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        match Plugin::load(&dir) {
+            Ok(plugin) => {
+                if registry
+                    .loaded
+                    .iter()
+                    .any(|loaded| loaded.manifest.name == plugin.manifest.name)
+                {
+                    warn!("Skipping plugin {}, the name is already used", dir_name);
+                    registry.infos.push(PluginInfo {
+                        dir_name,
+                        manifest: Some(plugin.manifest),
+                        error: Some("Another plugin has the same name".to_string()),
+                    });
+                    continue;
+                }
+
+                info!(
+                    "Loaded plugin {} {}",
+                    plugin.manifest.name, plugin.manifest.version
+                );
+                registry.infos.push(PluginInfo {
+                    dir_name,
+                    manifest: Some(plugin.manifest.clone()),
+                    error: None,
+                });
+                registry.loaded.push(Arc::new(plugin));
+            }
+            Err(e) => {
+                error!("Failed to load plugin {}: {}", dir_name, e);
+                registry.infos.push(PluginInfo {
+                    dir_name,
+                    manifest: None,
+                    error: Some(e.to_string()),
+                });
+            }
+        }
+    }
+
+    *PLUGINS.lock().unwrap() = registry;
+    Ok(())
+}
+
+/// Loads the plugins at startup, a broken plugin is reported by list_plugins instead of failing the startup
+pub fn init_plugins(app: &tauri::App) -> AppResult<()> {
+    let plugins_dir = app.path().app_data_dir()?.join(PLUGINS_DIR_NAME);
+    let _ = PLUGINS_DIR.set(plugins_dir.clone());
+
+    if let Err(e) = load_plugins(&plugins_dir) {
+        error!("Failed to load the plugins: {}", e);
+    }
+    Ok(())
+}
+
+fn plugins_with(capability: PluginCapability) -> Vec<Arc<Plugin>> {
+    PLUGINS
+        .lock()
+        .unwrap()
+        .loaded
+        .iter()
+        .filter(|plugin| plugin.manifest.has_capability(capability))
+        .cloned()
+        .collect()
+}
+
+/// Plugins that add a chunker, registered by the ChunkerOrchestrator after the built in chunkers
+pub fn chunker_plugins() -> Vec<Arc<Plugin>> {
+    plugins_with(PluginCapability::Chunker)
+}
+
+fn find_plugin(name: &str) -> Result<Arc<Plugin>, String> {
+    PLUGINS
+        .lock()
+        .unwrap()
+        .loaded
+        .iter()
+        .find(|plugin| plugin.manifest.name == name)
+        .cloned()
+        .ok_or_else(|| PluginError::NotFound(name.to_string()).to_string())
+}
+
+#[tauri::command]
+pub fn list_plugins() -> Vec<PluginInfo> {
+    PLUGINS.lock().unwrap().infos.clone()
+}
+
+/// Loads the plugins again, after one was added, removed or updated
+#[tauri::command]
+pub async fn reload_plugins(app_handle: AppHandle) -> Result<Vec<PluginInfo>, String> {
+    let plugins_dir = match PLUGINS_DIR.get() {
+        Some(dir) => dir.clone(),
+        None => app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| e.to_string())?
+            .join(PLUGINS_DIR_NAME),
+    };
+
+    tokio::task::spawn_blocking(move || load_plugins(&plugins_dir))
+        .await
+        .map_err(|e| format!("spawn_blocking error: {e}"))?
+        .map_err(|e| e.to_string())?;
+
+    Ok(list_plugins())
+}
+
+/// Actions the plugins offer for a search result, a failing plugin is logged and left out
+#[tauri::command]
+pub async fn get_plugin_actions(path: String) -> Result<Vec<PluginAction>, String> {
+    let plugins = plugins_with(PluginCapability::Actions);
+
+    tokio::task::spawn_blocking(move || {
+        let mut actions = Vec::new();
+        for plugin in plugins {
+            match plugin.actions(&path) {
+                Ok(plugin_actions) => actions.extend(plugin_actions),
+                Err(e) => error!(
+                    "Plugin {} failed to list actions: {}",
+                    plugin.manifest.name, e
+                ),
+            }
+        }
+        actions
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking error: {e}"))
+}
+
+/// Runs an action of a search result, or a launcher result when there's no path
+#[tauri::command]
+pub async fn run_plugin_action(
+    plugin: String,
+    id: String,
+    path: Option<String>,
+) -> Result<PluginActionResult, String> {
+    let plugin = find_plugin(&plugin)?;
+
+    tokio::task::spawn_blocking(move || plugin.run_action(&id, path.as_deref()))
+        .await
+        .map_err(|e| format!("spawn_blocking error: {e}"))?
+        .map_err(|e| e.to_string())
+}
+
+/// Launcher results from the plugins that provide them, a failing plugin is logged and left out
+#[tauri::command]
+pub async fn search_plugin_providers(query: String) -> Result<Vec<PluginProviderResult>, String> {
+    let plugins = plugins_with(PluginCapability::Provider);
+    if plugins.is_empty() || query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let mut results = Vec::new();
+        for plugin in plugins {
+            match plugin.provide(&query) {
+                Ok(plugin_results) => results.extend(plugin_results),
+                Err(e) => error!("Plugin {} failed to search: {}", plugin.manifest.name, e),
+            }
+        }
+        results
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking error: {e}"))
+}
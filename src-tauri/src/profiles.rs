@@ -0,0 +1,169 @@
+/*
+This file contains profiles, separate indexes for separate corpora like work and personal files. Each profile has its own SQLite database, vector store and settings, and the watched roots come from the database so they are separate too
+
+The default profile lives directly in the app data directory so existing indexes keep working, other profiles live in profiles/<name>. The active profile is stored in profiles.json because it has to be known before the database is opened, switching restarts the app
+*/
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+const DEFAULT_PROFILE_NAME: &str = "default";
+
+const PROFILES_CONFIG_FILE_NAME: &str = "profiles.json";
+const PROFILES_DIR_NAME: &str = "profiles";
+
+const MAX_PROFILE_NAME_LENGTH: usize = 64;
+
+/// Profile of this run, set once at startup
+static ACTIVE_PROFILE: OnceLock<String> = OnceLock::new();
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ProfilesConfig {
+    active: String,
+    profiles: Vec<String>,
+}
+
+impl Default for ProfilesConfig {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE_NAME.to_string(),
+            profiles: vec![DEFAULT_PROFILE_NAME.to_string()],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProfileInfo {
+    pub name: String,
+    pub is_active: bool,
+}
+
+fn load_config(app_data_dir: &Path) -> ProfilesConfig {
+    let mut config: ProfilesConfig =
+        fs::read_to_string(app_data_dir.join(PROFILES_CONFIG_FILE_NAME))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+    if !config
+        .profiles
+        .iter()
+        .any(|name| name == DEFAULT_PROFILE_NAME)
+    {
+        config.profiles.insert(0, DEFAULT_PROFILE_NAME.to_string());
+    }
+    if !config.profiles.contains(&config.active) {
+        config.active = DEFAULT_PROFILE_NAME.to_string();
+    }
+
+    config
+}
+
+fn save_config(app_data_dir: &Path, config: &ProfilesConfig) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(config)?;
+    fs::write(app_data_dir.join(PROFILES_CONFIG_FILE_NAME), json)
+}
+
+/// Profile names become directory names, so only allow characters that are safe in a path
+fn validate_profile_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_PROFILE_NAME_LENGTH {
+        return Err(format!(
+            "Profile names must be between 1 and {} characters",
+            MAX_PROFILE_NAME_LENGTH
+        ));
+    }
+
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err("Profile names can only contain letters, numbers, '-' and '_'".to_string());
+    }
+
+    Ok(())
+}
+
+fn profile_dir(app_data_dir: &Path, name: &str) -> PathBuf {
+    if name == DEFAULT_PROFILE_NAME {
+        app_data_dir.to_path_buf()
+    } else {
+        app_data_dir.join(PROFILES_DIR_NAME).join(name)
+    }
+}
+
+/// Reads the active profile and creates its directory
+/// Called once at startup before the database is opened, returns the directory the index lives in
+pub fn prepare_active_profile(app_data_dir: &Path) -> std::io::Result<PathBuf> {
+    let config = load_config(app_data_dir);
+    let dir = profile_dir(app_data_dir, &config.active);
+    fs::create_dir_all(&dir)?;
+
+    info!("Using profile {}", config.active);
+    let _ = ACTIVE_PROFILE.set(config.active);
+
+    Ok(dir)
+}
+
+/// Directory of the index of the active profile
+pub fn active_profile_dir(app_data_dir: &Path) -> PathBuf {
+    let name = ACTIVE_PROFILE
+        .get()
+        .map(String::as_str)
+        .unwrap_or(DEFAULT_PROFILE_NAME);
+    profile_dir(app_data_dir, name)
+}
+
+fn app_data_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|_| "Failed to get app data directory".to_string())
+}
+
+#[tauri::command]
+pub fn list_profiles(app_handle: AppHandle) -> Result<Vec<ProfileInfo>, String> {
+    let config = load_config(&app_data_dir(&app_handle)?);
+    let active = ACTIVE_PROFILE
+        .get()
+        .cloned()
+        .unwrap_or_else(|| config.active.clone());
+
+    Ok(config
+        .profiles
+        .into_iter()
+        .map(|name| ProfileInfo {
+            is_active: name == active,
+            name,
+        })
+        .collect())
+}
+
+/// Makes the profile active and restarts the app so everything opens the profile's index
+/// The profile is created if it doesn't exist yet
+#[tauri::command]
+pub fn switch_profile(name: String, app_handle: AppHandle) -> Result<(), String> {
+    validate_profile_name(&name)?;
+
+    if ACTIVE_PROFILE.get() == Some(&name) {
+        return Ok(());
+    }
+
+    let app_data_dir = app_data_dir(&app_handle)?;
+    let mut config = load_config(&app_data_dir);
+
+    if !config.profiles.contains(&name) {
+        fs::create_dir_all(profile_dir(&app_data_dir, &name))
+            .map_err(|e| format!("Failed to create profile {}: {}", name, e))?;
+        config.profiles.push(name.clone());
+    }
+    config.active = name;
+
+    save_config(&app_data_dir, &config).map_err(|e| format!("Failed to save profiles: {}", e))?;
+
+    app_handle.request_restart();
+    Ok(())
+}
@@ -1,29 +1,615 @@
+use rusqlite::params;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
-    sync::{Arc, Mutex},
+    collections::{HashMap, HashSet, VecDeque},
+    path::PathBuf,
+    sync::{Arc, Mutex, RwLock},
     thread::sleep,
-    time::Duration,
+    time::{Duration, Instant},
 };
-use sysinfo::{ProcessExt, System, SystemExt};
-use tauri::{Emitter, Manager, State};
-use tokio::time::interval;
+use sysinfo::{CpuExt, ProcessExt, System, SystemExt};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{error, info};
+
+#[cfg(target_os = "macos")]
+use std::ffi::{CStr, CString};
+#[cfg(target_os = "macos")]
+use std::os::raw::c_char;
+
+#[cfg(target_os = "macos")]
+extern "C" {
+    fn get_network_usage_swift() -> *mut c_char;
+    fn get_energy_counters_swift(pids_json: *const c_char) -> *mut c_char;
+    fn free_string_swift(pointer: *mut c_char);
+}
+
+use crate::database_handler::open_connection;
+use crate::file_processor::{get_processor, FileProcessorError, FileProcessorState};
+use crate::settings::{ResourceAlertSettings, SettingsManagerState};
+
+/// How often the subscribed PIDs are added to the history and checked for alerts
+const HISTORY_SAMPLE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Subscriptions can't sample more often than this, sysinfo needs some time between refreshes for the CPU usage
+const MIN_SUBSCRIPTION_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Time between the two process refreshes of an on-demand sample, sysinfo needs two to compute CPU and disk usage
+const ON_DEMAND_SAMPLE_DELAY: Duration = Duration::from_millis(100);
+
+/// CPU time at background QoS mostly runs on the efficiency cores, so it counts for less energy
+const BACKGROUND_CPU_ENERGY_WEIGHT: f64 = 0.5;
+
+/// Energy of one wakeup counted as this much CPU time, waking an idle CPU package isn't free
+const WAKEUP_ENERGY_COST_SECS: f64 = 0.0002;
+
+/// How often the system pressure is sampled
+const PRESSURE_SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Above this percentage of memory in use the system counts as under memory pressure
+const MEMORY_PRESSURE_USAGE: f32 = 90.0;
+
+/// Samples kept in memory per process, at one sample a minute this covers the last 6 hours
+const HISTORY_CAPACITY: usize = 360;
+
+/// History returned when the caller doesn't ask for a specific window
+const DEFAULT_HISTORY_WINDOW_SECS: u64 = 60 * 60;
+
+/// Persisted samples older than this are deleted
+const PERSISTED_HISTORY_RETENTION_SECS: i64 = 7 * 24 * 60 * 60;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppResourceUsage {
     pub pid: u32,
     pub cpu_usage: f64,    // CPU percentage (0-100)
     pub memory_bytes: u64, // Memory usage in bytes
+    pub disk_read_bytes_per_sec: u64,
+    pub disk_write_bytes_per_sec: u64,
+    /// None where per process network statistics aren't available, and on the first sample
+    pub network_received_bytes_per_sec: Option<u64>,
+    pub network_sent_bytes_per_sec: Option<u64>,
+    /// Estimate like Activity Monitor's energy impact, 100 is about one busy core. None where it can't be read
+    pub energy_impact: Option<f64>,
+}
+
+/// Cumulative counters of a process that the energy impact is estimated from
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+struct EnergyCounters {
+    pid: u32,
+    cpu_time_ns: u64,
+    background_cpu_time_ns: u64,
+    wakeups: u64,
+}
+
+/// Bytes a process has received and sent since it started, as reported by nettop
+#[derive(Debug, Deserialize, Clone, Copy)]
+struct NetworkTotals {
+    pid: u32,
+    bytes_in: u64,
+    bytes_out: u64,
+}
+
+/// Network totals of the previous read and when they were read, the rates are the difference to the next read
+static PREVIOUS_NETWORK_TOTALS: Mutex<Option<(Instant, HashMap<u32, NetworkTotals>)>> =
+    Mutex::new(None);
+
+/// Load of the whole machine, used to decide how much background work to run
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SystemLoad {
+    pub cpu_usage: f32,          // CPU percentage across all cores (0-100)
+    pub memory_usage: f32,       // Percentage of memory in use (0-100)
+    pub on_battery: bool,        // Running on battery instead of AC power
+    pub thermal_throttled: bool, // The OS is limiting CPU speed because of heat
+}
+
+/// How much background work (indexing, embedding, loading the LLM) the machine can take right now
+/// CPU usage isn't part of it because indexing itself drives the CPU up, the index queue reacts to CPU on its own
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum SystemPressure {
+    Normal,
+    /// Running on battery, background work should use less power
+    Reduced,
+    /// Thermally throttled or short on memory, background work should back off as much as it can
+    Critical,
+}
+
+impl SystemPressure {
+    fn from_load(load: &SystemLoad) -> Self {
+        if load.thermal_throttled || load.memory_usage > MEMORY_PRESSURE_USAGE {
+            SystemPressure::Critical
+        } else if load.on_battery {
+            SystemPressure::Reduced
+        } else {
+            SystemPressure::Normal
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn read_network_totals() -> Option<Vec<NetworkTotals>> {
+    let totals_json_ptr = unsafe { get_network_usage_swift() };
+    if totals_json_ptr.is_null() {
+        return None;
+    }
+
+    let totals_json = unsafe {
+        let result = CStr::from_ptr(totals_json_ptr)
+            .to_str()
+            .ok()
+            .map(|s| s.to_owned());
+        free_string_swift(totals_json_ptr);
+        result?
+    };
+
+    serde_json::from_str(&totals_json)
+        .map_err(|e| error!("Failed to parse network usage: {}", e))
+        .ok()
+}
+
+#[cfg(not(target_os = "macos"))]
+fn read_network_totals() -> Option<Vec<NetworkTotals>> {
+    None
+}
+
+/// Reads the energy counters with proc_pid_rusage, processes of other users are left out
+#[cfg(target_os = "macos")]
+fn read_energy_counters(pids: &[u32]) -> HashMap<u32, EnergyCounters> {
+    let Ok(pids_json) = serde_json::to_string(pids) else {
+        return HashMap::new();
+    };
+    let Ok(pids_cstring) = CString::new(pids_json) else {
+        return HashMap::new();
+    };
+
+    let counters_json_ptr = unsafe { get_energy_counters_swift(pids_cstring.as_ptr()) };
+    if counters_json_ptr.is_null() {
+        return HashMap::new();
+    }
+
+    let counters_json = unsafe {
+        let result = CStr::from_ptr(counters_json_ptr)
+            .to_str()
+            .map(|s| s.to_owned());
+        free_string_swift(counters_json_ptr);
+        result
+    };
+
+    counters_json
+        .ok()
+        .and_then(|json| serde_json::from_str::<Vec<EnergyCounters>>(&json).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|counters| (counters.pid, counters))
+        .collect()
+}
+
+#[cfg(not(target_os = "macos"))]
+fn read_energy_counters(_pids: &[u32]) -> HashMap<u32, EnergyCounters> {
+    HashMap::new()
+}
+
+/// Estimates the energy impact of each process from two reads of its counters elapsed_secs apart
+/// Weighted CPU time and wakeups are converted to a share of one core, so 100 is about one busy core
+fn energy_impacts(
+    before: &HashMap<u32, EnergyCounters>,
+    after: &HashMap<u32, EnergyCounters>,
+    elapsed_secs: f64,
+) -> HashMap<u32, f64> {
+    if elapsed_secs <= 0.0 {
+        return HashMap::new();
+    }
+
+    after
+        .iter()
+        .filter_map(|(pid, after)| {
+            let before = before.get(pid)?;
+            let cpu_ns = after.cpu_time_ns.saturating_sub(before.cpu_time_ns);
+            let background_ns = after
+                .background_cpu_time_ns
+                .saturating_sub(before.background_cpu_time_ns)
+                .min(cpu_ns);
+            let weighted_cpu_secs = ((cpu_ns - background_ns) as f64
+                + background_ns as f64 * BACKGROUND_CPU_ENERGY_WEIGHT)
+                / 1_000_000_000.0;
+            let wakeups = after.wakeups.saturating_sub(before.wakeups) as f64;
+
+            Some((
+                *pid,
+                (weighted_cpu_secs + wakeups * WAKEUP_ENERGY_COST_SECS) / elapsed_secs * 100.0,
+            ))
+        })
+        .collect()
+}
+
+fn per_second(bytes: u64, elapsed_secs: f64) -> u64 {
+    if elapsed_secs <= 0.0 {
+        return 0;
+    }
+    (bytes as f64 / elapsed_secs) as u64
+}
+
+/// Received and sent bytes per second of every process since the previous call (blocking)
+/// Empty on the first call and where per process network statistics aren't available
+fn network_rates() -> HashMap<u32, (u64, u64)> {
+    let Some(totals) = read_network_totals() else {
+        return HashMap::new();
+    };
+    let read_at = Instant::now();
+    let totals: HashMap<u32, NetworkTotals> =
+        totals.into_iter().map(|total| (total.pid, total)).collect();
+
+    let Ok(mut previous) = PREVIOUS_NETWORK_TOTALS.lock() else {
+        return HashMap::new();
+    };
+
+    let rates = match previous.as_ref() {
+        Some((previous_read_at, previous_totals)) => {
+            let elapsed_secs = read_at.duration_since(*previous_read_at).as_secs_f64();
+            totals
+                .iter()
+                .filter_map(|(pid, current)| {
+                    let previous = previous_totals.get(pid)?;
+                    Some((
+                        *pid,
+                        (
+                            per_second(
+                                current.bytes_in.saturating_sub(previous.bytes_in),
+                                elapsed_secs,
+                            ),
+                            per_second(
+                                current.bytes_out.saturating_sub(previous.bytes_out),
+                                elapsed_secs,
+                            ),
+                        ),
+                    ))
+                })
+                .collect()
+        }
+        None => HashMap::new(),
+    };
+
+    *previous = Some((read_at, totals));
+    rates
+}
+
+/// Builds the usage of a refreshed process, elapsed_secs is the time since the previous refresh
+/// sysinfo reads the disk counters with proc_pid_rusage on macOS
+fn usage_from_process(
+    pid: u32,
+    process: &sysinfo::Process,
+    elapsed_secs: f64,
+    network: &HashMap<u32, (u64, u64)>,
+    energy_impacts: &HashMap<u32, f64>,
+) -> AppResourceUsage {
+    let disk = process.disk_usage();
+    let network = network.get(&pid);
+
+    AppResourceUsage {
+        pid,
+        cpu_usage: process.cpu_usage() as f64,
+        memory_bytes: process.memory(),
+        disk_read_bytes_per_sec: per_second(disk.read_bytes, elapsed_secs),
+        disk_write_bytes_per_sec: per_second(disk.written_bytes, elapsed_secs),
+        network_received_bytes_per_sec: network.map(|(received, _)| *received),
+        network_sent_bytes_per_sec: network.map(|(_, sent)| *sent),
+        energy_impact: energy_impacts.get(&pid).copied(),
+    }
+}
+
+/// Latest load sampled by the pressure monitor, None until the first sample
+static LATEST_SYSTEM_LOAD: RwLock<Option<SystemLoad>> = RwLock::new(None);
+
+/// Returns the load from the last sample of the pressure monitor
+pub fn latest_system_load() -> Option<SystemLoad> {
+    LATEST_SYSTEM_LOAD.read().ok().and_then(|load| load.clone())
+}
+
+/// Returns the current system pressure, Normal until the pressure monitor has sampled the load
+pub fn system_pressure() -> SystemPressure {
+    latest_system_load()
+        .map(|load| SystemPressure::from_load(&load))
+        .unwrap_or(SystemPressure::Normal)
+}
+
+/// Samples the system load in the background and publishes it for the index queue, the embedder and the LLM server
+/// Emits system-pressure-changed whenever the pressure changes
+pub fn start_system_pressure_monitor(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut system = System::new();
+        let mut pressure = SystemPressure::Normal;
+
+        loop {
+            // sampling shells out to pmset on macOS so keep it off the async workers
+            let sampled = tokio::task::spawn_blocking(move || {
+                let load = sample_system_load(&mut system);
+                (system, load)
+            })
+            .await;
+
+            match sampled {
+                Ok((returned_system, load)) => {
+                    system = returned_system;
+
+                    let new_pressure = SystemPressure::from_load(&load);
+                    if let Ok(mut latest) = LATEST_SYSTEM_LOAD.write() {
+                        *latest = Some(load);
+                    }
+
+                    if new_pressure != pressure {
+                        info!("System pressure changed to {:?}", new_pressure);
+                        pressure = new_pressure;
+                        let _ = app_handle.emit("system-pressure-changed", pressure);
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to sample system load: {}", e);
+                    system = System::new();
+                }
+            }
+
+            tokio::time::sleep(PRESSURE_SAMPLE_INTERVAL).await;
+        }
+    });
+}
+
+/// Usage of a process at one point in time
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResourceSample {
+    pub timestamp: i64, // Unix time in seconds
+    pub cpu_usage: f64,
+    pub memory_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceAlertKind {
+    Cpu,
+    Memory,
+}
+
+/// Sent with resource-alert when an app crosses one of the configured thresholds
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResourceAlert {
+    pub pid: u32,
+    pub name: String,
+    pub kind: ResourceAlertKind,
+    pub cpu_usage: f64,
+    pub memory_bytes: u64,
+    /// CPU percentage or memory bytes, depending on the kind
+    pub threshold: f64,
+    /// How long the app has been over the threshold, 0 for memory alerts
+    pub duration_secs: u64,
+}
+
+/// Processes a subscription samples, "all" or {"pids": [...]} from the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceTarget {
+    All,
+    Pids(Vec<u32>),
+}
+
+impl ResourceTarget {
+    fn includes(&self, pid: u32) -> bool {
+        match self {
+            ResourceTarget::All => true,
+            ResourceTarget::Pids(pids) => pids.contains(&pid),
+        }
+    }
+}
+
+struct ResourceSubscription {
+    target: ResourceTarget,
+    interval: Duration,
+    next_sample_at: Instant,
+}
+
+#[derive(Default)]
+struct Subscriptions {
+    by_id: HashMap<u64, ResourceSubscription>,
+    next_id: u64,
+    /// Set while the sampling loop runs, the loop stops once the last subscription is gone
+    sampling: bool,
+}
+
+/// Sent with resource-sample, each subscriber only handles the events with its own subscription id
+#[derive(Debug, Serialize, Clone)]
+pub struct ResourceSampleEvent {
+    pub subscription_id: u64,
+    pub usage: HashMap<u32, AppResourceUsage>,
 }
 
 /// Holds the shared state for resource monitoring.
 #[derive(Default)]
 pub struct ResourceMonitorState {
-    /// List of user-requested PIDs to monitor.
-    monitored_pids: Arc<Mutex<Vec<u32>>>,
+    /// Active subscriptions, one sampling loop serves all of them
+    subscriptions: Arc<Mutex<Subscriptions>>,
 
-    /// Single boolean flag indicating if monitoring is active.
-    is_monitoring: Arc<Mutex<bool>>,
+    /// Latest samples of each subscribed PID, oldest first
+    history: Arc<Mutex<HashMap<u32, VecDeque<ResourceSample>>>>,
+}
+
+/// Adds the samples to the in-memory history and drops the history of PIDs that aren't sampled anymore
+fn record_samples(
+    history: &Mutex<HashMap<u32, VecDeque<ResourceSample>>>,
+    usage_map: &HashMap<u32, AppResourceUsage>,
+    timestamp: i64,
+) {
+    let Ok(mut history) = history.lock() else {
+        return;
+    };
+
+    history.retain(|pid, _| usage_map.contains_key(pid));
+
+    for (pid, usage) in usage_map {
+        let samples = history.entry(*pid).or_default();
+        if samples.len() == HISTORY_CAPACITY {
+            samples.pop_front();
+        }
+        samples.push_back(ResourceSample {
+            timestamp,
+            cpu_usage: usage.cpu_usage,
+            memory_bytes: usage.memory_bytes,
+        });
+    }
+}
+
+/// Seconds the latest samples have been above the CPU threshold without interruption
+fn cpu_over_threshold_secs(samples: &VecDeque<ResourceSample>, threshold: f64) -> u64 {
+    let Some(latest) = samples.back() else {
+        return 0;
+    };
+
+    let earliest = samples
+        .iter()
+        .rev()
+        .take_while(|sample| sample.cpu_usage > threshold)
+        .last();
+
+    match earliest {
+        // each sample stands for the interval before it
+        Some(earliest) => {
+            (latest.timestamp - earliest.timestamp).max(0) as u64
+                + HISTORY_SAMPLE_INTERVAL.as_secs()
+        }
+        None => 0,
+    }
+}
+
+/// Returns the alerts that just started, an alert fires once and is re-armed when the app goes back under the threshold
+fn evaluate_alerts(
+    history: &Mutex<HashMap<u32, VecDeque<ResourceSample>>>,
+    usage_map: &HashMap<u32, AppResourceUsage>,
+    process_names: &HashMap<u32, String>,
+    settings: &ResourceAlertSettings,
+    active_alerts: &mut HashSet<(u32, ResourceAlertKind)>,
+) -> Vec<ResourceAlert> {
+    let Ok(history) = history.lock() else {
+        return Vec::new();
+    };
+
+    active_alerts.retain(|(pid, _)| usage_map.contains_key(pid));
+
+    let mut alerts = Vec::new();
+    for (pid, usage) in usage_map {
+        let name = process_names.get(pid).cloned().unwrap_or_default();
+
+        if let Some(cpu_threshold) = settings.cpu_percent {
+            let over_secs = history
+                .get(pid)
+                .map(|samples| cpu_over_threshold_secs(samples, cpu_threshold))
+                .unwrap_or(0);
+
+            if over_secs >= settings.cpu_duration_secs() {
+                if active_alerts.insert((*pid, ResourceAlertKind::Cpu)) {
+                    alerts.push(ResourceAlert {
+                        pid: *pid,
+                        name: name.clone(),
+                        kind: ResourceAlertKind::Cpu,
+                        cpu_usage: usage.cpu_usage,
+                        memory_bytes: usage.memory_bytes,
+                        threshold: cpu_threshold,
+                        duration_secs: over_secs,
+                    });
+                }
+            } else if over_secs == 0 {
+                active_alerts.remove(&(*pid, ResourceAlertKind::Cpu));
+            }
+        }
+
+        if let Some(memory_threshold) = settings.memory_threshold_bytes() {
+            if usage.memory_bytes > memory_threshold {
+                if active_alerts.insert((*pid, ResourceAlertKind::Memory)) {
+                    alerts.push(ResourceAlert {
+                        pid: *pid,
+                        name,
+                        kind: ResourceAlertKind::Memory,
+                        cpu_usage: usage.cpu_usage,
+                        memory_bytes: usage.memory_bytes,
+                        threshold: memory_threshold as f64,
+                        duration_secs: 0,
+                    });
+                }
+            } else {
+                active_alerts.remove(&(*pid, ResourceAlertKind::Memory));
+            }
+        }
+    }
+
+    alerts
+}
+
+fn is_history_persisted(app_handle: &AppHandle) -> bool {
+    app_handle
+        .try_state::<SettingsManagerState>()
+        .and_then(|state| state.0.get_settings().ok())
+        .and_then(|settings| settings.persist_resource_history)
+        .unwrap_or(false)
+}
+
+fn history_db_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    let state = app_handle.try_state::<FileProcessorState>()?;
+    get_processor(&state)
+        .ok()
+        .map(|processor| processor.db_path.clone())
+}
+
+fn save_samples(
+    db_path: PathBuf,
+    usage_map: HashMap<u32, AppResourceUsage>,
+    timestamp: i64,
+) -> Result<(), FileProcessorError> {
+    let mut conn = open_connection(db_path)?;
+    let tx = conn.transaction()?;
+
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO resource_samples (pid, sampled_at, cpu_usage, memory_bytes) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for (pid, usage) in &usage_map {
+            stmt.execute(params![
+                pid,
+                timestamp,
+                usage.cpu_usage,
+                usage.memory_bytes as i64
+            ])?;
+        }
+    }
+
+    tx.execute(
+        "DELETE FROM resource_samples WHERE sampled_at < ?1",
+        [timestamp - PERSISTED_HISTORY_RETENTION_SECS],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+fn load_samples(
+    db_path: PathBuf,
+    pid: u32,
+    since: i64,
+) -> Result<Vec<ResourceSample>, FileProcessorError> {
+    let conn = open_connection(db_path)?;
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT sampled_at, cpu_usage, memory_bytes
+        FROM resource_samples
+        WHERE pid = ?1 AND sampled_at >= ?2
+        ORDER BY sampled_at
+        "#,
+    )?;
+
+    let samples = stmt
+        .query_map(params![pid, since], |row| {
+            Ok(ResourceSample {
+                timestamp: row.get(0)?,
+                cpu_usage: row.get(1)?,
+                memory_bytes: row.get::<_, i64>(2)? as u64,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(samples)
 }
 
 /// Initialize and register the ResourceMonitorState with your Tauri app.
@@ -31,120 +617,387 @@ pub fn init_resource_monitor<R: tauri::Runtime>(
     app: &mut tauri::App<R>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     app.manage(ResourceMonitorState::default());
-    println!("Resource monitoring initialized");
+    info!("Resource monitoring initialized");
     Ok(())
 }
 
-/// Starts resource monitoring for a given set of PIDs. Spawns a single background
-/// task if not already active, and emits updates via "resource-usage-updated".
+/// Registers a subscription and starts the sampling loop if it isn't running yet
+/// Every `interval_ms` the usage of the target processes is emitted as resource-sample with the returned subscription id
 #[tauri::command]
-pub async fn start_resource_monitoring(
-    pids: Vec<u32>,
-    app_handle: tauri::AppHandle,
+pub fn subscribe_resource_usage(
+    target: ResourceTarget,
+    interval_ms: Option<u64>,
+    app_handle: AppHandle,
     state: State<'_, ResourceMonitorState>,
-) -> Result<(), String> {
-    // Mark that we should be monitoring
-    {
-        let mut flag = state.is_monitoring.lock().unwrap();
-        *flag = true;
-    }
+) -> Result<u64, String> {
+    let interval = interval_ms
+        .map(Duration::from_millis)
+        .unwrap_or(HISTORY_SAMPLE_INTERVAL)
+        .max(MIN_SUBSCRIPTION_INTERVAL);
 
-    // Validate the PIDs once to ensure they exist before monitoring
-    {
-        let mut system = System::new();
-        system.refresh_processes();
+    let mut subscriptions = state.subscriptions.lock().map_err(|e| e.to_string())?;
+    let subscription_id = subscriptions.next_id;
+    subscriptions.next_id += 1;
+    subscriptions.by_id.insert(
+        subscription_id,
+        ResourceSubscription {
+            target,
+            interval,
+            next_sample_at: Instant::now(),
+        },
+    );
 
-        let mut valid_pids = Vec::new();
-        for pid in pids {
-            let sys_pid = sysinfo::Pid::from(pid as usize);
-            if system.process(sys_pid).is_some() {
-                valid_pids.push(pid);
-            }
-        }
-
-        // Store the valid PIDs
-        let mut monitored = state.monitored_pids.lock().unwrap();
-        *monitored = valid_pids;
+    // checked under the same lock the loop uses to stop, so a new subscription never misses a stopping loop
+    if !subscriptions.sampling {
+        subscriptions.sampling = true;
+        start_sampling_loop(
+            app_handle,
+            state.subscriptions.clone(),
+            state.history.clone(),
+        );
     }
 
-    // If a background task is already running, do nothing else here.
-    // We only spawn once, and let that task continuously monitor.
-    // TODO: handle re-spawning the task after it stops. The task should only run while the window is in focus
-    // otherwise, we don't need to update the resources if the user isn't looking at the app
-    let is_monitoring_now = state.is_monitoring.clone();
-    let monitored_pids_clone = state.monitored_pids.clone();
+    Ok(subscription_id)
+}
 
-    // Spawn a background monitoring task **only** if we aren’t already running it.
-    // TODO: implement check for existing task here
-    tokio::spawn(async move {
+/// Changes the processes of a subscription, i.e. when apps are started or quit
+#[tauri::command]
+pub fn update_resource_subscription(
+    subscription_id: u64,
+    target: ResourceTarget,
+    state: State<'_, ResourceMonitorState>,
+) -> Result<(), String> {
+    let mut subscriptions = state.subscriptions.lock().map_err(|e| e.to_string())?;
+    let subscription = subscriptions
+        .by_id
+        .get_mut(&subscription_id)
+        .ok_or_else(|| format!("Unknown resource subscription {}", subscription_id))?;
+    subscription.target = target;
+    Ok(())
+}
+
+/// Removes a subscription, the sampling loop stops after the last one is removed
+#[tauri::command]
+pub fn unsubscribe_resource_usage(
+    subscription_id: u64,
+    state: State<'_, ResourceMonitorState>,
+) -> Result<(), String> {
+    let mut subscriptions = state.subscriptions.lock().map_err(|e| e.to_string())?;
+    subscriptions.by_id.remove(&subscription_id);
+    Ok(())
+}
+
+/// The single sampling loop behind all subscriptions
+/// It only refreshes the processes when a subscription or the history is due, and samples the union of all targets so the
+/// CPU and disk deltas always cover the time since the previous refresh
+fn start_sampling_loop(
+    app_handle: AppHandle,
+    subscriptions: Arc<Mutex<Subscriptions>>,
+    history: Arc<Mutex<HashMap<u32, VecDeque<ResourceSample>>>>,
+) {
+    tauri::async_runtime::spawn(async move {
         let mut system = System::new();
-        let mut tick_interval = interval(Duration::from_secs(60));
+        let mut active_alerts = HashSet::new();
+        let mut last_refresh: Option<Instant> = None;
+        let mut previous_energy_counters = HashMap::new();
+        let mut next_history_at = Instant::now();
 
-        // The main loop
         loop {
-            // Check if we are still supposed to monitor
-            if !*is_monitoring_now.lock().unwrap() {
-                println!("Resource monitoring loop exiting...");
-                break;
+            let now = Instant::now();
+
+            let (due, targets, next_due_at) = {
+                let Ok(mut subscriptions) = subscriptions.lock() else {
+                    break;
+                };
+                if subscriptions.by_id.is_empty() {
+                    subscriptions.sampling = false;
+                    info!("Resource sampling loop exiting...");
+                    break;
+                }
+
+                let mut due = Vec::new();
+                for (id, subscription) in subscriptions.by_id.iter_mut() {
+                    if subscription.next_sample_at <= now {
+                        subscription.next_sample_at = now + subscription.interval;
+                        due.push((*id, subscription.target.clone()));
+                    }
+                }
+
+                let targets: Vec<ResourceTarget> = subscriptions
+                    .by_id
+                    .values()
+                    .map(|subscription| subscription.target.clone())
+                    .collect();
+                let next_due_at = subscriptions
+                    .by_id
+                    .values()
+                    .map(|subscription| subscription.next_sample_at)
+                    .min()
+                    .unwrap_or(now + HISTORY_SAMPLE_INTERVAL)
+                    .min(next_history_at);
+
+                (due, targets, next_due_at)
+            };
+
+            let history_due = next_history_at <= now;
+            if due.is_empty() && !history_due {
+                tokio::time::sleep(next_due_at.saturating_duration_since(now)).await;
+                continue;
             }
 
-            // Refresh all processes once per tick (sysinfo uses a delta to compute CPU)
+            // Refresh all processes once per sample (sysinfo uses a delta to compute CPU)
             system.refresh_processes();
 
-            // Collect usage for the monitored PIDs
-            let pids_to_monitor = { monitored_pids_clone.lock().unwrap().clone() };
-            let mut usage_map = HashMap::new();
+            // the disk counters of the first refresh cover the whole life of the process, so there's no rate yet
+            let elapsed_secs = last_refresh
+                .map(|refreshed_at| refreshed_at.elapsed().as_secs_f64())
+                .unwrap_or(0.0);
+            last_refresh = Some(Instant::now());
+
+            let sampled_pids: Vec<u32> = system
+                .processes()
+                .keys()
+                .map(|pid| usize::from(*pid) as u32)
+                .filter(|pid| targets.iter().any(|target| target.includes(*pid)))
+                .collect();
 
-            for pid in &pids_to_monitor {
+            // nettop takes a moment to sample, so keep it off the async workers
+            let network = tokio::task::spawn_blocking(network_rates)
+                .await
+                .unwrap_or_default();
+
+            let energy_counters = read_energy_counters(&sampled_pids);
+            let energy = energy_impacts(&previous_energy_counters, &energy_counters, elapsed_secs);
+            previous_energy_counters = energy_counters;
+
+            let mut usage_map = HashMap::new();
+            let mut process_names = HashMap::new();
+            for pid in &sampled_pids {
                 let sys_pid = sysinfo::Pid::from(*pid as usize);
                 if let Some(process) = system.process(sys_pid) {
+                    process_names.insert(*pid, process.name().to_string());
                     usage_map.insert(
                         *pid,
-                        AppResourceUsage {
-                            pid: *pid,
-                            cpu_usage: process.cpu_usage() as f64,
-                            memory_bytes: process.memory(),
-                        },
+                        usage_from_process(*pid, process, elapsed_secs, &network, &energy),
                     );
                 }
             }
 
-            if !usage_map.is_empty() {
-                let _ = app_handle.emit("resource-usage-updated", usage_map);
+            for (subscription_id, target) in due {
+                let usage = usage_map
+                    .iter()
+                    .filter(|(pid, _)| target.includes(**pid))
+                    .map(|(pid, usage)| (*pid, usage.clone()))
+                    .collect();
+                let _ = app_handle.emit(
+                    "resource-sample",
+                    ResourceSampleEvent {
+                        subscription_id,
+                        usage,
+                    },
+                );
             }
 
-            tick_interval.tick().await;
+            if history_due {
+                next_history_at = now + HISTORY_SAMPLE_INTERVAL;
+                record_history(
+                    &app_handle,
+                    &history,
+                    &usage_map,
+                    &process_names,
+                    &mut active_alerts,
+                );
+            }
         }
     });
+}
 
-    Ok(())
+/// Adds the samples to the history, checks the alerts and persists the samples if that's turned on
+fn record_history(
+    app_handle: &AppHandle,
+    history: &Mutex<HashMap<u32, VecDeque<ResourceSample>>>,
+    usage_map: &HashMap<u32, AppResourceUsage>,
+    process_names: &HashMap<u32, String>,
+    active_alerts: &mut HashSet<(u32, ResourceAlertKind)>,
+) {
+    let timestamp = chrono::Utc::now().timestamp();
+    record_samples(history, usage_map, timestamp);
+
+    let alert_settings = ResourceAlertSettings::from_app_handle(app_handle);
+    for alert in evaluate_alerts(
+        history,
+        usage_map,
+        process_names,
+        &alert_settings,
+        active_alerts,
+    ) {
+        let _ = app_handle.emit("resource-alert", alert);
+    }
+
+    if !usage_map.is_empty() && is_history_persisted(app_handle) {
+        if let Some(db_path) = history_db_path(app_handle) {
+            let samples = usage_map.clone();
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = save_samples(db_path, samples, timestamp) {
+                    error!("Failed to save resource samples: {}", e);
+                }
+            });
+        }
+    }
 }
 
-/// Stops the background resource monitoring loop.
+/// Returns the CPU and memory samples of the process over the last `window_secs` seconds, oldest first
+/// The samples come from the database when persist_resource_history is on, so they survive restarts
 #[tauri::command]
-pub fn stop_resource_monitoring(state: State<'_, ResourceMonitorState>) -> Result<(), String> {
-    {
-        let mut flag = state.is_monitoring.lock().unwrap();
-        *flag = false;
+pub async fn get_resource_history(
+    pid: u32,
+    window_secs: Option<u64>,
+    state: State<'_, ResourceMonitorState>,
+    app_handle: AppHandle,
+) -> Result<Vec<ResourceSample>, String> {
+    let window_secs = window_secs.unwrap_or(DEFAULT_HISTORY_WINDOW_SECS) as i64;
+    let since = chrono::Utc::now().timestamp() - window_secs;
+
+    if is_history_persisted(&app_handle) {
+        if let Some(db_path) = history_db_path(&app_handle) {
+            return tokio::task::spawn_blocking(move || load_samples(db_path, pid, since))
+                .await
+                .map_err(|e| format!("spawn_blocking error: {e}"))?
+                .map_err(|e| e.to_string());
+        }
     }
-    Ok(())
+
+    let history = state.history.lock().map_err(|e| e.to_string())?;
+    Ok(history
+        .get(&pid)
+        .map(|samples| {
+            samples
+                .iter()
+                .filter(|sample| sample.timestamp >= since)
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default())
 }
 
 /// Fetch CPU and memory usage for a single process on-demand (blocking).
+/// The network rates are left out, reading them is too slow for the per app calls
 pub fn get_process_resource_usage(pid: u32) -> Result<AppResourceUsage, String> {
     let mut system = System::new();
+    let energy_before = read_energy_counters(&[pid]);
     system.refresh_processes();
-    sleep(Duration::from_millis(100));
+    sleep(ON_DEMAND_SAMPLE_DELAY);
     system.refresh_processes();
+    let energy = energy_impacts(
+        &energy_before,
+        &read_energy_counters(&[pid]),
+        ON_DEMAND_SAMPLE_DELAY.as_secs_f64(),
+    );
 
     let sys_pid = sysinfo::Pid::from(pid as usize);
     if let Some(proc_) = system.process(sys_pid) {
-        Ok(AppResourceUsage {
+        Ok(usage_from_process(
             pid,
-            cpu_usage: proc_.cpu_usage() as f64,
-            memory_bytes: proc_.memory(),
-        })
+            proc_,
+            ON_DEMAND_SAMPLE_DELAY.as_secs_f64(),
+            &HashMap::new(),
+            &energy,
+        ))
     } else {
         Err(format!("Process with PID {} not found", pid))
     }
 }
+
+/// Fetch CPU, memory, disk and network usage for several processes at once (blocking), processes that aren't running are left out
+pub fn get_processes_resource_usage(pids: &[u32]) -> HashMap<u32, AppResourceUsage> {
+    let mut system = System::new();
+    let energy_before = read_energy_counters(pids);
+    system.refresh_processes();
+    sleep(ON_DEMAND_SAMPLE_DELAY);
+    system.refresh_processes();
+    let energy = energy_impacts(
+        &energy_before,
+        &read_energy_counters(pids),
+        ON_DEMAND_SAMPLE_DELAY.as_secs_f64(),
+    );
+    let network = network_rates();
+
+    pids.iter()
+        .filter_map(|pid| {
+            let process = system.process(sysinfo::Pid::from(*pid as usize))?;
+            Some((
+                *pid,
+                usage_from_process(
+                    *pid,
+                    process,
+                    ON_DEMAND_SAMPLE_DELAY.as_secs_f64(),
+                    &network,
+                    &energy,
+                ),
+            ))
+        })
+        .collect()
+}
+
+/// Samples the load of the whole machine
+/// CPU usage is measured since the previous refresh of the given System, so keep it around between calls
+pub fn sample_system_load(system: &mut System) -> SystemLoad {
+    system.refresh_cpu();
+    system.refresh_memory();
+
+    let memory_usage = if system.total_memory() > 0 {
+        (system.used_memory() as f64 / system.total_memory() as f64 * 100.0) as f32
+    } else {
+        0.0
+    };
+
+    SystemLoad {
+        cpu_usage: system.global_cpu_info().cpu_usage(),
+        memory_usage,
+        on_battery: is_on_battery(),
+        thermal_throttled: is_thermal_throttled(),
+    }
+}
+
+/// Reads the power source from pmset, e.g. "Now drawing from 'Battery Power'"
+#[cfg(target_os = "macos")]
+fn is_on_battery() -> bool {
+    std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains("'Battery Power'"))
+        .unwrap_or(false)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn is_on_battery() -> bool {
+    false
+}
+
+/// Reads the CPU speed limit from pmset, anything below 100 means the CPU is being throttled
+#[cfg(target_os = "macos")]
+fn is_thermal_throttled() -> bool {
+    let Ok(output) = std::process::Command::new("pmset")
+        .args(["-g", "therm"])
+        .output()
+    else {
+        return false;
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("CPU_Speed_Limit"))
+        .filter_map(|value| {
+            value
+                .trim_start_matches([' ', '='])
+                .trim()
+                .parse::<u32>()
+                .ok()
+        })
+        .any(|limit| limit < 100)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn is_thermal_throttled() -> bool {
+    false
+}
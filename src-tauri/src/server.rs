@@ -8,15 +8,22 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, Manager};
 use thiserror::Error;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
+use tokio::sync::Notify;
 use tokio::time::timeout;
+use tracing::{debug, error, info, instrument, warn};
 
+use crate::messages::{ErrorCode, UserMessage};
 use crate::model_registry::{ModelInfo, ModelRegistry, ModelRegistryError};
+use crate::resource_monitor::{system_pressure, SystemPressure};
 use crate::settings::SettingsManagerState;
+use crate::supervisor::supervise;
+use crate::usage_analytics::{record_feature, record_timing};
 use crate::vectordb_manager::{get_text_chunks_from_similarity_search, VectorDbManager};
 
 const SYSTEM_PROMPT: &str = "
@@ -69,6 +76,14 @@ struct CompletionRequest {
     n_predict: i32,
     temperature: f32,
     stop: Vec<String>,
+    stream: bool,
+}
+
+/// Sent with llm-token for every piece of a streamed answer
+#[derive(Debug, Clone, Serialize)]
+pub struct LlmToken {
+    pub request_id: String,
+    pub token: String,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -90,6 +105,16 @@ const SERVER_PORT: u16 = 8080;
 const SERVER_BINARY_NAME: &str = "llama-server";
 const SERVER_READY_TIMEOUT_SECS: u64 = 180;
 
+/// How often a deferred start checks whether the system pressure went back to normal
+const DEFERRED_START_POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// The model is loaded after this long even if the pressure stays up
+const MAX_START_DEFERRAL: Duration = Duration::from_secs(10 * 60);
+
+/// Set while loading the model on launch is deferred because of the system pressure
+static SERVER_START_DEFERRED: AtomicBool = AtomicBool::new(false);
+/// Wakes up a deferred start, i.e. when the user asks the LLM something
+static START_SERVER_NOW: Notify = Notify::const_new();
+
 impl LLMServer {
     pub async fn new(app_handle: AppHandle) -> Result<Self, LLMServerError> {
         Ok(Self {
@@ -100,6 +125,25 @@ impl LLMServer {
         })
     }
 
+    /// Create a server that listens on a specific port, i.e. for benchmarking a model without touching the main server
+    pub async fn with_port(app_handle: AppHandle, port: u16) -> Result<Self, LLMServerError> {
+        Ok(Self {
+            server_process: None,
+            port,
+            app_handle,
+            model_path: None,
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// PID of the running llama-server process, if it is running
+    pub fn pid(&self) -> Option<u32> {
+        self.server_process.as_ref().and_then(|child| child.id())
+    }
+
     pub async fn start(&mut self, model_name: &str) -> Result<(), LLMServerError> {
         // Check if we have a model path set
         let model_path = if let Some(path) = &self.model_path {
@@ -129,12 +173,12 @@ impl LLMServer {
         match timeout(ready_timeout, self.wait_for_server_ready()).await {
             Ok(Ok(_)) => Ok(()),
             Ok(Err(e)) => {
-                eprintln!("Error during server readiness check: {}", e);
+                error!("Error during server readiness check: {}", e);
                 let _ = self.stop();
                 Err(e)
             }
             Err(_) => {
-                eprintln!(
+                error!(
                     "Server did not become ready within {} seconds.",
                     SERVER_READY_TIMEOUT_SECS
                 );
@@ -148,7 +192,7 @@ impl LLMServer {
 
     pub async fn stop(&mut self) -> Result<(), LLMServerError> {
         if let Some(mut child) = self.server_process.take() {
-            println!("Stopping server...");
+            info!("Stopping server...");
             let _ = child.start_kill();
             // Give it a moment to shut down
             tokio::time::sleep(Duration::from_secs(1)).await;
@@ -209,7 +253,7 @@ impl LLMServer {
         server_path: &Path,
         model_path: &Path,
     ) -> Result<tokio::process::Child, LLMServerError> {
-        println!(
+        info!(
             "Starting server: {} with model: {} on port {}",
             server_path.display(),
             model_path.display(),
@@ -241,14 +285,14 @@ impl LLMServer {
             .spawn()
             .map_err(|e| LLMServerError::CommandError(format!("Failed to spawn server: {}", e)))?;
 
-        println!("Server process started (PID: {})", child.id().unwrap_or(0));
+        info!("Server process started (PID: {})", child.id().unwrap_or(0));
 
         // Capture and print server output
         if let Some(stdout) = child.stdout.take() {
             let mut reader = BufReader::new(stdout).lines();
             tokio::spawn(async move {
                 while let Ok(Some(line)) = reader.next_line().await {
-                    println!("[SERVER]: {}", line);
+                    debug!("[SERVER]: {}", line);
                 }
             });
         }
@@ -257,7 +301,7 @@ impl LLMServer {
             let mut reader = BufReader::new(stderr).lines();
             tokio::spawn(async move {
                 while let Ok(Some(line)) = reader.next_line().await {
-                    eprintln!("[SERVER ERROR]: {}", line);
+                    warn!("[SERVER ERROR]: {}", line);
                 }
             });
         }
@@ -271,7 +315,7 @@ impl LLMServer {
 
         let endpoint = format!("http://127.0.0.1:{}/health", self.port);
 
-        println!("Waiting for server to become ready...");
+        debug!("Waiting for server to become ready...");
 
         loop {
             // Sleep for 1s before checking to give the server some time
@@ -280,17 +324,17 @@ impl LLMServer {
             match client.get(&endpoint).send().await {
                 Ok(response) => {
                     if response.status().is_success() {
-                        println!("Server is ready at {}", endpoint);
+                        info!("Server is ready at {}", endpoint);
                         return Ok(());
                     }
-                    println!(
+                    debug!(
                         "Server responded with status: {} at {}",
                         response.status(),
                         endpoint
                     );
                 }
                 Err(e) => {
-                    println!("Server not ready at {}: {}", endpoint, e);
+                    debug!("Server not ready at {}: {}", endpoint, e);
                 }
             }
         }
@@ -313,22 +357,19 @@ impl LLMServer {
 
     fn stop_sync(&mut self) {
         if let Some(mut child) = self.server_process.take() {
-            println!("Stopping server synchronously...");
+            info!("Stopping server synchronously...");
             let _ = child.start_kill();
             // We can't wait asynchronously here, but that's usually okay
             // as the OS will clean up child processes
         }
     }
 
-    async fn send_completion_request(
-        &self,
+    fn build_completion_request(
         prompt: &str,
         chunks: &Vec<TextChunkResponse>,
-    ) -> Result<CompletionResponse, LLMServerError> {
-        let client: Client = Client::new();
-        let url: String = format!("http://127.0.0.1:{}/completion", self.port);
-
-        println!("the chunks: {:?}", chunks);
+        stream: bool,
+    ) -> CompletionRequest {
+        debug!("the chunks: {:?}", chunks);
 
         // flattens the formatted prompts into a single string that we can pass into the prompt as context that the LLM can use to answer the question
         let text_chunks = chunks
@@ -337,33 +378,43 @@ impl LLMServer {
             .collect::<Vec<_>>()
             .join("\n\n");
 
-        println!("the text chunks: {:?}", text_chunks);
+        debug!("the text chunks: {:?}", text_chunks);
 
         let formatted_prompt = format!(
             "<s>[INST] {}\n\nCONTEXT:\n{}\n\nQUESTION: {} [/INST]",
             SYSTEM_PROMPT, text_chunks, prompt
         );
 
-        println!("the formatted propmt: {:?}", formatted_prompt);
+        debug!("the formatted propmt: {:?}", formatted_prompt);
         // create LLM request
-        let request = CompletionRequest {
+        CompletionRequest {
             prompt: formatted_prompt,
             n_predict: 150,
             temperature: 0.7,
             stop: vec!["\nHuman:".to_string(), "\nUser:".to_string()],
-        };
+            stream,
+        }
+    }
 
-        // ensure the server is available and ready
+    /// ensure the server is available and ready
+    async fn ensure_ready(&self) -> Result<(), LLMServerError> {
         let ready_timeout = Duration::from_secs(5);
         match timeout(ready_timeout, self.wait_for_server_ready()).await {
-            Ok(Ok(_)) => {}
-            Ok(Err(e)) => {
-                return Err(e);
-            }
-            Err(_) => {
-                return Err(LLMServerError::ServerReadyTimeout(30));
-            }
+            Ok(result) => result,
+            Err(_) => Err(LLMServerError::ServerReadyTimeout(30)),
         }
+    }
+
+    async fn send_completion_request(
+        &self,
+        prompt: &str,
+        chunks: &Vec<TextChunkResponse>,
+    ) -> Result<CompletionResponse, LLMServerError> {
+        let client: Client = Client::new();
+        let url: String = format!("http://127.0.0.1:{}/completion", self.port);
+
+        let request = Self::build_completion_request(prompt, chunks, false);
+        self.ensure_ready().await?;
 
         let response = client.post(&url).json(&request).send().await?;
 
@@ -375,7 +426,7 @@ impl LLMServer {
             let full_content = match json_value.get("content").and_then(|v| v.as_str()) {
                 Some(content_str) => content_str.to_string(),
                 None => {
-                    println!("Content field not found or not a string");
+                    warn!("Content field not found or not a string");
                     String::new()
                 }
             };
@@ -390,7 +441,7 @@ impl LLMServer {
                 sources: source_with_file_paths,
             };
 
-            println!("The enhanced response: {:?}", final_response);
+            debug!("The enhanced response: {:?}", final_response);
 
             Ok(final_response)
         } else {
@@ -406,6 +457,66 @@ impl LLMServer {
             )))
         }
     }
+
+    /// Like send_completion_request, but calls on_token with every piece of the answer as llama.cpp generates it
+    /// The returned response has the full answer with the sources split off
+    async fn send_streaming_completion_request(
+        &self,
+        prompt: &str,
+        chunks: &Vec<TextChunkResponse>,
+        on_token: impl Fn(&str),
+    ) -> Result<CompletionResponse, LLMServerError> {
+        let client: Client = Client::new();
+        let url: String = format!("http://127.0.0.1:{}/completion", self.port);
+
+        let request = Self::build_completion_request(prompt, chunks, true);
+        self.ensure_ready().await?;
+
+        let mut response = client.post(&url).json(&request).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Could not read error body".to_string());
+
+            return Err(LLMServerError::CommandError(format!(
+                "Server returned error {}: {}",
+                status, error_body
+            )));
+        }
+
+        // the server sends server-sent events, one "data: {json}" line per token
+        let mut full_content = String::new();
+        let mut pending = String::new();
+        'events: while let Some(bytes) = response.chunk().await? {
+            pending.push_str(&String::from_utf8_lossy(&bytes));
+
+            while let Some(line_end) = pending.find('\n') {
+                let line: String = pending.drain(..=line_end).collect();
+                let Some(data) = line.trim().strip_prefix("data:") else {
+                    continue;
+                };
+
+                let event: serde_json::Value = serde_json::from_str(data.trim())?;
+                if let Some(token) = event.get("content").and_then(|v| v.as_str()) {
+                    full_content.push_str(token);
+                    on_token(token);
+                }
+
+                if event.get("stop").and_then(|v| v.as_bool()) == Some(true) {
+                    break 'events;
+                }
+            }
+        }
+
+        let (content, sources) = parse_llm_response(&full_content);
+
+        Ok(CompletionResponse {
+            content,
+            sources: reconcile_sources(sources, chunks),
+        })
+    }
 }
 
 /// initializes the server with the model
@@ -418,51 +529,79 @@ pub fn init_server(app: &mut tauri::App) -> Result<()> {
 
         // Add registry to the app state
         app.manage(registry);
-        println!("Model registry initialized in start_server");
+        info!("Model registry initialized in start_server");
     }
 
     // Launch background scan and server initialization
     let app_handle = app.app_handle().clone();
 
-    tauri::async_runtime::spawn(async move {
-        let registry_state = app_handle.state::<ModelRegistry>();
-
-        // scan for any downlaoded models
-        let _ = registry_state
-            .scan_downloaded_models(&app_handle, None)
-            .map_err(|e| {
-                ModelRegistryError::Io(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("Unable to scan downloaded models: {}", e),
-                ))
-            });
+    // loading twice could start a second server, so a crash is only reported
+    supervise(app_handle.clone(), "model_loader", false, move || {
+        let app_handle = app_handle.clone();
+        async move {
+            let registry_state = app_handle.state::<ModelRegistry>();
+
+            // scan for any downlaoded models
+            let _ = registry_state
+                .scan_downloaded_models(&app_handle, None)
+                .map_err(|e| {
+                    ModelRegistryError::Io(std::io::Error::new(
+                        std::io::ErrorKind::Other,
+                        format!("Unable to scan downloaded models: {}", e),
+                    ))
+                });
+
+            // get the user selected model and load it down below
+            let selected_model_id = match get_selected_model_from_settings(&app_handle) {
+                Ok(Some(id)) => id,
+                Ok(None) => {
+                    notify_model_selection_required(&app_handle);
+                    return;
+                }
+                Err(e) => {
+                    error!("Error getting settings: {}", e);
+                    return;
+                }
+            };
 
-        // get the user selected model and load it down below
-        let selected_model_id = match get_selected_model_from_settings(&app_handle) {
-            Ok(Some(id)) => id,
-            Ok(None) => {
-                notify_model_selection_required(&app_handle);
-                return;
-            }
-            Err(e) => {
-                eprintln!("Error getting settings: {}", e);
-                return;
-            }
-        };
+            // loading the model is heavy and nobody is waiting on it yet, so hold off on battery or on a busy machine
+            wait_for_normal_pressure().await;
 
-        // Try to load the selected model
-        load_selected_model(&app_handle, &selected_model_id).await;
+            // Try to load the selected model
+            load_selected_model(&app_handle, &selected_model_id).await;
+        }
     });
 
     Ok(())
 }
 
+/// Waits until the system pressure is back to normal, the deferral is capped and a question to the LLM ends it right away
+async fn wait_for_normal_pressure() {
+    if system_pressure() == SystemPressure::Normal {
+        return;
+    }
+
+    info!("Deferring the LLM server start until the system pressure goes down");
+    SERVER_START_DEFERRED.store(true, Ordering::Relaxed);
+
+    let deferred_at = std::time::Instant::now();
+    while system_pressure() != SystemPressure::Normal && deferred_at.elapsed() < MAX_START_DEFERRAL
+    {
+        tokio::select! {
+            _ = START_SERVER_NOW.notified() => break,
+            _ = tokio::time::sleep(DEFERRED_START_POLL_INTERVAL) => {}
+        }
+    }
+
+    SERVER_START_DEFERRED.store(false, Ordering::Relaxed);
+}
+
 /// Get the selected model ID from settings
 fn get_selected_model_from_settings(app_handle: &AppHandle) -> Result<Option<String>, String> {
     let settings_state = app_handle.state::<SettingsManagerState>();
     let settings = settings_state.0.get_settings().map_err(|e| e.to_string())?;
 
-    println!("the selected model id: {:?}", settings.selected_model_id);
+    debug!("the selected model id: {:?}", settings.selected_model_id);
 
     Ok(settings.selected_model_id)
 }
@@ -494,13 +633,13 @@ async fn start_server_with_model(app_handle: &AppHandle, model: ModelInfo) {
         Ok(mut server) => {
             // Set the model path
             if let Err(e) = server.set_model_path(&model.path).await {
-                eprintln!("Error setting model path: {}", e);
+                error!("Error setting model path: {}", e);
                 return;
             }
 
             // Start the server
             if let Err(e) = server.start(&model.name).await {
-                eprintln!("Error starting LLM server: {}", e);
+                error!("Error starting LLM server: {}", e);
                 return;
             }
 
@@ -509,10 +648,10 @@ async fn start_server_with_model(app_handle: &AppHandle, model: ModelInfo) {
             let mut server_guard = server_state.lock().await;
             *server_guard = Some(server);
 
-            println!("LLM server initialized");
+            info!("LLM server initialized");
         }
         Err(e) => {
-            eprintln!("Failed to create LLM server: {}", e);
+            error!("Failed to create LLM server: {}", e);
         }
     }
 }
@@ -521,24 +660,21 @@ async fn start_server_with_model(app_handle: &AppHandle, model: ModelInfo) {
 fn notify_model_selection_required(app_handle: &AppHandle) {
     let _ = app_handle.emit(
         "model-selection-required",
-        "Please select a model to use for AI features",
+        UserMessage::new(ErrorCode::ModelSelectionRequired),
     );
 }
 
 fn notify_model_download_required(app_handle: &AppHandle, model_name: &str) {
     let _ = app_handle.emit(
         "model-download-required",
-        format!(
-            "The selected model '{}' needs to be downloaded before use",
-            model_name
-        ),
+        UserMessage::new(ErrorCode::ModelDownloadRequired).with_param("model", model_name),
     );
 }
 
 fn notify_model_not_found(app_handle: &AppHandle) {
     let _ = app_handle.emit(
         "model-selection-required",
-        "The previously selected model is no longer available. Please select a new model.",
+        UserMessage::new(ErrorCode::ModelNotFound),
     );
 }
 
@@ -562,30 +698,99 @@ pub struct TextChunkResponse {
 
 // Example of how to use this in a Tauri command
 #[tauri::command]
-pub async fn ask_llm(app_handle: AppHandle, prompt: String) -> Result<CompletionResponse, String> {
-    println!("Incoming prompt: {:?}", prompt);
-
-    // Get the server state
-    let server_state = app_handle.state::<tokio::sync::Mutex<Option<LLMServer>>>();
+#[instrument(skip_all)]
+pub async fn ask_llm(
+    app_handle: AppHandle,
+    prompt: String,
+) -> Result<CompletionResponse, UserMessage> {
+    debug!("Incoming prompt: {:?}", prompt);
+    record_feature(&app_handle, "ask");
+    let started = Instant::now();
+
+    // Get the server state, it's only managed once the LLM commands are registered
+    let Some(server_state) = app_handle.try_state::<tokio::sync::Mutex<Option<LLMServer>>>() else {
+        return Err(no_server_error());
+    };
     let server_guard = server_state.lock().await;
 
-    let context_chunks: Vec<TextChunkResponse> =
-        match VectorDbManager::search_similar(&app_handle, &prompt).await {
-            Ok(results) => get_text_chunks_from_similarity_search(results)?,
-            Err(e) => {
-                eprintln!("Unable to get chunks): {}", e);
-                Vec::new()
-            }
-        };
+    let context_chunks = get_context_chunks(&app_handle, &prompt).await?;
 
     // Check if we have a server instance
-    if let Some(server) = &*server_guard {
-        server
+    let response = match &*server_guard {
+        Some(server) => server
             .send_completion_request(&prompt, &context_chunks)
             .await
-            .map_err(|e| format!("Failed to get response: {}", e))
+            .map_err(|e| UserMessage::new(ErrorCode::LlmRequestFailed).with_param("error", e)),
+        None => Err(no_server_error()),
+    };
+
+    if response.is_ok() {
+        record_timing(&app_handle, "ask", started.elapsed(), None);
+    }
+    response
+}
+
+/// Answers the prompt like ask_llm, but emits llm-token with the request id for every piece of the answer as it's generated
+/// The returned response is the full answer with its sources
+#[tauri::command]
+#[instrument(skip_all, fields(request_id = %request_id))]
+pub async fn ask_llm_stream(
+    app_handle: AppHandle,
+    prompt: String,
+    request_id: String,
+) -> Result<CompletionResponse, UserMessage> {
+    record_feature(&app_handle, "ask");
+    let started = Instant::now();
+
+    let server_state = app_handle.state::<tokio::sync::Mutex<Option<LLMServer>>>();
+    let server_guard = server_state.lock().await;
+
+    let context_chunks = get_context_chunks(&app_handle, &prompt).await?;
+
+    let response = match &*server_guard {
+        Some(server) => server
+            .send_streaming_completion_request(&prompt, &context_chunks, |token| {
+                let _ = app_handle.emit(
+                    "llm-token",
+                    LlmToken {
+                        request_id: request_id.clone(),
+                        token: token.to_string(),
+                    },
+                );
+            })
+            .await
+            .map_err(|e| UserMessage::new(ErrorCode::LlmRequestFailed).with_param("error", e)),
+        None => Err(no_server_error()),
+    };
+
+    if response.is_ok() {
+        record_timing(&app_handle, "ask", started.elapsed(), None);
+    }
+    response
+}
+
+/// Finds the chunks most similar to the prompt, they are passed to the LLM as context
+async fn get_context_chunks(
+    app_handle: &AppHandle,
+    prompt: &str,
+) -> Result<Vec<TextChunkResponse>, UserMessage> {
+    match VectorDbManager::search_similar(app_handle, prompt).await {
+        Ok(results) => get_text_chunks_from_similarity_search(results)
+            .map_err(|e| UserMessage::new(ErrorCode::LlmContextFailed).with_param("error", e)),
+        Err(e) => {
+            warn!("Unable to get chunks): {}", e);
+            Ok(Vec::new())
+        }
+    }
+}
+
+/// Error for a question asked while no server is running, a deferred start is started right away
+fn no_server_error() -> UserMessage {
+    if SERVER_START_DEFERRED.load(Ordering::Relaxed) {
+        START_SERVER_NOW.notify_one();
+        UserMessage::new(ErrorCode::LlmStarting)
     } else {
-        Err("No LLM server is currently running. Please select a model first.".into())
+        UserMessage::new(ErrorCode::LlmNotRunning)
     }
 }
 
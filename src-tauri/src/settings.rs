@@ -1,11 +1,21 @@
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Manager};
+use tauri::{AppHandle, Emitter, Manager};
 use thiserror::Error;
+use tracing::{error, info, warn};
+
+use crate::database_handler::open_connection;
+use crate::file_processor::DEFAULT_INDEXABLE_EXTENSIONS;
+
+/// Version of the settings layout written by this build, bumped with every entry in SETTINGS_MIGRATIONS
+pub const CURRENT_SETTINGS_VERSION: u32 = 1;
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct AppSettings {
+    /// Layout version of the stored settings, 0 for settings saved before versioning
+    #[serde(default)]
+    pub version: u32,
     pub theme: Option<String>,
     pub custom_model_path: Option<String>,
     pub selected_model_id: Option<String>,
@@ -13,7 +23,451 @@ pub struct AppSettings {
     pub window_height: Option<u32>,
     pub global_hotkey: Option<String>,
     pub index_concurrency: Option<usize>,
+    pub adaptive_concurrency: Option<bool>,
     pub selected_categories: Option<Vec<String>>,
+    pub hf_token: Option<String>,
+    pub hf_mirror_url: Option<String>,
+    pub exclude_patterns: Option<Vec<String>>,
+    pub use_default_deny_list: Option<bool>,
+    pub respect_ignore_files: Option<bool>,
+    pub indexable_extensions: Option<Vec<String>>,
+    pub max_file_size_bytes: Option<u64>,
+    pub download_cloud_placeholders: Option<bool>,
+    pub collapse_duplicate_results: Option<bool>,
+    pub watcher_debounce_ms: Option<u64>,
+    pub watcher_max_batch_size: Option<usize>,
+    pub watcher_poll_interval_secs: Option<u64>,
+    pub live_indexing: Option<bool>,
+    pub fts_tokenizer: Option<String>,
+    pub fts_prefix_indexes: Option<bool>,
+    pub fts_split_identifiers: Option<bool>,
+    pub db_maintenance_interval_days: Option<u64>,
+    pub encrypt_index: Option<bool>,
+    pub result_count: Option<usize>,
+    /// "keyword", "semantic" or "hybrid"
+    pub default_search_mode: Option<String>,
+    pub show_hidden_files: Option<bool>,
+    pub search: Option<SearchSettings>,
+    pub launch_at_login: Option<bool>,
+    /// Keeps the window hidden on start, only the global shortcut shows it
+    pub start_hidden: Option<bool>,
+    /// Makes the open browser tabs searchable from the launcher
+    pub index_browser_tabs: Option<bool>,
+    /// Stores the resource usage samples in the database so the history survives restarts
+    pub persist_resource_history: Option<bool>,
+    pub resource_alerts: Option<ResourceAlertSettings>,
+    pub api_server: Option<ApiServerSettings>,
+    /// Records feature use counts and timings in the local database, nothing is sent anywhere
+    pub usage_analytics: Option<bool>,
+}
+
+/// Semantic results further away than this cosine distance are dropped
+pub const DEFAULT_SEMANTIC_DISTANCE_THRESHOLD: f32 = 0.85;
+/// Number of nearest chunks fetched from the vector store per search
+pub const DEFAULT_SEMANTIC_TOP_K: usize = 10;
+pub const DEFAULT_SEMANTIC_WEIGHT: f32 = 0.7;
+pub const DEFAULT_KEYWORD_WEIGHT: f32 = 0.3;
+
+/// Retrieval tuning, unset fields use the defaults above
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SearchSettings {
+    pub semantic_distance_threshold: Option<f32>,
+    pub top_k: Option<usize>,
+    /// Weights of the semantic and keyword scores when semantic results are reranked
+    pub semantic_weight: Option<f32>,
+    pub keyword_weight: Option<f32>,
+    /// Reranks semantic results by combining their distance with a keyword match on the query
+    pub rerank: Option<bool>,
+    /// Adds Spotlight matches from outside the indexed directories to file search results, macOS only
+    pub spotlight_fallback: Option<bool>,
+}
+
+impl SearchSettings {
+    /// Reads the search settings, falls back to the defaults if settings aren't available
+    pub fn from_app_handle(app_handle: &AppHandle) -> Self {
+        app_handle
+            .try_state::<SettingsManagerState>()
+            .and_then(|state| state.0.get_settings().ok())
+            .and_then(|settings| settings.search)
+            .unwrap_or_default()
+    }
+
+    pub fn semantic_distance_threshold(&self) -> f32 {
+        self.semantic_distance_threshold
+            .unwrap_or(DEFAULT_SEMANTIC_DISTANCE_THRESHOLD)
+    }
+
+    pub fn top_k(&self) -> usize {
+        self.top_k.unwrap_or(DEFAULT_SEMANTIC_TOP_K)
+    }
+
+    /// Returns the semantic and keyword weights
+    pub fn hybrid_weights(&self) -> (f32, f32) {
+        (
+            self.semantic_weight.unwrap_or(DEFAULT_SEMANTIC_WEIGHT),
+            self.keyword_weight.unwrap_or(DEFAULT_KEYWORD_WEIGHT),
+        )
+    }
+
+    pub fn rerank(&self) -> bool {
+        self.rerank.unwrap_or(false)
+    }
+
+    pub fn spotlight_fallback(&self) -> bool {
+        self.spotlight_fallback.unwrap_or(true)
+    }
+
+    fn validate(&self, errors: &mut Vec<FieldError>) {
+        // cosine distance is between 0 and 2
+        if self
+            .semantic_distance_threshold
+            .is_some_and(|threshold| !(0.0..=2.0).contains(&threshold))
+        {
+            errors.push(FieldError::new(
+                "search.semantic_distance_threshold",
+                "Must be between 0 and 2",
+            ));
+        }
+
+        if self
+            .top_k
+            .is_some_and(|top_k| !(1..=MAX_RESULT_COUNT).contains(&top_k))
+        {
+            errors.push(FieldError::new(
+                "search.top_k",
+                format!("Must be between 1 and {}", MAX_RESULT_COUNT),
+            ));
+        }
+
+        for (field, weight) in [
+            ("search.semantic_weight", self.semantic_weight),
+            ("search.keyword_weight", self.keyword_weight),
+        ] {
+            if weight.is_some_and(|weight| !weight.is_finite() || weight < 0.0) {
+                errors.push(FieldError::new(field, "Must be a positive number"));
+            }
+        }
+
+        let (semantic_weight, keyword_weight) = self.hybrid_weights();
+        if semantic_weight + keyword_weight <= 0.0 {
+            errors.push(FieldError::new(
+                "search.semantic_weight",
+                "The semantic and keyword weights can't both be 0",
+            ));
+        }
+    }
+}
+
+/// Minutes an app has to stay over the CPU threshold when cpu_duration_minutes isn't set
+pub const DEFAULT_CPU_ALERT_DURATION_MINUTES: u64 = 5;
+
+/// Thresholds of the resource alerts, each alert is off while its threshold is unset
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ResourceAlertSettings {
+    /// CPU percentage an app has to stay above for cpu_duration_minutes
+    pub cpu_percent: Option<f64>,
+    pub cpu_duration_minutes: Option<u64>,
+    pub memory_gb: Option<f64>,
+}
+
+impl ResourceAlertSettings {
+    /// Reads the alert settings, all alerts are off if settings aren't available
+    pub fn from_app_handle(app_handle: &AppHandle) -> Self {
+        app_handle
+            .try_state::<SettingsManagerState>()
+            .and_then(|state| state.0.get_settings().ok())
+            .and_then(|settings| settings.resource_alerts)
+            .unwrap_or_default()
+    }
+
+    pub fn cpu_duration_secs(&self) -> u64 {
+        self.cpu_duration_minutes
+            .unwrap_or(DEFAULT_CPU_ALERT_DURATION_MINUTES)
+            * 60
+    }
+
+    pub fn memory_threshold_bytes(&self) -> Option<u64> {
+        self.memory_gb
+            .map(|gb| (gb * 1024.0 * 1024.0 * 1024.0) as u64)
+    }
+
+    fn validate(&self, errors: &mut Vec<FieldError>) {
+        if self
+            .cpu_percent
+            .is_some_and(|percent| !percent.is_finite() || percent <= 0.0)
+        {
+            errors.push(FieldError::new(
+                "resource_alerts.cpu_percent",
+                "Must be a positive number",
+            ));
+        }
+
+        if self.cpu_duration_minutes == Some(0) {
+            errors.push(FieldError::new(
+                "resource_alerts.cpu_duration_minutes",
+                "Must be at least 1 minute",
+            ));
+        }
+
+        if self
+            .memory_gb
+            .is_some_and(|gb| !gb.is_finite() || gb <= 0.0)
+        {
+            errors.push(FieldError::new(
+                "resource_alerts.memory_gb",
+                "Must be a positive number",
+            ));
+        }
+    }
+}
+
+/// Port of the local API when api_server.port isn't set
+pub const DEFAULT_API_SERVER_PORT: u16 = 51731;
+
+/// The local HTTP API for scripts and editor plugins, it's off unless enabled
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ApiServerSettings {
+    pub enabled: Option<bool>,
+    pub port: Option<u16>,
+}
+
+impl ApiServerSettings {
+    pub fn from_app_handle(app_handle: &AppHandle) -> Self {
+        app_handle
+            .try_state::<SettingsManagerState>()
+            .and_then(|state| state.0.get_settings().ok())
+            .and_then(|settings| settings.api_server)
+            .unwrap_or_default()
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(false)
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(DEFAULT_API_SERVER_PORT)
+    }
+
+    fn validate(&self, errors: &mut Vec<FieldError>) {
+        // ports below 1024 need root
+        if self.port.is_some_and(|port| port < 1024) {
+            errors.push(FieldError::new(
+                "api_server.port",
+                "Must be between 1024 and 65535",
+            ));
+        }
+    }
+}
+
+/// Values accepted for default_search_mode
+pub const SEARCH_MODES: [&str; 3] = ["keyword", "semantic", "hybrid"];
+
+const THEMES: [&str; 3] = ["light", "dark", "system"];
+const FTS_TOKENIZERS: [&str; 3] = ["trigram", "porter", "unicode61"];
+
+const HOTKEY_MODIFIERS: [&str; 12] = [
+    "cmd",
+    "command",
+    "cmdorctrl",
+    "commandorcontrol",
+    "ctrl",
+    "control",
+    "alt",
+    "option",
+    "shift",
+    "super",
+    "meta",
+    "hyper",
+];
+const HOTKEY_NAMED_KEYS: [&str; 16] = [
+    "space",
+    "enter",
+    "return",
+    "tab",
+    "escape",
+    "backspace",
+    "delete",
+    "up",
+    "down",
+    "left",
+    "right",
+    "home",
+    "end",
+    "pageup",
+    "pagedown",
+    "backquote",
+];
+
+const MAX_INDEX_CONCURRENCY: usize = 64;
+const MAX_RESULT_COUNT: usize = 1000;
+const MAX_WATCHER_DEBOUNCE_MS: u64 = 60_000;
+const MIN_WINDOW_SIZE: u32 = 200;
+
+/// A settings field with a value that can't be used
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// Checks every configured value and returns an error for each one that can't be used
+    /// Unset fields are always valid, they fall back to their defaults
+    pub fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        if let Some(theme) = &self.theme {
+            if !THEMES.contains(&theme.as_str()) {
+                errors.push(FieldError::new("theme", format!("Unknown theme {}", theme)));
+            }
+        }
+
+        if let Some(path) = self.custom_model_path.as_deref().filter(|p| !p.is_empty()) {
+            if !std::path::Path::new(path).exists() {
+                errors.push(FieldError::new(
+                    "custom_model_path",
+                    format!("{} doesn't exist", path),
+                ));
+            }
+        }
+
+        for (field, size) in [
+            ("window_width", self.window_width),
+            ("window_height", self.window_height),
+        ] {
+            if size.is_some_and(|size| size < MIN_WINDOW_SIZE) {
+                errors.push(FieldError::new(
+                    field,
+                    format!("Must be at least {} pixels", MIN_WINDOW_SIZE),
+                ));
+            }
+        }
+
+        if let Some(hotkey) = &self.global_hotkey {
+            if let Err(message) = validate_hotkey(hotkey) {
+                errors.push(FieldError::new("global_hotkey", message));
+            }
+        }
+
+        if let Some(concurrency) = self.index_concurrency {
+            if !(1..=MAX_INDEX_CONCURRENCY).contains(&concurrency) {
+                errors.push(FieldError::new(
+                    "index_concurrency",
+                    format!("Must be between 1 and {}", MAX_INDEX_CONCURRENCY),
+                ));
+            }
+        }
+
+        if let Some(url) = self.hf_mirror_url.as_deref().filter(|url| !url.is_empty()) {
+            match reqwest::Url::parse(url) {
+                Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+                _ => errors.push(FieldError::new(
+                    "hf_mirror_url",
+                    format!("{} is not an http(s) URL", url),
+                )),
+            }
+        }
+
+        for pattern in self.exclude_patterns.iter().flatten() {
+            if let Err(e) = globset::Glob::new(pattern) {
+                errors.push(FieldError::new(
+                    "exclude_patterns",
+                    format!("Invalid pattern {}: {}", pattern, e),
+                ));
+            }
+        }
+
+        for extension in self.indexable_extensions.iter().flatten() {
+            if let Err(e) = normalize_extension(extension) {
+                errors.push(FieldError::new("indexable_extensions", e.to_string()));
+            }
+        }
+
+        if self
+            .watcher_debounce_ms
+            .is_some_and(|ms| ms > MAX_WATCHER_DEBOUNCE_MS)
+        {
+            errors.push(FieldError::new(
+                "watcher_debounce_ms",
+                format!("Must be at most {}ms", MAX_WATCHER_DEBOUNCE_MS),
+            ));
+        }
+
+        if self.watcher_max_batch_size == Some(0) {
+            errors.push(FieldError::new(
+                "watcher_max_batch_size",
+                "Must be at least 1",
+            ));
+        }
+
+        if self.watcher_poll_interval_secs == Some(0) {
+            errors.push(FieldError::new(
+                "watcher_poll_interval_secs",
+                "Must be at least 1 second",
+            ));
+        }
+
+        if let Some(tokenizer) = &self.fts_tokenizer {
+            if !FTS_TOKENIZERS.contains(&tokenizer.to_lowercase().as_str()) {
+                errors.push(FieldError::new(
+                    "fts_tokenizer",
+                    format!("Unknown tokenizer {}", tokenizer),
+                ));
+            }
+        }
+
+        if let Some(count) = self.result_count {
+            if !(1..=MAX_RESULT_COUNT).contains(&count) {
+                errors.push(FieldError::new(
+                    "result_count",
+                    format!("Must be between 1 and {}", MAX_RESULT_COUNT),
+                ));
+            }
+        }
+
+        if let Some(mode) = &self.default_search_mode {
+            if !SEARCH_MODES.contains(&mode.as_str()) {
+                errors.push(FieldError::new(
+                    "default_search_mode",
+                    format!("Unknown search mode {}", mode),
+                ));
+            }
+        }
+
+        if let Some(search) = &self.search {
+            search.validate(&mut errors);
+        }
+
+        if let Some(resource_alerts) = &self.resource_alerts {
+            resource_alerts.validate(&mut errors);
+        }
+
+        if let Some(api_server) = &self.api_server {
+            api_server.validate(&mut errors);
+        }
+
+        errors
+    }
+
+    /// Returns the user's indexable extensions or the defaults if they haven't configured any
+    pub fn get_indexable_extensions(&self) -> Vec<String> {
+        match &self.indexable_extensions {
+            Some(extensions) => extensions.clone(),
+            None => DEFAULT_INDEXABLE_EXTENSIONS
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect(),
+        }
+    }
 }
 
 #[derive(Error, Debug)]
@@ -23,13 +477,174 @@ pub enum SettingsError {
 
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+
+    #[error("Invalid value: {0}")]
+    InvalidValue(String),
+
+    #[error("Invalid settings: {}", format_field_errors(.0))]
+    Validation(Vec<FieldError>),
+}
+
+fn format_field_errors(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|error| format!("{}: {}", error.field, error.message))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Error returned by update_settings, field errors are kept separate so the UI can show them next to the fields
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UpdateSettingsError {
+    Invalid { errors: Vec<FieldError> },
+    Failed { message: String },
+}
+
+impl From<SettingsError> for UpdateSettingsError {
+    fn from(error: SettingsError) -> Self {
+        match error {
+            SettingsError::Validation(errors) => UpdateSettingsError::Invalid { errors },
+            other => UpdateSettingsError::Failed {
+                message: format!("Failed to update settings: {}", other),
+            },
+        }
+    }
+}
+
+/// Checks a shortcut like "CmdOrCtrl+Shift+K", one or more modifiers followed by a single key
+fn validate_hotkey(hotkey: &str) -> std::result::Result<(), String> {
+    let parts: Vec<String> = hotkey
+        .split('+')
+        .map(|part| part.trim().to_lowercase())
+        .collect();
+
+    let Some((key, modifiers)) = parts.split_last() else {
+        return Err("The shortcut is empty".to_string());
+    };
+
+    if modifiers.is_empty() {
+        return Err("The shortcut needs at least one modifier".to_string());
+    }
+
+    if let Some(unknown) = modifiers
+        .iter()
+        .find(|modifier| !HOTKEY_MODIFIERS.contains(&modifier.as_str()))
+    {
+        return Err(format!("Unknown modifier {}", unknown));
+    }
+
+    let is_function_key = key
+        .strip_prefix('f')
+        .and_then(|number| number.parse::<u8>().ok())
+        .is_some_and(|number| (1..=24).contains(&number));
+    let is_single_character = key.chars().count() == 1 && key.chars().all(|c| c.is_ascii_graphic());
+
+    if !is_function_key && !is_single_character && !HOTKEY_NAMED_KEYS.contains(&key.as_str()) {
+        return Err(format!("Unknown key {}", key));
+    }
+
+    Ok(())
 }
 
 type Result<T, E = SettingsError> = std::result::Result<T, E>;
 
+/// A change to the stored settings JSON, applied in order of version when older settings are loaded
+/// Works on the raw JSON so fields can be renamed or converted before they are deserialized
+struct SettingsMigration {
+    version: u32,
+    description: &'static str,
+    apply: fn(&mut serde_json::Map<String, serde_json::Value>),
+}
+
+const SETTINGS_MIGRATIONS: [SettingsMigration; 1] = [SettingsMigration {
+    version: 1,
+    description: "add the settings version",
+    apply: normalize_unversioned_settings,
+}];
+
+/// Settings saved before versioning could store an empty string for fields that were cleared in the UI
+fn normalize_unversioned_settings(settings: &mut serde_json::Map<String, serde_json::Value>) {
+    for value in settings.values_mut() {
+        if value.as_str().is_some_and(|s| s.trim().is_empty()) {
+            *value = serde_json::Value::Null;
+        }
+    }
+}
+
+/// Upgrades stored settings to CURRENT_SETTINGS_VERSION
+/// Returns the settings and whether anything was migrated, so the upgraded settings can be saved
+pub fn migrate_settings(json: &str) -> Result<(AppSettings, bool)> {
+    let mut value: serde_json::Value = serde_json::from_str(json)?;
+    let Some(map) = value.as_object_mut() else {
+        return Err(SettingsError::InvalidValue(
+            "Stored settings are not a JSON object".to_string(),
+        ));
+    };
+
+    let stored_version = map
+        .get("version")
+        .and_then(|version| version.as_u64())
+        .unwrap_or(0) as u32;
+
+    if stored_version > CURRENT_SETTINGS_VERSION {
+        // written by a newer build, unknown fields are ignored and the rest is used as is
+        warn!(
+            "Settings version {} is newer than this build supports ({})",
+            stored_version, CURRENT_SETTINGS_VERSION
+        );
+    }
+
+    let mut migrated = false;
+    for migration in SETTINGS_MIGRATIONS
+        .iter()
+        .filter(|migration| migration.version > stored_version)
+    {
+        (migration.apply)(map);
+        map.insert("version".to_string(), migration.version.into());
+        migrated = true;
+
+        info!(
+            "Applied settings migration {}: {}",
+            migration.version, migration.description
+        );
+    }
+
+    Ok((serde_json::from_value(value)?, migrated))
+}
+
 pub struct SettingsManager {
     settings: Mutex<AppSettings>,
     db_path: String,
+    app_handle: AppHandle,
+}
+
+/// A setting that changed, sent in the settings-changed event
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SettingChange {
+    pub key: String,
+    pub old_value: serde_json::Value,
+    pub new_value: serde_json::Value,
+}
+
+/// Compares the serialized settings and returns the fields that differ
+pub fn diff_settings(old: &AppSettings, new: &AppSettings) -> Vec<SettingChange> {
+    let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return Vec::new();
+    };
+
+    new.into_iter()
+        .filter_map(|(key, new_value)| {
+            let old_value = old.get(&key).cloned().unwrap_or(serde_json::Value::Null);
+            (old_value != new_value).then_some(SettingChange {
+                key,
+                old_value,
+                new_value,
+            })
+        })
+        .collect()
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -42,15 +657,16 @@ pub enum SettingValue {
 }
 
 impl SettingsManager {
-    pub fn new(db_path: &str) -> Self {
+    pub fn new(db_path: &str, app_handle: AppHandle) -> Self {
         Self {
             settings: Mutex::new(AppSettings::default()),
             db_path: db_path.to_string(),
+            app_handle,
         }
     }
 
     fn get_connection(&self) -> Result<Connection> {
-        Connection::open(&self.db_path).map_err(SettingsError::Database)
+        open_connection(&self.db_path).map_err(SettingsError::Database)
     }
 
     pub fn initialize(&self) -> Result<()> {
@@ -64,15 +680,21 @@ impl SettingsManager {
 
         match settings_result {
             Ok(json) => {
-                let loaded_settings: AppSettings = serde_json::from_str(&json)?;
+                let (loaded_settings, migrated) = migrate_settings(&json)?;
 
                 let mut settings = self.settings.lock().unwrap();
                 *settings = loaded_settings;
+                drop(settings);
+
+                if migrated {
+                    self.save()?;
+                }
             }
             Err(rusqlite::Error::QueryReturnedNoRows) => {
-                println!("error");
+                info!("No saved settings, saving the defaults");
 
                 // No settings found, save defaults
+                self.settings.lock().unwrap().version = CURRENT_SETTINGS_VERSION;
                 self.save()?;
             }
             Err(e) => return Err(SettingsError::Database(e)),
@@ -103,13 +725,84 @@ impl SettingsManager {
     }
 
     // Update the entire settings object
-    pub fn update(&self, new_settings: AppSettings) -> Result<()> {
+    pub fn update(&self, mut new_settings: AppSettings) -> Result<()> {
+        let errors = new_settings.validate();
+        if !errors.is_empty() {
+            return Err(SettingsError::Validation(errors));
+        }
+
+        // the UI sends back what it loaded, which may not carry the version
+        new_settings.version = new_settings.version.max(CURRENT_SETTINGS_VERSION);
+
         let mut settings = self.settings.lock().unwrap();
-        *settings = new_settings;
+        let previous = std::mem::replace(&mut *settings, new_settings);
         drop(settings); // Release the lock
         self.save()?;
+        self.notify_changes(&previous);
         Ok(())
     }
+
+    /// Emits settings-changed with the fields that differ from the previous settings
+    fn notify_changes(&self, previous: &AppSettings) {
+        let current = self.settings.lock().unwrap().clone();
+        let changes = diff_settings(previous, &current);
+
+        if changes.is_empty() {
+            return;
+        }
+
+        if let Err(e) = self.app_handle.emit("settings-changed", &changes) {
+            error!("Failed to emit settings-changed event: {}", e);
+        }
+    }
+
+    /// Add an extension to the indexable extensions and return the updated list
+    pub fn add_indexable_extension(&self, extension: &str) -> Result<Vec<String>> {
+        let extension = normalize_extension(extension)?;
+
+        let mut settings = self.settings.lock().unwrap();
+        let previous = settings.clone();
+        let mut extensions = settings.get_indexable_extensions();
+        if !extensions.contains(&extension) {
+            extensions.push(extension);
+        }
+        settings.indexable_extensions = Some(extensions.clone());
+        drop(settings);
+
+        self.save()?;
+        self.notify_changes(&previous);
+        Ok(extensions)
+    }
+
+    /// Remove an extension from the indexable extensions and return the updated list
+    pub fn remove_indexable_extension(&self, extension: &str) -> Result<Vec<String>> {
+        let extension = normalize_extension(extension)?;
+
+        let mut settings = self.settings.lock().unwrap();
+        let previous = settings.clone();
+        let mut extensions = settings.get_indexable_extensions();
+        extensions.retain(|ext| *ext != extension);
+        settings.indexable_extensions = Some(extensions.clone());
+        drop(settings);
+
+        self.save()?;
+        self.notify_changes(&previous);
+        Ok(extensions)
+    }
+}
+
+/// Lowercases the extension and strips the leading dot so ".PDF" and "pdf" are the same extension
+pub fn normalize_extension(extension: &str) -> Result<String> {
+    let normalized = extension.trim().trim_start_matches('.').to_lowercase();
+
+    if normalized.is_empty() || normalized.contains(|c: char| c == '/' || c.is_whitespace()) {
+        return Err(SettingsError::InvalidValue(format!(
+            "'{}' is not a valid file extension",
+            extension
+        )));
+    }
+
+    Ok(normalized)
 }
 
 pub struct SettingsManagerState(pub Arc<SettingsManager>);
@@ -120,7 +813,7 @@ pub fn init_settings(
     app_handle: AppHandle,
 ) -> std::result::Result<(), Box<dyn std::error::Error>> {
     // Create settings manager
-    let settings_manager = SettingsManager::new(db_path);
+    let settings_manager = SettingsManager::new(db_path, app_handle.clone());
 
     // Initialize settings (load or create default)
     settings_manager.initialize()?;
@@ -128,7 +821,7 @@ pub fn init_settings(
     // Store in app state
     app_handle.manage(SettingsManagerState(Arc::new(settings_manager)));
 
-    println!("Settings initialized");
+    info!("Settings initialized");
     Ok(())
 }
 
@@ -142,13 +835,47 @@ pub async fn get_settings(
         .map_err(|e| format!("Failed to get settings: {}", e))
 }
 
+/// Saves the settings if every value is valid, otherwise returns an error for each invalid field
 #[tauri::command]
 pub async fn update_settings(
     settings_manager: tauri::State<'_, SettingsManagerState>,
     settings: AppSettings,
-) -> Result<(), String> {
+) -> Result<(), UpdateSettingsError> {
     settings_manager
         .0
         .update(settings)
-        .map_err(|e| format!("Failed to update settings: {}", e))
+        .map_err(UpdateSettingsError::from)
+}
+
+#[tauri::command]
+pub async fn get_indexable_extensions(
+    settings_manager: tauri::State<'_, SettingsManagerState>,
+) -> Result<Vec<String>, String> {
+    settings_manager
+        .0
+        .get_settings()
+        .map(|settings| settings.get_indexable_extensions())
+        .map_err(|e| format!("Failed to get settings: {}", e))
+}
+
+#[tauri::command]
+pub async fn add_indexable_extension(
+    settings_manager: tauri::State<'_, SettingsManagerState>,
+    extension: String,
+) -> Result<Vec<String>, String> {
+    settings_manager
+        .0
+        .add_indexable_extension(&extension)
+        .map_err(|e| format!("Failed to add extension: {}", e))
+}
+
+#[tauri::command]
+pub async fn remove_indexable_extension(
+    settings_manager: tauri::State<'_, SettingsManagerState>,
+    extension: String,
+) -> Result<Vec<String>, String> {
+    settings_manager
+        .0
+        .remove_indexable_extension(&extension)
+        .map_err(|e| format!("Failed to remove extension: {}", e))
 }
@@ -0,0 +1,210 @@
+/*
+This file contains the export and import of the settings so a setup can be copied to another machine. The export is a single JSON file with the settings and optionally the tags with the paths of the files they are on, the index itself is moved with index_archive instead
+*/
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, State};
+use tokio::task;
+use tracing::info;
+
+use crate::database_handler::open_connection;
+use crate::file_processor::{get_processor, FileProcessor, FileProcessorError, FileProcessorState};
+use crate::settings::{migrate_settings, AppSettings, SettingsManagerState};
+use crate::tags::change_file_tag;
+
+/// Bumped when the layout of the export file changes
+const SETTINGS_EXPORT_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SettingsExport {
+    format_version: u32,
+    exported_at: String,
+    /// Home directory of the exporting machine, tagged paths below it are moved to the importing user's home
+    home_dir: Option<String>,
+    settings: serde_json::Value,
+    #[serde(default)]
+    tags: Vec<ExportedTag>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExportedTag {
+    name: String,
+    paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsImportReport {
+    pub tags_imported: usize,
+    /// Tagged files that aren't indexed on this machine
+    pub missing_files: usize,
+}
+
+fn home_dir_string() -> Option<String> {
+    dirs::home_dir().map(|home| home.to_string_lossy().trim_end_matches('/').to_string())
+}
+
+/// Copies the settings that belong to this machine, they are never exported or overwritten by an import
+fn copy_machine_settings(from: &AppSettings, to: &mut AppSettings) {
+    // the token is a secret and the rest points at files or state on this machine
+    to.hf_token = from.hf_token.clone();
+    to.custom_model_path = from.custom_model_path.clone();
+    to.encrypt_index = from.encrypt_index;
+    to.window_width = from.window_width;
+    to.window_height = from.window_height;
+    to.launch_at_login = from.launch_at_login;
+}
+
+fn load_tags(db_path: PathBuf) -> Result<Vec<ExportedTag>, FileProcessorError> {
+    let conn = open_connection(db_path)?;
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT t.name, f.path
+        FROM tags t
+        JOIN file_tags ft ON ft.tag_id = t.id
+        JOIN files f ON f.id = ft.file_id
+        ORDER BY t.name COLLATE NOCASE, f.path
+        "#,
+    )?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get(1)?)))?;
+
+    let mut tags: Vec<ExportedTag> = Vec::new();
+    for row in rows {
+        let (name, path) = row?;
+        match tags.last_mut() {
+            Some(tag) if tag.name == name => tag.paths.push(path),
+            _ => tags.push(ExportedTag {
+                name,
+                paths: vec![path],
+            }),
+        }
+    }
+
+    Ok(tags)
+}
+
+/// Writes the settings, and the tags if asked for, to a JSON file at `path`
+#[tauri::command]
+pub async fn export_settings(
+    path: String,
+    include_tags: bool,
+    settings_manager: State<'_, SettingsManagerState>,
+    state: State<'_, FileProcessorState>,
+) -> Result<(), String> {
+    let mut settings = settings_manager
+        .0
+        .get_settings()
+        .map_err(|e| format!("Failed to get settings: {}", e))?;
+    copy_machine_settings(&AppSettings::default(), &mut settings);
+
+    let tags = if include_tags {
+        let processor: FileProcessor = get_processor(&state)?;
+        let db_path = processor.db_path.clone();
+        task::spawn_blocking(move || load_tags(db_path))
+            .await
+            .map_err(|e| format!("spawn_blocking error: {e}"))?
+            .map_err(|e| e.to_string())?
+    } else {
+        Vec::new()
+    };
+
+    let export = SettingsExport {
+        format_version: SETTINGS_EXPORT_FORMAT_VERSION,
+        exported_at: Local::now().to_rfc3339(),
+        home_dir: home_dir_string(),
+        settings: serde_json::to_value(&settings).map_err(|e| e.to_string())?,
+        tags,
+    };
+
+    let json = serde_json::to_string_pretty(&export).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+
+    info!("Exported settings to {}", path);
+    Ok(())
+}
+
+/// Replaces the settings with the ones in the file and adds its tags to the files that are indexed here
+/// Machine specific settings like the Hugging Face token are kept
+#[tauri::command]
+pub async fn import_settings(
+    path: String,
+    settings_manager: State<'_, SettingsManagerState>,
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<SettingsImportReport, String> {
+    let json = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let export: SettingsExport = serde_json::from_str(&json)
+        .map_err(|e| format!("{} is not a settings export: {}", path, e))?;
+
+    if export.format_version > SETTINGS_EXPORT_FORMAT_VERSION {
+        return Err(format!(
+            "The settings were exported by a newer version of the app (format {})",
+            export.format_version
+        ));
+    }
+
+    // exports from older versions go through the same migrations as stored settings
+    let (mut imported, _) =
+        migrate_settings(&export.settings.to_string()).map_err(|e| e.to_string())?;
+
+    let current = settings_manager
+        .0
+        .get_settings()
+        .map_err(|e| format!("Failed to get settings: {}", e))?;
+    copy_machine_settings(&current, &mut imported);
+
+    settings_manager
+        .0
+        .update(imported)
+        .map_err(|e| format!("Failed to import settings: {}", e))?;
+
+    let mut report = SettingsImportReport {
+        tags_imported: 0,
+        missing_files: 0,
+    };
+
+    if !export.tags.is_empty() {
+        let processor: FileProcessor = get_processor(&state)?;
+        let home_dirs = (export.home_dir, home_dir_string());
+
+        for tag in export.tags {
+            let mut tagged_any = false;
+
+            for tagged_path in tag.paths {
+                let tagged_path = match &home_dirs {
+                    (Some(from), Some(to)) if tagged_path.starts_with(&format!("{}/", from)) => {
+                        format!("{}{}", to, &tagged_path[from.len()..])
+                    }
+                    _ => tagged_path,
+                };
+
+                match change_file_tag(
+                    processor.db_path.clone(),
+                    tagged_path,
+                    tag.name.clone(),
+                    true,
+                )
+                .await
+                {
+                    Ok(_) => tagged_any = true,
+                    Err(_) => report.missing_files += 1,
+                }
+            }
+
+            if tagged_any {
+                report.tags_imported += 1;
+            }
+        }
+
+        let _ = app_handle.emit("files-updated", ());
+    }
+
+    info!(
+        "Imported settings from {} with {} tags",
+        path, report.tags_imported
+    );
+    Ok(report)
+}
@@ -0,0 +1,157 @@
+/*
+This file contains the Spotlight fallback of the file search. Files outside the indexed directories are looked up by name in Spotlight's metadata store with mdfind and added after the indexed results, labeled as Spotlight results, so the launcher still finds files in folders that aren't indexed yet
+*/
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tracing::error;
+
+use crate::file_processor::{get_file_metadata, FileMetadata, FileProcessor, FileSource};
+use crate::settings::SearchSettings;
+
+/// Spotlight results added to a search, after the indexed ones
+#[cfg(target_os = "macos")]
+const MAX_SPOTLIGHT_RESULTS: usize = 20;
+
+/// Shorter queries match too many files to be useful
+const MIN_QUERY_CHARS: usize = 3;
+
+/// Runs mdfind with -name and returns the paths it finds that `accept` keeps
+/// mdfind is stopped once enough paths are kept or it takes longer than the search can wait
+#[cfg(target_os = "macos")]
+fn mdfind_paths(query: &str, mut accept: impl FnMut(&Path) -> bool) -> Vec<PathBuf> {
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    const MDFIND_TIMEOUT: Duration = Duration::from_millis(1500);
+
+    let mut child = match Command::new("mdfind")
+        .arg("-name")
+        .arg(query)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            error!("Failed to run mdfind: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let Some(stdout) = child.stdout.take() else {
+        let _ = child.kill();
+        return Vec::new();
+    };
+
+    // lines are read on their own thread so the wait for them can time out
+    let (sender, receiver) = mpsc::channel::<String>();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let deadline = Instant::now() + MDFIND_TIMEOUT;
+    let mut paths = Vec::new();
+    while paths.len() < MAX_SPOTLIGHT_RESULTS {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        // times out, or mdfind finished
+        let Ok(line) = receiver.recv_timeout(remaining) else {
+            break;
+        };
+
+        let path = PathBuf::from(line);
+        if accept(&path) {
+            paths.push(path);
+        }
+    }
+
+    let _ = child.kill();
+    let _ = child.wait();
+    paths
+}
+
+#[cfg(not(target_os = "macos"))]
+fn mdfind_paths(_query: &str, _accept: impl FnMut(&Path) -> bool) -> Vec<PathBuf> {
+    Vec::new()
+}
+
+fn indexed_roots(processor: &FileProcessor) -> Result<Vec<PathBuf>, String> {
+    let conn = processor.get_read_connection()?;
+
+    let mut stmt = conn
+        .prepare("SELECT path FROM directories WHERE is_root = 1")
+        .map_err(|e| format!("Failed to prepare statement: {e}"))?;
+
+    let roots = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| format!("Query error: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Row error: {e}"))?;
+
+    Ok(roots.into_iter().map(PathBuf::from).collect())
+}
+
+/// Files Spotlight finds for the query that are outside the indexed roots and not in `known_paths`
+fn spotlight_files(
+    query: &str,
+    roots: &[PathBuf],
+    known_paths: &HashSet<String>,
+) -> Vec<FileMetadata> {
+    let paths = mdfind_paths(query, |path| {
+        path.is_file()
+            && !roots.iter().any(|root| path.starts_with(root))
+            && !known_paths.contains(path.to_string_lossy().as_ref())
+    });
+
+    let mut files = Vec::with_capacity(paths.len());
+    for path in paths {
+        if let Err(e) = get_file_metadata(&path, &mut files) {
+            error!("Failed to read {} from Spotlight: {}", path.display(), e);
+        }
+    }
+
+    for file in &mut files {
+        file.source = FileSource::Spotlight;
+    }
+    files
+}
+
+/// Appends Spotlight matches from outside the indexed directories to the search results
+/// The results are returned unchanged when the fallback is turned off or the query is too short
+pub async fn with_spotlight_results(
+    app_handle: &AppHandle,
+    processor: &FileProcessor,
+    query: &str,
+    mut files: Vec<FileMetadata>,
+) -> Vec<FileMetadata> {
+    let query = query.trim().to_string();
+    if !cfg!(target_os = "macos")
+        || !SearchSettings::from_app_handle(app_handle).spotlight_fallback()
+        || query.chars().count() < MIN_QUERY_CHARS
+    {
+        return files;
+    }
+
+    let roots = match indexed_roots(processor) {
+        Ok(roots) => roots,
+        Err(e) => {
+            error!("Failed to load the indexed directories: {}", e);
+            return files;
+        }
+    };
+    let known_paths: HashSet<String> = files.iter().map(|file| file.base.path.clone()).collect();
+
+    match tokio::task::spawn_blocking(move || spotlight_files(&query, &roots, &known_paths)).await {
+        Ok(spotlight) => files.extend(spotlight),
+        Err(e) => error!("spawn_blocking error: {}", e),
+    }
+
+    files
+}
@@ -0,0 +1,113 @@
+/*
+This file contains the crash handling of the background tasks. The panic hook logs every panic with its backtrace, and the watcher loop, the index workers and the model loading run under supervise, which tells the frontend with the background-task-crashed event and restarts the task where it's safe to instead of letting it die silently
+*/
+
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::future::Future;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tracing::{error, info};
+
+/// Restarts of a task before it's left stopped, a task that keeps panicking would only flood the logs
+const MAX_RESTARTS: u32 = 5;
+
+/// Wait before the first restart, doubled after every crash
+const INITIAL_RESTART_DELAY: Duration = Duration::from_secs(1);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackgroundTaskCrash {
+    pub task: String,
+    pub message: String,
+    /// Whether the task is started again, false once it ran out of restarts or can't be restarted
+    pub restarting: bool,
+    pub restarts: u32,
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Logs every panic with the thread, location and backtrace, then runs the default hook
+/// Installed right after logging starts so panics during the rest of the startup are logged too
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |panic_info| {
+        let thread = std::thread::current();
+        let location = panic_info
+            .location()
+            .map(|location| location.to_string())
+            .unwrap_or_else(|| "unknown location".to_string());
+
+        error!(
+            thread = thread.name().unwrap_or("unnamed"),
+            location = %location,
+            backtrace = %Backtrace::force_capture(),
+            "Panic: {}",
+            panic_message(panic_info.payload())
+        );
+
+        default_hook(panic_info);
+    }));
+}
+
+/// Runs the task made by `make_task` in the background and watches it for panics
+/// A crash is sent to the frontend with the background-task-crashed event, and when `restart` is set a new task is made and started after a delay
+/// Only pass `restart` for tasks that pick up where they left off when started again
+pub fn supervise<F, Fut>(app_handle: AppHandle, task: &'static str, restart: bool, make_task: F)
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    tauri::async_runtime::spawn(async move {
+        let mut restarts = 0;
+        let mut delay = INITIAL_RESTART_DELAY;
+
+        loop {
+            let message = match tauri::async_runtime::spawn(make_task()).await {
+                Ok(()) => return,
+                Err(tauri::Error::JoinError(e)) if e.is_panic() => {
+                    panic_message(e.into_panic().as_ref())
+                }
+                Err(e) => {
+                    error!("Background task {} stopped: {}", task, e);
+                    return;
+                }
+            };
+
+            let restarting = restart && restarts < MAX_RESTARTS;
+            error!(
+                "Background task {} crashed after {} restarts: {}",
+                task, restarts, message
+            );
+
+            let _ = app_handle.emit(
+                "background-task-crashed",
+                BackgroundTaskCrash {
+                    task: task.to_string(),
+                    message,
+                    restarting,
+                    restarts,
+                },
+            );
+
+            if !restarting {
+                return;
+            }
+
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_RESTART_DELAY);
+            restarts += 1;
+            info!("Restarting background task {}", task);
+        }
+    });
+}
@@ -1,4 +1,6 @@
 import AppKit
+import ApplicationServices
+import CoreServices
 import Foundation
 import UniformTypeIdentifiers
 
@@ -8,9 +10,38 @@ struct AppMetadata: Codable, Hashable {
     var pid: Int32?
     var icon: String?
     var resource_usage: Float64?
+    var bundle_id: String? = nil
+    var version: String? = nil
+    var last_used: String? = nil
 }
 
 class AppHandler {
+    // Builds the metadata with the bundle id and version from Info.plist and the last used date from Spotlight
+    static func makeAppMetadata(name: String, path: String, pid: Int32?) -> AppMetadata {
+        let bundle = Bundle(path: path)
+
+        return AppMetadata(
+            name: name,
+            path: path,
+            pid: pid,
+            icon: nil,
+            resource_usage: nil,
+            bundle_id: bundle?.bundleIdentifier,
+            version: bundle?.infoDictionary?["CFBundleShortVersionString"] as? String,
+            last_used: getLastUsedDate(path: path)
+        )
+    }
+
+    static func getLastUsedDate(path: String) -> String? {
+        guard let item = MDItemCreate(kCFAllocatorDefault, path as CFString),
+            let date = MDItemCopyAttribute(item, kMDItemLastUsedDate) as? Date
+        else {
+            return nil
+        }
+
+        return ISO8601DateFormatter().string(from: date)
+    }
+
     static func getInstalledApps() -> [AppMetadata] {
         let applicationDirectories = [
             "/Applications",
@@ -57,13 +88,7 @@ class AppHandler {
 
                 let appName = fileURL.deletingPathExtension().lastPathComponent
 
-                let app = AppMetadata(
-                    name: appName,
-                    path: fileURL.path,
-                    pid: nil,
-                    icon: nil,
-                    resource_usage: nil
-                )
+                let app = makeAppMetadata(name: appName, path: fileURL.path, pid: nil)
 
                 installedApps.append(app)
             }
@@ -90,13 +115,7 @@ class AppHandler {
 
                 // pid can be negative if the app is in an error state or transition state, here we check that
                 let validPid: Int32? = app.processIdentifier > 0 ? app.processIdentifier : nil
-                return AppMetadata(
-                    name: appName,
-                    path: bundlePath,
-                    pid: validPid,
-                    icon: nil,
-                    resource_usage: nil
-                )
+                return makeAppMetadata(name: appName, path: bundlePath, pid: validPid)
             }
             .sorted { $0.name < $1.name }
     }
@@ -171,6 +190,44 @@ class AppHandler {
         return app.terminate()
     }
 
+    // Hide all windows of an application, like cmd+H
+    static func hideApplication(pid: Int32) -> Bool {
+        guard let app = NSRunningApplication(processIdentifier: pid) else {
+            return false
+        }
+
+        return app.hide()
+    }
+
+    // Minimize all windows of an application to the Dock
+    // AppKit can't minimize other apps' windows, so this goes through the accessibility API and needs the accessibility permission
+    static func minimizeApplication(pid: Int32) -> Bool {
+        guard NSRunningApplication(processIdentifier: pid) != nil else {
+            return false
+        }
+
+        let appElement = AXUIElementCreateApplication(pid)
+        var windowsValue: CFTypeRef?
+        let result = AXUIElementCopyAttributeValue(
+            appElement, kAXWindowsAttribute as CFString, &windowsValue)
+
+        guard result == .success, let windows = windowsValue as? [AXUIElement] else {
+            print("Failed to get windows for pid \(pid), accessibility permission may be missing")
+            return false
+        }
+
+        var minimizedAny = false
+        for window in windows {
+            let minimized = AXUIElementSetAttributeValue(
+                window, kAXMinimizedAttribute as CFString, kCFBooleanTrue)
+            if minimized == .success {
+                minimizedAny = true
+            }
+        }
+
+        return minimizedAny
+    }
+
     // Restart an application
     static func restartApplication(path: String, completion: @escaping (Bool, Error?) -> Void) {
         let url = URL(fileURLWithPath: path)
@@ -268,6 +325,18 @@ public func forceQuitAppSwift(pid: Int32) -> Bool {
     return AppHandler.forceQuitApplication(pid: pid)
 }
 
+// C-compatible function to hide an app
+@_cdecl("hide_app_swift")
+public func hideAppSwift(pid: Int32) -> Bool {
+    return AppHandler.hideApplication(pid: pid)
+}
+
+// C-compatible function to minimize an app's windows
+@_cdecl("minimize_app_swift")
+public func minimizeAppSwift(pid: Int32) -> Bool {
+    return AppHandler.minimizeApplication(pid: pid)
+}
+
 // C-compatible function to restart an app
 @_cdecl("restart_app_swift")
 public func restartAppSwift(path: UnsafePointer<CChar>?) -> Bool {
@@ -294,6 +363,104 @@ public func restartAppSwift(path: UnsafePointer<CChar>?) -> Bool {
     return result
 }
 
+struct RecentDocument: Codable {
+    var name: String
+    var path: String
+}
+
+class RecentDocumentsHandler {
+    // macOS keeps the recent documents of every app in a shared file list named after its bundle id
+    static let sharedFileListDirectory = FileManager.default.homeDirectoryForCurrentUser
+        .appendingPathComponent(
+            "Library/Application Support/com.apple.sharedfilelist/com.apple.LSSharedFileList.ApplicationRecentDocuments"
+        )
+
+    static func getRecentDocuments(appPath: String, limit: Int) -> [RecentDocument] {
+        guard let bundleId = Bundle(path: appPath)?.bundleIdentifier?.lowercased() else {
+            return []
+        }
+
+        // macOS 14 switched to .sfl3, older versions use .sfl2
+        let candidates = ["sfl3", "sfl2"].map {
+            sharedFileListDirectory.appendingPathComponent("\(bundleId).\($0)")
+        }
+        guard
+            let fileURL = candidates.first(where: {
+                FileManager.default.fileExists(atPath: $0.path)
+            }),
+            let data = try? Data(contentsOf: fileURL),
+            let unarchiver = try? NSKeyedUnarchiver(forReadingFrom: data)
+        else {
+            return []
+        }
+
+        // the list contains classes that don't support secure coding
+        unarchiver.requiresSecureCoding = false
+        guard
+            let archive = unarchiver.decodeObject(forKey: NSKeyedArchiveRootObjectKey)
+                as? NSDictionary,
+            let items = archive["items"] as? [NSDictionary]
+        else {
+            return []
+        }
+
+        var documents: [RecentDocument] = []
+        for item in items {
+            if documents.count >= limit {
+                break
+            }
+
+            guard let bookmark = item["Bookmark"] as? Data else {
+                continue
+            }
+
+            var isStale = false
+            guard
+                let url = try? URL(
+                    resolvingBookmarkData: bookmark,
+                    options: [.withoutUI, .withoutMounting],
+                    relativeTo: nil,
+                    bookmarkDataIsStale: &isStale),
+                FileManager.default.fileExists(atPath: url.path)
+            else {
+                continue
+            }
+
+            documents.append(RecentDocument(name: url.lastPathComponent, path: url.path))
+        }
+
+        return documents
+    }
+}
+
+// C-compatible function to get the recent documents of an app as JSON
+@_cdecl("get_recent_documents_swift")
+public func getRecentDocumentsSwift(path: UnsafePointer<CChar>?, limit: Int32)
+    -> UnsafeMutablePointer<CChar>?
+{
+    guard let path = path,
+        let pathString = String(cString: path, encoding: .utf8)
+    else {
+        return nil
+    }
+
+    let encoder = JSONEncoder()
+
+    do {
+        let documents = RecentDocumentsHandler.getRecentDocuments(
+            appPath: pathString, limit: Int(limit))
+        let jsonData = try encoder.encode(documents)
+
+        if let jsonString = String(data: jsonData, encoding: .utf8) {
+            return strdup(jsonString)
+        }
+    } catch {
+        print("Error encoding recent documents: \(error)")
+    }
+
+    return nil
+}
+
 func getIcon(file path: String) -> NSImage? {
     guard FileManager.default.fileExists(atPath: path) else { return nil }
     return NSWorkspace.shared.icon(forFile: path)
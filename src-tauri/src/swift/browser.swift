@@ -0,0 +1,169 @@
+import AppKit
+import Foundation
+
+struct BrowserTab: Codable {
+    var browser: String
+    var window_index: Int
+    var tab_index: Int
+    var title: String
+    var url: String
+}
+
+enum Browser: String, CaseIterable {
+    case safari
+    case chrome
+
+    var applicationName: String {
+        switch self {
+        case .safari: return "Safari"
+        case .chrome: return "Google Chrome"
+        }
+    }
+
+    var bundleIdentifier: String {
+        switch self {
+        case .safari: return "com.apple.Safari"
+        case .chrome: return "com.google.Chrome"
+        }
+    }
+
+    // Safari calls the tab title "name", Chrome calls it "title"
+    var titleProperty: String {
+        switch self {
+        case .safari: return "name"
+        case .chrome: return "title"
+        }
+    }
+
+    // Scripting a browser that isn't running would launch it
+    var isRunning: Bool {
+        return !NSRunningApplication.runningApplications(withBundleIdentifier: bundleIdentifier)
+            .isEmpty
+    }
+}
+
+class BrowserHandler {
+    // ASCII unit and record separators, they don't show up in titles or URLs
+    static let fieldSeparator: Character = "\u{1F}"
+    static let recordSeparator: Character = "\u{1E}"
+
+    static func runAppleScript(_ source: String) -> NSAppleEventDescriptor? {
+        guard let script = NSAppleScript(source: source) else {
+            return nil
+        }
+
+        var error: NSDictionary?
+        let result = script.executeAndReturnError(&error)
+        if let error = error {
+            print("AppleScript error: \(error)")
+            return nil
+        }
+
+        return result
+    }
+
+    static func getTabs(browser: Browser) -> [BrowserTab] {
+        guard browser.isRunning else {
+            return []
+        }
+
+        let source = """
+            set output to ""
+            tell application "\(browser.applicationName)"
+                repeat with w from 1 to count of windows
+                    repeat with t from 1 to count of tabs of window w
+                        set tabTitle to \(browser.titleProperty) of tab t of window w
+                        set tabURL to URL of tab t of window w
+                        if tabTitle is missing value then set tabTitle to ""
+                        if tabURL is missing value then set tabURL to ""
+                        set output to output & w & (ASCII character 31) & t & (ASCII character 31) & tabTitle & (ASCII character 31) & tabURL & (ASCII character 30)
+                    end repeat
+                end repeat
+            end tell
+            return output
+            """
+
+        guard let output = runAppleScript(source)?.stringValue else {
+            return []
+        }
+
+        return output.split(separator: recordSeparator).compactMap { record in
+            let fields = record.split(
+                separator: fieldSeparator, maxSplits: 3, omittingEmptySubsequences: false)
+            guard fields.count == 4,
+                let windowIndex = Int(fields[0]),
+                let tabIndex = Int(fields[1])
+            else {
+                return nil
+            }
+
+            return BrowserTab(
+                browser: browser.rawValue,
+                window_index: windowIndex,
+                tab_index: tabIndex,
+                title: String(fields[2]),
+                url: String(fields[3])
+            )
+        }
+    }
+
+    // Selects the tab, brings its window to the front and activates the browser
+    static func focusTab(browser: Browser, windowIndex: Int, tabIndex: Int) -> Bool {
+        guard browser.isRunning else {
+            return false
+        }
+
+        let selectTab: String
+        switch browser {
+        case .safari:
+            selectTab = "set current tab of window \(windowIndex) to tab \(tabIndex) of window \(windowIndex)"
+        case .chrome:
+            selectTab = "set active tab index of window \(windowIndex) to \(tabIndex)"
+        }
+
+        let source = """
+            tell application "\(browser.applicationName)"
+                \(selectTab)
+                set index of window \(windowIndex) to 1
+                activate
+            end tell
+            """
+
+        return runAppleScript(source) != nil
+    }
+}
+
+// C-compatible function to get the open tabs of all running browsers as JSON
+@_cdecl("get_browser_tabs_swift")
+public func getBrowserTabsSwift() -> UnsafeMutablePointer<CChar>? {
+    let encoder = JSONEncoder()
+
+    do {
+        let tabs = Browser.allCases.flatMap { BrowserHandler.getTabs(browser: $0) }
+        let jsonData = try encoder.encode(tabs)
+
+        if let jsonString = String(data: jsonData, encoding: .utf8) {
+            return strdup(jsonString)
+        }
+    } catch {
+        print("Error encoding browser tabs: \(error)")
+    }
+
+    return nil
+}
+
+// C-compatible function to focus a browser tab
+@_cdecl("focus_browser_tab_swift")
+public func focusBrowserTabSwift(
+    browser: UnsafePointer<CChar>?, windowIndex: Int32, tabIndex: Int32
+) -> Bool {
+    guard let browser = browser,
+        let browserName = String(cString: browser, encoding: .utf8),
+        let browser = Browser(rawValue: browserName)
+    else {
+        return false
+    }
+
+    return BrowserHandler.focusTab(
+        browser: browser, windowIndex: Int(windowIndex), tabIndex: Int(tabIndex))
+}
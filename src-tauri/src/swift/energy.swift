@@ -0,0 +1,71 @@
+import Darwin
+import Foundation
+
+struct ProcessEnergyCounters: Codable {
+    var pid: Int32
+    var cpu_time_ns: UInt64
+    // CPU time spent at the background, utility and maintenance QoS classes
+    var background_cpu_time_ns: UInt64
+    var wakeups: UInt64
+}
+
+class EnergyHandler {
+    // the rusage CPU times are in mach absolute time units, which aren't nanoseconds on Apple silicon
+    static let timebase: mach_timebase_info_data_t = {
+        var info = mach_timebase_info_data_t()
+        mach_timebase_info(&info)
+        return info
+    }()
+
+    static func nanoseconds(_ machTime: UInt64) -> UInt64 {
+        return machTime * UInt64(timebase.numer) / UInt64(timebase.denom)
+    }
+
+    // Returns nil for processes of other users, proc_pid_rusage isn't allowed for them
+    static func getCounters(pid: Int32) -> ProcessEnergyCounters? {
+        var info = rusage_info_v4()
+        let result = withUnsafeMutablePointer(to: &info) { pointer in
+            pointer.withMemoryRebound(to: rusage_info_t?.self, capacity: 1) {
+                proc_pid_rusage(pid, RUSAGE_INFO_V4, $0)
+            }
+        }
+
+        guard result == 0 else {
+            return nil
+        }
+
+        let backgroundTime =
+            info.ri_cpu_time_qos_background + info.ri_cpu_time_qos_utility
+            + info.ri_cpu_time_qos_maintenance
+
+        return ProcessEnergyCounters(
+            pid: pid,
+            cpu_time_ns: nanoseconds(info.ri_user_time + info.ri_system_time),
+            background_cpu_time_ns: nanoseconds(backgroundTime),
+            wakeups: info.ri_pkg_idle_wkups + info.ri_interrupt_wkups
+        )
+    }
+}
+
+// C-compatible function to get the energy counters of the given PIDs, takes and returns JSON
+@_cdecl("get_energy_counters_swift")
+public func getEnergyCountersSwift(pidsJson: UnsafePointer<CChar>?) -> UnsafeMutablePointer<CChar>? {
+    guard let pidsJson = pidsJson,
+        let pidsData = String(cString: pidsJson, encoding: .utf8)?.data(using: .utf8),
+        let pids = try? JSONDecoder().decode([Int32].self, from: pidsData)
+    else {
+        return nil
+    }
+
+    do {
+        let counters = pids.compactMap { EnergyHandler.getCounters(pid: $0) }
+        let jsonData = try JSONEncoder().encode(counters)
+        if let jsonString = String(data: jsonData, encoding: .utf8) {
+            return strdup(jsonString)
+        }
+    } catch {
+        print("Error encoding energy counters: \(error)")
+    }
+
+    return nil
+}
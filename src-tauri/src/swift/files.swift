@@ -0,0 +1,21 @@
+import Foundation
+
+// C-compatible function to move a file or folder to the Trash
+@_cdecl("trash_file_swift")
+public func trashFileSwift(path: UnsafePointer<CChar>?) -> Bool {
+    guard let path = path,
+        let pathString = String(cString: path, encoding: .utf8)
+    else {
+        return false
+    }
+
+    let url = URL(fileURLWithPath: pathString)
+
+    do {
+        try FileManager.default.trashItem(at: url, resultingItemURL: nil)
+        return true
+    } catch {
+        print("Error moving \(pathString) to Trash: \(error)")
+        return false
+    }
+}
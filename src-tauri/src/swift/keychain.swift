@@ -0,0 +1,81 @@
+import Foundation
+import Security
+
+// C-compatible function to read a generic password from the keychain
+// The OSStatus is written to status, errSecItemNotFound when there is no such item
+// Returns nil when the password couldn't be read, the caller must free the result with free_string_swift
+@_cdecl("load_keychain_password_swift")
+public func loadKeychainPasswordSwift(
+    service: UnsafePointer<CChar>?,
+    account: UnsafePointer<CChar>?,
+    status: UnsafeMutablePointer<Int32>?
+) -> UnsafeMutablePointer<CChar>? {
+    guard let service = service,
+        let account = account,
+        let serviceString = String(cString: service, encoding: .utf8),
+        let accountString = String(cString: account, encoding: .utf8)
+    else {
+        status?.pointee = errSecParam
+        return nil
+    }
+
+    let query: [String: Any] = [
+        kSecClass as String: kSecClassGenericPassword,
+        kSecAttrService as String: serviceString,
+        kSecAttrAccount as String: accountString,
+        kSecReturnData as String: true,
+        kSecMatchLimit as String: kSecMatchLimitOne,
+    ]
+
+    var result: AnyObject?
+    let copyStatus = SecItemCopyMatching(query as CFDictionary, &result)
+    status?.pointee = copyStatus
+
+    guard copyStatus == errSecSuccess,
+        let data = result as? Data,
+        let password = String(data: data, encoding: .utf8)
+    else {
+        return nil
+    }
+
+    return strdup(password)
+}
+
+// C-compatible function to store a generic password in the keychain, replacing the one that is there
+// The password is handed to the Security framework directly so it never shows up in the process list
+// Returns the OSStatus, errSecSuccess when the password was stored
+@_cdecl("store_keychain_password_swift")
+public func storeKeychainPasswordSwift(
+    service: UnsafePointer<CChar>?,
+    account: UnsafePointer<CChar>?,
+    password: UnsafePointer<CChar>?
+) -> Int32 {
+    guard let service = service,
+        let account = account,
+        let password = password,
+        let serviceString = String(cString: service, encoding: .utf8),
+        let accountString = String(cString: account, encoding: .utf8),
+        let passwordString = String(cString: password, encoding: .utf8)
+    else {
+        return errSecParam
+    }
+
+    let query: [String: Any] = [
+        kSecClass as String: kSecClassGenericPassword,
+        kSecAttrService as String: serviceString,
+        kSecAttrAccount as String: accountString,
+    ]
+    let passwordData = Data(passwordString.utf8)
+
+    let updateStatus = SecItemUpdate(
+        query as CFDictionary,
+        [kSecValueData as String: passwordData] as CFDictionary
+    )
+    if updateStatus != errSecItemNotFound {
+        return updateStatus
+    }
+
+    var item = query
+    item[kSecValueData as String] = passwordData
+    return SecItemAdd(item as CFDictionary, nil)
+}
@@ -0,0 +1,29 @@
+import Foundation
+import ServiceManagement
+
+// C-compatible function to register or unregister the app as a login item
+// Returns false if the change failed, e.g. when the user denied it in System Settings
+@_cdecl("set_launch_at_login_swift")
+public func setLaunchAtLoginSwift(enabled: Bool) -> Bool {
+    let service = SMAppService.mainApp
+
+    do {
+        if enabled {
+            if service.status != .enabled {
+                try service.register()
+            }
+        } else if service.status == .enabled {
+            try service.unregister()
+        }
+        return true
+    } catch {
+        print("Error changing login item: \(error)")
+        return false
+    }
+}
+
+// C-compatible function that returns whether the app is registered as a login item
+@_cdecl("is_launch_at_login_enabled_swift")
+public func isLaunchAtLoginEnabledSwift() -> Bool {
+    return SMAppService.mainApp.status == .enabled
+}
@@ -0,0 +1,87 @@
+import Foundation
+
+struct ProcessNetworkTotals: Codable {
+    var pid: Int32
+    var bytes_in: UInt64
+    var bytes_out: UInt64
+}
+
+class NetworkUsageHandler {
+    // There is no public API for per process network statistics, nettop prints the totals of every process
+    // -L 1 prints a single CSV sample, -x prints raw numbers instead of "1.2 MiB"
+    static func getNetworkTotals() -> [ProcessNetworkTotals]? {
+        let process = Process()
+        process.executableURL = URL(fileURLWithPath: "/usr/bin/nettop")
+        process.arguments = ["-P", "-L", "1", "-x", "-n", "-J", "bytes_in,bytes_out"]
+
+        let pipe = Pipe()
+        process.standardOutput = pipe
+        process.standardError = FileHandle.nullDevice
+
+        do {
+            try process.run()
+        } catch {
+            print("Failed to run nettop: \(error)")
+            return nil
+        }
+
+        // read before waiting, otherwise nettop blocks once the pipe buffer is full
+        let data = pipe.fileHandleForReading.readDataToEndOfFile()
+        process.waitUntilExit()
+
+        guard process.terminationStatus == 0,
+            let output = String(data: data, encoding: .utf8)
+        else {
+            return nil
+        }
+
+        let lines = output.split(separator: "\n")
+        guard let header = lines.first else {
+            return nil
+        }
+
+        let columns = header.split(separator: ",", omittingEmptySubsequences: false).map(String.init)
+        guard let bytesInIndex = columns.firstIndex(of: "bytes_in"),
+            let bytesOutIndex = columns.firstIndex(of: "bytes_out")
+        else {
+            return nil
+        }
+
+        return lines.dropFirst().compactMap { line in
+            let fields = line.split(separator: ",", omittingEmptySubsequences: false)
+            guard fields.count > max(bytesInIndex, bytesOutIndex, 1) else {
+                return nil
+            }
+
+            // the second column is "<process name>.<pid>"
+            guard let pidField = fields[1].split(separator: ".").last,
+                let pid = Int32(pidField),
+                let bytesIn = UInt64(fields[bytesInIndex]),
+                let bytesOut = UInt64(fields[bytesOutIndex])
+            else {
+                return nil
+            }
+
+            return ProcessNetworkTotals(pid: pid, bytes_in: bytesIn, bytes_out: bytesOut)
+        }
+    }
+}
+
+// C-compatible function to get the received and sent bytes of every process as JSON
+@_cdecl("get_network_usage_swift")
+public func getNetworkUsageSwift() -> UnsafeMutablePointer<CChar>? {
+    guard let totals = NetworkUsageHandler.getNetworkTotals() else {
+        return nil
+    }
+
+    do {
+        let jsonData = try JSONEncoder().encode(totals)
+        if let jsonString = String(data: jsonData, encoding: .utf8) {
+            return strdup(jsonString)
+        }
+    } catch {
+        print("Error encoding network usage: \(error)")
+    }
+
+    return nil
+}
@@ -0,0 +1,100 @@
+import AppKit
+import Foundation
+import ImageIO
+import PDFKit
+import Vision
+
+// Scale PDF pages are rendered at before their text is recognized, small print needs more than 1x
+let ocrRenderScale: CGFloat = 2.0
+
+// Separates the text of the pages in the returned string, the same separator tesseract uses
+let ocrPageSeparator = "\u{0C}"
+
+func recognizeText(in image: CGImage) -> String {
+    let request = VNRecognizeTextRequest()
+    request.recognitionLevel = .accurate
+    request.usesLanguageCorrection = true
+
+    let handler = VNImageRequestHandler(cgImage: image, options: [:])
+    do {
+        try handler.perform([request])
+    } catch {
+        print("Text recognition failed: \(error)")
+        return ""
+    }
+
+    let lines = (request.results ?? []).compactMap { observation in
+        observation.topCandidates(1).first?.string
+    }
+    return lines.joined(separator: "\n")
+}
+
+func renderPage(_ page: PDFPage) -> CGImage? {
+    let bounds = page.bounds(for: .mediaBox)
+    let width = Int(bounds.width * ocrRenderScale)
+    let height = Int(bounds.height * ocrRenderScale)
+
+    guard width > 0, height > 0,
+        let context = CGContext(
+            data: nil,
+            width: width,
+            height: height,
+            bitsPerComponent: 8,
+            bytesPerRow: 0,
+            space: CGColorSpaceCreateDeviceRGB(),
+            bitmapInfo: CGImageAlphaInfo.noneSkipLast.rawValue)
+    else {
+        return nil
+    }
+
+    // scans are drawn on white, a transparent background renders black
+    context.setFillColor(CGColor(gray: 1.0, alpha: 1.0))
+    context.fill(CGRect(x: 0, y: 0, width: width, height: height))
+    context.scaleBy(x: ocrRenderScale, y: ocrRenderScale)
+    page.draw(with: .mediaBox, to: context)
+
+    return context.makeImage()
+}
+
+// C-compatible function to recognize the text of an image or of every page of a PDF with Vision
+// Returns the text of the pages separated by form feeds
+@_cdecl("recognize_text_swift")
+public func recognizeTextSwift(path: UnsafePointer<CChar>?) -> UnsafeMutablePointer<CChar>? {
+    guard let path = path,
+        let pathString = String(cString: path, encoding: .utf8)
+    else {
+        return nil
+    }
+
+    let url = URL(fileURLWithPath: pathString)
+    var pages: [String] = []
+
+    if url.pathExtension.lowercased() == "pdf" {
+        guard let document = PDFDocument(url: url) else {
+            return nil
+        }
+
+        for index in 0..<document.pageCount {
+            guard let page = document.page(at: index), let image = renderPage(page) else {
+                pages.append("")
+                continue
+            }
+            pages.append(autoreleasepool { recognizeText(in: image) })
+        }
+    } else {
+        guard let source = CGImageSourceCreateWithURL(url as CFURL, nil) else {
+            return nil
+        }
+
+        // TIFFs can hold several pages
+        for index in 0..<CGImageSourceGetCount(source) {
+            guard let image = CGImageSourceCreateImageAtIndex(source, index, nil) else {
+                pages.append("")
+                continue
+            }
+            pages.append(autoreleasepool { recognizeText(in: image) })
+        }
+    }
+
+    return strdup(pages.joined(separator: ocrPageSeparator))
+}
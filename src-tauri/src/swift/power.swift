@@ -0,0 +1,22 @@
+import AppKit
+
+private var wakeObserver: NSObjectProtocol?
+
+// C-compatible function to register a callback that runs every time the Mac wakes from sleep
+// Registering again replaces the previous callback
+@_cdecl("register_wake_callback_swift")
+public func registerWakeCallbackSwift(callback: @escaping @convention(c) () -> Void) {
+    let notificationCenter = NSWorkspace.shared.notificationCenter
+
+    if let observer = wakeObserver {
+        notificationCenter.removeObserver(observer)
+    }
+
+    wakeObserver = notificationCenter.addObserver(
+        forName: NSWorkspace.didWakeNotification,
+        object: nil,
+        queue: nil
+    ) { _ in
+        callback()
+    }
+}
@@ -0,0 +1,90 @@
+import AppKit
+import Foundation
+
+class SystemActionHandler {
+    static func runAppleScript(_ source: String) -> Bool {
+        guard let script = NSAppleScript(source: source) else {
+            return false
+        }
+
+        var error: NSDictionary?
+        _ = script.executeAndReturnError(&error)
+        if let error = error {
+            print("AppleScript error: \(error)")
+            return false
+        }
+
+        return true
+    }
+
+    static func runProcess(_ path: String, arguments: [String]) -> Bool {
+        let process = Process()
+        process.executableURL = URL(fileURLWithPath: path)
+        process.arguments = arguments
+
+        do {
+            try process.run()
+            process.waitUntilExit()
+            return process.terminationStatus == 0
+        } catch {
+            print("Failed to run \(path): \(error)")
+            return false
+        }
+    }
+
+    static func emptyTrash() -> Bool {
+        return runAppleScript("tell application \"Finder\" to empty trash")
+    }
+
+    static func toggleDarkMode() -> Bool {
+        return runAppleScript(
+            """
+            tell application "System Events"
+                tell appearance preferences
+                    set dark mode to not dark mode
+                end tell
+            end tell
+            """)
+    }
+
+    static func sleep() -> Bool {
+        return runProcess("/usr/bin/pmset", arguments: ["sleepnow"])
+    }
+
+    // Sends the lock screen shortcut (ctrl+cmd+Q), this needs the accessibility permission
+    static func lockScreen() -> Bool {
+        return runAppleScript(
+            "tell application \"System Events\" to keystroke \"q\" using {control down, command down}"
+        )
+    }
+
+    // Focus modes have no public API, this runs a shortcut the user creates in the Shortcuts app
+    static func toggleDoNotDisturb() -> Bool {
+        return runProcess("/usr/bin/shortcuts", arguments: ["run", "Toggle Do Not Disturb"])
+    }
+
+    static func run(id: String) -> Bool {
+        switch id {
+        case "empty_trash": return emptyTrash()
+        case "toggle_dark_mode": return toggleDarkMode()
+        case "sleep": return sleep()
+        case "lock_screen": return lockScreen()
+        case "toggle_do_not_disturb": return toggleDoNotDisturb()
+        default:
+            print("Unknown system action: \(id)")
+            return false
+        }
+    }
+}
+
+// C-compatible function to run a system action by id
+@_cdecl("run_system_action_swift")
+public func runSystemActionSwift(id: UnsafePointer<CChar>?) -> Bool {
+    guard let id = id,
+        let idString = String(cString: id, encoding: .utf8)
+    else {
+        return false
+    }
+
+    return SystemActionHandler.run(id: idString)
+}
@@ -0,0 +1,54 @@
+import AppKit
+import Foundation
+import QuickLookThumbnailing
+
+// Longest a single thumbnail may take, some files make Quick Look hang
+let thumbnailTimeout: DispatchTimeInterval = .seconds(5)
+
+// C-compatible function to render a thumbnail of a file with Quick Look, returns a PNG data URL
+@_cdecl("get_file_thumbnail_swift")
+public func getFileThumbnailSwift(path: UnsafePointer<CChar>?, size: Int32)
+    -> UnsafeMutablePointer<CChar>?
+{
+    guard let path = path,
+        let pathString = String(cString: path, encoding: .utf8)
+    else {
+        return nil
+    }
+
+    let request = QLThumbnailGenerator.Request(
+        fileAt: URL(fileURLWithPath: pathString),
+        size: CGSize(width: CGFloat(size), height: CGFloat(size)),
+        scale: 2.0,
+        representationTypes: .thumbnail)
+
+    let semaphore = DispatchSemaphore(value: 0)
+    var dataUrl: String?
+
+    QLThumbnailGenerator.shared.generateBestRepresentation(for: request) { thumbnail, error in
+        defer { semaphore.signal() }
+
+        guard let thumbnail = thumbnail, error == nil else {
+            return
+        }
+
+        let bitmap = NSBitmapImageRep(cgImage: thumbnail.cgImage)
+        guard let pngData = bitmap.representation(using: .png, properties: [:]) else {
+            return
+        }
+
+        dataUrl = "data:image/png;base64,\(pngData.base64EncodedString())"
+    }
+
+    if semaphore.wait(timeout: .now() + thumbnailTimeout) == .timedOut {
+        QLThumbnailGenerator.shared.cancel(request)
+        print("Timed out generating a thumbnail for \(pathString)")
+        return nil
+    }
+
+    guard let dataUrl = dataUrl else {
+        return nil
+    }
+
+    return strdup(dataUrl)
+}
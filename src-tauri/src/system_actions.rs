@@ -0,0 +1,154 @@
+/*
+This file contains the system actions of the command palette, like emptying the trash or locking the screen. The catalog lives here so the launcher can search it, the actions themselves run through the Swift bridge
+*/
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(target_os = "macos")]
+use std::ffi::CString;
+#[cfg(target_os = "macos")]
+use std::os::raw::c_char;
+use tracing::info;
+
+#[cfg(target_os = "macos")]
+extern "C" {
+    fn run_system_action_swift(id: *const c_char) -> bool;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemActionId {
+    EmptyTrash,
+    ToggleDarkMode,
+    Sleep,
+    LockScreen,
+    ToggleDoNotDisturb,
+}
+
+const ALL_SYSTEM_ACTIONS: [SystemActionId; 5] = [
+    SystemActionId::EmptyTrash,
+    SystemActionId::ToggleDarkMode,
+    SystemActionId::Sleep,
+    SystemActionId::LockScreen,
+    SystemActionId::ToggleDoNotDisturb,
+];
+
+impl SystemActionId {
+    /// Id passed to the Swift bridge, matches the serde name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SystemActionId::EmptyTrash => "empty_trash",
+            SystemActionId::ToggleDarkMode => "toggle_dark_mode",
+            SystemActionId::Sleep => "sleep",
+            SystemActionId::LockScreen => "lock_screen",
+            SystemActionId::ToggleDoNotDisturb => "toggle_do_not_disturb",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            SystemActionId::EmptyTrash => "Empty Trash",
+            SystemActionId::ToggleDarkMode => "Toggle Dark Mode",
+            SystemActionId::Sleep => "Sleep",
+            SystemActionId::LockScreen => "Lock Screen",
+            SystemActionId::ToggleDoNotDisturb => "Toggle Do Not Disturb",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            SystemActionId::EmptyTrash => "Permanently deletes the items in the Trash",
+            SystemActionId::ToggleDarkMode => "Switches between the light and dark appearance",
+            SystemActionId::Sleep => "Puts the computer to sleep",
+            SystemActionId::LockScreen => "Locks the screen, needs the accessibility permission",
+            SystemActionId::ToggleDoNotDisturb => {
+                "Runs the \"Toggle Do Not Disturb\" shortcut from the Shortcuts app"
+            }
+        }
+    }
+
+    /// Other words the user might search for
+    fn keywords(&self) -> &'static [&'static str] {
+        match self {
+            SystemActionId::EmptyTrash => &["bin", "delete", "clean"],
+            SystemActionId::ToggleDarkMode => &["appearance", "theme", "light", "night"],
+            SystemActionId::Sleep => &["suspend", "standby"],
+            SystemActionId::LockScreen => &["lock", "password", "away"],
+            SystemActionId::ToggleDoNotDisturb => &["dnd", "focus", "notifications", "silence"],
+        }
+    }
+
+    /// Actions that can't be undone are confirmed by the frontend before they run
+    fn requires_confirmation(&self) -> bool {
+        matches!(self, SystemActionId::EmptyTrash)
+    }
+
+    fn matches(&self, query: &str) -> bool {
+        let name = self.name().to_lowercase();
+        query.split_whitespace().all(|word| {
+            name.contains(word)
+                || self
+                    .keywords()
+                    .iter()
+                    .any(|keyword| keyword.starts_with(word))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemAction {
+    pub id: SystemActionId,
+    pub name: String,
+    pub description: String,
+    pub requires_confirmation: bool,
+}
+
+impl From<SystemActionId> for SystemAction {
+    fn from(id: SystemActionId) -> Self {
+        Self {
+            id,
+            name: id.name().to_string(),
+            description: id.description().to_string(),
+            requires_confirmation: id.requires_confirmation(),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn run_action(id: SystemActionId) -> bool {
+    let Ok(id_cstring) = CString::new(id.as_str()) else {
+        return false;
+    };
+    unsafe { run_system_action_swift(id_cstring.as_ptr()) }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn run_action(_id: SystemActionId) -> bool {
+    false
+}
+
+/// Returns the system actions matching the query, all of them when the query is empty
+#[tauri::command]
+pub fn list_system_actions(query: Option<String>) -> Vec<SystemAction> {
+    let query = query.unwrap_or_default().to_lowercase();
+
+    ALL_SYSTEM_ACTIONS
+        .iter()
+        .filter(|id| id.matches(&query))
+        .map(|id| SystemAction::from(*id))
+        .collect()
+}
+
+#[tauri::command]
+pub async fn run_system_action(id: SystemActionId) -> Result<(), String> {
+    let succeeded = tokio::task::spawn_blocking(move || run_action(id))
+        .await
+        .map_err(|e| format!("spawn_blocking error: {e}"))?;
+
+    if !succeeded {
+        return Err(format!("Failed to run {}", id.name()));
+    }
+
+    info!("Ran system action {}", id.as_str());
+    Ok(())
+}
@@ -0,0 +1,227 @@
+/*
+This file contains user tags for indexed files. Tags are stored in the tags and file_tags tables and are added to the file's files_fts entry so that searching for a tag finds the files it is on
+*/
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, State};
+use tokio::task;
+
+use crate::database_handler::open_connection;
+use crate::file_processor::{
+    delete_from_fts, get_processor, rows_to_file_metadata, FileMetadata, FileProcessor,
+    FileProcessorError, FileProcessorState,
+};
+use crate::fts_index::active_fts_options;
+use crate::tokenizer::build_doc_text;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagCount {
+    pub name: String,
+    pub file_count: i64,
+}
+
+/// Returns the names of the tags on a file, sorted so the file's doc_text is always built the same way
+pub fn load_file_tags(conn: &Connection, file_id: i64) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT t.name
+        FROM file_tags ft
+        JOIN tags t ON t.id = ft.tag_id
+        WHERE ft.file_id = ?1
+        ORDER BY t.name COLLATE NOCASE
+        "#,
+    )?;
+
+    let tags = stmt
+        .query_map([file_id], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    Ok(tags)
+}
+
+fn normalize_tag(tag: &str) -> Result<String, FileProcessorError> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err(FileProcessorError::Other(
+            "Tag name can't be empty".to_string(),
+        ));
+    }
+    Ok(tag.to_string())
+}
+
+/// Adds or removes a tag on the file at the given path and rewrites the file's files_fts entry
+/// Returns the file's tags after the change
+pub async fn change_file_tag(
+    db_path: PathBuf,
+    path: String,
+    tag: String,
+    add: bool,
+) -> Result<Vec<String>, FileProcessorError> {
+    let tag = normalize_tag(&tag)?;
+
+    task::spawn_blocking(move || -> Result<Vec<String>, FileProcessorError> {
+        let mut conn = open_connection(db_path)?;
+        let tx = conn.transaction()?;
+
+        let (file_id, name, extension): (i64, String, String) = tx
+            .query_row(
+                "SELECT id, name, extension FROM files WHERE path = ?1",
+                [&path],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?
+            .ok_or_else(|| FileProcessorError::Other(format!("{} is not indexed", path)))?;
+
+        // the old entry has to be deleted with the old tags before they change
+        delete_from_fts(&tx, file_id, &name, &path, &extension)?;
+
+        if add {
+            tx.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", [&tag])?;
+            tx.execute(
+                r#"
+                INSERT OR IGNORE INTO file_tags (file_id, tag_id)
+                SELECT ?1, id FROM tags WHERE name = ?2
+                "#,
+                params![file_id, tag],
+            )?;
+        } else {
+            tx.execute(
+                r#"
+                DELETE FROM file_tags
+                WHERE file_id = ?1 AND tag_id = (SELECT id FROM tags WHERE name = ?2)
+                "#,
+                params![file_id, tag],
+            )?;
+            delete_unused_tag(&tx, &tag)?;
+        }
+
+        let tags = load_file_tags(&tx, file_id)?;
+        let doc_text = build_doc_text(&name, &path, &extension, &tags, &active_fts_options());
+        tx.execute(
+            "INSERT INTO files_fts(rowid, doc_text) VALUES (?1, ?2)",
+            params![file_id, doc_text],
+        )?;
+
+        tx.commit()?;
+        Ok(tags)
+    })
+    .await
+    .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
+}
+
+/// Tags only exist while they are on at least one file
+fn delete_unused_tag(tx: &Transaction, tag: &str) -> rusqlite::Result<()> {
+    tx.execute(
+        r#"
+        DELETE FROM tags
+        WHERE name = ?1
+          AND NOT EXISTS (SELECT 1 FROM file_tags WHERE tag_id = tags.id)
+        "#,
+        [tag],
+    )?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn add_tag(
+    path: String,
+    tag: String,
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<Vec<String>, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let tags = change_file_tag(processor.db_path.clone(), path, tag, true)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let _ = app_handle.emit("files-updated", ());
+    Ok(tags)
+}
+
+#[tauri::command]
+pub async fn remove_tag(
+    path: String,
+    tag: String,
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<Vec<String>, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let tags = change_file_tag(processor.db_path.clone(), path, tag, false)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let _ = app_handle.emit("files-updated", ());
+    Ok(tags)
+}
+
+/// Returns every tag with the number of files it is on, most used first
+#[tauri::command]
+pub async fn list_tags(state: State<'_, FileProcessorState>) -> Result<Vec<TagCount>, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let conn = processor.get_read_connection()?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT t.name, COUNT(ft.file_id)
+            FROM tags t
+            LEFT JOIN file_tags ft ON ft.tag_id = t.id
+            GROUP BY t.id
+            ORDER BY 2 DESC, t.name COLLATE NOCASE
+            "#,
+        )
+        .map_err(|e| format!("Failed to prepare statement: {e}"))?;
+
+    let tags = stmt
+        .query_map([], |row| {
+            Ok(TagCount {
+                name: row.get(0)?,
+                file_count: row.get(1)?,
+            })
+        })
+        .map_err(|e| format!("Query error: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Row error: {e}"))?;
+
+    Ok(tags)
+}
+
+#[tauri::command]
+pub async fn get_files_by_tag(
+    tag: String,
+    state: State<'_, FileProcessorState>,
+) -> Result<Vec<FileMetadata>, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+
+    let conn = processor.get_read_connection()?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT
+              f.id,
+              f.name,
+              f.path,
+              f.extension,
+              f.size,
+              f.created_at,
+              f.updated_at
+            FROM files f
+            JOIN file_tags ft ON ft.file_id = f.id
+            JOIN tags t ON t.id = ft.tag_id
+            WHERE t.name = ?1
+            ORDER BY f.name COLLATE NOCASE
+            "#,
+        )
+        .map_err(|e| format!("Failed to prepare statement: {e}"))?;
+
+    let rows = stmt
+        .query([tag.trim()])
+        .map_err(|e| format!("Query error: {e}"))?;
+
+    rows_to_file_metadata(rows)
+}
@@ -0,0 +1,146 @@
+/*
+This file contains the thumbnails of file results. Images, PDFs and videos are rendered with Quick Look through the Swift bridge and returned as data URLs in FileMetadata. Rendering is slow, so every thumbnail is stored in the app cache directory keyed by the file's path and modification time, a file that changes gets a new thumbnail
+*/
+
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use tracing::error;
+
+#[cfg(target_os = "macos")]
+use std::ffi::{CStr, CString};
+#[cfg(target_os = "macos")]
+use std::os::raw::c_char;
+
+#[cfg(target_os = "macos")]
+extern "C" {
+    fn get_file_thumbnail_swift(path: *const c_char, size: i32) -> *mut c_char;
+    fn free_string_swift(pointer: *mut c_char);
+}
+
+use crate::file_processor::FileMetadata;
+use crate::icon_cache::modified_secs;
+
+const THUMBNAIL_CACHE_DIR_NAME: &str = "thumbnails";
+
+/// Size of the thumbnails in points, Quick Look renders them at 2x
+#[cfg(target_os = "macos")]
+const THUMBNAIL_SIZE: i32 = 64;
+
+/// Only the first results get thumbnails, the list shows fewer than this at once
+const MAX_THUMBNAILS_PER_QUERY: usize = 50;
+
+/// Extensions Quick Look renders a thumbnail of the content for, other files would only get their icon
+const THUMBNAIL_EXTENSIONS: [&str; 14] = [
+    "png", "jpg", "jpeg", "gif", "heic", "webp", "tiff", "bmp", "svg", "pdf", "mp4", "mov", "m4v",
+    "avi",
+];
+
+fn has_thumbnail(extension: &str) -> bool {
+    THUMBNAIL_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+}
+
+/// Name of the cache file, the modification time is part of it so a changed file misses the cache
+fn cache_file_name(path: &str, modified_secs: u64) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(path.as_bytes());
+    hasher.update(modified_secs.to_le_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(target_os = "macos")]
+fn render_thumbnail(path: &str) -> Option<String> {
+    let path_cstring = CString::new(path).ok()?;
+
+    let thumbnail_ptr = unsafe { get_file_thumbnail_swift(path_cstring.as_ptr(), THUMBNAIL_SIZE) };
+    if thumbnail_ptr.is_null() {
+        return None;
+    }
+
+    unsafe {
+        let result = CStr::from_ptr(thumbnail_ptr)
+            .to_str()
+            .ok()
+            .map(|s| s.to_owned());
+        free_string_swift(thumbnail_ptr);
+        result
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn render_thumbnail(_path: &str) -> Option<String> {
+    None
+}
+
+/// Returns the thumbnail of the file from the cache, rendering and caching it on a miss
+fn thumbnail_for(cache_dir: Option<&Path>, path: &str) -> Option<String> {
+    let modified_secs = modified_secs(path)?;
+    let cache_path = cache_dir.map(|dir| dir.join(cache_file_name(path, modified_secs)));
+
+    if let Some(cached) = cache_path
+        .as_ref()
+        .and_then(|cache_path| fs::read_to_string(cache_path).ok())
+    {
+        return Some(cached);
+    }
+
+    let thumbnail = render_thumbnail(path)?;
+
+    if let Some(cache_path) = &cache_path {
+        if let Err(e) = fs::write(cache_path, &thumbnail) {
+            error!("Failed to cache the thumbnail of {}: {}", path, e);
+        }
+    }
+
+    Some(thumbnail)
+}
+
+fn thumbnail_cache_dir(app_handle: &AppHandle) -> Option<PathBuf> {
+    let dir = app_handle
+        .path()
+        .app_cache_dir()
+        .ok()?
+        .join(THUMBNAIL_CACHE_DIR_NAME);
+
+    match fs::create_dir_all(&dir) {
+        Ok(()) => Some(dir),
+        Err(e) => {
+            error!("Failed to create the thumbnail cache: {}", e);
+            None
+        }
+    }
+}
+
+/// Fills in the thumbnails of the first files that have one, this blocks while thumbnails are rendered
+fn attach_thumbnails(app_handle: &AppHandle, files: &mut [FileMetadata]) {
+    let cache_dir = thumbnail_cache_dir(app_handle);
+
+    for file in files
+        .iter_mut()
+        .filter(|file| has_thumbnail(&file.extension))
+        .take(MAX_THUMBNAILS_PER_QUERY)
+    {
+        file.thumbnail = thumbnail_for(cache_dir.as_deref(), &file.base.path);
+    }
+}
+
+/// Adds the thumbnails off the async runtime, the files are returned without them if rendering fails
+pub async fn with_thumbnails(
+    app_handle: &AppHandle,
+    files: Vec<FileMetadata>,
+) -> Vec<FileMetadata> {
+    let app_handle = app_handle.clone();
+    let fallback = files.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let mut files = files;
+        attach_thumbnails(&app_handle, &mut files);
+        files
+    })
+    .await
+    .unwrap_or_else(|e| {
+        error!("spawn_blocking error: {}", e);
+        fallback
+    })
+}
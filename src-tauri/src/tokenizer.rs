@@ -1,3 +1,64 @@
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::settings::AppSettings;
+
+/// How file names and paths are split into tokens in files_fts
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FtsTokenizer {
+    /// Overlapping 3 character slices, matches any part of a name but not word stems
+    #[default]
+    Trigram,
+    /// Whole words with the porter stemmer, so "report" also matches "reports" and "reporting"
+    Porter,
+    /// Whole words without stemming
+    Unicode61,
+}
+
+impl FtsTokenizer {
+    /// Unknown values fall back to trigram, the tokenizer the index was built with before this was configurable
+    pub fn from_setting(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "porter" => FtsTokenizer::Porter,
+            "unicode61" => FtsTokenizer::Unicode61,
+            _ => FtsTokenizer::Trigram,
+        }
+    }
+
+    /// The fts5 tokenize option for the table, trigrams are built here and stored as words
+    pub fn fts5_tokenize(&self) -> &'static str {
+        match self {
+            FtsTokenizer::Porter => "porter unicode61",
+            FtsTokenizer::Trigram | FtsTokenizer::Unicode61 => "unicode61",
+        }
+    }
+}
+
+/// The options files_fts was built with, changing any of them means the table has to be rebuilt
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FtsOptions {
+    pub tokenizer: FtsTokenizer,
+    /// Adds fts5 prefix indexes so the last word of a query matches as a prefix
+    pub prefix_indexes: bool,
+    /// Also indexes the words inside CamelCase and snake_case names, "MyFile_name" is found by "my file name"
+    pub split_identifiers: bool,
+}
+
+impl FtsOptions {
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            tokenizer: settings
+                .fts_tokenizer
+                .as_deref()
+                .map(FtsTokenizer::from_setting)
+                .unwrap_or_default(),
+            prefix_indexes: settings.fts_prefix_indexes.unwrap_or(false),
+            split_identifiers: settings.fts_split_identifiers.unwrap_or(false),
+        }
+    }
+}
+
 // builds the 3 character trigram
 // if the len < 3, we'll jsut return the entire string
 pub fn build_trigrams(s: &str) -> String {
@@ -19,12 +80,166 @@ pub fn build_trigrams(s: &str) -> String {
     tokens.join(" ")
 }
 
-/// Combine name/path/extension trigrams into one doc_text string that fs5 can search over
-pub fn build_doc_text(name: &str, path: &str, extension: &str) -> String {
+/// Combine name/path/extension and the file's tags into one doc_text string that fts5 can search over
+/// Trigrams are only built for the trigram tokenizer, the word tokenizers split the text themselves
+pub fn build_doc_text(
+    name: &str,
+    path: &str,
+    extension: &str,
+    tags: &[String],
+    options: &FtsOptions,
+) -> String {
     let mut parts = Vec::new();
-    parts.push(build_trigrams(name));
-    parts.push(build_trigrams(path));
-    parts.push(build_trigrams(extension));
+
+    for text in [name, path, extension]
+        .into_iter()
+        .chain(tags.iter().map(|tag| tag.as_str()))
+    {
+        match options.tokenizer {
+            FtsTokenizer::Trigram => parts.push(build_trigrams(text)),
+            FtsTokenizer::Porter | FtsTokenizer::Unicode61 => parts.push(text.to_string()),
+        }
+    }
+
+    if options.split_identifiers {
+        for text in [name, path] {
+            for word in split_identifier_words(text) {
+                match options.tokenizer {
+                    FtsTokenizer::Trigram => parts.push(build_trigrams(&word)),
+                    FtsTokenizer::Porter | FtsTokenizer::Unicode61 => parts.push(word),
+                }
+            }
+        }
+    }
 
     parts.join(" ")
 }
+
+/// Builds the MATCH expression for a search query with the same options the documents were indexed with
+pub fn build_match_query(query: &str, options: &FtsOptions) -> String {
+    match options.tokenizer {
+        FtsTokenizer::Trigram => build_trigrams(query),
+        FtsTokenizer::Porter | FtsTokenizer::Unicode61 => {
+            let words: Vec<String> = if options.split_identifiers {
+                split_identifier_words(query)
+            } else {
+                query
+                    .split(|c: char| !c.is_alphanumeric())
+                    .filter(|word| !word.is_empty())
+                    .map(|word| word.to_string())
+                    .collect()
+            };
+
+            let last = words.len().saturating_sub(1);
+            words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| {
+                    // quoting keeps words like AND/OR/NOT from being read as operators
+                    let phrase = format!("\"{}\"", word.replace('"', "\"\""));
+                    if options.prefix_indexes && i == last {
+                        format!("{}*", phrase)
+                    } else {
+                        phrase
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+    }
+}
+
+/// Splits a name into lowercase words at separators, lower to upper case changes and letter/digit changes
+/// i.e. "MyFileName_v2.txt" -> "my", "file", "name", "v", "2", "txt"
+pub fn split_identifier_words(s: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut prev: Option<char> = None;
+
+    for c in s.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(current.to_lowercase());
+                current.clear();
+            }
+            prev = None;
+            continue;
+        }
+
+        if let Some(p) = prev {
+            let case_change = p.is_lowercase() && c.is_uppercase();
+            let digit_change = p.is_ascii_digit() != c.is_ascii_digit();
+            if (case_change || digit_change) && !current.is_empty() {
+                words.push(current.to_lowercase());
+                current.clear();
+            }
+        }
+
+        current.push(c);
+        prev = Some(c);
+    }
+
+    if !current.is_empty() {
+        words.push(current.to_lowercase());
+    }
+
+    words
+}
+
+/// Tokenizer of the embedding model, kept once the embedder is loaded so chunks can be sized in model tokens
+static MODEL_TOKENIZER: OnceLock<tokenizers::Tokenizer> = OnceLock::new();
+
+/// Keeps a copy of the embedding model's tokenizer, without the truncation and padding the model uses
+/// so long texts are counted in full
+pub fn set_model_tokenizer(tokenizer: &tokenizers::Tokenizer) {
+    let mut tokenizer = tokenizer.clone();
+    if tokenizer.with_truncation(None).is_err() {
+        return;
+    }
+    tokenizer.with_padding(None);
+    let _ = MODEL_TOKENIZER.set(tokenizer);
+}
+
+pub fn has_model_tokenizer() -> bool {
+    MODEL_TOKENIZER.get().is_some()
+}
+
+/// Tokens the model adds around every text, like [CLS] and [SEP]
+pub fn model_added_tokens() -> usize {
+    MODEL_TOKENIZER
+        .get()
+        .and_then(|tokenizer| tokenizer.encode("", true).ok())
+        .map(|encoding| encoding.len())
+        .unwrap_or(0)
+}
+
+/// Number of model tokens in the text without the ones added around it, None until the embedder is loaded
+pub fn model_token_count(text: &str) -> Option<usize> {
+    let tokenizer = MODEL_TOKENIZER.get()?;
+    tokenizer
+        .encode(text, false)
+        .ok()
+        .map(|encoding| encoding.len())
+}
+
+/// Number of model tokens in each word, None until the embedder is loaded
+/// The words are tokenized together in one pass and every token is counted for the word it starts in
+pub fn model_token_counts(words: &[&str]) -> Option<Vec<usize>> {
+    let tokenizer = MODEL_TOKENIZER.get()?;
+    let text = words.join(" ");
+    let encoding = tokenizer.encode(text.as_str(), false).ok()?;
+
+    let mut counts = vec![0; words.len()];
+    let mut word = 0;
+    // byte offset in the joined text where the current word ends
+    let mut word_end = words.first()?.len();
+    for &(start, _) in encoding.get_offsets() {
+        while word + 1 < words.len() && start >= word_end {
+            word += 1;
+            word_end += 1 + words[word].len();
+        }
+        counts[word] += 1;
+    }
+
+    Some(counts)
+}
@@ -0,0 +1,199 @@
+/*
+This file contains the menu bar tray. It shows the indexing status from the file processor events and has items to pause or resume indexing, index a folder and open the launcher, so the common indexing controls don't need the main window
+*/
+
+use serde::Deserialize;
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Listener, Manager};
+use tauri_plugin_dialog::DialogExt;
+use tracing::error;
+
+use crate::file_processor::{get_processor, set_indexing_paused, FileProcessorState};
+use crate::indexed_directories::add_indexed_directory;
+use crate::AppResult;
+
+const TRAY_ID: &str = "kita-tray";
+
+const STATUS_ITEM_ID: &str = "tray-status";
+const TOGGLE_PAUSE_ITEM_ID: &str = "tray-toggle-pause";
+const INDEX_FOLDER_ITEM_ID: &str = "tray-index-folder";
+const OPEN_LAUNCHER_ITEM_ID: &str = "tray-open-launcher";
+
+const IDLE_STATUS: &str = "Index up to date";
+
+/// Menu items whose text follows the indexing state
+struct TrayMenuState {
+    status: MenuItem<tauri::Wry>,
+    toggle_pause: MenuItem<tauri::Wry>,
+}
+
+/// The fields of the file-processing-progress payload the tray shows
+#[derive(Debug, Deserialize)]
+struct IndexingProgress {
+    total: usize,
+    processed: usize,
+    percentage: usize,
+}
+
+fn progress_status(progress: &IndexingProgress) -> String {
+    if progress.percentage >= 100 {
+        return IDLE_STATUS.to_string();
+    }
+
+    format!(
+        "Indexing {}% ({} of {} files)",
+        progress.percentage, progress.processed, progress.total
+    )
+}
+
+fn set_status(app_handle: &AppHandle, status: &str) {
+    if let Some(state) = app_handle.try_state::<TrayMenuState>() {
+        let _ = state.status.set_text(status);
+    }
+    if let Some(tray) = app_handle.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(format!("Kita: {}", status)));
+    }
+}
+
+fn set_paused_text(app_handle: &AppHandle, paused: bool) {
+    if let Some(state) = app_handle.try_state::<TrayMenuState>() {
+        let text = if paused {
+            "Resume indexing"
+        } else {
+            "Pause indexing"
+        };
+        let _ = state.toggle_pause.set_text(text);
+    }
+}
+
+fn show_launcher(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn toggle_pause(app_handle: &AppHandle) {
+    let state = app_handle.state::<FileProcessorState>();
+    let paused = get_processor(&state)
+        .map(|processor| processor.index_queue.is_paused())
+        .unwrap_or(false);
+
+    if let Err(e) = set_indexing_paused(!paused, state, app_handle.clone()) {
+        error!("Failed to toggle indexing: {}", e);
+    }
+}
+
+fn pick_folder_to_index(app_handle: &AppHandle) {
+    let app_handle_for_folder = app_handle.clone();
+
+    app_handle
+        .dialog()
+        .file()
+        .set_title("Select a Folder to Index")
+        .pick_folder(move |folder| {
+            let Some(path) = folder.and_then(|folder| folder.into_path().ok()) else {
+                return;
+            };
+
+            let app_handle = app_handle_for_folder.clone();
+            tauri::async_runtime::spawn(async move {
+                let path = path.to_string_lossy().into_owned();
+                let state = app_handle.state::<FileProcessorState>();
+                if let Err(e) = add_indexed_directory(path.clone(), state, app_handle.clone()).await
+                {
+                    error!("Failed to index {} from the tray: {}", path, e);
+                }
+            });
+        });
+}
+
+fn handle_menu_event(app_handle: &AppHandle, event: MenuEvent) {
+    match event.id().0.as_str() {
+        TOGGLE_PAUSE_ITEM_ID => toggle_pause(app_handle),
+        INDEX_FOLDER_ITEM_ID => pick_folder_to_index(app_handle),
+        OPEN_LAUNCHER_ITEM_ID => show_launcher(app_handle),
+        _ => {}
+    }
+}
+
+/// Follows the file processor events to keep the status and pause items current
+fn listen_for_indexing_events(app_handle: &AppHandle) {
+    let app_handle_for_progress = app_handle.clone();
+    app_handle.listen(
+        "file-processing-progress",
+        move |event| match serde_json::from_str::<IndexingProgress>(event.payload()) {
+            Ok(progress) => set_status(&app_handle_for_progress, &progress_status(&progress)),
+            Err(e) => error!("Failed to parse indexing progress: {}", e),
+        },
+    );
+
+    let app_handle_for_complete = app_handle.clone();
+    app_handle.listen("indexing_complete", move |_| {
+        set_status(&app_handle_for_complete, IDLE_STATUS);
+    });
+
+    let app_handle_for_paused = app_handle.clone();
+    app_handle.listen("indexing-paused-changed", move |event| {
+        let paused = serde_json::from_str::<bool>(event.payload()).unwrap_or(false);
+        set_paused_text(&app_handle_for_paused, paused);
+    });
+}
+
+/// Adds the tray to the menu bar, must run after the file processor is initialized
+pub fn init_tray(app: &tauri::App) -> AppResult<()> {
+    let status = MenuItem::with_id(app, STATUS_ITEM_ID, IDLE_STATUS, false, None::<&str>)?;
+    let toggle_pause = MenuItem::with_id(
+        app,
+        TOGGLE_PAUSE_ITEM_ID,
+        "Pause indexing",
+        true,
+        None::<&str>,
+    )?;
+    let index_folder = MenuItem::with_id(
+        app,
+        INDEX_FOLDER_ITEM_ID,
+        "Index a Folder…",
+        true,
+        None::<&str>,
+    )?;
+    let open_launcher = MenuItem::with_id(
+        app,
+        OPEN_LAUNCHER_ITEM_ID,
+        "Open Kita",
+        true,
+        Some("CmdOrCtrl+Shift+C"),
+    )?;
+
+    let menu = Menu::with_items(
+        app,
+        &[
+            &status,
+            &toggle_pause,
+            &PredefinedMenuItem::separator(app)?,
+            &index_folder,
+            &open_launcher,
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::quit(app, Some("Quit Kita"))?,
+        ],
+    )?;
+
+    let mut tray = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .tooltip(format!("Kita: {}", IDLE_STATUS))
+        .show_menu_on_left_click(true)
+        .on_menu_event(handle_menu_event);
+    if let Some(icon) = app.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app)?;
+
+    app.manage(TrayMenuState {
+        status,
+        toggle_pause,
+    });
+    listen_for_indexing_events(app.handle());
+
+    Ok(())
+}
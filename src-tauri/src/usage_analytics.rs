@@ -0,0 +1,312 @@
+/*
+This file contains the local usage analytics. When usage_analytics is turned on in the settings, feature use counts and timings like search latency and indexing throughput are stored in the index database, get_usage_stats summarizes them to tune the defaults
+Nothing is recorded unless the user opts in and nothing ever leaves the machine. Events are buffered in memory and written in batches so recording doesn't slow down the commands it measures
+*/
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tokio::task;
+use tracing::error;
+
+use crate::database_handler::open_connection;
+use crate::file_processor::{get_processor, FileProcessor, FileProcessorError, FileProcessorState};
+use crate::settings::SettingsManagerState;
+
+/// How often the buffered events are written to the database
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Timings older than this are deleted when events are written
+const TIMING_RETENTION_DAYS: u32 = 90;
+
+const DEFAULT_STATS_DAYS: u32 = 30;
+
+struct TimingSample {
+    metric: String,
+    duration_ms: f64,
+    items: Option<u64>,
+}
+
+#[derive(Default)]
+struct PendingUsage {
+    counts: BTreeMap<String, u64>,
+    timings: Vec<TimingSample>,
+}
+
+static PENDING_USAGE: Mutex<PendingUsage> = Mutex::new(PendingUsage {
+    counts: BTreeMap::new(),
+    timings: Vec::new(),
+});
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureUsage {
+    pub feature: String,
+    pub count: i64,
+    pub last_used_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimingStats {
+    pub metric: String,
+    pub samples: usize,
+    pub average_ms: f64,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    /// Items handled per second over every sample that counted items, like files for indexing
+    pub items_per_second: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageStats {
+    pub enabled: bool,
+    pub days: u32,
+    pub features: Vec<FeatureUsage>,
+    pub timings: Vec<TimingStats>,
+}
+
+fn is_enabled(app_handle: &AppHandle) -> bool {
+    app_handle
+        .try_state::<SettingsManagerState>()
+        .and_then(|state| state.0.get_settings().ok())
+        .and_then(|settings| settings.usage_analytics)
+        .unwrap_or(false)
+}
+
+/// Counts one use of the feature, does nothing unless usage analytics are turned on
+pub fn record_feature(app_handle: &AppHandle, feature: &str) {
+    if !is_enabled(app_handle) {
+        return;
+    }
+
+    let mut pending = PENDING_USAGE.lock().unwrap();
+    *pending.counts.entry(feature.to_string()).or_default() += 1;
+}
+
+/// Records how long the operation took, `items` is the number of things it handled when throughput matters
+pub fn record_timing(app_handle: &AppHandle, metric: &str, duration: Duration, items: Option<u64>) {
+    if !is_enabled(app_handle) {
+        return;
+    }
+
+    PENDING_USAGE.lock().unwrap().timings.push(TimingSample {
+        metric: metric.to_string(),
+        duration_ms: duration.as_secs_f64() * 1000.0,
+        items,
+    });
+}
+
+/// Writes the buffered events and drops timings past the retention
+fn flush_pending(db_path: PathBuf) -> Result<(), FileProcessorError> {
+    let pending = std::mem::take(&mut *PENDING_USAGE.lock().unwrap());
+    if pending.counts.is_empty() && pending.timings.is_empty() {
+        return Ok(());
+    }
+
+    let mut conn = open_connection(db_path)?;
+    let tx = conn.transaction()?;
+
+    {
+        let mut count_stmt = tx.prepare(
+            r#"
+            INSERT INTO usage_counts (feature, count, last_used_at)
+            VALUES (?1, ?2, CURRENT_TIMESTAMP)
+            ON CONFLICT(feature) DO UPDATE SET
+                count = count + excluded.count,
+                last_used_at = excluded.last_used_at
+            "#,
+        )?;
+        for (feature, count) in &pending.counts {
+            count_stmt.execute(params![feature, *count as i64])?;
+        }
+
+        let mut timing_stmt = tx.prepare(
+            "INSERT INTO usage_timings (metric, duration_ms, items) VALUES (?1, ?2, ?3)",
+        )?;
+        for timing in &pending.timings {
+            timing_stmt.execute(params![
+                timing.metric,
+                timing.duration_ms,
+                timing.items.map(|items| items as i64)
+            ])?;
+        }
+    }
+
+    tx.execute(
+        "DELETE FROM usage_timings WHERE recorded_at < datetime('now', ?1)",
+        [format!("-{} days", TIMING_RETENTION_DAYS)],
+    )?;
+
+    tx.commit()?;
+    Ok(())
+}
+
+async fn flush(db_path: PathBuf) -> Result<(), FileProcessorError> {
+    task::spawn_blocking(move || flush_pending(db_path))
+        .await
+        .map_err(|e| FileProcessorError::Other(format!("spawn_blocking error: {e}")))?
+}
+
+/// Writes the buffered events to the database every minute while the app runs
+pub fn start_usage_flusher(db_path: PathBuf, app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            // events recorded before the user opted out are dropped
+            if !is_enabled(&app_handle) {
+                *PENDING_USAGE.lock().unwrap() = PendingUsage::default();
+                continue;
+            }
+
+            if let Err(e) = flush(db_path.clone()).await {
+                error!("Failed to save usage analytics: {}", e);
+            }
+        }
+    });
+}
+
+/// Value at the percentile of durations sorted in ascending order
+fn percentile(sorted: &[f64], percentile: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let index = ((sorted.len() - 1) as f64 * percentile).round() as usize;
+    sorted[index.min(sorted.len() - 1)]
+}
+
+fn timing_stats(metric: String, samples: Vec<(f64, Option<i64>)>) -> TimingStats {
+    let mut durations: Vec<f64> = samples.iter().map(|(duration, _)| *duration).collect();
+    durations.sort_by(|a, b| a.total_cmp(b));
+
+    let (counted_items, counted_ms) = samples
+        .iter()
+        .filter_map(|(duration, items)| items.map(|items| (items as f64, *duration)))
+        .fold((0.0, 0.0), |(items, ms), (sample_items, sample_ms)| {
+            (items + sample_items, ms + sample_ms)
+        });
+
+    TimingStats {
+        metric,
+        samples: durations.len(),
+        average_ms: durations.iter().sum::<f64>() / durations.len().max(1) as f64,
+        median_ms: percentile(&durations, 0.5),
+        p95_ms: percentile(&durations, 0.95),
+        max_ms: durations.last().copied().unwrap_or(0.0),
+        items_per_second: (counted_ms > 0.0).then(|| counted_items / (counted_ms / 1000.0)),
+    }
+}
+
+fn load_usage_stats(
+    processor: &FileProcessor,
+    days: u32,
+) -> Result<(Vec<FeatureUsage>, Vec<TimingStats>), String> {
+    let conn = processor.get_read_connection()?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT feature, count, last_used_at
+            FROM usage_counts
+            ORDER BY count DESC, feature
+            "#,
+        )
+        .map_err(|e| format!("Failed to prepare statement: {e}"))?;
+
+    let features = stmt
+        .query_map([], |row| {
+            Ok(FeatureUsage {
+                feature: row.get(0)?,
+                count: row.get(1)?,
+                last_used_at: row.get(2).ok(),
+            })
+        })
+        .map_err(|e| format!("Query error: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Row error: {e}"))?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT metric, duration_ms, items
+            FROM usage_timings
+            WHERE recorded_at >= datetime('now', ?1)
+            "#,
+        )
+        .map_err(|e| format!("Failed to prepare statement: {e}"))?;
+
+    let mut samples_by_metric: BTreeMap<String, Vec<(f64, Option<i64>)>> = BTreeMap::new();
+    let rows = stmt
+        .query_map([format!("-{} days", days)], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, f64>(1)?,
+                row.get::<_, Option<i64>>(2)?,
+            ))
+        })
+        .map_err(|e| format!("Query error: {e}"))?;
+    for row in rows {
+        let (metric, duration_ms, items) = row.map_err(|e| format!("Row error: {e}"))?;
+        samples_by_metric
+            .entry(metric)
+            .or_default()
+            .push((duration_ms, items));
+    }
+
+    let timings = samples_by_metric
+        .into_iter()
+        .map(|(metric, samples)| timing_stats(metric, samples))
+        .collect();
+
+    Ok((features, timings))
+}
+
+/// Returns the feature use counts and the timings of the last `days` days, 30 by default
+/// Works when analytics are off too, so the data recorded before opting out can still be seen or cleared
+#[tauri::command]
+pub async fn get_usage_stats(
+    days: Option<u32>,
+    state: State<'_, FileProcessorState>,
+    app_handle: AppHandle,
+) -> Result<UsageStats, String> {
+    let processor: FileProcessor = get_processor(&state)?;
+    let days = days.unwrap_or(DEFAULT_STATS_DAYS).max(1);
+
+    // include what was recorded since the last flush
+    if is_enabled(&app_handle) {
+        flush(processor.db_path.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    let (features, timings) = load_usage_stats(&processor, days)?;
+
+    Ok(UsageStats {
+        enabled: is_enabled(&app_handle),
+        days,
+        features,
+        timings,
+    })
+}
+
+/// Deletes everything recorded so far
+#[tauri::command]
+pub async fn clear_usage_stats(state: State<'_, FileProcessorState>) -> Result<(), String> {
+    let processor: FileProcessor = get_processor(&state)?;
+    *PENDING_USAGE.lock().unwrap() = PendingUsage::default();
+
+    let db_path = processor.db_path.clone();
+    task::spawn_blocking(move || -> Result<(), FileProcessorError> {
+        let conn = open_connection(db_path)?;
+        conn.execute_batch("DELETE FROM usage_counts; DELETE FROM usage_timings;")?;
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking error: {e}"))?
+    .map_err(|e| e.to_string())
+}
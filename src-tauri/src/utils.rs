@@ -1,3 +1,7 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
 pub fn get_category_from_extension(extension: &str) -> String {
     let ext = extension.to_lowercase();
 
@@ -36,3 +40,21 @@ pub fn get_category_from_extension(extension: &str) -> String {
         _ => "other".to_string(),
     }
 }
+
+/// Copies a directory and everything in it, the destination is created if it doesn't exist
+pub fn copy_dir_all(source: &Path, destination: &Path) -> io::Result<()> {
+    fs::create_dir_all(destination)?;
+
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let target = destination.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,24 +1,32 @@
 use arrow_array::types::Float32Type;
 use arrow_array::FixedSizeListArray;
+use arrow_array::Float32Array;
 use arrow_array::RecordBatch;
 use arrow_array::RecordBatchIterator;
 use arrow_array::StringArray;
 use arrow_schema::{DataType, Field, Schema};
 use futures::TryStreamExt;
 use lancedb::query::ExecutableQuery;
+use lancedb::query::QueryBase;
 use lancedb::query::QueryExecutionOptions;
 use lancedb::{Connection, Error};
-use std::path::PathBuf;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tauri::AppHandle;
 use tauri::Manager;
 use thiserror::Error;
 use tokio::sync::Mutex;
+use tracing::{error, info};
 
 use crate::chunker::Chunk;
 use crate::embedder;
 use crate::embedder::Embedder;
+use crate::encryption::vector_store_dir;
+use crate::profiles::active_profile_dir;
 use crate::server::TextChunkResponse;
+use crate::settings::SearchSettings;
+use crate::utils::copy_dir_all;
 use crate::AppResult;
 
 pub struct VectorDbManager {
@@ -45,24 +53,44 @@ impl VectorDbManager {
     pub async fn initialize_vectordb(
         app_handle: AppHandle,
     ) -> VectorDbResult<Arc<Mutex<VectorDbManager>>> {
-        let app_data_dir: PathBuf = app_handle
-            .path()
-            .app_data_dir()
-            .map_err(|_| VectorDbError::Other("Failed to get app data directory".into()))?;
-
-        let vectordb_path: PathBuf = app_data_dir.join("vector_db");
+        let vectordb_path: PathBuf = vectordb_path(&app_handle)?;
 
         let manager: VectorDbManager = Self::new_vectordb_client(&vectordb_path).await?;
 
         Ok(Arc::new(Mutex::new(manager)))
     }
 
+    /// Copies the vector store to the given directory
+    /// The manager stays locked during the copy so no embeddings are written halfway through
+    pub async fn export_store(app_handle: &AppHandle, destination: &Path) -> VectorDbResult<()> {
+        let state = app_handle.state::<Arc<Mutex<VectorDbManager>>>();
+        let _manager = state.lock().await;
+
+        copy_dir_all(&vectordb_path(app_handle)?, destination)?;
+        Ok(())
+    }
+
+    /// Replaces the vector store with the copy in the given directory and reconnects to it
+    pub async fn import_store(app_handle: &AppHandle, source: &Path) -> VectorDbResult<()> {
+        let state = app_handle.state::<Arc<Mutex<VectorDbManager>>>();
+        let mut manager = state.lock().await;
+
+        let vectordb_path = vectordb_path(app_handle)?;
+        if vectordb_path.exists() {
+            std::fs::remove_dir_all(&vectordb_path)?;
+        }
+        copy_dir_all(source, &vectordb_path)?;
+
+        *manager = Self::new_vectordb_client(&vectordb_path).await?;
+        Ok(())
+    }
+
     async fn new_vectordb_client(vdb_path: &PathBuf) -> VectorDbResult<Self> {
         let client = lancedb::connect(&vdb_path.to_string_lossy())
             .execute()
             .await
             .map_err(|e| {
-                println!("Unable to create LanceDB client: {}", e);
+                error!("Unable to create LanceDB client: {}", e);
                 VectorDbError::LanceError(e.to_string())
             })?;
 
@@ -162,7 +190,7 @@ impl VectorDbManager {
         let manager = state.lock().await;
 
         if let Err(e) = manager.ensure_embedding_table_exists().await {
-            println!("Error ensuring table exists: {}", e);
+            error!("Error ensuring table exists: {}", e);
             return Ok(Vec::new());
         }
 
@@ -182,8 +210,10 @@ impl VectorDbManager {
             VectorDbError::LanceError(format!("Failed to create vector query: {}", e))
         })?;
 
+        let top_k = SearchSettings::from_app_handle(app_handle).top_k();
         let nev_vec = vector_query
             .distance_type(lancedb::DistanceType::Cosine)
+            .limit(top_k)
             .clone();
 
         let results: Vec<RecordBatch> = nev_vec
@@ -198,6 +228,91 @@ impl VectorDbManager {
 
         Ok(results)
     }
+
+    /// Returns the chunk with the given id, the ids come from chunk_records
+    pub async fn get_chunk(
+        app_handle: &AppHandle,
+        chunk_id: &str,
+    ) -> VectorDbResult<Option<ChunkRecord>> {
+        let state = app_handle.state::<Arc<Mutex<VectorDbManager>>>();
+        let manager = state.lock().await;
+
+        if let Err(e) = manager.ensure_embedding_table_exists().await {
+            error!("Error ensuring table exists: {}", e);
+            return Ok(None);
+        }
+
+        let table = manager
+            .client
+            .open_table(TABLE_NAME)
+            .execute()
+            .await
+            .map_err(|e| VectorDbError::LanceError(format!("Failed to open table: {}", e)))?;
+
+        // ids are built from file ids and indexes, quotes are escaped in case one is passed anyway
+        let results: Vec<RecordBatch> = table
+            .query()
+            .only_if(format!("id = '{}'", chunk_id.replace('\'', "''")))
+            .limit(1)
+            .execute()
+            .await
+            .map_err(|e| VectorDbError::LanceError(format!("Chunk query failed: {}", e)))?
+            .try_collect::<Vec<_>>()
+            .await
+            .map_err(|e| {
+                VectorDbError::LanceError(format!("Chunk query collection failed: {}", e))
+            })?;
+
+        Ok(chunk_records(&results).into_iter().next())
+    }
+}
+
+/// A stored chunk with the distance to the query when it comes from a similarity search
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkRecord {
+    pub id: String,
+    pub file_id: String,
+    pub file_path: String,
+    pub text: String,
+    pub distance: Option<f32>,
+}
+
+fn string_column<'a>(batch: &'a RecordBatch, name: &str) -> Option<&'a StringArray> {
+    batch
+        .column_by_name(name)?
+        .as_any()
+        .downcast_ref::<StringArray>()
+}
+
+/// Reads every row of the batches, batches missing a column are skipped
+pub fn chunk_records(results: &[RecordBatch]) -> Vec<ChunkRecord> {
+    let mut records = Vec::new();
+
+    for batch in results {
+        let (Some(ids), Some(texts), Some(file_ids), Some(file_paths)) = (
+            string_column(batch, "id"),
+            string_column(batch, "text"),
+            string_column(batch, "file_id"),
+            string_column(batch, "file_path"),
+        ) else {
+            continue;
+        };
+        let distances = batch
+            .column_by_name("_distance")
+            .and_then(|column| column.as_any().downcast_ref::<Float32Array>());
+
+        for i in 0..batch.num_rows() {
+            records.push(ChunkRecord {
+                id: ids.value(i).to_string(),
+                file_id: file_ids.value(i).to_string(),
+                file_path: file_paths.value(i).to_string(),
+                text: texts.value(i).to_string(),
+                distance: distances.map(|distances| distances.value(i)),
+            });
+        }
+    }
+
+    records
 }
 
 fn from_chunks_embeddings_to_data(
@@ -255,6 +370,16 @@ pub async fn init_vectordb(app_handle: AppHandle) -> VectorDbResult<Arc<Mutex<Ve
     VectorDbManager::initialize_vectordb(app_handle).await
 }
 
+fn vectordb_path(app_handle: &AppHandle) -> VectorDbResult<PathBuf> {
+    let app_data_dir: PathBuf = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|_| VectorDbError::Other("Failed to get app data directory".into()))?;
+
+    // inside the mounted encrypted image when the index is encrypted
+    Ok(vector_store_dir(&active_profile_dir(&app_data_dir)))
+}
+
 fn get_embeddings_schema() -> Arc<Schema> {
     Arc::new(Schema::new(vec![
         Field::new("id", DataType::Utf8, false),
@@ -333,11 +458,12 @@ pub fn init_vector_db(app: &tauri::App) -> AppResult<()> {
     // Initialize the embedder and store it in the app state so we can use it
     match embedder::Embedder::new() {
         Ok(embedder) => {
+            crate::tokenizer::set_model_tokenizer(&embedder.model.tokenizer);
             app.manage(std::sync::Arc::new(embedder));
-            println!("Embedder initialized");
+            info!("Embedder initialized");
         }
         Err(e) => {
-            eprintln!("Failed to initialize embedder: {}", e);
+            error!("Failed to initialize embedder: {}", e);
             return Err(Box::new(std::io::Error::new(
                 std::io::ErrorKind::Other,
                 format!("Embedder initialization failed: {}", e),
@@ -348,11 +474,11 @@ pub fn init_vector_db(app: &tauri::App) -> AppResult<()> {
     match result {
         Ok(manager) => {
             app.manage(manager);
-            println!("Vector DB initialized");
+            info!("Vector DB initialized");
             Ok(())
         }
         Err(e) => {
-            eprintln!("Failed to initialize vector DB: {}", e);
+            error!("Failed to initialize vector DB: {}", e);
             Err(Box::new(std::io::Error::new(
                 std::io::ErrorKind::Other,
                 format!("Vector database initialization failed: {}", e),
@@ -1,10 +1,121 @@
-use tauri::Manager;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+
+use crate::login_item::should_start_hidden;
+
+/// Label of the quick-ask window, the frontend renders the quick-ask view for windows with this label
+pub const QUICK_ASK_WINDOW_LABEL: &str = "quick-ask";
+
+const QUICK_ASK_WIDTH: f64 = 600.0;
+const QUICK_ASK_HEIGHT: f64 = 360.0;
+
+/// Label of the chat window, the frontend renders the chat view with the conversations for windows with this label
+pub const CHAT_WINDOW_LABEL: &str = "chat";
+
+const CHAT_WIDTH: f64 = 960.0;
+const CHAT_HEIGHT: f64 = 680.0;
+const CHAT_MIN_WIDTH: f64 = 640.0;
+const CHAT_MIN_HEIGHT: f64 = 420.0;
+
+/// Set once the window was shown or kept hidden on start, later calls always show it
+static STARTUP_SHOW_HANDLED: AtomicBool = AtomicBool::new(false);
 
 #[tauri::command]
 pub async fn show_main_window(window: tauri::Window) {
+    let is_startup = !STARTUP_SHOW_HANDLED.swap(true, Ordering::Relaxed);
+    if is_startup && should_start_hidden(window.app_handle()) {
+        // stays hidden until the global shortcut shows it
+        return;
+    }
+
     let window = window.get_webview_window("main").unwrap();
 
     window.show().unwrap();
     window.open_devtools();
     window.close_devtools();
 }
+
+/// Shows the quick-ask window, or hides it when it's already visible
+/// The window is created the first time and hides itself when it loses focus, like a spotlight panel
+#[tauri::command]
+pub async fn toggle_quick_ask_window(app_handle: AppHandle) -> Result<(), String> {
+    if let Some(window) = app_handle.get_webview_window(QUICK_ASK_WINDOW_LABEL) {
+        if window.is_visible().map_err(|e| e.to_string())? {
+            window.hide().map_err(|e| e.to_string())?;
+        } else {
+            window.center().map_err(|e| e.to_string())?;
+            window.show().map_err(|e| e.to_string())?;
+            window.set_focus().map_err(|e| e.to_string())?;
+        }
+        return Ok(());
+    }
+
+    let window = WebviewWindowBuilder::new(
+        &app_handle,
+        QUICK_ASK_WINDOW_LABEL,
+        WebviewUrl::App("index.html".into()),
+    )
+    .title("Quick ask")
+    .inner_size(QUICK_ASK_WIDTH, QUICK_ASK_HEIGHT)
+    .resizable(false)
+    .decorations(false)
+    .transparent(true)
+    .shadow(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .center()
+    .focused(true)
+    .build()
+    .map_err(|e| e.to_string())?;
+
+    let window_for_events = window.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Focused(false) = event {
+            let _ = window_for_events.hide();
+        }
+    });
+
+    Ok(())
+}
+
+/// Shows the chat window, or hides it when it's already visible and focused
+/// Closing the window only hides it, so answers that are still streaming keep going and the open conversation stays loaded
+#[tauri::command]
+pub async fn toggle_chat_window(app_handle: AppHandle) -> Result<(), String> {
+    if let Some(window) = app_handle.get_webview_window(CHAT_WINDOW_LABEL) {
+        let is_visible = window.is_visible().map_err(|e| e.to_string())?;
+        let is_focused = window.is_focused().map_err(|e| e.to_string())?;
+
+        if is_visible && is_focused {
+            window.hide().map_err(|e| e.to_string())?;
+        } else {
+            window.show().map_err(|e| e.to_string())?;
+            window.set_focus().map_err(|e| e.to_string())?;
+        }
+        return Ok(());
+    }
+
+    let window = WebviewWindowBuilder::new(
+        &app_handle,
+        CHAT_WINDOW_LABEL,
+        WebviewUrl::App("index.html".into()),
+    )
+    .title("Kita Chat")
+    .inner_size(CHAT_WIDTH, CHAT_HEIGHT)
+    .min_inner_size(CHAT_MIN_WIDTH, CHAT_MIN_HEIGHT)
+    .resizable(true)
+    .center()
+    .focused(true)
+    .build()
+    .map_err(|e| e.to_string())?;
+
+    let window_for_events = window.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::CloseRequested { api, .. } = event {
+            api.prevent_close();
+            let _ = window_for_events.hide();
+        }
+    });
+
+    Ok(())
+}
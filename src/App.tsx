@@ -1,14 +1,19 @@
-import { useCallback, useEffect, useMemo, useState } from "react";
+import { useCallback, useEffect, useMemo, useRef, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen, UnlistenFn } from "@tauri-apps/api/event";
 import "./globals.css";
 import Footer from "./Footer";
 import {
   AppMetadata,
+  AppResourceUsage,
   AppSettings,
+  BackgroundTaskCrash,
   Contact,
   FileMetadata,
+  FilePreview,
   IndexingProgress,
+  ResourceAlert,
+  ResourceSampleEvent,
   searchCategories,
   SearchCategory,
   Section,
@@ -21,17 +26,40 @@ import { documentDir } from "@tauri-apps/api/path";
 import Settings from "./settings/Settings";
 import AppTable from "./AppTable";
 import FilesTable from "./FilesTable";
+import FilePreviewDialog from "./FilePreviewDialog";
 import SectionNav from "./SectionNav";
 import { Command, File } from "lucide-react";
 import { register } from "@tauri-apps/plugin-global-shortcut";
 import { handleShortcut } from "./globalShortcut";
 import { useGetContacts } from "./lib/hooks/useGetContacts";
+import { ioBytesPerSec } from "./lib/utils";
 import { Button } from "./components/ui/button";
 
 await register("CommandOrControl+Shift+C", handleShortcut).then(() =>
   console.log("shortcut successfully registered")
 );
 
+// opens the floating quick-ask window, the handler also runs on key release so only presses toggle it
+await register("CommandOrControl+Shift+A", (event) => {
+  if (event.state === "Pressed") {
+    invoke("toggle_quick_ask_window").catch((err) =>
+      console.error("Failed to toggle the quick-ask window:", err)
+    );
+  }
+});
+
+// opens the chat window with the saved conversations
+await register("CommandOrControl+Shift+L", (event) => {
+  if (event.state === "Pressed") {
+    invoke("toggle_chat_window").catch((err) =>
+      console.error("Failed to toggle the chat window:", err)
+    );
+  }
+});
+
+// how often the running apps' resource usage is sampled
+const RESOURCE_SAMPLE_INTERVAL_MS = 10_000;
+
 export default function App() {
   const [searchQuery, setSearchQuery] = useState<string>("");
   const [selectedCategories, setSelectedCategories] = useState<
@@ -41,13 +69,20 @@ export default function App() {
   const [indexingProgress, setIndexingProgress] =
     useState<IndexingProgress | null>(null);
   const [isSettingsOpen, setIsSettingsOpen] = useState<boolean>(false);
+  const [previewFile, setPreviewFile] = useState<FileMetadata | null>(null);
+  const [previewText, setPreviewText] = useState<{
+    content: string;
+    truncated: boolean;
+  }>({ content: "", truncated: false });
   const [appsData, setAppsData] = useState<AppMetadata[]>([]);
   const [filesData, setFilesData] = useState<FileMetadata[]>([]);
   const [semanticData, setSemanticData] = useState<SemanticMetadata[]>([]);
   const [selectedItem, setSelectedItem] = useState<string>();
   const [resourceData, setResourceData] = useState<
-    Record<number, { cpu_usage: number; memory_bytes: number }>
+    Record<number, AppResourceUsage>
   >({});
+  const resourceSubscription = useRef<Promise<number> | null>(null);
+  const resourceSubscriptionId = useRef<number | null>(null);
   const [showProgress, setShowProgress] = useState(false);
   const [indexElapsedTime, setIndexElapsedTime] = useState<number | null>(null);
   const [currentSection, setCurrentSection] = useState<"apps" | "files">(
@@ -117,13 +152,47 @@ export default function App() {
   }, [searchQuery]);
 
   // handles opening an app when the user selects it
-  const handleAppSelect = useCallback(async (app: AppMetadata) => {
-    await invoke<AppMetadata[]>("launch_or_switch_to_app", { app });
-  }, []);
+  const handleAppSelect = useCallback(
+    async (app: AppMetadata) => {
+      // the launch is recorded in the open history by the backend
+      await invoke<AppMetadata[]>("launch_or_switch_to_app", {
+        app,
+        query: searchQuery,
+      });
+    },
+    [searchQuery]
+  );
 
   // handles opening a file when the user selects it
-  const handleFileSelect = useCallback(async (file: FileMetadata) => {
-    await invoke<FileMetadata[]>("open_file", { filePath: file.path });
+  const handleFileSelect = useCallback(
+    async (file: FileMetadata) => {
+      await invoke<FileMetadata[]>("open_file", { filePath: file.path });
+      invoke("record_opened_item", {
+        kind: "file",
+        path: file.path,
+        name: file.name,
+        query: searchQuery,
+      }).catch((err) => console.error("Failed to record opened file:", err));
+    },
+    [searchQuery]
+  );
+
+  // peeks at a file with Quick Look, or shows its text when Quick Look isn't available
+  const handleFilePreview = useCallback(async (file: FileMetadata) => {
+    try {
+      const preview = await invoke<FilePreview>("preview_file", {
+        path: file.path,
+      });
+      if (preview.kind === "text") {
+        setPreviewText({
+          content: preview.content,
+          truncated: preview.truncated,
+        });
+        setPreviewFile(file);
+      }
+    } catch (err) {
+      errorToast(`Failed to preview file: ${err}`);
+    }
   }, []);
 
   // toggles categories in the index dialog
@@ -294,11 +363,6 @@ export default function App() {
         console.log("got apps data");
         setFilesData(filesData);
         console.log("got files data");
-        const pids = appData
-          .filter((app) => app.pid != null)
-          .map((app) => app.pid);
-
-        await invoke("start_resource_monitoring", { pids });
       } catch (err) {
         console.error("Failed to initialize data:", err);
       }
@@ -307,37 +371,112 @@ export default function App() {
     initialize();
   }, []);
 
-  // resource usage monitoring - updates every second
+  // the resource subscription follows the running apps as they start and quit
+  const runningPidsKey = useMemo(
+    () =>
+      appsData
+        .filter((app) => app.pid != null)
+        .map((app) => app.pid)
+        .sort((a, b) => a - b)
+        .join(","),
+    [appsData]
+  );
+
+  useEffect(() => {
+    const pids = runningPidsKey ? runningPidsKey.split(",").map(Number) : [];
+    const target = { pids };
+
+    if (!resourceSubscription.current) {
+      resourceSubscription.current = invoke<number>(
+        "subscribe_resource_usage",
+        { target, intervalMs: RESOURCE_SAMPLE_INTERVAL_MS }
+      ).then((subscriptionId) => {
+        resourceSubscriptionId.current = subscriptionId;
+        return subscriptionId;
+      });
+      resourceSubscription.current.catch((err) => {
+        console.error("Failed to subscribe to resource usage:", err);
+      });
+      return;
+    }
+
+    resourceSubscription.current
+      .then((subscriptionId) =>
+        invoke("update_resource_subscription", { subscriptionId, target })
+      )
+      .catch((err) => {
+        console.error("Failed to update the resource subscription:", err);
+      });
+  }, [runningPidsKey]);
+
+  // resource usage monitoring
   useEffect(() => {
     let unlistenUsage: UnlistenFn | undefined;
+    let unlistenAlerts: UnlistenFn | undefined;
 
     const setupResourceMonitoring = async () => {
       try {
-        unlistenUsage = await listen("resource-usage-updated", (event) => {
-          const updates = event.payload as Record<
-            number,
-            { cpu_usage: number; memory_bytes: number }
-          >;
-
-          setResourceData((prev) => {
-            const newState = { ...prev };
-            let hasChanges = false;
-
-            Object.entries(updates).forEach(([pidStr, usage]) => {
-              const pidNum = Number(pidStr);
-              if (
-                !prev[pidNum] ||
-                prev[pidNum].cpu_usage !== usage.cpu_usage ||
-                prev[pidNum].memory_bytes !== usage.memory_bytes
-              ) {
-                newState[pidNum] = usage;
-                hasChanges = true;
-              }
+        unlistenUsage = await listen<ResourceSampleEvent>(
+          "resource-sample",
+          (event) => {
+            // other windows can have their own subscriptions
+            if (
+              event.payload.subscription_id !== resourceSubscriptionId.current
+            ) {
+              return;
+            }
+            const updates = event.payload.usage;
+
+            setResourceData((prev) => {
+              const newState = { ...prev };
+              let hasChanges = false;
+
+              Object.entries(updates).forEach(([pidStr, usage]) => {
+                const pidNum = Number(pidStr);
+                if (
+                  !prev[pidNum] ||
+                  prev[pidNum].cpu_usage !== usage.cpu_usage ||
+                  prev[pidNum].memory_bytes !== usage.memory_bytes ||
+                  prev[pidNum].energy_impact !== usage.energy_impact ||
+                  ioBytesPerSec(prev[pidNum]) !== ioBytesPerSec(usage)
+                ) {
+                  newState[pidNum] = usage;
+                  hasChanges = true;
+                }
+              });
+
+              return hasChanges ? newState : prev;
             });
+          }
+        );
 
-            return hasChanges ? newState : prev;
-          });
-        });
+        unlistenAlerts = await listen<ResourceAlert>(
+          "resource-alert",
+          (event) => {
+            const alert = event.payload;
+            const message =
+              alert.kind === "cpu"
+                ? `${alert.name} has used over ${alert.threshold}% CPU for ${Math.round(
+                    alert.duration_secs / 60
+                  )} minutes`
+                : `${alert.name} is using ${(
+                    alert.memory_bytes /
+                    1024 ** 3
+                  ).toFixed(1)} GB of memory`;
+
+            errorToast(message, {
+              duration: 10000,
+              action: {
+                label: "Force quit",
+                onClick: () => {
+                  invoke("force_quit_application", { pid: alert.pid }).catch(
+                    (err) => errorToast(`Failed to terminate ${alert.name}: ${err}`)
+                  );
+                },
+              },
+            });
+          }
+        );
       } catch (err) {
         console.error("Failed to set up resource monitoring:", err);
       }
@@ -347,9 +486,35 @@ export default function App() {
 
     return () => {
       if (unlistenUsage) unlistenUsage();
-      invoke("stop_resource_monitoring").catch((err) => {
-        console.error("Failed to stop resource monitoring:", err);
-      });
+      if (unlistenAlerts) unlistenAlerts();
+      resourceSubscription.current
+        ?.then((subscriptionId) =>
+          invoke("unsubscribe_resource_usage", { subscriptionId })
+        )
+        .catch((err) => {
+          console.error("Failed to stop resource monitoring:", err);
+        });
+      resourceSubscription.current = null;
+    };
+  }, []);
+
+  // Crashed background tasks
+  useEffect(() => {
+    const unlisten = listen<BackgroundTaskCrash>(
+      "background-task-crashed",
+      (event) => {
+        const crash = event.payload;
+        errorToast(
+          crash.restarting
+            ? `${crash.task} crashed and is restarting: ${crash.message}`
+            : `${crash.task} crashed and was stopped: ${crash.message}`,
+          { duration: 10000 }
+        );
+      }
+    );
+
+    return () => {
+      unlisten.then((fn) => fn());
     };
   }, []);
 
@@ -386,6 +551,62 @@ export default function App() {
     };
   }, []);
 
+  // Running apps are pushed by the backend when they start or quit
+  useEffect(() => {
+    let unlistenStarted: UnlistenFn | undefined;
+    let unlistenTerminated: UnlistenFn | undefined;
+
+    const setupRunningAppsListeners = async () => {
+      try {
+        unlistenStarted = await listen<AppMetadata>("app-started", (event) => {
+          const started = event.payload;
+          setAppsData((prev) => {
+            const existing = prev.find((app) => app.path === started.path);
+            if (!existing) return [...prev, started];
+
+            return prev.map((app) =>
+              app.path === started.path
+                ? {
+                    ...app,
+                    pid: started.pid,
+                    icon: started.icon ?? app.icon,
+                  }
+                : app
+            );
+          });
+        });
+
+        unlistenTerminated = await listen<AppMetadata>(
+          "app-terminated",
+          (event) => {
+            const terminated = event.payload;
+            // the app stays in the list as an installed app, which the backend sends with a null pid
+            setAppsData((prev) =>
+              prev.map((app) =>
+                app.pid === terminated.pid
+                  ? ({
+                      ...app,
+                      pid: null,
+                      resource_usage: undefined,
+                    } as unknown as AppMetadata)
+                  : app
+              )
+            );
+          }
+        );
+      } catch (err) {
+        console.error("Failed to set up running apps listeners:", err);
+      }
+    };
+
+    setupRunningAppsListeners();
+
+    return () => {
+      if (unlistenStarted) unlistenStarted();
+      if (unlistenTerminated) unlistenTerminated();
+    };
+  }, []);
+
   useEffect(() => {
     let unlistenFilesUpdate: UnlistenFn | undefined;
 
@@ -519,7 +740,17 @@ export default function App() {
     const handleKeyDown = (e: KeyboardEvent) => {
       if (appsData.length === 0 && filesData.length === 0) return;
 
-      if (e.key === "ArrowDown") {
+      if (e.key === "y" && e.metaKey) {
+        // Cmd+Y previews the selected file, like in Finder
+        if (
+          currentSection === "files" &&
+          currentItemIndex >= 0 &&
+          filteredFiles.length > currentItemIndex
+        ) {
+          e.preventDefault();
+          handleFilePreview(filteredFiles[currentItemIndex]);
+        }
+      } else if (e.key === "ArrowDown") {
         e.preventDefault();
 
         if (currentSection === "apps") {
@@ -591,13 +822,15 @@ export default function App() {
     currentItemIndex,
     handleAppSelect,
     handleFileSelect,
+    handleFilePreview,
   ]);
 
-  const sortAppsByRunningStatusAndName = (
-    apps: AppMetadata[]
-  ): AppMetadata[] => {
+  const sortApps = (apps: AppMetadata[]): AppMetadata[] => {
     return [...apps].sort((a, b) => {
-      // First sort by running status (running apps first)
+      // Apps the user launches often and recently come first
+      if (a.frecency !== b.frecency) return b.frecency - a.frecency;
+
+      // Then sort by running status (running apps first)
       if (a.pid && !b.pid) return -1;
       if (!a.pid && b.pid) return 1;
 
@@ -608,7 +841,7 @@ export default function App() {
 
   // memoized full section array
   const sections: Section[] = useMemo(() => {
-    const sortedApps = sortAppsByRunningStatusAndName(filteredApps);
+    const sortedApps = sortApps(filteredApps);
 
     console.log("apps", sortedApps);
 
@@ -775,6 +1008,12 @@ export default function App() {
         showProgress={showProgress}
         indexElapsedTime={indexElapsedTime ?? 0}
       />
+      <FilePreviewDialog
+        file={previewFile}
+        content={previewText.content}
+        truncated={previewText.truncated}
+        onClose={() => setPreviewFile(null)}
+      />
     </div>
   );
 }
@@ -1,18 +1,30 @@
 import React, { useState, useMemo, memo, useCallback } from "react";
-import { Package, MemoryStick, Cpu, X, RefreshCw, Loader2 } from "lucide-react";
+import {
+  Package,
+  MemoryStick,
+  Cpu,
+  HardDrive,
+  BatteryWarning,
+  X,
+  RefreshCw,
+  Loader2,
+} from "lucide-react";
 import { invoke } from "@tauri-apps/api/core";
 import { successToast, errorToast } from "./components/ui/toast";
-import { cn, FormatFileSize } from "./lib/utils";
-import { AppMetadata, Column } from "./types/types";
+import { cn, FormatFileSize, ioBytesPerSec } from "./lib/utils";
+import { AppMetadata, AppResourceUsage, Column } from "./types/types";
 
 interface Props {
   data: AppMetadata[];
   refreshApps: () => Promise<void>;
-  appResourceData?: Record<number, { cpu_usage: number; memory_bytes: number }>;
+  appResourceData?: Record<number, AppResourceUsage>;
   onRowClick?: (app: AppMetadata) => void;
   selectedItemName?: string;
 }
 
+// apps above this energy impact are flagged, 100 is about one busy core
+const HIGH_ENERGY_IMPACT = 30;
+
 const columns: Column<AppMetadata>[] = [
   {
     key: "name",
@@ -36,6 +48,16 @@ const columns: Column<AppMetadata>[] = [
             <div className="relative w-[6px] h-[6px] bg-green-500 rounded-full shadow-lg shadow-green-500/50" />
           </div>
         )}
+        {(app?.resource_usage?.energy_impact ?? 0) > HIGH_ENERGY_IMPACT && (
+          <span
+            className="ml-2"
+            title={`High energy impact (${app.resource_usage?.energy_impact?.toFixed(
+              0
+            )})`}
+          >
+            <BatteryWarning className="h-3 w-3 text-amber-500" />
+          </span>
+        )}
       </div>
     ),
   },
@@ -58,6 +80,12 @@ const columns: Column<AppMetadata>[] = [
       <CpuCell pid={app.pid} cpuUsage={app.resource_usage?.cpu_usage} />
     ),
   },
+  {
+    key: "io",
+    header: "I/O",
+    width: 20,
+    render: (app) => <IoCell pid={app.pid} usage={app.resource_usage} />,
+  },
   {
     key: "actions",
     header: "Actions",
@@ -106,6 +134,42 @@ const CpuCell = React.memo(
   }
 );
 
+// disk and network throughput, the tooltip splits it up
+const IoCell = React.memo(
+  function IoCell({ pid, usage }: { pid?: number; usage?: AppResourceUsage }) {
+    if (!pid || !usage) return null;
+
+    const details = [
+      `Disk read: ${FormatFileSize(usage.disk_read_bytes_per_sec)}/s`,
+      `Disk write: ${FormatFileSize(usage.disk_write_bytes_per_sec)}/s`,
+    ];
+    // serde sends null where the OS doesn't report network usage
+    if (usage.network_received_bytes_per_sec != null) {
+      details.push(
+        `Network in: ${FormatFileSize(usage.network_received_bytes_per_sec)}/s`,
+        `Network out: ${FormatFileSize(usage.network_sent_bytes_per_sec)}/s`
+      );
+    }
+
+    return (
+      <div
+        className="flex items-center justify-start gap-1 text-xs text-gray-200"
+        title={details.join("\n")}
+      >
+        <HardDrive className="w-3 h-3" />
+        {FormatFileSize(ioBytesPerSec(usage))}/s
+      </div>
+    );
+  },
+  (prev, next) => {
+    return (
+      prev.pid === next.pid &&
+      (prev.usage ? ioBytesPerSec(prev.usage) : undefined) ===
+        (next.usage ? ioBytesPerSec(next.usage) : undefined)
+    );
+  }
+);
+
 export default function AppTable(props: Props) {
   const {
     data,
@@ -149,9 +213,8 @@ export default function AppTable(props: Props) {
         return {
           ...app,
           resource_usage: {
+            ...appResourceData[app.pid],
             pid: app.pid,
-            cpu_usage: appResourceData[app.pid].cpu_usage,
-            memory_bytes: appResourceData[app.pid].memory_bytes,
           },
         };
       }
@@ -197,6 +260,12 @@ export default function AppTable(props: Props) {
         return sortDirection === "asc" ? cpuA - cpuB : cpuB - cpuA;
       }
 
+      if (sortKey === "io") {
+        const ioA = a.resource_usage ? ioBytesPerSec(a.resource_usage) : 0;
+        const ioB = b.resource_usage ? ioBytesPerSec(b.resource_usage) : 0;
+        return sortDirection === "asc" ? ioA - ioB : ioB - ioA;
+      }
+
       return 0;
     });
 
@@ -0,0 +1,234 @@
+import { KeyboardEvent, useCallback, useEffect, useRef, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { MessageSquare, Plus, Trash2 } from "lucide-react";
+import { Button } from "./components/ui/button";
+import { Input } from "./components/ui/input";
+import { errorToast } from "./components/ui/toast";
+import { ChatInterface } from "./RagMode";
+import { cn } from "./lib/utils";
+import { formatError } from "./lib/messages";
+import {
+  ChatMessage,
+  CompletionResponse,
+  Conversation,
+  ConversationMessage,
+  LlmToken,
+} from "./types/types";
+
+// the full chat view in its own window, every conversation is saved by the backend so they come back after a relaunch
+export default function Chat() {
+  const inputRef = useRef<HTMLInputElement>(null);
+  const [conversations, setConversations] = useState<Conversation[]>([]);
+  const [activeId, setActiveId] = useState<number | null>(null);
+  // read by ask after its awaits, the user may have switched conversations by then
+  const activeIdRef = useRef<number | null>(null);
+  const [messages, setMessages] = useState<ConversationMessage[]>([]);
+  const [question, setQuestion] = useState<string>("");
+  // the answer that is still streaming and the conversation it belongs to
+  const [pending, setPending] = useState<{
+    conversationId: number;
+    answer: string;
+  } | null>(null);
+
+  const loadConversations = useCallback(async () => {
+    try {
+      const saved = await invoke<Conversation[]>("list_conversations");
+      setConversations(saved);
+      // open the most recent conversation when nothing is selected yet
+      setActiveId((current) => current ?? saved[0]?.id ?? null);
+    } catch (err) {
+      errorToast(`Failed to load conversations: ${err}`);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadConversations();
+
+    const unlisten = listen("conversations-updated", () => {
+      loadConversations();
+    });
+
+    return () => {
+      unlisten.then((unlistenFn) => unlistenFn());
+    };
+  }, [loadConversations]);
+
+  useEffect(() => {
+    activeIdRef.current = activeId;
+    if (activeId === null) {
+      setMessages([]);
+      return;
+    }
+
+    invoke<ConversationMessage[]>("get_conversation_messages", {
+      conversationId: activeId,
+    })
+      .then(setMessages)
+      .catch((err) => errorToast(`Failed to load messages: ${err}`));
+    inputRef.current?.focus();
+  }, [activeId]);
+
+  const startConversation = async () => {
+    try {
+      const conversation = await invoke<Conversation>("create_conversation", {
+        title: null,
+      });
+      setActiveId(conversation.id);
+      return conversation.id;
+    } catch (err) {
+      errorToast(`Failed to start a conversation: ${err}`);
+      return null;
+    }
+  };
+
+  const deleteConversation = async (conversationId: number) => {
+    try {
+      await invoke("delete_conversation", { conversationId });
+      if (conversationId === activeId) {
+        setActiveId(
+          conversations.find((conversation) => conversation.id !== conversationId)
+            ?.id ?? null
+        );
+      }
+    } catch (err) {
+      errorToast(`Failed to delete the conversation: ${err}`);
+    }
+  };
+
+  const ask = async () => {
+    const prompt = question.trim();
+    if (!prompt || pending) return;
+
+    const conversationId = activeId ?? (await startConversation());
+    if (conversationId === null) return;
+    activeIdRef.current = conversationId;
+
+    setQuestion("");
+
+    const requestId = crypto.randomUUID();
+    setPending({ conversationId, answer: "" });
+
+    const unlistenTokens = await listen<LlmToken>("llm-token", (event) => {
+      if (event.payload.request_id === requestId) {
+        setPending((prev) =>
+          prev ? { ...prev, answer: prev.answer + event.payload.token } : prev
+        );
+      }
+    });
+
+    try {
+      const userMessage = await invoke<ConversationMessage>(
+        "add_conversation_message",
+        { conversationId, role: "user", content: prompt, sources: null }
+      );
+      if (activeIdRef.current === conversationId) {
+        setMessages((prev) => [...prev, userMessage]);
+      }
+
+      const response = await invoke<CompletionResponse>("ask_llm_stream", {
+        prompt,
+        requestId,
+      });
+
+      // saved to the conversation it was asked in, even when the user switched to another one meanwhile
+      const answer = await invoke<ConversationMessage>(
+        "add_conversation_message",
+        {
+          conversationId,
+          role: "assistant",
+          content: response.content,
+          sources: response.sources ?? [],
+        }
+      );
+      if (activeIdRef.current === conversationId) {
+        setMessages((prev) => [...prev, answer]);
+      }
+    } catch (err) {
+      errorToast(`Failed to get an answer: ${formatError(err)}`);
+    } finally {
+      unlistenTokens();
+      setPending(null);
+    }
+  };
+
+  const handleKeyDown = (e: KeyboardEvent<HTMLInputElement>) => {
+    if (e.key === "Enter") {
+      e.preventDefault();
+      ask();
+    }
+  };
+
+  const visibleMessages: ChatMessage[] =
+    pending && pending.conversationId === activeId && pending.answer
+      ? [...messages, { role: "assistant", content: pending.answer }]
+      : messages;
+
+  return (
+    <div className="flex h-screen w-screen bg-background text-primary-foreground">
+      <div className="flex w-60 flex-col border-r border-border">
+        <div className="flex items-center justify-between p-3 border-b border-border">
+          <span className="text-sm font-medium">Conversations</span>
+          <Button
+            variant="ghost"
+            size="icon"
+            onClick={startConversation}
+            title="New conversation"
+          >
+            <Plus className="h-4 w-4" />
+          </Button>
+        </div>
+        <div className="flex-1 overflow-y-auto scrollbar">
+          {conversations.map((conversation) => (
+            <div
+              key={conversation.id}
+              onClick={() => setActiveId(conversation.id)}
+              className={cn(
+                conversation.id === activeId && "bg-muted",
+                "group flex cursor-pointer items-center gap-2 px-3 py-2 text-sm hover:bg-muted"
+              )}
+            >
+              <MessageSquare className="h-4 w-4 flex-shrink-0 opacity-60" />
+              <span className="flex-1 truncate">{conversation.title}</span>
+              <button
+                onClick={(e) => {
+                  e.stopPropagation();
+                  deleteConversation(conversation.id);
+                }}
+                className="opacity-0 group-hover:opacity-60 hover:opacity-100"
+                title="Delete conversation"
+              >
+                <Trash2 className="h-3 w-3" />
+              </button>
+            </div>
+          ))}
+        </div>
+      </div>
+
+      <div className="flex flex-1 flex-col p-4 gap-3 min-w-0">
+        <div className="flex-1 overflow-y-auto scrollbar">
+          {visibleMessages.length > 0 ? (
+            <ChatInterface
+              chatMessages={visibleMessages}
+              isProcessing={
+                pending?.conversationId === activeId && !pending?.answer
+              }
+            />
+          ) : (
+            <div className="flex h-full items-center justify-center text-sm opacity-60">
+              Ask anything about your files
+            </div>
+          )}
+        </div>
+        <Input
+          ref={inputRef}
+          value={question}
+          onChange={(e) => setQuestion(e.target.value)}
+          onKeyDown={handleKeyDown}
+          placeholder="Ask about your files..."
+          disabled={pending !== null}
+        />
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,48 @@
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogTitle,
+} from "./components/ui/dialog";
+import { ScrollArea } from "./components/ui/scroll-area";
+import { FileMetadata } from "./types/types";
+
+interface FilePreviewDialogProps {
+  file: FileMetadata | null;
+  content: string;
+  truncated: boolean;
+  onClose: () => void;
+}
+
+// shows the extracted text of a file when Quick Look isn't available
+export default function FilePreviewDialog({
+  file,
+  content,
+  truncated,
+  onClose,
+}: FilePreviewDialogProps) {
+  return (
+    <Dialog open={file !== null} onOpenChange={(open) => !open && onClose()}>
+      <DialogContent className="sm:max-w-lg md:max-w-2xl overflow-hidden p-0 border border-border">
+        <div className="flex flex-col">
+          <div className="flex items-center border-b border-border p-4">
+            <DialogTitle className="font-normal text-gray-200 truncate">
+              {file?.name}
+            </DialogTitle>
+            <DialogDescription />
+          </div>
+          <ScrollArea className="h-[60vh]">
+            <pre className="whitespace-pre-wrap p-4 text-xs text-muted-foreground">
+              {content}
+            </pre>
+            {truncated && (
+              <p className="px-4 pb-4 text-xs text-muted-foreground italic">
+                Preview truncated, open the file to see the rest
+              </p>
+            )}
+          </ScrollArea>
+        </div>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -49,7 +49,15 @@ const getColumns = (
       return (
         <div className="flex flex-col min-w-0 max-w-md">
           <div className="flex items-center space-x-2 overflow-hidden text-white">
-            {getFileIcon(file.extension)}
+            {file.thumbnail ? (
+              <img
+                src={file.thumbnail}
+                alt=""
+                className="h-8 w-8 rounded object-cover flex-shrink-0"
+              />
+            ) : (
+              getFileIcon(file.extension)
+            )}
             <span className="text-sm truncate text-white">
               {truncateFilename(file.name, 40, true)}
             </span>
@@ -61,6 +69,15 @@ const getColumns = (
             {isSemanticMatch && semanticMatch && (
               <SemanticRelevance distance={semanticMatch.distance} />
             )}
+            {file.source === "spotlight" && (
+              <Badge
+                variant="outline"
+                className="text-xs font-normal text-gray-400 flex-shrink-0"
+                title="Found by Spotlight, this folder isn't indexed"
+              >
+                Spotlight
+              </Badge>
+            )}
           </div>
           {/* {file.path && (
             <div className="flex items-center text-xs text-gray-500 ml-6">
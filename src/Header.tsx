@@ -3,12 +3,14 @@ import { Input } from "./components/ui/input";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { cn } from "./lib/utils";
+import { formatMessage } from "./lib/messages";
 import {
   AppSettings,
   ChatMessage,
   CompletionResponse,
   Contact,
   Model,
+  UserMessage,
 } from "./types/types";
 import RagMode from "./RagMode";
 
@@ -335,10 +337,10 @@ export default function Header(props: Props) {
     });
 
     // Listen for model download required event
-    const unlistenDownloadPromise = listen(
+    const unlistenDownloadPromise = listen<UserMessage>(
       "model-download-required",
       (event) => {
-        console.log("Model download required:", event.payload);
+        console.log("Model download required:", formatMessage(event.payload));
         setModelStatus("not-downloaded");
         setShowModelMissingPrompt(true);
         // If we're in RAG mode, open settings to allow download
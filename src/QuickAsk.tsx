@@ -0,0 +1,108 @@
+import { KeyboardEvent, useEffect, useRef, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import { Input } from "./components/ui/input";
+import { SourceBadge } from "./RagMode";
+import { formatError } from "./lib/messages";
+import { CompletionResponse, LlmToken } from "./types/types";
+
+// the floating window opened with its own shortcut, it asks the LLM a single question without opening the launcher
+export default function QuickAsk() {
+  const inputRef = useRef<HTMLInputElement>(null);
+  const [question, setQuestion] = useState<string>("");
+  const [answer, setAnswer] = useState<string>("");
+  const [sources, setSources] = useState<string[]>([]);
+  const [error, setError] = useState<string | null>(null);
+  const [isAsking, setIsAsking] = useState<boolean>(false);
+
+  // focus the input every time the window is shown
+  useEffect(() => {
+    let unlistenFocus: UnlistenFn | undefined;
+
+    getCurrentWindow()
+      .onFocusChanged(({ payload: focused }) => {
+        if (focused) inputRef.current?.focus();
+      })
+      .then((unlisten) => {
+        unlistenFocus = unlisten;
+      });
+    inputRef.current?.focus();
+
+    return () => {
+      if (unlistenFocus) unlistenFocus();
+    };
+  }, []);
+
+  const ask = async () => {
+    const prompt = question.trim();
+    if (!prompt || isAsking) return;
+
+    const requestId = crypto.randomUUID();
+    setAnswer("");
+    setSources([]);
+    setError(null);
+    setIsAsking(true);
+
+    const unlistenTokens = await listen<LlmToken>("llm-token", (event) => {
+      if (event.payload.request_id === requestId) {
+        setAnswer((prev) => prev + event.payload.token);
+      }
+    });
+
+    try {
+      const response = await invoke<CompletionResponse>("ask_llm_stream", {
+        prompt,
+        requestId,
+      });
+      // the streamed text still has the sources list at the end, the final answer doesn't
+      setAnswer(response.content);
+      setSources(response.sources ?? []);
+    } catch (err) {
+      setError(formatError(err));
+    } finally {
+      unlistenTokens();
+      setIsAsking(false);
+    }
+  };
+
+  const handleKeyDown = (e: KeyboardEvent<HTMLInputElement>) => {
+    if (e.key === "Enter") {
+      e.preventDefault();
+      ask();
+    } else if (e.key === "Escape") {
+      e.preventDefault();
+      getCurrentWindow().hide();
+    }
+  };
+
+  return (
+    <div className="h-screen w-screen p-2">
+      <div className="flex h-full flex-col gap-2 rounded-lg border border-border bg-background p-3 text-primary-foreground">
+        <Input
+          ref={inputRef}
+          value={question}
+          onChange={(e) => setQuestion(e.target.value)}
+          onKeyDown={handleKeyDown}
+          placeholder="Ask about your files..."
+          disabled={isAsking}
+        />
+        <div className="flex-1 overflow-y-auto text-sm whitespace-pre-wrap">
+          {error ? (
+            <span className="text-red-500">{error}</span>
+          ) : (
+            answer || (isAsking && <span className="opacity-60">Thinking...</span>)
+          )}
+        </div>
+        {sources.length > 0 && (
+          <div className="flex flex-col gap-1 border-t border-border pt-2">
+            <div className="text-xs opacity-70">Sources:</div>
+            {sources.map((source, idx) => (
+              <SourceBadge key={source} source={source} idx={idx} />
+            ))}
+          </div>
+        )}
+      </div>
+    </div>
+  );
+}
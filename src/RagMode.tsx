@@ -81,7 +81,7 @@ interface ChatInterfaceProps {
   isProcessing: boolean;
 }
 
-function ChatInterface(props: ChatInterfaceProps) {
+export function ChatInterface(props: ChatInterfaceProps) {
   const { chatMessages, isProcessing } = props;
 
   return (
@@ -140,7 +140,7 @@ interface SourceBadgeProps {
   idx: number;
 }
 
-function SourceBadge(props: SourceBadgeProps) {
+export function SourceBadge(props: SourceBadgeProps) {
   const { source, idx } = props;
   const [fileName, setFileName] = useState<string | null>(null);
 
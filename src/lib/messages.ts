@@ -0,0 +1,45 @@
+import { UserMessage } from "@/src/types/types";
+
+// English texts of the backend messages by key, a translation replaces this catalog
+const messages: Record<string, string> = {
+  "model.selection_required": "Please select a model to use for AI features",
+  "model.download_required":
+    "The selected model '{model}' needs to be downloaded before use",
+  "model.not_found":
+    "The previously selected model is no longer available. Please select a new model.",
+  "model.download_failed": "Failed to download the model: {error}",
+  "llm.starting": "The LLM server is starting, please try again in a moment.",
+  "llm.not_running":
+    "No LLM server is currently running. Please select a model first.",
+  "llm.context_failed": "Failed to find the context for the question: {error}",
+  "llm.request_failed": "Failed to get response: {error}",
+  "indexing.save_failed": "File processing error: {error}",
+  "indexing.chunking_failed": "Chunking/embedding error: {error}",
+  "indexing.no_embeddings": "No valid embeddings generated",
+  "indexing.embeddings_failed": "Failed to store the embeddings: {error}",
+};
+
+export function isUserMessage(value: unknown): value is UserMessage {
+  return (
+    typeof value === "object" &&
+    value !== null &&
+    "code" in value &&
+    "key" in value
+  );
+}
+
+// falls back to the English text sent by the backend for keys missing from the catalog
+export function formatMessage(message: UserMessage): string {
+  const template = messages[message.key];
+  if (!template) return message.message;
+
+  return Object.entries(message.params).reduce(
+    (text, [name, value]) => text.split(`{${name}}`).join(value),
+    template
+  );
+}
+
+// errors of commands are either a UserMessage or a plain string
+export function formatError(error: unknown): string {
+  return isUserMessage(error) ? formatMessage(error) : String(error);
+}
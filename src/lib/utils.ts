@@ -1,6 +1,6 @@
 import { clsx, type ClassValue } from "clsx";
 import { twMerge } from "tailwind-merge";
-import { SearchCategory } from "@/src/types/types";
+import { AppResourceUsage, SearchCategory } from "@/src/types/types";
 
 export function cn(...inputs: ClassValue[]): string {
   return twMerge(clsx(inputs));
@@ -164,6 +164,16 @@ export function isValidJSON(str: string) {
   }
 }
 
+// total disk and network throughput of a process, network is missing where the OS doesn't report it
+export function ioBytesPerSec(usage: AppResourceUsage): number {
+  return (
+    usage.disk_read_bytes_per_sec +
+    usage.disk_write_bytes_per_sec +
+    (usage.network_received_bytes_per_sec ?? 0) +
+    (usage.network_sent_bytes_per_sec ?? 0)
+  );
+}
+
 export function FormatFileSize(bytes: number | undefined): string {
   if (bytes === undefined || bytes === 0) return "0 B";
 
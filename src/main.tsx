@@ -1,13 +1,27 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
-import App from "./App";
+import { getCurrentWindow } from "@tauri-apps/api/window";
 import { ThemeProvider } from "./ThemeProvider";
 import { Toaster } from "./components/ui/sonner";
 
+// every window loads this page, the quick-ask and chat windows only load their own view so the launcher's shortcuts aren't registered twice
+async function loadRoot() {
+  switch (getCurrentWindow().label) {
+    case "quick-ask":
+      return (await import("./QuickAsk")).default;
+    case "chat":
+      return (await import("./Chat")).default;
+    default:
+      return (await import("./App")).default;
+  }
+}
+
+const Root = await loadRoot();
+
 ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
   <React.StrictMode>
     <ThemeProvider>
-      <App />
+      <Root />
       <Toaster offset={8} duration={2000} />
     </ThemeProvider>
   </React.StrictMode>
@@ -15,7 +15,8 @@ import { open } from "@tauri-apps/plugin-dialog";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { Badge } from "../components/ui/badge";
-import { AppSettings, Model } from "../types/types";
+import { AppSettings, Model, UserMessage } from "../types/types";
+import { formatMessage } from "../lib/messages";
 
 interface DownloadProgress {
   progress: number;
@@ -130,14 +131,14 @@ export default function Models() {
       );
     });
 
-    const unlisten3 = listen<{ model_id: string; error: string }>(
+    const unlisten3 = listen<{ model_id: string; message: UserMessage }>(
       "model-download-error",
       (event) => {
-        const { model_id, error } = event.payload;
+        const { model_id, message } = event.payload;
         setDownloadStatus({
           isDownloading: false,
           progress: 0,
-          error,
+          error: formatMessage(message),
           model_id,
         });
       }
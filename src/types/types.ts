@@ -18,12 +18,164 @@ export interface FileMetadata extends BaseMetadata {
   size: number;
   updated_at?: string;
   created_at?: string;
+  thumbnail?: string | null;
+  source?: FileSource;
+}
+
+export type FileSource = "index" | "spotlight";
+
+export interface FeatureUsage {
+  feature: string;
+  count: number;
+  last_used_at?: string | null;
+}
+
+export interface TimingStats {
+  metric: string;
+  samples: number;
+  average_ms: number;
+  median_ms: number;
+  p95_ms: number;
+  max_ms: number;
+  items_per_second?: number | null;
+}
+
+export interface UsageStats {
+  enabled: boolean;
+  days: number;
+  features: FeatureUsage[];
+  timings: TimingStats[];
+}
+
+export interface LogEntry {
+  timestamp: string;
+  level: string;
+  target: string;
+  message: string;
+  span?: string | null;
+}
+
+export type ErrorCode =
+  | "model_selection_required"
+  | "model_download_required"
+  | "model_not_found"
+  | "model_download_failed"
+  | "llm_starting"
+  | "llm_not_running"
+  | "llm_context_failed"
+  | "llm_request_failed"
+  | "index_save_failed"
+  | "index_chunking_failed"
+  | "index_no_embeddings"
+  | "index_embeddings_failed";
+
+export interface UserMessage {
+  code: ErrorCode;
+  key: string;
+  params: Record<string, string>;
+  message: string;
+}
+
+export type PluginCapability = "chunker" | "actions" | "provider";
+
+export interface PluginManifest {
+  name: string;
+  version: string;
+  description?: string | null;
+  api_version: number;
+  capabilities: PluginCapability[];
+  extensions: string[];
+  permissions: {
+    read_dirs: string[];
+    data_dir: boolean;
+  };
+}
+
+export interface PluginInfo {
+  dir_name: string;
+  manifest?: PluginManifest | null;
+  error?: string | null;
+}
+
+export interface PluginAction {
+  plugin: string;
+  id: string;
+  title: string;
+}
+
+export interface PluginActionResult {
+  message?: string | null;
+  open_url?: string | null;
+}
+
+export interface PluginProviderResult {
+  plugin: string;
+  id: string;
+  title: string;
+  subtitle?: string | null;
+}
+
+export interface BackgroundTaskCrash {
+  task: string;
+  message: string;
+  restarting: boolean;
+  restarts: number;
+}
+
+export interface CategoryCount {
+  category: string;
+  count: number;
 }
 
 export interface AppMetadata extends BaseMetadata {
   pid: number;
   resource_usage?: AppResourceUsage;
   icon?: string;
+  frecency: number;
+  recent_documents: RecentDocument[];
+  bundle_id?: string;
+  version?: string;
+  last_used?: string;
+}
+
+export interface RecentDocument {
+  name: string;
+  path: string;
+}
+
+export type SystemActionId =
+  | "empty_trash"
+  | "toggle_dark_mode"
+  | "sleep"
+  | "lock_screen"
+  | "toggle_do_not_disturb";
+
+export interface SystemAction {
+  id: SystemActionId;
+  name: string;
+  description: string;
+  requires_confirmation: boolean;
+}
+
+export interface BrowserTab {
+  browser: "safari" | "chrome";
+  window_index: number;
+  tab_index: number;
+  title: string;
+  url: string;
+}
+
+export interface QuitOutcome {
+  pid: number;
+  name?: string;
+  memory_bytes: number;
+  error?: string;
+}
+
+export interface BatchQuitReport {
+  succeeded: QuitOutcome[];
+  failed: QuitOutcome[];
+  freed_memory_bytes: number;
 }
 
 export interface SemanticMetadata extends BaseMetadata {
@@ -37,12 +189,118 @@ export interface AppResourceUsage {
   pid: number;
   cpu_usage: number;
   memory_bytes: number;
+  disk_read_bytes_per_sec: number;
+  disk_write_bytes_per_sec: number;
+  network_received_bytes_per_sec?: number;
+  network_sent_bytes_per_sec?: number;
+  energy_impact?: number;
+}
+
+export type ResourceAlertKind = "cpu" | "memory";
+
+export interface ResourceAlert {
+  pid: number;
+  name: string;
+  kind: ResourceAlertKind;
+  cpu_usage: number;
+  memory_bytes: number;
+  threshold: number;
+  duration_secs: number;
+}
+
+export type ResourceTarget = "all" | { pids: number[] };
+
+export interface ResourceSampleEvent {
+  subscription_id: number;
+  usage: Record<number, AppResourceUsage>;
+}
+
+export interface ResourceSample {
+  timestamp: number;
+  cpu_usage: number;
+  memory_bytes: number;
 }
 
 export interface IndexingProgress {
   total: number;
   processed: number;
   percentage: number;
+  current_file?: string;
+  bytes_processed?: number;
+  total_bytes?: number;
+  failed?: number;
+  eta_seconds?: number;
+}
+
+export interface WatchedRootStatus {
+  path: string;
+  event_count: number;
+  polled: boolean;
+}
+
+export interface WatcherStatus {
+  is_running: boolean;
+  watched_roots: WatchedRootStatus[];
+  pending_events: number;
+  last_error?: string;
+}
+
+export interface TagCount {
+  name: string;
+  file_count: number;
+}
+
+export interface Favorite {
+  kind: "file" | "app";
+  path: string;
+  name: string;
+  position: number;
+}
+
+export interface RecentItem {
+  kind: "file" | "app";
+  path: string;
+  name: string;
+  last_query?: string;
+  last_opened_at: string;
+  open_count: number;
+}
+
+export interface MaintenanceReport {
+  size_before_bytes: number;
+  size_after_bytes: number;
+  reclaimed_bytes: number;
+  duration_ms: number;
+}
+
+export interface IndexArchiveReport {
+  archive_path: string;
+  file_count: number;
+}
+
+export interface DirectorySettings {
+  indexable_extensions?: string[];
+  max_file_size_bytes?: number;
+  semantic_indexing?: boolean;
+  max_depth?: number;
+}
+
+export interface IndexedDirectory {
+  path: string;
+  file_count: number;
+  exists: boolean;
+  added_at?: string;
+  settings: DirectorySettings;
+}
+
+export interface SettingsImportReport {
+  tags_imported: number;
+  missing_files: number;
+}
+
+export interface ProfileInfo {
+  name: string;
+  is_active: boolean;
 }
 
 export interface SelectPathsOptions {
@@ -128,6 +386,7 @@ export interface Model {
 }
 
 export interface AppSettings {
+  version?: number;
   theme?: string;
   custom_model_path?: string;
   selected_model_id?: string;
@@ -135,7 +394,82 @@ export interface AppSettings {
   window_height?: number;
   global_hotkey?: string;
   index_concurrency?: number;
+  adaptive_concurrency?: boolean;
   selected_categories?: string[];
+  hf_token?: string;
+  hf_mirror_url?: string;
+  exclude_patterns?: string[];
+  use_default_deny_list?: boolean;
+  respect_ignore_files?: boolean;
+  indexable_extensions?: string[];
+  max_file_size_bytes?: number;
+  download_cloud_placeholders?: boolean;
+  collapse_duplicate_results?: boolean;
+  watcher_debounce_ms?: number;
+  watcher_max_batch_size?: number;
+  watcher_poll_interval_secs?: number;
+  live_indexing?: boolean;
+  fts_tokenizer?: "trigram" | "porter" | "unicode61";
+  fts_prefix_indexes?: boolean;
+  fts_split_identifiers?: boolean;
+  db_maintenance_interval_days?: number;
+  encrypt_index?: boolean;
+  result_count?: number;
+  default_search_mode?: SearchMode;
+  show_hidden_files?: boolean;
+  search?: SearchSettings;
+  launch_at_login?: boolean;
+  start_hidden?: boolean;
+  index_browser_tabs?: boolean;
+  persist_resource_history?: boolean;
+  resource_alerts?: ResourceAlertSettings;
+  api_server?: ApiServerSettings;
+  usage_analytics?: boolean;
+}
+
+export interface ApiServerSettings {
+  enabled?: boolean;
+  port?: number;
+}
+
+export interface ApiServerStatus {
+  enabled: boolean;
+  running: boolean;
+  port: number;
+  token: string | null;
+}
+
+export interface ResourceAlertSettings {
+  cpu_percent?: number;
+  cpu_duration_minutes?: number;
+  memory_gb?: number;
+}
+
+export interface SearchSettings {
+  semantic_distance_threshold?: number;
+  top_k?: number;
+  semantic_weight?: number;
+  keyword_weight?: number;
+  rerank?: boolean;
+  spotlight_fallback?: boolean;
+}
+
+export type SearchMode = "keyword" | "semantic" | "hybrid";
+
+export interface FieldError {
+  // nested fields are joined with a dot, e.g. "search.top_k"
+  field: string;
+  message: string;
+}
+
+export type UpdateSettingsError =
+  | { kind: "invalid"; errors: FieldError[] }
+  | { kind: "failed"; message: string };
+
+export interface SettingChange {
+  key: keyof AppSettings;
+  old_value: unknown;
+  new_value: unknown;
 }
 
 export interface ChatMessage {
@@ -144,11 +478,34 @@ export interface ChatMessage {
   sources?: string[];
 }
 
+export interface Conversation {
+  id: number;
+  title: string;
+  created_at?: string | null;
+  updated_at?: string | null;
+}
+
+export interface ConversationMessage extends ChatMessage {
+  id: number;
+  conversation_id: number;
+  sources: string[];
+  created_at?: string | null;
+}
+
 export interface CompletionResponse {
   content: string;
   sources?: string[];
 }
 
+export interface LlmToken {
+  request_id: string;
+  token: string;
+}
+
+export type FilePreview =
+  | { kind: "quick_look" }
+  | { kind: "text"; content: string; truncated: boolean };
+
 export interface Contact {
   id: String;
   given_name?: String;